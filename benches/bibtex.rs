@@ -0,0 +1,64 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use reference_tool::bibtex;
+use reference_tool::models::{BibtexOptions, PublicationInfo, Reference};
+
+/// `count` journal articles with a few authors, abstracts and categories each
+fn references(count: usize) -> Vec<Reference> {
+    (0..count)
+        .map(|id| Reference {
+            title: format!("The {}th study of {{Holography}} and $AdS_5$ black holes", id),
+            authors: vec![
+                "Maldacena, Juan Martin".to_string(),
+                "van der Berg, Jan".to_string(),
+                format!("Author{}, A. B.", id),
+            ],
+            arxiv_id: Some(format!("2301.{:05}", id)),
+            inspire_id: Some(id.to_string()),
+            categories: vec!["hep-th".to_string(), "gr-qc".to_string()],
+            year: Some(2000 + (id % 24) as u32),
+            abstract_text: Some("We find 95% agreement with the {conjectured} result & more. ".repeat(5)),
+            document_type: vec!["article".to_string()],
+            doi: Some(format!("10.1000/{}", id)),
+            publication_info: Some(PublicationInfo {
+                journal_title: Some("JHEP".to_string()),
+                journal_volume: Some("05".to_string()),
+                artid: Some(id.to_string()),
+                year: Some(2023),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn bench_generation(c: &mut Criterion) {
+    let references = references(10_000);
+    let options = BibtexOptions { keywords: true, url: true, include_abstract: true, ..Default::default() };
+
+    let mut group = c.benchmark_group("bibtex_generation");
+    group.throughput(Throughput::Elements(references.len() as u64));
+    group.bench_function("10k_default", |b| {
+        b.iter(|| references.iter().map(Reference::to_bibtex).collect::<Vec<_>>().join("\n"))
+    });
+    group.bench_function("10k_all_fields", |b| {
+        b.iter(|| references.iter().map(|r| r.to_bibtex_with(&options)).collect::<Vec<_>>().join("\n"))
+    });
+    group.bench_function("10k_keys", |b| b.iter(|| references.iter().map(Reference::bibtex_key).collect::<Vec<_>>()));
+    group.finish();
+}
+
+fn bench_parsing(c: &mut Criterion) {
+    let content = references(10_000).iter().map(Reference::to_bibtex).collect::<Vec<_>>().join("\n");
+
+    let mut group = c.benchmark_group("bibtex_parsing");
+    group.throughput(Throughput::Bytes(content.len() as u64));
+    group.bench_function("10k_entries", |b| b.iter(|| bibtex::parse(&content).unwrap()));
+    group.bench_function("10k_to_references", |b| {
+        b.iter(|| bibtex::parse(&content).unwrap().iter().map(|entry| entry.to_reference()).collect::<Vec<_>>())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_generation, bench_parsing);
+criterion_main!(benches);
@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use reference_tool::graph;
+use reference_tool::models::Paper;
+use reference_tool::network::CitationNetwork;
+use reference_tool::similarity;
+
+/// Deterministic pseudo-random numbers (a 64-bit LCG), so every run benchmarks the same graph
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % bound.max(1)
+    }
+}
+
+/// A network of `papers` papers, each citing `citations` earlier papers and
+/// written by three of 500 authors
+fn synthetic_network(papers: usize, citations: usize) -> CitationNetwork {
+    let mut rng = Lcg(42);
+    let mut network = CitationNetwork::new();
+    for id in 0..papers {
+        network.add_paper(Paper {
+            id: id.to_string(),
+            title: format!("Paper {}", id),
+            authors: (0..3).map(|_| format!("Author{}, A.", rng.next(500))).collect(),
+            year: Some(1990 + (id * 30 / papers.max(1)) as u32),
+            ..Default::default()
+        });
+        if id > 0 {
+            let cited = (0..citations).map(|_| rng.next(id).to_string()).collect();
+            network.add_citations(&id.to_string(), cited);
+        }
+    }
+    network
+}
+
+fn bench_algorithms(c: &mut Criterion) {
+    let mut group = c.benchmark_group("network");
+    for papers in [1_000, 10_000] {
+        let network = synthetic_network(papers, 20);
+        let last = (papers - 1).to_string();
+        group.bench_with_input(BenchmarkId::new("shortest_path", papers), &network, |b, network| {
+            b.iter(|| network.shortest_path(&last, "0"))
+        });
+        group.bench_with_input(BenchmarkId::new("author_stats", papers), &network, |b, network| {
+            b.iter(|| network.author_stats())
+        });
+        group.bench_with_input(BenchmarkId::new("validate", papers), &network, |b, network| {
+            b.iter(|| network.validate())
+        });
+        group.bench_with_input(BenchmarkId::new("citation_counts", papers), &network, |b, network| {
+            b.iter(|| (0..papers).map(|id| network.citation_count(&id.to_string())).sum::<usize>())
+        });
+    }
+    group.finish();
+}
+
+fn bench_exports(c: &mut Criterion) {
+    let network = synthetic_network(5_000, 20);
+    let json = network.to_json().unwrap();
+
+    let mut group = c.benchmark_group("network_export");
+    group.bench_function("to_json", |b| b.iter(|| network.to_json().unwrap()));
+    group.bench_function("from_json", |b| b.iter(|| CitationNetwork::from_json(&json).unwrap()));
+    group.bench_function("to_graphml", |b| b.iter(|| graph::to_graphml(&network)));
+    group.bench_function("to_dot", |b| b.iter(|| graph::to_dot(&network)));
+    group.finish();
+}
+
+fn bench_coupling(c: &mut Criterion) {
+    let mut rng = Lcg(7);
+    // Reference lists drawn from a shared pool, so some pairs are coupled
+    let lists: Vec<HashSet<String>> = (0..100)
+        .map(|_| (0..40).map(|_| rng.next(1_000).to_string()).collect())
+        .collect();
+
+    c.bench_function("cluster_by_coupling/100", |b| b.iter(|| similarity::cluster_by_coupling(&lists, 0.05)));
+}
+
+criterion_group!(benches, bench_algorithms, bench_exports, bench_coupling);
+criterion_main!(benches);
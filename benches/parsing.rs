@@ -0,0 +1,97 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde_json::{json, Value};
+
+use reference_tool::api::InspireClient;
+use reference_tool::models::Reference;
+
+/// A literature search response with `count` hits of `authors` authors each
+fn search_response(count: usize, authors: usize) -> Value {
+    let hits: Vec<Value> = (0..count)
+        .map(|id| json!({
+            "revision_id": 3,
+            "updated": "2024-01-01T00:00:00+00:00",
+            "metadata": {
+                "control_number": id,
+                "titles": [{"title": format!("Paper number {} on holography", id)}],
+                "authors": (0..authors).map(|a| json!({"full_name": format!("Author{}, A.", a)})).collect::<Vec<_>>(),
+                "arxiv_eprints": [{"value": format!("2301.{:05}", id), "categories": ["hep-th"]}],
+                "inspire_categories": [{"term": "Theory-HEP"}],
+                "preprint_date": "2023-01-15",
+                "texkeys": [format!("Author0:2023{}", id)],
+                "abstracts": [{"source": "arXiv", "value": "We study things at length. ".repeat(20)}],
+                "document_type": ["article"],
+                "dois": [{"value": format!("10.1000/{}", id)}],
+                "publication_info": [{"journal_title": "JHEP", "journal_volume": "05", "artid": "123", "year": 2023}],
+                "citation_count": id % 500
+            }
+        }))
+        .collect();
+    json!({"hits": {"hits": hits, "total": count}})
+}
+
+/// A literature record with `count` reference entries
+fn record_with_references(count: usize) -> Value {
+    let references: Vec<Value> = (0..count)
+        .map(|id| json!({
+            "record": {"$ref": format!("https://inspirehep.net/api/literature/{}", id)},
+            "reference": {
+                "title": {"title": format!("Cited paper {}", id)},
+                "authors": [{"full_name": "Doe, J."}, {"full_name": "Roe, R."}],
+                "arxiv_eprint": format!("1901.{:05}", id),
+                "dois": [format!("10.1000/ref{}", id)],
+                "label": id.to_string(),
+                "publication_info": {"journal_title": "Phys.Rev.D", "journal_volume": "99", "year": 2019}
+            }
+        }))
+        .collect();
+    json!({"metadata": {"control_number": 1, "references": references}})
+}
+
+fn bench_search_results(c: &mut Criterion) {
+    let client = InspireClient::new();
+    let mut group = c.benchmark_group("parse_search_results");
+    for count in [100, 1000] {
+        let response = search_response(count, 10);
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &response, |b, response| {
+            b.iter(|| client.parse_search_results(response).unwrap())
+        });
+    }
+    group.finish();
+
+    // Collaboration papers carry thousands of authors
+    let response = search_response(10, 3000);
+    c.bench_function("parse_search_results/3000_authors", |b| {
+        b.iter(|| client.parse_search_results(&response).unwrap())
+    });
+}
+
+fn bench_references(c: &mut Criterion) {
+    let client = InspireClient::new();
+    let record = record_with_references(2000);
+    c.bench_function("parse_references/2000", |b| b.iter(|| client.parse_references(&record)));
+
+    // Deserializing a text payload end to end, as the client does with a response body
+    let text = search_response(1000, 10).to_string();
+    c.bench_function("parse_search_results/from_text_1000", |b| {
+        b.iter(|| client.parse_search_results(&serde_json::from_str(&text).unwrap()).unwrap())
+    });
+}
+
+fn bench_json_export(c: &mut Criterion) {
+    let client = InspireClient::new();
+    let references: Vec<Reference> = client.parse_search_results(&search_response(10_000, 5)).unwrap()
+        .into_iter()
+        .map(Reference::from)
+        .collect();
+    let json = serde_json::to_string_pretty(&references).unwrap();
+
+    let mut group = c.benchmark_group("json_export");
+    group.throughput(Throughput::Elements(references.len() as u64));
+    group.bench_function("write_10k", |b| b.iter(|| serde_json::to_string_pretty(&references).unwrap()));
+    group.bench_function("read_10k", |b| b.iter(|| serde_json::from_str::<Vec<Reference>>(&json).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, bench_search_results, bench_references, bench_json_export);
+criterion_main!(benches);
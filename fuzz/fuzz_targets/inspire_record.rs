@@ -0,0 +1,19 @@
+#![no_main]
+
+use std::sync::OnceLock;
+
+use libfuzzer_sys::fuzz_target;
+use reference_tool::api::InspireClient;
+use serde_json::{json, Value};
+
+static CLIENT: OnceLock<InspireClient> = OnceLock::new();
+
+// Any JSON, as a whole search response and as the metadata of a single hit:
+// malformed records may fail to parse but must never panic
+fuzz_target!(|data: &[u8]| {
+    let Ok(value) = serde_json::from_slice::<Value>(data) else { return };
+    let client = CLIENT.get_or_init(InspireClient::new);
+
+    let _ = client.parse_search_results(&value);
+    let _ = client.parse_search_results(&json!({"hits": {"hits": [{"metadata": value}]}}));
+});
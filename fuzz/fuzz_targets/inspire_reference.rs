@@ -0,0 +1,19 @@
+#![no_main]
+
+use std::sync::OnceLock;
+
+use libfuzzer_sys::fuzz_target;
+use reference_tool::api::InspireClient;
+use serde_json::{json, Value};
+
+static CLIENT: OnceLock<InspireClient> = OnceLock::new();
+
+// Any JSON, as a whole literature record and as one entry of its reference
+// list: malformed entries are skipped but must never panic
+fuzz_target!(|data: &[u8]| {
+    let Ok(value) = serde_json::from_slice::<Value>(data) else { return };
+    let client = CLIENT.get_or_init(InspireClient::new);
+
+    let _ = client.parse_references(&value);
+    let _ = client.parse_references(&json!({"metadata": {"references": [value]}}));
+});
@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Reference;
+use crate::report::{ReportFormat, Table};
+
+/// Most rows in the ASCII histogram; older references share wider bins
+const HISTOGRAM_ROWS: u32 = 20;
+/// Length of the longest histogram bar
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// How old the references of a paper were when it appeared
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceAging {
+    /// Publication year of the citing paper
+    pub paper_year: u32,
+    pub references: usize,
+    /// References without a year, left out of everything below
+    pub undated: usize,
+    /// Number of references of each age in years; references dated after the
+    /// paper (later published versions) count as age 0
+    pub ages: BTreeMap<u32, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub median_age: Option<f64>,
+    /// Age limit for `recent_fraction`
+    pub recent_years: u32,
+    /// Fraction of the dated references younger than `recent_years`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recent_fraction: Option<f64>,
+}
+
+impl ReferenceAging {
+    /// Age distribution of `references` cited by a paper from `paper_year`
+    pub fn compute(paper_year: u32, references: &[Reference], recent_years: u32) -> Self {
+        let mut sorted_ages: Vec<u32> = references.iter()
+            .filter_map(|reference| reference.year)
+            .map(|year| paper_year.saturating_sub(year))
+            .collect();
+        sorted_ages.sort_unstable();
+
+        let mut ages = BTreeMap::new();
+        for &age in &sorted_ages {
+            *ages.entry(age).or_insert(0) += 1;
+        }
+        let median_age = match sorted_ages.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(f64::from(sorted_ages[n / 2])),
+            n => Some(f64::from(sorted_ages[n / 2 - 1] + sorted_ages[n / 2]) / 2.0),
+        };
+        let recent_fraction = (!sorted_ages.is_empty()).then(|| {
+            let recent = sorted_ages.iter().filter(|&&age| age < recent_years).count();
+            recent as f64 / sorted_ages.len() as f64
+        });
+
+        Self {
+            paper_year,
+            references: references.len(),
+            undated: references.len() - sorted_ages.len(),
+            ages,
+            median_age,
+            recent_years,
+            recent_fraction,
+        }
+    }
+
+    /// Headline numbers as a two-column table
+    pub fn summary_table(&self) -> Table {
+        let mut table = Table::new(&["metric", "value"]);
+        table.push_row(vec!["paper_year".to_string(), self.paper_year.to_string()]);
+        table.push_row(vec!["references".to_string(), self.references.to_string()]);
+        table.push_row(vec!["undated".to_string(), self.undated.to_string()]);
+        table.push_row(vec![
+            "median_age".to_string(),
+            self.median_age.map(|age| format!("{:.1}", age)).unwrap_or_default(),
+        ]);
+        table.push_row(vec![
+            format!("younger_than_{}_years", self.recent_years),
+            self.recent_fraction.map(|fraction| format!("{:.1}%", fraction * 100.0)).unwrap_or_default(),
+        ]);
+        table
+    }
+
+    /// Number of references of each age, youngest first
+    pub fn ages_table(&self) -> Table {
+        let mut table = Table::new(&["age", "references"]);
+        for (age, count) in &self.ages {
+            table.push_row(vec![age.to_string(), count.to_string()]);
+        }
+        table
+    }
+
+    /// The age distribution as horizontal bars, one row per age (or range of
+    /// ages, when they span more than `HISTOGRAM_ROWS` years):
+    ///
+    /// ```text
+    ///  0-4 | ######## 4
+    ///  5-9 | ## 1
+    /// ```
+    pub fn histogram(&self) -> String {
+        let Some(&oldest) = self.ages.keys().next_back() else {
+            return String::new();
+        };
+        let bin_width = oldest / HISTOGRAM_ROWS + 1;
+        let mut bins = vec![0; (oldest / bin_width + 1) as usize];
+        for (age, count) in &self.ages {
+            bins[(age / bin_width) as usize] += count;
+        }
+        let largest = bins.iter().copied().max().unwrap_or(0).max(1);
+
+        let labels: Vec<String> = (0..bins.len() as u32)
+            .map(|bin| match bin_width {
+                1 => bin.to_string(),
+                _ => format!("{}-{}", bin * bin_width, (bin + 1) * bin_width - 1),
+            })
+            .collect();
+        let label_width = labels.iter().map(String::len).max().unwrap_or(0);
+        labels.iter().zip(&bins)
+            .map(|(label, &count)| {
+                let mut bar = "#".repeat((count * HISTOGRAM_WIDTH).div_ceil(largest));
+                if !bar.is_empty() {
+                    bar.push(' ');
+                }
+                format!("{:>width$} | {}{}\n", label, bar, count, width = label_width)
+            })
+            .collect()
+    }
+
+    /// Render the report: Markdown gets the summary and the histogram, CSV the
+    /// summary and the per-age counts
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => format!(
+                "# Reference ages\n\n## Summary\n\n{}\n## Age distribution (years)\n\n```text\n{}```\n",
+                self.summary_table().to_markdown(),
+                self.histogram()
+            ),
+            ReportFormat::Csv => format!("{}\n{}", self.summary_table().to_csv(), self.ages_table().to_csv()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(year: Option<u32>) -> Reference {
+        Reference { title: "Reference".to_string(), year, ..Default::default() }
+    }
+
+    #[test]
+    fn test_compute() {
+        let references: Vec<Reference> = [Some(2020), Some(2019), Some(2010), Some(1990), Some(2021), None]
+            .into_iter()
+            .map(reference)
+            .collect();
+        let aging = ReferenceAging::compute(2020, &references, 5);
+        assert_eq!(aging.references, 6);
+        assert_eq!(aging.undated, 1);
+        assert_eq!(aging.ages, BTreeMap::from([(0, 2), (1, 1), (10, 1), (30, 1)]));
+        assert_eq!(aging.median_age, Some(1.0));
+        assert_eq!(aging.recent_fraction, Some(0.6));
+
+        let even = ReferenceAging::compute(2020, &references[..4], 5);
+        assert_eq!(even.median_age, Some(5.5));
+
+        let empty = ReferenceAging::compute(2020, &[reference(None)], 5);
+        assert_eq!(empty.median_age, None);
+        assert_eq!(empty.recent_fraction, None);
+        assert_eq!(empty.histogram(), "");
+    }
+
+    #[test]
+    fn test_histogram() {
+        let references: Vec<Reference> = [2020, 2020, 2018].into_iter().map(|year| reference(Some(year))).collect();
+        let aging = ReferenceAging::compute(2020, &references, 5);
+        let bar = |n| "#".repeat(n);
+        assert_eq!(aging.histogram(), format!("0 | {} 2\n1 | 0\n2 | {} 1\n", bar(40), bar(20)));
+
+        // A 45-year span is shown in bins of three years
+        let references: Vec<Reference> = [2020, 1975].into_iter().map(|year| reference(Some(year))).collect();
+        let histogram = ReferenceAging::compute(2020, &references, 5).histogram();
+        assert_eq!(histogram.lines().count(), 16);
+        assert!(histogram.starts_with("  0-2 | "));
+        assert!(histogram.ends_with("45-47 | ######################################## 1\n"));
+    }
+
+    #[test]
+    fn test_render() {
+        let aging = ReferenceAging::compute(2020, &[reference(Some(2015))], 5);
+        let markdown = aging.render(ReportFormat::Markdown);
+        assert!(markdown.contains("| younger_than_5_years | 0.0% |"));
+        assert!(markdown.contains("```text\n0 | 0\n"));
+        assert!(markdown.ends_with("5 | ######################################## 1\n```\n"));
+        assert!(aging.render(ReportFormat::Csv).ends_with("age,references\n5,1\n"));
+    }
+}
@@ -1,13 +1,143 @@
-use reqwest::Client;
+use std::collections::HashMap;
+#[cfg(feature = "cache")]
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use futures::lock::Mutex as AsyncMutex;
+use futures::stream::{self, Stream, TryStreamExt};
+#[cfg(feature = "cache")]
+use lru::LruCache;
+use reqwest::{Client, Request, RequestBuilder, Response, StatusCode};
 use serde_json::Value;
 use anyhow::{Result, anyhow};
-use log::{debug, info};
+use log::{debug, info, warn};
 
-use crate::models::{Paper, Reference};
+use crate::arxiv::ArxivId;
+use crate::dates::{DateOrder, PartialDate};
+use crate::error::ApiError;
+use crate::metrics::METRICS;
+#[cfg(feature = "native")]
+use crate::config::ApiConfig;
+#[cfg(feature = "native")]
+use crate::throttle::{self, Outcome, Throttle};
+use crate::query::{LiteratureQuery, SearchSort};
+use crate::records::{self, Endpoint, InspireRecord};
+use crate::models::{looks_like_review, normalize_doi, normalize_isbn, Conference, Dataset, Experiment, FieldSources, Funding, Institution, Job, License, Paper, PublicationInfo, Reference, ThesisInfo};
+use crate::warnings::{Warning, WarningKind, Warnings};
+
+/// Number of literature records kept in memory by a client
+#[cfg(feature = "cache")]
+pub const RECORD_CACHE_SIZE: usize = 1024;
+
+/// Hits requested per page by `InspireClient::search_literature_pages`
+pub const SEARCH_PAGE_SIZE: usize = 250;
+
+/// Most hits INSPIRE serves for one search, however it is paged
+pub const MAX_SEARCH_RESULTS: usize = 10_000;
+
+/// Records requested per literature search by `InspireClient::prefetch_records`
+pub const RECORD_BATCH_SIZE: usize = 25;
+
+/// Initial delay between requests (`api.request_delay_ms` in the config)
+pub const DEFAULT_REQUEST_DELAY: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Times a request is retried when INSPIRE cannot be reached at all
+/// (`api.connect_retries` in the config)
+pub const CONNECT_RETRIES: u32 = 4;
+
+/// Wait before the first retry of an unreachable server; doubled on each further retry
+pub const CONNECT_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Times a request is retried when INSPIRE rate limits it (429) or is
+/// temporarily unavailable (502-504) (`api.max_retries` in the config)
+pub const MAX_RETRIES: u32 = 3;
+
+/// Wait before the first retry of an unavailable server without `Retry-After`;
+/// doubled on each further retry
+pub const RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Longest `Retry-After` honored before retrying a rate-limited request
+pub const MAX_RETRY_AFTER: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Largest response body read by default (`api.max_response_mb` in the config)
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 128 * 1024 * 1024;
+
+/// Longest wait for a response body by default (`api.body_timeout_seconds` in the config)
+pub const DEFAULT_BODY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// Failed fetches (or parses) of a literature record after which the client
+/// stops requesting it for the rest of its lifetime
+pub const RECORD_FAILURE_LIMIT: u32 = 2;
+
+/// Consecutive server errors (5xx) after which requests move on to the next mirror
+pub const FAILOVER_SERVER_ERRORS: u32 = 3;
+
+/// Connection pool and protocol settings of the HTTP client (the `[api]`
+/// pool options in the config); unset fields keep reqwest's defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionOptions {
+    /// Idle connections kept open per host (reqwest keeps any number)
+    pub pool_max_idle_per_host: Option<usize>,
+    /// How long an idle connection stays open (reqwest closes it after 90 s)
+    pub pool_idle_timeout: Option<std::time::Duration>,
+    /// Speak HTTP/2 from the first request instead of starting with HTTP/1.1,
+    /// so concurrent requests share one connection
+    pub http2_prior_knowledge: bool,
+}
+
+/// How one API root answered `InspireClient::check_endpoints`
+#[derive(Debug, Clone)]
+pub struct EndpointCheck {
+    pub base_url: String,
+    /// Response time, or why the probe failed
+    pub outcome: std::result::Result<std::time::Duration, String>,
+}
 
 pub struct InspireClient {
     client: Client,
     base_url: String,
+    /// API roots tried in turn when `base_url` is down or keeps failing
+    mirrors: Vec<String>,
+    /// Endpoint requests go to: 0 for `base_url`, then `mirrors` from 1
+    active_endpoint: AtomicUsize,
+    /// Server errors in a row from the active endpoint
+    endpoint_errors: AtomicU32,
+    /// Recently fetched literature records by INSPIRE record ID, so records
+    /// reached from several branches of a network build are fetched once
+    #[cfg(feature = "cache")]
+    records: Mutex<LruCache<String, Arc<Value>>>,
+    /// Per-record gates held while a record is being fetched, so concurrent
+    /// requests for it wait for that fetch instead of repeating it
+    in_flight: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
+    /// Failed fetches and parses per literature record ID
+    record_failures: Mutex<HashMap<String, u32>>,
+    /// Keep the untouched metadata on parsed papers and references (`raw`)
+    keep_raw: bool,
+    /// How ambiguous numeric dates in records are read
+    date_order: DateOrder,
+    /// Spacing between requests, adapted to how INSPIRE responds
+    #[cfg(feature = "native")]
+    throttle: Throttle,
+    /// Retries of requests that never reached the server (DNS, TLS, refused)
+    #[cfg(feature = "native")]
+    connect_retries: u32,
+    /// Retries of requests that were rate limited or found the server
+    /// temporarily unavailable; other HTTP error responses are returned as they are
+    #[cfg(feature = "native")]
+    max_retries: u32,
+    /// Response bodies larger than this are refused
+    max_response_bytes: usize,
+    /// Longest wait for a whole response body once the headers came in
+    #[cfg(feature = "native")]
+    body_timeout: std::time::Duration,
+    /// Longest wait for the response headers of a request
+    request_timeout: Option<std::time::Duration>,
+}
+
+impl Default for InspireClient {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl InspireClient {
@@ -15,68 +145,846 @@ impl InspireClient {
         Self {
             client: Client::new(),
             base_url: "https://inspirehep.net/api".to_string(),
+            mirrors: Vec::new(),
+            active_endpoint: AtomicUsize::new(0),
+            endpoint_errors: AtomicU32::new(0),
+            #[cfg(feature = "cache")]
+            records: Mutex::new(LruCache::new(NonZeroUsize::new(RECORD_CACHE_SIZE).unwrap())),
+            in_flight: Mutex::new(HashMap::new()),
+            record_failures: Mutex::new(HashMap::new()),
+            keep_raw: false,
+            date_order: DateOrder::default(),
+            #[cfg(feature = "native")]
+            throttle: Throttle::new(DEFAULT_REQUEST_DELAY),
+            #[cfg(feature = "native")]
+            connect_retries: CONNECT_RETRIES,
+            #[cfg(feature = "native")]
+            max_retries: MAX_RETRIES,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            #[cfg(feature = "native")]
+            body_timeout: DEFAULT_BODY_TIMEOUT,
+            request_timeout: None,
+        }
+    }
+    
+    /// A client set up from the `[api]` section of the config: API roots,
+    /// connection pool, timeouts, delay between requests and retries. Unset
+    /// settings keep the defaults of `InspireClient::new`.
+    #[cfg(feature = "native")]
+    pub fn from_config(api: &ApiConfig) -> Result<Self> {
+        let mut client = Self::new()
+            .with_base_urls(&api.base_urls())
+            .with_connection_options(&api.connection_options())?
+            .with_max_response_bytes(api.max_response_mb.map_or(DEFAULT_MAX_RESPONSE_BYTES, |mb| mb * 1024 * 1024))
+            .with_body_timeout(api.body_timeout_seconds.map_or(DEFAULT_BODY_TIMEOUT, std::time::Duration::from_secs))
+            .with_date_order(api.date_order.unwrap_or_default());
+        if let Some(delay) = api.request_delay_ms {
+            client = client.with_request_delay(std::time::Duration::from_millis(delay));
+        }
+        if let Some(timeout) = api.timeout_seconds {
+            client = client.with_request_timeout(std::time::Duration::from_secs(timeout));
+        }
+        if let Some(retries) = api.connect_retries {
+            client = client.with_connect_retries(retries);
+        }
+        if let Some(retries) = api.max_retries {
+            client = client.with_max_retries(retries);
+        }
+        Ok(client)
+    }
+    
+    /// Send requests to the first of these API roots, failing over to the
+    /// next one when it cannot be reached or keeps answering with server errors.
+    /// An empty list keeps the INSPIRE API.
+    pub fn with_base_urls(mut self, base_urls: &[String]) -> Self {
+        let mut base_urls = base_urls.iter().map(|url| url.trim_end_matches('/').to_string());
+        if let Some(primary) = base_urls.next() {
+            self.base_url = primary;
+            self.mirrors = base_urls.collect();
         }
+        self
     }
     
-    /// Get paper information by ArXiv ID
+    /// The primary API root followed by its mirrors
+    pub fn base_urls(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.base_url.as_str()).chain(self.mirrors.iter().map(String::as_str))
+    }
+    
+    /// The API root requests currently go to
+    pub fn active_base_url(&self) -> &str {
+        self.endpoint(self.active_endpoint.load(Ordering::Relaxed))
+    }
+    
+    /// Probe every API root with a one-hit search. When the active endpoint
+    /// fails its probe, further requests go to the first one that answered.
+    #[cfg(feature = "native")]
+    pub async fn check_endpoints(&self) -> Vec<EndpointCheck> {
+        let mut checks = Vec::new();
+        for base_url in self.base_urls() {
+            let started = std::time::Instant::now();
+            let request = self.client.get(format!("{}/literature", base_url))
+                .query(&[("size", "1"), ("fields", "control_number")]);
+            let outcome = match request.send().await {
+                Ok(response) if response.status().is_success() => Ok(started.elapsed()),
+                Ok(response) => Err(format!("HTTP {}", response.status())),
+                Err(error) => Err(ApiError::from_request(error, base_url).to_string()),
+            };
+            checks.push(EndpointCheck { base_url: base_url.to_string(), outcome });
+        }
+        
+        let active = self.active_endpoint.load(Ordering::Relaxed);
+        if checks[active].outcome.is_err() {
+            if let Some(healthy) = checks.iter().position(|check| check.outcome.is_ok()) {
+                self.active_endpoint.store(healthy, Ordering::Relaxed);
+                self.endpoint_errors.store(0, Ordering::Relaxed);
+            }
+        }
+        checks
+    }
+    
+    fn endpoint(&self, index: usize) -> &str {
+        match index {
+            0 => &self.base_url,
+            _ => &self.mirrors[index - 1],
+        }
+    }
+    
+    /// Start the adaptive throttle at this delay between requests instead of the default
+    #[cfg(feature = "native")]
+    pub fn with_request_delay(mut self, delay: std::time::Duration) -> Self {
+        self.throttle = Throttle::new(delay);
+        self
+    }
+    
+    /// Retry requests that cannot reach the server this many times instead of
+    /// `CONNECT_RETRIES`, waiting twice as long before each retry
+    #[cfg(feature = "native")]
+    pub fn with_connect_retries(mut self, retries: u32) -> Self {
+        self.connect_retries = retries;
+        self
+    }
+    
+    /// Retry rate-limited (429) and unavailable (502-504) requests this many
+    /// times instead of `MAX_RETRIES`, waiting as `Retry-After` asks or twice
+    /// as long before each retry
+    #[cfg(feature = "native")]
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+    
+    /// Refuse response bodies larger than this many bytes instead of
+    /// `DEFAULT_MAX_RESPONSE_BYTES`
+    pub fn with_max_response_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_response_bytes = max_bytes;
+        self
+    }
+    
+    /// Give up on response bodies that take longer than this to arrive instead
+    /// of `DEFAULT_BODY_TIMEOUT`
+    #[cfg(feature = "native")]
+    pub fn with_body_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.body_timeout = timeout;
+        self
+    }
+    
+    /// Give up on requests whose response headers take longer than this to
+    /// arrive (no limit by default; the browser build ignores it)
+    pub fn with_request_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+    
+    /// Rebuild the HTTP client with these pool and protocol settings
+    #[cfg(feature = "native")]
+    pub fn with_connection_options(mut self, options: &ConnectionOptions) -> Result<Self> {
+        let mut builder = Client::builder();
+        if let Some(max_idle) = options.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        if let Some(timeout) = options.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if options.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        self.client = builder.build()?;
+        Ok(self)
+    }
+    
+    /// Keep the untouched INSPIRE metadata on every parsed paper and reference
+    pub fn with_raw(mut self, keep_raw: bool) -> Self {
+        self.keep_raw = keep_raw;
+        self
+    }
+    
+    /// Read ambiguous numeric dates (`03-04-2023`) day first or month first
+    pub fn with_date_order(mut self, order: DateOrder) -> Self {
+        self.date_order = order;
+        self
+    }
+    
+    /// Get paper information by ArXiv ID. A version suffix (`2301.12345v2`) is
+    /// accepted; INSPIRE keeps one record for all versions.
     pub async fn get_paper_by_arxiv(&self, arxiv_id: &str) -> Result<Paper> {
-        let url = format!("{}/literature", self.base_url);
-        let query = format!("arxiv:{}", arxiv_id);
+        let unversioned = ArxivId::parse(arxiv_id)
+            .map_or_else(|| arxiv_id.to_string(), |id| id.id);
+        let query = format!("arxiv:{}", unversioned);
+        self.search_single(&query).await?
+            .ok_or_else(|| anyhow!("Paper not found with ArXiv ID: {}", arxiv_id))
+    }
+    
+    /// Get paper information by DOI (`10.1103/PhysRevD.1.1`, also with a `doi:`
+    /// or `https://doi.org/` prefix), for papers not on arXiv
+    pub async fn get_paper_by_doi(&self, doi: &str) -> Result<Paper> {
+        let normalized = normalize_doi(doi).ok_or_else(|| anyhow!("Not a DOI: {}", doi))?;
+        let query = format!("doi:\"{}\"", normalized);
+        self.search_single(&query).await?
+            .ok_or_else(|| anyhow!("Paper not found with DOI: {}", doi))
+    }
+    
+    /// Get paper information by INSPIRE TeX key (e.g. `Maldacena:1997re`)
+    pub async fn get_paper_by_texkey(&self, texkey: &str) -> Result<Paper> {
+        let query = format!("texkeys:\"{}\"", texkey);
+        self.search_single(&query).await?
+            .ok_or_else(|| anyhow!("Paper not found with TeX key: {}", texkey))
+    }
+    
+    /// Get up to `limit` papers citing the given INSPIRE record, most cited first
+    pub async fn get_citing_papers(&self, paper_id: &str, limit: usize) -> Result<Vec<Paper>> {
+        let query = format!("refersto:recid:{}", paper_id);
+        self.search_papers(&query, limit, Some("mostcited")).await
+    }
+    
+    /// Get up to `limit` papers citing the given INSPIRE record, most recent first
+    pub async fn get_recent_citing_papers(&self, paper_id: &str, limit: usize) -> Result<Vec<Paper>> {
+        let query = format!("refersto:recid:{}", paper_id);
+        self.search_papers(&query, limit, Some("mostrecent")).await
+    }
+    
+    /// Get all papers citing the given INSPIRE record, most recent first,
+    /// fetching as many result pages as needed (up to `MAX_SEARCH_RESULTS`)
+    pub async fn get_paper_citations(&self, paper_id: &str) -> Result<Vec<Paper>> {
+        let query = LiteratureQuery::new().with_citing(paper_id);
+        let pages: Vec<Vec<Paper>> = self.search_literature_pages(&query, MAX_SEARCH_RESULTS, SearchSort::MostRecent)
+            .try_collect()
+            .await?;
+        let papers: Vec<Paper> = pages.into_iter().flatten().collect();
+        if papers.len() == MAX_SEARCH_RESULTS {
+            warn!("Record {} has more than {} citations; only the most recent are listed", paper_id, MAX_SEARCH_RESULTS);
+        }
+        Ok(papers)
+    }
+    
+    /// Get up to `limit` papers by an author (e.g. `Doe, J.`), most recent first,
+    /// optionally only those from `since` onwards
+    pub async fn get_author_papers(&self, author: &str, since: Option<u32>, limit: usize) -> Result<Vec<Paper>> {
+        let query = LiteratureQuery::new().with_author(author).with_min_year(since);
+        self.search_literature(&query, limit, SearchSort::MostRecent).await
+    }
+    
+    /// Get up to `limit` papers matching a query in INSPIRE search syntax
+    /// (`t "black hole" and a Maldacena`, or plain free text), in INSPIRE's
+    /// default order
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<Paper>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(anyhow!("Empty search query"));
+        }
+        self.search_papers(query, limit, None).await
+    }
+    
+    /// Get up to `limit` papers matching a literature query
+    pub async fn search_literature(&self, query: &LiteratureQuery, limit: usize, sort: SearchSort) -> Result<Vec<Paper>> {
+        self.search_papers(&query.to_string(), limit, Some(sort.as_param())).await
+    }
+    
+    /// Get up to `limit` papers matching a literature query, with a warning
+    /// when the query matched more records than `limit`
+    pub async fn search_literature_with_warnings(&self, query: &LiteratureQuery, limit: usize, sort: SearchSort) -> Result<(Vec<Paper>, Warnings)> {
+        let json = self.search_page_json(&query.to_string(), limit, 1, Some(sort.as_param())).await?;
+        let papers = self.parse_search_results(&json)?;
+        let mut warnings = Warnings::new();
+        if let Some(total) = json["hits"]["total"].as_u64().filter(|&total| total > papers.len() as u64) {
+            warnings.push(Warning::new(
+                WarningKind::TruncatedResults,
+                format!("Showing {} of {} matching papers; raise the limit to get more", papers.len(), total),
+            ));
+        }
+        Ok((papers, warnings))
+    }
+    
+    /// Run a literature search and return the first hit, if any
+    async fn search_single(&self, query: &str) -> Result<Option<Paper>> {
+        Ok(self.search_papers(query, 1, None).await?.into_iter().next())
+    }
+    
+    /// Page through a literature search, yielding up to `limit` papers one page
+    /// of `SEARCH_PAGE_SIZE` at a time, so result sets of tens of thousands of
+    /// records can be processed without holding them all in memory
+    pub fn search_literature_pages<'a>(&'a self, query: &'a LiteratureQuery, limit: usize, sort: SearchSort) -> impl Stream<Item = Result<Vec<Paper>>> + 'a {
+        let query = query.to_string();
+        stream::try_unfold((1, 0), move |(page, fetched)| {
+            let query = query.clone();
+            async move {
+                if fetched >= limit {
+                    return Ok(None);
+                }
+                let mut papers = self.search_page(&query, SEARCH_PAGE_SIZE, page, Some(sort.as_param())).await?;
+                let last_page = papers.len() < SEARCH_PAGE_SIZE;
+                papers.truncate(limit - fetched);
+                if papers.is_empty() {
+                    return Ok(None);
+                }
+                let fetched = if last_page { limit } else { fetched + papers.len() };
+                Ok(Some((papers, (page + 1, fetched))))
+            }
+        })
+    }
+    
+    /// Run a literature search and parse up to `size` hits
+    async fn search_papers(&self, query: &str, size: usize, sort: Option<&str>) -> Result<Vec<Paper>> {
+        self.search_page(query, size, 1, sort).await
+    }
+    
+    /// Run a literature search and parse page `page` (from 1) of `size` hits
+    async fn search_page(&self, query: &str, size: usize, page: usize, sort: Option<&str>) -> Result<Vec<Paper>> {
+        let json = self.search_page_json(query, size, page, sort).await?;
+        self.parse_search_results(&json)
+    }
+    
+    /// Run a literature search and return page `page` (from 1) of `size` hits
+    /// as sent by INSPIRE
+    async fn search_page_json(&self, query: &str, size: usize, page: usize, sort: Option<&str>) -> Result<Value> {
+        let url = format!("{}/literature", self.active_base_url());
         
-        debug!("Searching for paper with query: {}", query);
+        debug!("Searching for papers with query: {} (page {})", query, page);
         
-        let response = self.client
-            .get(&url)
-            .query(&[("q", query.as_str()), ("size", "1")])
-            .send()
-            .await?;
+        let size = size.to_string();
+        let page = page.to_string();
+        let mut params = vec![("q", query), ("size", size.as_str()), ("page", page.as_str())];
+        if let Some(sort) = sort {
+            params.push(("sort", sort));
+        }
+        
+        let response = self.send(self.client.get(&url).query(&params)).await?;
             
         if !response.status().is_success() {
-            return Err(anyhow!("Failed to fetch paper: {}", response.status()));
+            return Err(ApiError::status("Failed to fetch paper", response.status()).into());
         }
         
-        let json: Value = response.json().await?;
+        self.read_json(response, &format!("literature search page {}", page)).await
+    }
+    
+    /// Parse the hits of a literature search response (`{"hits": {"hits": [...]}}`)
+    pub fn parse_search_results(&self, json: &Value) -> Result<Vec<Paper>> {
         let hits = json["hits"]["hits"].as_array()
             .ok_or_else(|| anyhow!("Invalid response format"))?;
             
-        if hits.is_empty() {
-            return Err(anyhow!("Paper not found with ArXiv ID: {}", arxiv_id));
+        hits.iter()
+            .map(|hit| {
+                let paper = self.parse_record(hit)?;
+                self.invalidate_if_updated(&paper);
+                Ok(paper)
+            })
+            .collect()
+    }
+    
+    /// Drop a cached record that is older than a copy of it just seen in search results
+    #[cfg(feature = "cache")]
+    fn invalidate_if_updated(&self, paper: &Paper) {
+        let Some(updated) = &paper.last_updated else { return };
+        let mut records = self.lock_records();
+        let stale = records.peek(&paper.id)
+            .is_some_and(|record| record["updated"].as_str() != Some(updated.as_str()));
+        if stale {
+            debug!("Record {} was updated at {}, dropping the cached copy", paper.id, updated);
+            records.pop(&paper.id);
+        }
+    }
+    
+    #[cfg(not(feature = "cache"))]
+    fn invalidate_if_updated(&self, _paper: &Paper) {}
+    
+    /// Fetch a record of any INSPIRE collection by record ID, as sent by INSPIRE
+    pub async fn get_record(&self, endpoint: Endpoint, id: &str) -> Result<Value> {
+        let url = format!("{}/{}/{}", self.active_base_url(), endpoint, id);
+        
+        debug!("Fetching {} record {}", endpoint, id);
+        
+        let response = self.send(self.client.get(&url)).await?;
+        
+        if !response.status().is_success() {
+            return Err(ApiError::status(format!("Failed to fetch {} record {}", endpoint, id), response.status()).into());
         }
         
-        let paper_data = &hits[0]["metadata"];
-        self.parse_paper(paper_data)
+        self.read_json(response, &format!("{} record {}", endpoint, id)).await
     }
     
-    /// Get references for a paper by its INSPIRE ID
-    pub async fn get_paper_references(&self, paper_id: &str) -> Result<Vec<Reference>> {
-        let url = format!("{}/literature/{}", self.base_url, paper_id);
+    /// Search any INSPIRE collection and return up to `size` hits as sent by INSPIRE
+    pub async fn search_records(&self, endpoint: Endpoint, query: &str, size: usize) -> Result<Vec<Value>> {
+        let url = format!("{}/{}", self.active_base_url(), endpoint);
+        let size = size.to_string();
         
-        debug!("Fetching paper details for ID: {}", paper_id);
+        debug!("Searching {} with query: {}", endpoint, query);
+        
+        let response = self.send(self.client.get(&url).query(&[("q", query), ("size", size.as_str())])).await?;
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
-            
         if !response.status().is_success() {
-            return Err(anyhow!("Failed to fetch paper details: {}", response.status()));
+            return Err(ApiError::status(format!("Failed to search {}", endpoint), response.status()).into());
         }
         
-        let json: Value = response.json().await?;
+        let mut json = self.read_json(response, &format!("{} search", endpoint)).await?;
+        match json["hits"]["hits"].take() {
+            Value::Array(hits) => Ok(hits),
+            _ => Err(anyhow!("Invalid response format")),
+        }
+    }
+    
+    /// Get a typed record by INSPIRE record ID (all digits), or else by the
+    /// name it is usually looked up by (`CERN` for an institution)
+    pub async fn get_typed_record<T: InspireRecord>(&self, name_or_recid: &str) -> Result<T> {
+        let record = if records::is_record_id(name_or_recid) {
+            Some(self.get_record(T::ENDPOINT, name_or_recid).await?)
+        } else {
+            let query = format!("{}:\"{}\"", T::NAME_FIELD, name_or_recid);
+            self.search_records(T::ENDPOINT, &query, 1).await?.into_iter().next()
+        };
+        record.and_then(|record| T::from_metadata(&record["metadata"]))
+            .ok_or_else(|| anyhow!("No {} record found for {}", T::ENDPOINT, name_or_recid))
+    }
+    
+    /// Search a collection and parse up to `size` hits as typed records
+    pub async fn search_typed_records<T: InspireRecord>(&self, query: &str, size: usize) -> Result<Vec<T>> {
+        let hits = self.search_records(T::ENDPOINT, query, size).await?;
+        Ok(hits.iter().filter_map(|hit| T::from_metadata(&hit["metadata"])).collect())
+    }
+    
+    /// Get an institution by INSPIRE record ID (all digits) or by the name
+    /// author affiliations use (its INSPIRE ICN, e.g. `CERN`)
+    pub async fn get_institution(&self, name_or_recid: &str) -> Result<Institution> {
+        self.get_typed_record(name_or_recid).await
+    }
+    
+    /// Get a conference by INSPIRE record ID (all digits), CNUM (`C17-07-05`)
+    /// or acronym (`EPS-HEP2017`)
+    pub async fn get_conference(&self, conference: &str) -> Result<Conference> {
+        if !records::is_cnum(conference) {
+            return self.get_typed_record(conference).await;
+        }
+        let query = format!("cnum:\"{}\"", conference);
+        self.search_typed_records(&query, 1).await?.into_iter().next()
+            .ok_or_else(|| anyhow!("No conferences record found for {}", conference))
+    }
+    
+    /// Get up to `limit` conferences of a series (e.g. `EPS-HEP`), newest first
+    pub async fn get_conference_series(&self, series: &str, limit: usize) -> Result<Vec<Conference>> {
+        let query = format!("series.name:\"{}\"", series);
+        let mut conferences: Vec<Conference> = self.search_typed_records(&query, limit).await?;
+        conferences.sort_by_key(|conference| std::cmp::Reverse(conference.opening_date));
+        Ok(conferences)
+    }
+    
+    /// Get an experiment by INSPIRE record ID (all digits) or name (`CERN-LHC-ATLAS`)
+    pub async fn get_experiment(&self, name_or_recid: &str) -> Result<Experiment> {
+        self.get_typed_record(name_or_recid).await
+    }
+    
+    /// Get up to `limit` job listings matching a query in INSPIRE search syntax
+    /// (e.g. `arxiv_categories:hep-th and status:open`)
+    pub async fn search_jobs(&self, query: &str, limit: usize) -> Result<Vec<Job>> {
+        self.search_typed_records(query, limit).await
+    }
+    
+    /// Get references for a paper by its INSPIRE ID
+    pub async fn get_paper_references(&self, paper_id: &str) -> Result<Vec<Reference>> {
+        Ok(self.get_paper_references_with_warnings(paper_id).await?.0)
+    }
+    
+    /// Get references for a paper by its INSPIRE ID, with a warning for each
+    /// entry that could not be parsed or carries only raw text
+    pub async fn get_paper_references_with_warnings(&self, paper_id: &str) -> Result<(Vec<Reference>, Warnings)> {
+        let json = self.fetch_record(paper_id).await?;
+        Ok(self.parse_references_with_warnings(&json))
+    }
+    
+    /// Parse the reference list of a literature record, skipping entries that
+    /// cannot be parsed
+    pub fn parse_references(&self, record: &Value) -> Vec<Reference> {
+        self.parse_references_with_warnings(record).0
+    }
+    
+    /// Parse the reference list of a literature record, skipping entries that
+    /// cannot be parsed and keeping those with neither title nor identifier,
+    /// with a warning for each of both
+    pub fn parse_references_with_warnings(&self, record: &Value) -> (Vec<Reference>, Warnings) {
         let empty_vec = vec![];
-        let references = json["metadata"]["references"].as_array()
+        let references = record["metadata"]["references"].as_array()
             .unwrap_or(&empty_vec);
             
         info!("Found {} references", references.len());
         
         let mut refs = Vec::new();
-        for reference in references.iter() {
-            if let Ok(parsed_ref) = self.parse_reference(reference) {
-                refs.push(parsed_ref);
+        let mut warnings = Warnings::new();
+        for (index, reference) in references.iter().enumerate() {
+            let position = reference["reference"]["label"].as_str()
+                .map(|label| format!("[{}]", label))
+                .unwrap_or_else(|| format!("#{}", index + 1));
+            match self.parse_reference(reference) {
+                Ok(parsed_ref) => {
+                    let identified = parsed_ref.arxiv_id.is_some() || parsed_ref.inspire_id.is_some() || parsed_ref.doi.is_some();
+                    if !identified && reference["reference"]["title"]["title"].as_str().is_none() {
+                        warnings.push(Warning::new(
+                            WarningKind::UnparsedReference,
+                            format!("Reference {} has neither a title nor an identifier", position),
+                        ).with_item(position));
+                    }
+                    refs.push(parsed_ref);
+                }
+                Err(e) => warnings.push(Warning::new(
+                    WarningKind::UnparsedReference,
+                    format!("Reference {} could not be parsed: {}", position, e),
+                ).with_item(position)),
+            }
+        }
+        
+        (refs, warnings)
+    }
+    
+    /// Fetch the records of many INSPIRE IDs with one literature search per
+    /// batch and cache them, so later lookups by ID need no request of their own.
+    /// IDs already cached are skipped; returns the number of records fetched.
+    /// Without the `cache` feature there is nowhere to keep them, so nothing is sent.
+    pub async fn prefetch_records(&self, paper_ids: &[String]) -> Result<usize> {
+        if !cfg!(feature = "cache") {
+            return Ok(0);
+        }
+        let missing: Vec<&String> = paper_ids.iter()
+            .filter(|id| self.cached_record(id).is_none())
+            .collect();
+        
+        let mut fetched = 0;
+        for batch in missing.chunks(RECORD_BATCH_SIZE) {
+            let query = batch.iter()
+                .map(|id| format!("recid:{}", id))
+                .collect::<Vec<_>>()
+                .join(" or ");
+            
+            debug!("Fetching a batch of {} records", batch.len());
+            
+            let hits = self.search_records(Endpoint::Literature, &query, batch.len()).await?;
+            
+            for hit in hits {
+                if let Some(id) = hit["metadata"]["control_number"].as_u64() {
+                    self.cache_record(id.to_string(), Arc::new(hit));
+                    fetched += 1;
+                }
+            }
+        }
+        
+        Ok(fetched)
+    }
+    
+    /// Get paper information by INSPIRE record ID
+    pub async fn get_paper_by_id(&self, paper_id: &str) -> Result<Paper> {
+        let json = self.fetch_record(paper_id).await?;
+        self.parse_record(&json).inspect_err(|_| self.record_failure(paper_id))
+    }
+    
+    /// Current revision of an INSPIRE record (served from the cache when the
+    /// record was fetched before)
+    pub async fn get_record_revision(&self, paper_id: &str) -> Result<Option<u64>> {
+        Ok(self.fetch_record(paper_id).await?["revision_id"].as_u64())
+    }
+    
+    /// Fetch the full literature record for an INSPIRE record ID, reusing a cached
+    /// copy or waiting for a fetch of the same record already in flight
+    async fn fetch_record(&self, paper_id: &str) -> Result<Arc<Value>> {
+        let failures = self.record_failures(paper_id);
+        if failures >= RECORD_FAILURE_LIMIT {
+            return Err(ApiError::Skipped { what: format!("literature record {}", paper_id), failures }.into());
+        }
+        if let Some(record) = self.cached_record(paper_id) {
+            debug!("Using cached record for ID: {}", paper_id);
+            return Ok(record);
+        }
+        
+        let gate = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            Arc::clone(in_flight.entry(paper_id.to_string()).or_default())
+        };
+        let result = {
+            let _guard = gate.lock().await;
+            match self.cached_record(paper_id) {
+                Some(record) => {
+                    debug!("Reusing record fetched concurrently for ID: {}", paper_id);
+                    Ok(record)
+                }
+                None => self.fetch_record_uncached(paper_id).await,
+            }
+        };
+        
+        // The last request through the gate removes it; gates are only cloned under this lock
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        if Arc::strong_count(&gate) == 2 {
+            in_flight.remove(paper_id);
+        }
+        result
+    }
+    
+    async fn fetch_record_uncached(&self, paper_id: &str) -> Result<Arc<Value>> {
+        let record = self.get_record(Endpoint::Literature, paper_id).await
+            .inspect_err(|_| self.record_failure(paper_id))?;
+        let record = Arc::new(record);
+        self.cache_record(paper_id.to_string(), Arc::clone(&record));
+        Ok(record)
+    }
+    
+    /// Number of failed fetches and parses of a literature record so far
+    pub fn record_failures(&self, paper_id: &str) -> u32 {
+        self.lock_record_failures().get(paper_id).copied().unwrap_or(0)
+    }
+    
+    /// Whether a literature record failed often enough to no longer be requested
+    pub fn is_record_skipped(&self, paper_id: &str) -> bool {
+        self.record_failures(paper_id) >= RECORD_FAILURE_LIMIT
+    }
+    
+    fn record_failure(&self, paper_id: &str) {
+        let mut failures = self.lock_record_failures();
+        let count = failures.entry(paper_id.to_string()).or_default();
+        *count += 1;
+        if *count == RECORD_FAILURE_LIMIT {
+            warn!("Literature record {} failed {} times; not requesting it again", paper_id, count);
+        }
+    }
+    
+    fn lock_record_failures(&self) -> std::sync::MutexGuard<'_, HashMap<String, u32>> {
+        self.record_failures.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    
+    /// A record fetched earlier by this client, if still cached
+    #[cfg(feature = "cache")]
+    fn cached_record(&self, paper_id: &str) -> Option<Arc<Value>> {
+        self.lock_records().get(paper_id).cloned()
+    }
+    
+    #[cfg(not(feature = "cache"))]
+    fn cached_record(&self, _paper_id: &str) -> Option<Arc<Value>> {
+        None
+    }
+    
+    #[cfg(feature = "cache")]
+    fn cache_record(&self, paper_id: String, record: Arc<Value>) {
+        self.lock_records().put(paper_id, record);
+    }
+    
+    #[cfg(not(feature = "cache"))]
+    fn cache_record(&self, _paper_id: String, _record: Arc<Value>) {}
+    
+    #[cfg(feature = "cache")]
+    fn lock_records(&self) -> std::sync::MutexGuard<'_, LruCache<String, Arc<Value>>> {
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    
+    /// Read a JSON response body, refusing bodies over the size limit or slower
+    /// than the body timeout; `what` names the record or search in errors
+    async fn read_json(&self, response: Response, what: &str) -> Result<Value> {
+        #[cfg(feature = "native")]
+        let body = tokio::time::timeout(self.body_timeout, self.read_body(response, what)).await
+            .map_err(|_| ApiError::BodyTimeout { what: what.to_string(), seconds: self.body_timeout.as_secs() })??;
+        #[cfg(not(feature = "native"))]
+        let body = self.read_body(response, what).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+    
+    /// Read a response body of at most `max_response_bytes`, stopping as soon
+    /// as it grows past that
+    async fn read_body(&self, response: Response, what: &str) -> std::result::Result<Vec<u8>, ApiError> {
+        let too_large = || ApiError::TooLarge { what: what.to_string(), limit: self.max_response_bytes };
+        if response.content_length().is_some_and(|length| length > self.max_response_bytes as u64) {
+            return Err(too_large());
+        }
+        
+        // The fetch backend on wasm32 only hands out whole bodies
+        #[cfg(target_arch = "wasm32")]
+        let body = response.bytes().await?.to_vec();
+        #[cfg(not(target_arch = "wasm32"))]
+        let body = {
+            let mut response = response;
+            let mut body = Vec::new();
+            while let Some(chunk) = response.chunk().await? {
+                if body.len() + chunk.len() > self.max_response_bytes {
+                    return Err(too_large());
+                }
+                body.extend_from_slice(&chunk);
+            }
+            body
+        };
+        if body.len() > self.max_response_bytes {
+            return Err(too_large());
+        }
+        Ok(body)
+    }
+    
+    /// Send a request, moving it to the next mirror when its endpoint fails
+    /// over. Any other response, including an HTTP error status, is returned
+    /// to the caller.
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let mut request = request.build()?;
+        let mut failovers_left = self.mirrors.len();
+        loop {
+            // A request with a streamed body cannot be sent twice
+            let retry = if failovers_left > 0 { request.try_clone() } else { None };
+            let endpoint = self.endpoint_of(&request);
+            let result = self.send_with_retries(request).await;
+            let failed_over = endpoint.is_some_and(|endpoint| self.record_endpoint_result(endpoint, &result));
+            match (retry, endpoint) {
+                (Some(retry), Some(endpoint)) if failed_over => {
+                    request = self.rebase(retry, endpoint)?;
+                    failovers_left -= 1;
+                }
+                _ => return Ok(result?),
+            }
+        }
+    }
+    
+    /// Index of the endpoint a request was built for
+    fn endpoint_of(&self, request: &Request) -> Option<usize> {
+        let url = request.url().as_str();
+        (0..=self.mirrors.len())
+            .filter(|&index| url.starts_with(self.endpoint(index)))
+            .max_by_key(|&index| self.endpoint(index).len())
+    }
+    
+    /// Count a result against the endpoint it came from, moving on to the next
+    /// endpoint when that one is unreachable or keeps failing. Returns whether
+    /// the request should be sent again to the new endpoint.
+    fn record_endpoint_result(&self, endpoint: usize, result: &std::result::Result<Response, ApiError>) -> bool {
+        let failed = match result {
+            Err(error) => error.is_connect(),
+            Ok(response) if response.status().is_server_error() => {
+                self.endpoint_errors.fetch_add(1, Ordering::Relaxed) + 1 >= FAILOVER_SERVER_ERRORS
             }
+            Ok(_) => {
+                self.endpoint_errors.store(0, Ordering::Relaxed);
+                false
+            }
+        };
+        if !failed || self.mirrors.is_empty() {
+            return false;
         }
         
-        Ok(refs)
+        // Concurrent failures of the same endpoint move on only once
+        let next = (endpoint + 1) % (self.mirrors.len() + 1);
+        if self.active_endpoint.compare_exchange(endpoint, next, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+            self.endpoint_errors.store(0, Ordering::Relaxed);
+            warn!("{} is failing; switching to {}", self.endpoint(endpoint), self.endpoint(next));
+        }
+        true
+    }
+    
+    /// The same request sent to the active endpoint instead
+    fn rebase(&self, mut request: Request, endpoint: usize) -> Result<Request> {
+        let path = request.url().as_str()[self.endpoint(endpoint).len()..].to_string();
+        *request.url_mut() = format!("{}{}", self.active_base_url(), path).parse()?;
+        Ok(request)
+    }
+    
+    /// Send a request, retrying it with exponential backoff while the server
+    /// cannot be reached (up to `connect_retries` times), and separately while
+    /// it rate limits the request (429, waiting as long as `Retry-After` asks)
+    /// or is temporarily unavailable (502-504, unless there is a mirror to fail
+    /// over to), up to `max_retries` times
+    #[cfg(feature = "native")]
+    async fn send_with_retries(&self, mut request: Request) -> std::result::Result<Response, ApiError> {
+        let (mut connect_attempts, mut connect_delay) = (0, CONNECT_RETRY_DELAY);
+        let (mut http_attempts, mut http_delay) = (0, RETRY_DELAY);
+        loop {
+            // A request with a streamed body cannot be sent twice
+            let Some(retry) = request.try_clone() else { return self.send_once(request).await };
+            let result = self.send_once(request).await;
+            let wait = match &result {
+                Err(error) if error.is_connect() && connect_attempts < self.connect_retries => {
+                    connect_attempts += 1;
+                    debug!("{}; retrying in {} ms ({} of {})", error, connect_delay.as_millis(), connect_attempts, self.connect_retries);
+                    let wait = connect_delay;
+                    connect_delay *= 2;
+                    wait
+                }
+                Ok(response) if self.is_retryable(response.status()) && http_attempts < self.max_retries => {
+                    http_attempts += 1;
+                    let wait = throttle::retry_after(response).map_or(http_delay, |wait| wait.min(MAX_RETRY_AFTER));
+                    debug!("HTTP {}; retrying in {} ms ({} of {})", response.status(), wait.as_millis(), http_attempts, self.max_retries);
+                    http_delay *= 2;
+                    wait
+                }
+                _ => return result,
+            };
+            tokio::time::sleep(wait).await;
+            request = retry;
+        }
+    }
+    
+    #[cfg(not(feature = "native"))]
+    async fn send_with_retries(&self, request: Request) -> std::result::Result<Response, ApiError> {
+        self.send_once(request).await
+    }
+    
+    /// Whether a response status is worth retrying: rate limiting always,
+    /// gateway errors and unavailability only without mirrors, since with
+    /// mirrors repeated server errors fail over instead
+    #[cfg(feature = "native")]
+    fn is_retryable(&self, status: StatusCode) -> bool {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => true,
+            StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => self.mirrors.is_empty(),
+            _ => false,
+        }
+    }
+    
+    /// Send a request once the throttle allows it, recording its latency and any
+    /// rate limiting in the metrics
+    async fn send_once(&self, request: Request) -> std::result::Result<Response, ApiError> {
+        #[cfg(feature = "native")]
+        self.throttle.wait().await;
+        
+        // `Instant` is unavailable on wasm32-unknown-unknown, so latency is only recorded natively
+        #[cfg(not(target_arch = "wasm32"))]
+        let started = std::time::Instant::now();
+        let host = request.url().host_str().unwrap_or(&self.base_url).to_string();
+        let response = self.execute(request).await;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let latency = started.elapsed();
+            METRICS.record_inspire_latency(latency);
+            #[cfg(feature = "native")]
+            self.throttle.record(Outcome::of(response.as_ref().and_then(|response| response.as_ref().ok()), latency));
+        }
+        
+        let Some(response) = response else {
+            let seconds = self.request_timeout.map_or(0, |timeout| timeout.as_secs());
+            return Err(ApiError::ResponseTimeout { host, seconds });
+        };
+        let response = response.map_err(|error| ApiError::from_request(error, &self.base_url))?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            METRICS.record_rate_limited();
+        }
+        Ok(response)
+    }
+    
+    /// Execute a request; `None` when the request timeout passed first
+    #[cfg(feature = "native")]
+    async fn execute(&self, request: Request) -> Option<reqwest::Result<Response>> {
+        match self.request_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.client.execute(request)).await.ok(),
+            None => Some(self.client.execute(request).await),
+        }
+    }
+    
+    #[cfg(not(feature = "native"))]
+    async fn execute(&self, request: Request) -> Option<reqwest::Result<Response>> {
+        Some(self.client.execute(request).await)
     }
     
     /// Parse paper data from INSPIRE API response
@@ -98,6 +1006,8 @@ impl InspireClient {
             })
             .unwrap_or_default();
             
+        let affiliations = parse_affiliations(&data["authors"]);
+            
         let arxiv_id = data["arxiv_eprints"].as_array()
             .and_then(|eprints| eprints.first())
             .and_then(|eprint| eprint["value"].as_str())
@@ -112,21 +1022,80 @@ impl InspireClient {
             })
             .unwrap_or_default();
             
-        let year = data["preprint_date"].as_str()
+        let date = data["preprint_date"].as_str()
             .or_else(|| data["imprints"][0]["date"].as_str())
-            .and_then(|date| date.split('-').next())
-            .and_then(|year_str| year_str.parse().ok());
+            .and_then(|date| PartialDate::parse_with(date, self.date_order));
+        let year = date.map(|date| date.year);
             
+        let texkey = data["texkeys"][0].as_str()
+            .map(|s| s.to_string());
+            
+        let abstract_text = data["abstracts"][0]["value"].as_str()
+            .map(|s| s.to_string());
+            
+        let document_type = parse_document_type(&data["document_type"]);
+        
+        let doi = data["dois"][0]["value"].as_str()
+            .map(|s| s.to_string());
+            
+        // A book record carries its own ISBN; a chapter or proceedings paper
+        // only the `parent_isbn` of its volume
+        let publication_info = with_isbn(
+            parse_publication_info(&data["publication_info"][0]),
+            data["isbns"][0]["value"].as_str(),
+        );
+        
+        let thesis_info = parse_thesis_info(&data["thesis_info"], self.date_order);
+        
+        let license = parse_license(&data["license"]);
+        
+        let funding = parse_funding(&data["funding_info"]);
+        
+        let datasets = parse_datasets(data);
+        
+        let citation_count = data["citation_count"].as_u64()
+            .map(|count| count as u32);
+            
+        let is_review = looks_like_review(&document_type, citation_count, &title);
+        
+        let retracted = data["withdrawn"].as_bool().unwrap_or(false);
+        
         Ok(Paper {
             id,
             title,
             authors,
+            affiliations,
             arxiv_id,
             categories,
             year,
+            date,
+            texkey,
+            abstract_text,
+            document_type,
+            doi,
+            publication_info,
+            thesis_info,
+            datasets,
+            license,
+            funding,
+            citation_count,
+            is_review,
+            retracted,
+            revision: None,
+            last_updated: None,
+            raw: self.keep_raw.then(|| data.clone()),
         })
     }
     
+    /// Parse a whole record (search hit or record response): its metadata plus
+    /// the record-level revision and update time
+    fn parse_record(&self, record: &Value) -> Result<Paper> {
+        let mut paper = self.parse_paper(&record["metadata"])?;
+        paper.revision = record["revision_id"].as_u64();
+        paper.last_updated = record["updated"].as_str().map(|s| s.to_string());
+        Ok(paper)
+    }
+    
     /// Parse reference data from INSPIRE API response
     fn parse_reference(&self, data: &Value) -> Result<Reference> {
         let title = data["reference"]["title"]["title"].as_str()
@@ -146,7 +1115,7 @@ impl InspireClient {
             .map(|s| s.to_string());
             
         let inspire_id = data["record"]["$ref"].as_str()
-            .and_then(|url| url.split('/').last())
+            .and_then(|url| url.split('/').next_back())
             .map(|s| s.to_string());
             
         let categories = data["reference"]["inspire_categories"].as_array()
@@ -158,26 +1127,687 @@ impl InspireClient {
             })
             .unwrap_or_default();
             
-        let year = data["reference"]["imprint"]["date"].as_str()
-            .and_then(|date| date.split('-').next())
-            .and_then(|year_str| year_str.parse().ok());
+        let publication_info = with_isbn(
+            parse_publication_info(&data["reference"]["publication_info"]),
+            data["reference"]["isbn"].as_str(),
+        );
+        
+        let date = data["reference"]["imprint"]["date"].as_str()
+            .and_then(|date| PartialDate::parse_with(date, self.date_order));
+        let year = date.map(|date| date.year)
+            .or_else(|| publication_info.as_ref().and_then(|info| info.year));
             
+        let document_type = parse_document_type(&data["reference"]["document_type"]);
+        
+        let doi = data["reference"]["dois"][0].as_str()
+            .map(|s| s.to_string());
+            
+        let is_review = looks_like_review(&document_type, None, &title);
+        
+        let label = data["reference"]["label"].as_str()
+            .map(|s| s.to_string());
+        
         Ok(Reference {
             title,
             authors,
+            affiliations: Vec::new(),
             arxiv_id,
+            arxiv_version: None,
             inspire_id,
             categories,
             year,
+            date,
+            texkey: None,
+            abstract_text: None,
+            document_type,
+            doi,
+            publication_info,
+            thesis_info: None,
+            license: None,
+            citation_count: None,
+            is_review,
+            retracted: false,
+            label,
+            contexts: Vec::new(),
+            raw: self.keep_raw.then(|| data.clone()),
+            tags: Vec::new(),
+            notes: Vec::new(),
+            open_access: None,
+            sources: FieldSources::default(),
         })
     }
 }
 
+/// Parse `document_type`, which is a list on records and a single string on references
+fn parse_document_type(data: &Value) -> Vec<String> {
+    match data {
+        Value::String(document_type) => vec![document_type.clone()],
+        Value::Array(types) => types.iter()
+            .filter_map(|t| t.as_str())
+            .map(|s| s.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Parse a `publication_info` object, returning `None` if it carries nothing useful
+fn parse_publication_info(data: &Value) -> Option<PublicationInfo> {
+    let string = |field: &str| match &data[field] {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+    
+    let info = PublicationInfo {
+        journal_title: string("journal_title"),
+        journal_volume: string("journal_volume"),
+        journal_issue: string("journal_issue"),
+        page_start: string("page_start"),
+        page_end: string("page_end"),
+        artid: string("artid"),
+        year: data["year"].as_u64().map(|year| year as u32),
+        cnum: string("cnum"),
+        conf_acronym: string("conf_acronym"),
+        parent_title: string("parent_title"),
+        isbn: string("parent_isbn").as_deref().and_then(normalize_isbn),
+        issn: None,
+    };
+    
+    (info != PublicationInfo::default()).then_some(info)
+}
+
+/// Distinct institutions of a record's authors, in author order
+fn parse_affiliations(authors: &Value) -> Vec<String> {
+    let mut affiliations: Vec<String> = Vec::new();
+    let names = authors.as_array().into_iter().flatten()
+        .flat_map(|author| author["affiliations"].as_array().into_iter().flatten())
+        .filter_map(|affiliation| affiliation["value"].as_str());
+    for name in names {
+        if !affiliations.iter().any(|known| known == name) {
+            affiliations.push(name.to_string());
+        }
+    }
+    affiliations
+}
+
+/// Publication info with a valid `isbn` filled in, keeping an ISBN already
+/// set from `parent_isbn`
+fn with_isbn(info: Option<PublicationInfo>, isbn: Option<&str>) -> Option<PublicationInfo> {
+    let Some(isbn) = isbn.and_then(normalize_isbn) else {
+        return info;
+    };
+    let mut info = info.unwrap_or_default();
+    info.isbn.get_or_insert(isbn);
+    Some(info)
+}
+
+/// Collect links to associated datasets: DOIs registered as data material and HEPData records
+fn parse_datasets(data: &Value) -> Vec<Dataset> {
+    let data_dois = data["dois"].as_array()
+        .into_iter()
+        .flatten()
+        .filter(|doi| doi["material"].as_str() == Some("data"))
+        .filter_map(|doi| Some((doi["value"].as_str()?, doi["source"].as_str().unwrap_or("DOI"))))
+        .map(|(doi, source)| Dataset {
+            source: source.to_string(),
+            doi: Some(doi.to_string()),
+            url: Some(format!("https://doi.org/{}", doi)),
+        });
+    
+    let hepdata = data["external_system_identifiers"].as_array()
+        .into_iter()
+        .flatten()
+        .filter(|id| id["schema"].as_str().is_some_and(|schema| schema.eq_ignore_ascii_case("hepdata")))
+        .filter_map(|id| id["value"].as_str())
+        .map(|value| Dataset {
+            source: "HEPData".to_string(),
+            doi: None,
+            url: Some(format!("https://www.hepdata.net/record/{}", value)),
+        });
+    
+    data_dois.chain(hepdata).collect()
+}
+
+/// Parse a `thesis_info` object
+fn parse_thesis_info(data: &Value, order: DateOrder) -> Option<ThesisInfo> {
+    if !data.is_object() {
+        return None;
+    }
+    
+    Some(ThesisInfo {
+        institution: data["institutions"][0]["name"].as_str().map(|s| s.to_string()),
+        degree_type: data["degree_type"].as_str().map(|s| s.to_string()),
+        year: data["date"].as_str()
+            .and_then(|date| PartialDate::parse_with(date, order))
+            .map(|date| date.year),
+    })
+}
+
+/// Parse a record's `license` list, preferring an open-access license when several are given
+fn parse_license(data: &Value) -> Option<License> {
+    let licenses: Vec<License> = data.as_array()?
+        .iter()
+        .map(|license| License {
+            name: license["license"].as_str().map(|s| s.to_string()),
+            url: license["url"].as_str().map(|s| s.to_string()),
+            material: license["material"].as_str().map(|s| s.to_string()),
+        })
+        .collect();
+    
+    let open = licenses.iter().position(|license| license.is_open_access()).unwrap_or(0);
+    licenses.into_iter().nth(open)
+}
+
+/// Parse a record's `funding_info` list, skipping entries that name no agency
+fn parse_funding(data: &Value) -> Vec<Funding> {
+    data.as_array()
+        .map(|funding| {
+            funding.iter()
+                .filter_map(|grant| Some(Funding {
+                    agency: grant["agency"].as_str()?.trim().to_string(),
+                    grant_number: grant["grant_number"].as_str().map(|s| s.to_string()),
+                    project_number: grant["project_number"].as_str().map(|s| s.to_string()),
+                }))
+                .filter(|grant| !grant.agency.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    #[cfg(feature = "cache")]
+    #[tokio::test]
+    async fn test_cached_records_are_not_refetched() {
+        // An unroutable base URL makes any real request fail
+        let client = InspireClient {
+            base_url: "http://127.0.0.1:9".to_string(),
+            #[cfg(feature = "native")]
+            connect_retries: 0,
+            ..InspireClient::new()
+        };
+        let record = json!({
+            "metadata": {
+                "control_number": 42,
+                "titles": [{"title": "Cached Paper"}],
+                "references": [{"reference": {"title": {"title": "Cited"}}}]
+            },
+            "revision_id": 7,
+            "updated": "2024-01-01T00:00:00+00:00"
+        });
+        client.lock_records().put("42".to_string(), Arc::new(record));
+
+        let paper = client.get_paper_by_id("42").await.unwrap();
+        assert_eq!(paper.title, "Cached Paper");
+        assert_eq!(paper.revision, Some(7));
+        assert_eq!(client.get_record_revision("42").await.unwrap(), Some(7));
+
+        assert_eq!(client.get_paper_references("42").await.unwrap().len(), 1);
+        let error = client.get_paper_by_id("43").await.unwrap_err();
+        assert!(error.downcast_ref::<ApiError>().is_some_and(ApiError::is_connect));
+        
+        // A newer copy in search results makes the cached record stale
+        let updated = Paper { id: "42".to_string(), last_updated: Some("2024-02-01T00:00:00+00:00".to_string()), ..Default::default() };
+        client.invalidate_if_updated(&updated);
+        assert!(client.cached_record("42").is_none());
+    }
+
+    #[cfg(feature = "cache")]
+    #[tokio::test]
+    async fn test_concurrent_fetches_of_a_record_are_coalesced() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&requests);
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let counter = Arc::clone(&counter);
+                tokio::spawn(async move {
+                    let mut buffer = [0; 1024];
+                    let _ = stream.read(&mut buffer).await;
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+                    let body = r#"{"metadata":{"control_number":7,"titles":[{"title":"Shared"}]}}"#;
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(), body
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        let client = InspireClient { base_url: format!("http://{}", addr), ..InspireClient::new() };
+        let (first, second) = tokio::join!(client.get_paper_by_id("7"), client.get_paper_by_id("7"));
+
+        assert_eq!(first.unwrap().title, "Shared");
+        assert_eq!(second.unwrap().title, "Shared");
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert!(client.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[cfg(feature = "cache")]
+    #[tokio::test]
+    async fn test_prefetched_records_are_cached() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&requests);
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = [0; 1024];
+                let _ = stream.read(&mut buffer).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                let body = json!({"hits": {"hits": [
+                    {"metadata": {"control_number": 7, "titles": [{"title": "First"}], "references": [{"reference": {"title": {"title": "Cited"}}}]}},
+                    {"metadata": {"control_number": 8, "titles": [{"title": "Second"}]}}
+                ]}}).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(), body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let client = InspireClient { base_url: format!("http://{}", addr), ..InspireClient::new() };
+        let ids = vec!["7".to_string(), "8".to_string()];
+        assert_eq!(client.prefetch_records(&ids).await.unwrap(), 2);
+        assert_eq!(client.get_paper_references("7").await.unwrap().len(), 1);
+        assert_eq!(client.get_paper_by_id("8").await.unwrap().title, "Second");
+
+        // Everything is cached now, so a second prefetch sends nothing
+        assert_eq!(client.prefetch_records(&ids).await.unwrap(), 0);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_paper_by_doi() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let query = Arc::new(Mutex::new(String::new()));
+        let received = Arc::clone(&query);
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 1024];
+            let read = stream.read(&mut buffer).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+            let path = request.split_whitespace().nth(1).unwrap_or_default();
+            let url = reqwest::Url::parse(&format!("http://localhost{}", path)).unwrap();
+            *received.lock().unwrap() = url.query_pairs().find(|(key, _)| key == "q").unwrap().1.into_owned();
+            let body = json!({"hits": {"hits": [{"metadata": {"control_number": 9, "titles": [{"title": "Not on arXiv"}]}}]}}).to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(), body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+
+        let client = InspireClient { base_url: format!("http://{}", addr), ..InspireClient::new() };
+        let paper = client.get_paper_by_doi("https://doi.org/10.1016/0370-2693(86)90001-0").await.unwrap();
+        assert_eq!(paper.title, "Not on arXiv");
+        assert_eq!(*query.lock().unwrap(), "doi:\"10.1016/0370-2693(86)90001-0\"");
+        assert!(client.get_paper_by_doi("hep-th/9711200").await.unwrap_err().to_string().contains("Not a DOI"));
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let params = Arc::new(Mutex::new(Vec::new()));
+        let received = Arc::clone(&params);
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 1024];
+            let read = stream.read(&mut buffer).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+            let path = request.split_whitespace().nth(1).unwrap_or_default();
+            let url = reqwest::Url::parse(&format!("http://localhost{}", path)).unwrap();
+            *received.lock().unwrap() = url.query_pairs().map(|(key, value)| (key.into_owned(), value.into_owned())).collect();
+            let body = json!({"hits": {"hits": [
+                {"metadata": {"control_number": 1, "titles": [{"title": "First"}]}},
+                {"metadata": {"control_number": 2, "titles": [{"title": "Second"}]}}
+            ]}}).to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(), body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+
+        let client = InspireClient { base_url: format!("http://{}", addr), ..InspireClient::new() };
+        let papers = client.search(" t \"black hole\" and a Maldacena ", 2).await.unwrap();
+        assert_eq!(papers.iter().map(|p| p.title.as_str()).collect::<Vec<_>>(), ["First", "Second"]);
+        let params = params.lock().unwrap().clone();
+        assert!(params.contains(&("q".to_string(), "t \"black hole\" and a Maldacena".to_string())));
+        assert!(params.contains(&("size".to_string(), "2".to_string())));
+        assert!(!params.iter().any(|(key, _)| key == "sort"));
+        assert!(client.search("  ", 10).await.is_err());
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn test_unreachable_server_is_retried() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Take a free port, then only start listening on it after the first attempt failed
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        tokio::spawn(async move {
+            tokio::time::sleep(CONNECT_RETRY_DELAY / 2).await;
+            let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 1024];
+            let _ = stream.read(&mut buffer).await;
+            let body = r#"{"metadata":{"control_number":7,"titles":[{"title":"Back online"}]}}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(), body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+
+        let client = InspireClient { base_url: format!("http://{}", addr), ..InspireClient::new() };
+        assert_eq!(client.get_paper_by_id("7").await.unwrap().title, "Back online");
+
+        // Without connect retries the first failure is returned, whatever the HTTP retry budget
+        let client = InspireClient { base_url: "http://127.0.0.1:9".to_string(), ..InspireClient::new() }
+            .with_connect_retries(0)
+            .with_max_retries(5);
+        let error = client.get_paper_by_id("8").await.unwrap_err();
+        assert!(error.to_string().starts_with("cannot reach 127.0.0.1"));
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn test_rate_limited_requests_are_retried() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Rate limits the first request, then answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&requests);
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = [0; 1024];
+                let _ = stream.read(&mut buffer).await;
+                let response = match counter.fetch_add(1, Ordering::SeqCst) {
+                    0 => "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 1\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                    _ => {
+                        let body = r#"{"metadata":{"control_number":7,"titles":[{"title":"After the wait"}]}}"#;
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            body.len(), body
+                        )
+                    }
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let client = InspireClient { base_url: format!("http://{}", addr), ..InspireClient::new() };
+        let started = std::time::Instant::now();
+        assert_eq!(client.get_paper_by_id("7").await.unwrap().title, "After the wait");
+        assert!(started.elapsed() >= std::time::Duration::from_secs(1));
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // Unavailability is retried up to the HTTP limit, not the connect one; other server errors are not
+        let (base_url, requests) = serve_status("503 Service Unavailable").await;
+        let client = InspireClient::new().with_base_urls(&[base_url]).with_max_retries(1).with_connect_retries(5);
+        assert!(client.get_paper_by_id("7").await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        let (base_url, requests) = serve_status("500 Internal Server Error").await;
+        let client = InspireClient::new().with_base_urls(&[base_url]);
+        assert!(client.get_paper_by_id("7").await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    /// A server answering every request with `status` and a one-record body,
+    /// counting the requests
+    #[cfg(feature = "native")]
+    async fn serve_status(status: &'static str) -> (String, Arc<AtomicUsize>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&requests);
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = [0; 1024];
+                let _ = stream.read(&mut buffer).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                let body = r#"{"metadata":{"control_number":7,"titles":[{"title":"Mirrored"}]},"hits":{"hits":[]}}"#;
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (format!("http://{}/api", addr), requests)
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn test_failover_to_mirror() {
+        // An unreachable primary fails over on the first request
+        let (mirror, _) = serve_status("200 OK").await;
+        let client = InspireClient::new()
+            .with_base_urls(&["http://127.0.0.1:9/api".to_string(), format!("{}/", mirror)])
+            .with_connect_retries(0);
+        assert_eq!(client.get_paper_by_id("7").await.unwrap().title, "Mirrored");
+        assert_eq!(client.active_base_url(), mirror);
+
+        // A primary answering with server errors fails over after a few of them
+        let (primary, primary_requests) = serve_status("503 Service Unavailable").await;
+        let (mirror, mirror_requests) = serve_status("200 OK").await;
+        let client = InspireClient::new().with_base_urls(&[primary.clone(), mirror.clone()]);
+        for id in 1..FAILOVER_SERVER_ERRORS {
+            assert!(client.get_paper_by_id(&id.to_string()).await.is_err());
+        }
+        assert_eq!(client.active_base_url(), primary);
+        assert_eq!(client.get_paper_by_id("7").await.unwrap().title, "Mirrored");
+        assert_eq!(client.active_base_url(), mirror);
+        assert_eq!(primary_requests.load(Ordering::SeqCst), FAILOVER_SERVER_ERRORS as usize);
+        assert_eq!(mirror_requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn test_response_limits() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (base_url, _) = serve_status("200 OK").await;
+        let client = InspireClient::new().with_base_urls(&[base_url]).with_max_response_bytes(16);
+        let error = client.get_paper_by_id("7").await.unwrap_err();
+        assert_eq!(error.to_string(), "literature record 7 is larger than the 16-byte response limit");
+        assert!(client.cached_record("7").is_none());
+
+        // Headers at once, then a body that never finishes
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 1024];
+            let _ = stream.read(&mut buffer).await;
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 100\r\n\r\n{").await;
+            tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+        });
+        let client = InspireClient { base_url: format!("http://{}", addr), ..InspireClient::new() }
+            .with_body_timeout(std::time::Duration::from_millis(200));
+        let error = client.get_paper_by_id("8").await.unwrap_err();
+        assert!(matches!(error.downcast_ref::<ApiError>(), Some(ApiError::BodyTimeout { .. })));
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn test_from_config() {
+        let (primary, _) = serve_status("200 OK").await;
+        let api = ApiConfig {
+            base_urls: Some(vec![format!("{}/", primary), "https://mirror.example/api".to_string()]),
+            connect_retries: Some(2),
+            max_retries: Some(1),
+            timeout_seconds: Some(5),
+            ..Default::default()
+        };
+        let client = InspireClient::from_config(&api).unwrap();
+        assert_eq!(client.base_urls().collect::<Vec<_>>(), [primary.as_str(), "https://mirror.example/api"]);
+        assert_eq!((client.connect_retries, client.max_retries), (2, 1));
+        assert_eq!(client.request_timeout, Some(std::time::Duration::from_secs(5)));
+        assert_eq!(client.body_timeout, DEFAULT_BODY_TIMEOUT);
+
+        let defaults = InspireClient::from_config(&ApiConfig { base_url: None, max_retries: None, timeout_seconds: None, ..Default::default() }).unwrap();
+        assert_eq!(defaults.active_base_url(), "https://inspirehep.net/api");
+        assert_eq!((defaults.connect_retries, defaults.max_retries), (CONNECT_RETRIES, MAX_RETRIES));
+        assert_eq!(defaults.request_timeout, None);
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn test_request_timeout() {
+        use tokio::io::AsyncReadExt;
+
+        // Accepts the request but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 1024];
+            let _ = stream.read(&mut buffer).await;
+            tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+        });
+        let client = InspireClient { base_url: format!("http://{}", addr), ..InspireClient::new() }
+            .with_request_timeout(std::time::Duration::from_millis(200));
+        let error = client.get_paper_by_id("9").await.unwrap_err();
+        assert!(matches!(error.downcast_ref::<ApiError>(), Some(ApiError::ResponseTimeout { .. })));
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn test_check_endpoints() {
+        let (primary, _) = serve_status("503 Service Unavailable").await;
+        let (mirror, _) = serve_status("200 OK").await;
+        let client = InspireClient::new().with_base_urls(&[primary.clone(), mirror.clone()]);
+        assert_eq!(client.base_urls().collect::<Vec<_>>(), [primary.as_str(), mirror.as_str()]);
+
+        let checks = client.check_endpoints().await;
+        assert_eq!(checks[0].outcome, Err("HTTP 503 Service Unavailable".to_string()));
+        assert!(checks[1].outcome.is_ok());
+        assert_eq!(client.active_base_url(), mirror);
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn test_connection_pool_options() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Answers every request on a kept-alive connection and counts connections
+        async fn serve() -> (String, Arc<AtomicUsize>) {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let connections = Arc::new(AtomicUsize::new(0));
+            let counter = Arc::clone(&connections);
+            tokio::spawn(async move {
+                loop {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    tokio::spawn(async move {
+                        let mut buffer = [0; 1024];
+                        while stream.read(&mut buffer).await.unwrap_or(0) > 0 {
+                            let body = r#"{"metadata":{"control_number":7,"titles":[{"title":"Pooled"}]}}"#;
+                            let response = format!(
+                                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                                body.len(), body
+                            );
+                            if stream.write_all(response.as_bytes()).await.is_err() {
+                                break;
+                            }
+                        }
+                    });
+                }
+            });
+            (format!("http://{}", addr), connections)
+        }
+
+        for (options, expected) in [
+            (ConnectionOptions::default(), 1),
+            (ConnectionOptions { pool_max_idle_per_host: Some(0), ..Default::default() }, 2),
+        ] {
+            let (base_url, connections) = serve().await;
+            let client = InspireClient { base_url, ..InspireClient::new() }.with_connection_options(&options).unwrap();
+            // Different IDs, so the second request is not served from the record cache
+            assert_eq!(client.get_paper_by_id("7").await.unwrap().title, "Pooled");
+            assert_eq!(client.get_paper_by_id("8").await.unwrap().title, "Pooled");
+            assert_eq!(connections.load(Ordering::SeqCst), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_literature_pages() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = [0; 1024];
+                let read = stream.read(&mut buffer).await.unwrap_or(0);
+                // A full first page, then a short last one
+                let (first, count) = if String::from_utf8_lossy(&buffer[..read]).contains("page=1") {
+                    (1, SEARCH_PAGE_SIZE)
+                } else {
+                    (SEARCH_PAGE_SIZE + 1, 3)
+                };
+                let hits: Vec<Value> = (first..first + count)
+                    .map(|id| json!({"metadata": {"control_number": id, "titles": [{"title": "Paper"}]}}))
+                    .collect();
+                let body = json!({"hits": {"hits": hits, "total": SEARCH_PAGE_SIZE + 3}}).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(), body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let client = InspireClient { base_url: format!("http://{}", addr), ..InspireClient::new() };
+        let query = LiteratureQuery::new().with_author("Doe, J.");
+        let pages: Vec<Vec<Paper>> = client.search_literature_pages(&query, 1000, SearchSort::MostRecent).try_collect().await.unwrap();
+        assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), [SEARCH_PAGE_SIZE, 3]);
+        assert_eq!(pages[1][0].id, (SEARCH_PAGE_SIZE + 1).to_string());
+
+        let pages: Vec<Vec<Paper>> = client.search_literature_pages(&query, 10, SearchSort::MostRecent).try_collect().await.unwrap();
+        assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), [10]);
+
+        let citations = client.get_paper_citations("42").await.unwrap();
+        assert_eq!(citations.len(), SEARCH_PAGE_SIZE + 3);
+
+        // Only the first page is fetched, so the last three matches are missing
+        let (papers, warnings) = client.search_literature_with_warnings(&query, SEARCH_PAGE_SIZE, SearchSort::MostRecent).await.unwrap();
+        assert_eq!(papers.len(), SEARCH_PAGE_SIZE);
+        assert_eq!(warnings.count(WarningKind::TruncatedResults), 1);
+    }
+
     #[test]
     fn test_parse_paper() {
         let client = InspireClient::new();
@@ -185,15 +1815,17 @@ mod tests {
             "control_number": 123456,
             "titles": [{"title": "Test Paper Title"}],
             "authors": [
-                {"full_name": "John Doe"},
-                {"full_name": "Jane Smith"}
+                {"full_name": "John Doe", "affiliations": [{"value": "CERN"}, {"value": "DESY"}]},
+                {"full_name": "Jane Smith", "affiliations": [{"value": "CERN"}]}
             ],
             "arxiv_eprints": [{"value": "2301.12345"}],
             "inspire_categories": [
                 {"term": "hep-th"},
                 {"term": "hep-ph"}
             ],
-            "preprint_date": "2023-01-15"
+            "preprint_date": "2023-01-15",
+            "texkeys": ["Doe:2023abc", "Doe:2023xyz"],
+            "abstracts": [{"source": "arXiv", "value": "We study things."}]
         });
 
         let paper = client.parse_paper(&paper_data).unwrap();
@@ -201,9 +1833,13 @@ mod tests {
         assert_eq!(paper.id, "123456");
         assert_eq!(paper.title, "Test Paper Title");
         assert_eq!(paper.authors, vec!["John Doe", "Jane Smith"]);
+        assert_eq!(paper.affiliations, vec!["CERN", "DESY"]);
         assert_eq!(paper.arxiv_id, Some("2301.12345".to_string()));
         assert_eq!(paper.categories, vec!["hep-th", "hep-ph"]);
         assert_eq!(paper.year, Some(2023));
+        assert_eq!(paper.date.unwrap().to_string(), "2023-01-15");
+        assert_eq!(paper.texkey, Some("Doe:2023abc".to_string()));
+        assert_eq!(paper.abstract_text, Some("We study things.".to_string()));
     }
 
     #[test]
@@ -215,7 +1851,8 @@ mod tests {
                 "authors": [{"full_name": "Alice Cooper"}],
                 "arxiv_eprint": "1234.5678",
                 "inspire_categories": [{"term": "hep-ex"}],
-                "imprint": {"date": "2022-05-10"}
+                "imprint": {"date": "2022-05-10"},
+                "label": "42"
             },
             "record": {"$ref": "https://inspirehep.net/api/literature/789012"}
         });
@@ -228,6 +1865,268 @@ mod tests {
         assert_eq!(reference.inspire_id, Some("789012".to_string()));
         assert_eq!(reference.categories, vec!["hep-ex"]);
         assert_eq!(reference.year, Some(2022));
+        assert_eq!(reference.date.unwrap().to_string(), "2022-05-10");
+        assert_eq!(reference.label, Some("42".to_string()));
+        assert_eq!(reference.raw, None);
+        
+        let reference = InspireClient::new().with_raw(true).parse_reference(&ref_data).unwrap();
+        assert_eq!(reference.raw, Some(ref_data));
+    }
+
+    #[test]
+    fn test_parse_thesis() {
+        let client = InspireClient::new();
+        let paper_data = json!({
+            "control_number": 1766244,
+            "titles": [{"title": "Aspects of Holography"}],
+            "document_type": ["thesis"],
+            "thesis_info": {
+                "date": "2019-06",
+                "degree_type": "phd",
+                "institutions": [{"name": "Princeton U."}]
+            }
+        });
+
+        let paper = client.parse_paper(&paper_data).unwrap();
+        
+        assert_eq!(paper.document_type, vec!["thesis"]);
+        let thesis = paper.thesis_info.unwrap();
+        assert_eq!(thesis.institution, Some("Princeton U.".to_string()));
+        assert_eq!(thesis.degree_type, Some("phd".to_string()));
+        assert_eq!(thesis.year, Some(2019));
+    }
+
+    #[test]
+    fn test_parse_conference_paper() {
+        let client = InspireClient::new();
+        let paper_data = json!({
+            "control_number": 1632870,
+            "titles": [{"title": "Status of the Experiment"}],
+            "document_type": ["conference paper"],
+            "dois": [{"value": "10.22323/1.314.0001"}],
+            "publication_info": [{
+                "cnum": "C17-07-05",
+                "conf_acronym": "EPS-HEP2017",
+                "journal_title": "PoS",
+                "journal_volume": "EPS-HEP2017",
+                "artid": "001",
+                "year": 2017
+            }]
+        });
+
+        let paper = client.parse_paper(&paper_data).unwrap();
+        
+        assert_eq!(paper.doi, Some("10.22323/1.314.0001".to_string()));
+        let info = paper.publication_info.unwrap();
+        assert_eq!(info.cnum, Some("C17-07-05".to_string()));
+        assert_eq!(info.conf_acronym, Some("EPS-HEP2017".to_string()));
+        assert_eq!(info.artid, Some("001".to_string()));
+        assert!(paper.thesis_info.is_none());
+        assert!(paper.license.is_none());
+    }
+
+    #[test]
+    fn test_parse_license() {
+        let client = InspireClient::new();
+        let paper_data = json!({
+            "control_number": 2000000,
+            "titles": [{"title": "Open Paper"}],
+            "license": [
+                {"license": "arXiv nonexclusive-distrib 1.0", "material": "preprint",
+                 "url": "http://arxiv.org/licenses/nonexclusive-distrib/1.0/"},
+                {"license": "CC BY 4.0", "material": "publication",
+                 "url": "https://creativecommons.org/licenses/by/4.0/"}
+            ]
+        });
+
+        let license = client.parse_paper(&paper_data).unwrap().license.unwrap();
+        assert_eq!(license.name, Some("CC BY 4.0".to_string()));
+        assert_eq!(license.material, Some("publication".to_string()));
+        assert!(license.is_open_access());
+
+        let license = parse_license(&json!([{"license": "arXiv nonexclusive-distrib 1.0"}])).unwrap();
+        assert!(!license.is_open_access());
+    }
+
+    #[test]
+    fn test_parse_funding() {
+        let client = InspireClient::new();
+        let paper_data = json!({
+            "control_number": 2000001,
+            "titles": [{"title": "Funded Paper"}],
+            "funding_info": [
+                {"agency": "European Research Council", "grant_number": "101002846", "project_number": "ERC-2020-COG"},
+                {"agency": "DOE", "grant_number": "DE-SC0011632"},
+                {"grant_number": "12345"}
+            ]
+        });
+
+        let funding = client.parse_paper(&paper_data).unwrap().funding;
+        assert_eq!(funding.len(), 2);
+        assert_eq!(funding[0].agency, "European Research Council");
+        assert_eq!(funding[0].project_number.as_deref(), Some("ERC-2020-COG"));
+        assert_eq!(funding[1].grant_number.as_deref(), Some("DE-SC0011632"));
+        assert!(parse_funding(&Value::Null).is_empty());
+    }
+
+    #[test]
+    fn test_parse_references_with_warnings() {
+        let client = InspireClient::new();
+        let record = json!({"metadata": {"references": [
+            {"reference": {"title": {"title": "Titled"}, "label": "1"}},
+            {"reference": {"arxiv_eprint": "2301.12345", "label": "2"}},
+            {"reference": {"misc": ["Private communication"], "label": "3"}},
+            {"raw_refs": [{"value": "J. Doe, unpublished"}]}
+        ]}});
+
+        let (references, warnings) = client.parse_references_with_warnings(&record);
+        assert_eq!(references.len(), 4);
+        let items: Vec<_> = warnings.iter().map(|w| (w.kind, w.item.as_deref())).collect();
+        assert_eq!(items, [
+            (WarningKind::UnparsedReference, Some("[3]")),
+            (WarningKind::UnparsedReference, Some("#4")),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_reference_proceedings() {
+        let client = InspireClient::new();
+        let ref_data = json!({
+            "reference": {
+                "title": {"title": "A Talk"},
+                "document_type": "conference paper",
+                "dois": ["10.1000/xyz"],
+                "isbn": "978-0-521-67053-1",
+                "publication_info": {
+                    "parent_title": "Proceedings of Something",
+                    "page_start": "12",
+                    "year": 2010
+                }
+            }
+        });
+
+        let reference = client.parse_reference(&ref_data).unwrap();
+        
+        assert_eq!(reference.document_type, vec!["conference paper"]);
+        assert_eq!(reference.doi, Some("10.1000/xyz".to_string()));
+        assert_eq!(reference.year, Some(2010));
+        assert_eq!(reference.bibtex_entry_type(), "inproceedings");
+        assert!(reference.to_bibtex().contains("booktitle = {Proceedings of Something}"));
+        assert_eq!(reference.publication_info.unwrap().isbn, Some("9780521670531".to_string()));
+    }
+
+    #[test]
+    fn test_parse_isbns() {
+        let client = InspireClient::new();
+        let book = json!({
+            "control_number": 1,
+            "titles": [{"title": "String Theory"}],
+            "document_type": ["book"],
+            "isbns": [{"value": "9780521670531", "medium": "print"}]
+        });
+        let info = client.parse_paper(&book).unwrap().publication_info.unwrap();
+        assert_eq!(info.isbn, Some("9780521670531".to_string()));
+
+        let chapter = json!({
+            "control_number": 2,
+            "titles": [{"title": "A Chapter"}],
+            "document_type": ["book chapter"],
+            "publication_info": [{"parent_isbn": "0-8044-2957-X", "page_start": "1"}]
+        });
+        let info = client.parse_paper(&chapter).unwrap().publication_info.unwrap();
+        assert_eq!(info.isbn, Some("080442957X".to_string()));
+
+        let invalid = json!({"control_number": 3, "titles": [{"title": "T"}], "isbns": [{"value": "123"}]});
+        assert!(client.parse_paper(&invalid).unwrap().publication_info.is_none());
+    }
+
+    #[test]
+    fn test_parse_review() {
+        let client = InspireClient::new();
+        let paper_data = json!({
+            "control_number": 1,
+            "titles": [{"title": "TASI Lectures on the Holographic Principle"}],
+            "document_type": ["article"],
+            "citation_count": 2500
+        });
+
+        let paper = client.parse_paper(&paper_data).unwrap();
+        assert_eq!(paper.citation_count, Some(2500));
+        assert!(paper.is_review);
+
+        let ref_data = json!({
+            "reference": {
+                "title": {"title": "Particle physics overview"},
+                "document_type": "review"
+            }
+        });
+        assert!(client.parse_reference(&ref_data).unwrap().is_review);
+        assert!(!paper.retracted);
+    }
+
+    #[test]
+    fn test_parse_malformed_records() {
+        let client = InspireClient::new();
+        // Fields of the wrong type or shape are ignored, never a panic
+        let paper_data = json!({
+            "control_number": 3,
+            "titles": "not a list",
+            "authors": [{"full_name": 7}, "Doe, J.", null],
+            "arxiv_eprints": {"value": "2301.12345"},
+            "preprint_date": "",
+            "publication_info": [{"year": "2020", "journal_volume": 12}],
+            "thesis_info": [],
+            "license": {"license": "CC BY"},
+            "citation_count": -1,
+            "withdrawn": "yes"
+        });
+        let paper = client.parse_paper(&paper_data).unwrap();
+        assert_eq!(paper.title, "Unknown Title");
+        assert!(paper.authors.is_empty());
+        assert_eq!(paper.citation_count, None);
+        assert!(client.parse_paper(&json!([1, 2, 3])).is_err());
+
+        for reference in [json!(null), json!("text"), json!({"reference": []}), json!({"record": {"$ref": 5}})] {
+            assert!(client.parse_reference(&reference).is_ok());
+        }
+        assert!(client.parse_search_results(&json!({"hits": {"hits": [{"metadata": null}]}})).is_err());
+        assert!(client.parse_references(&json!({"metadata": {"references": "none"}})).is_empty());
+    }
+
+    #[test]
+    fn test_parse_withdrawn() {
+        let client = InspireClient::new();
+        let paper_data = json!({
+            "control_number": 2,
+            "titles": [{"title": "A withdrawn preprint"}],
+            "withdrawn": true
+        });
+        assert!(client.parse_paper(&paper_data).unwrap().retracted);
+    }
+
+    #[test]
+    fn test_parse_datasets() {
+        let client = InspireClient::new();
+        let paper_data = json!({
+            "control_number": 1234567,
+            "titles": [{"title": "Measurement with public data"}],
+            "dois": [
+                {"value": "10.1007/JHEP01(2020)001", "material": "publication"},
+                {"value": "10.17182/hepdata.12345.v1", "material": "data", "source": "HEPData"}
+            ],
+            "external_system_identifiers": [
+                {"schema": "HEPDATA", "value": "ins1234567"},
+                {"schema": "CDS", "value": "2700000"}
+            ]
+        });
+
+        let paper = client.parse_paper(&paper_data).unwrap();
+        
+        assert_eq!(paper.datasets.len(), 2);
+        assert_eq!(paper.datasets[0].doi, Some("10.17182/hepdata.12345.v1".to_string()));
+        assert_eq!(paper.datasets[0].source, "HEPData");
+        assert_eq!(paper.datasets[1].doi, None);
+        assert_eq!(paper.datasets[1].url, Some("https://www.hepdata.net/record/ins1234567".to_string()));
     }
 
     #[test]
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use log::debug;
+use regex::Regex;
+use reqwest::Client;
+
+use crate::models::{DataSource, Reference};
+
+/// Most identifiers sent to the arXiv API in one `id_list`
+const ID_LIST_SIZE: usize = 100;
+
+lazy_static! {
+    /// New-style (`2301.12345`) and old-style (`hep-th/9711200`) identifiers,
+    /// with an optional version suffix
+    static ref ARXIV_ID_RE: Regex = Regex::new(
+        r"^(\d{4}\.\d{4,5}|[a-z-]+(?:\.[A-Z]{2})?/\d{7})(?:v(\d+))?$"
+    ).unwrap();
+
+    /// Entry IDs of an arXiv API feed, which always carry the latest version
+    static ref ENTRY_ID_RE: Regex = Regex::new(
+        r"<id>https?://arxiv\.org/abs/([^<]+?)v(\d+)</id>"
+    ).unwrap();
+}
+
+/// An arXiv identifier, optionally pinned to one version (`2301.12345v2`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArxivId {
+    /// The identifier without version, e.g. `2301.12345` or `hep-th/9711200`
+    pub id: String,
+    pub version: Option<u32>,
+}
+
+impl ArxivId {
+    /// Parse an identifier, accepting an `arXiv:` prefix or an abs-page URL
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let text = ["https://arxiv.org/abs/", "http://arxiv.org/abs/", "arXiv:", "arxiv:"].iter()
+            .find_map(|prefix| text.strip_prefix(prefix))
+            .unwrap_or(text);
+        let caps = ARXIV_ID_RE.captures(text)?;
+        Some(Self {
+            id: caps[1].to_string(),
+            version: caps.get(2).and_then(|version| version.as_str().parse().ok()),
+        })
+    }
+
+    /// The same work without a pinned version
+    pub fn unversioned(&self) -> Self {
+        Self { id: self.id.clone(), version: None }
+    }
+}
+
+impl fmt::Display for ArxivId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.version {
+            Some(version) => write!(f, "{}v{}", self.id, version),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+impl FromStr for ArxivId {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Self::parse(text).ok_or_else(|| anyhow!("Not an arXiv identifier: {}", text))
+    }
+}
+
+/// Client for the arXiv API, used for what INSPIRE does not record (the
+/// versions of a preprint)
+pub struct ArxivClient {
+    client: Client,
+    base_url: String,
+}
+
+impl Default for ArxivClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArxivClient {
+    pub fn new() -> Self {
+        Self {
+            client: Client::new(),
+            base_url: "https://export.arxiv.org/api/query".to_string(),
+        }
+    }
+
+    /// Use a different API endpoint (e.g. a mirror or a test server)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// The latest version of each identifier (given without version), keyed by
+    /// identifier. Identifiers arXiv does not know are left out.
+    pub async fn latest_versions(&self, ids: &[String]) -> Result<HashMap<String, u32>> {
+        let mut versions = HashMap::new();
+        for chunk in ids.chunks(ID_LIST_SIZE) {
+            debug!("Looking up the latest versions of {} arXiv preprints", chunk.len());
+            let max_results = chunk.len().to_string();
+            let response = self.client.get(&self.base_url)
+                .query(&[("id_list", chunk.join(",")), ("max_results", max_results)])
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(anyhow!("Failed to query the arXiv API: {}", response.status()));
+            }
+
+            versions.extend(parse_latest_versions(&response.text().await?));
+        }
+        Ok(versions)
+    }
+
+    /// Pin every reference with an arXiv ID but no version to the latest
+    /// version. Returns the number of references pinned.
+    pub async fn pin_versions(&self, references: &mut [Reference]) -> Result<usize> {
+        let ids: Vec<String> = references.iter()
+            .filter(|reference| reference.arxiv_version.is_none())
+            .filter_map(|reference| reference.arxiv_id.clone())
+            .collect();
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let versions = self.latest_versions(&ids).await?;
+        let mut pinned = 0;
+        for reference in references.iter_mut().filter(|reference| reference.arxiv_version.is_none()) {
+            if let Some(version) = reference.arxiv_id.as_ref().and_then(|id| versions.get(id)) {
+                reference.arxiv_version = Some(*version);
+                reference.sources.arxiv_version_source = Some(DataSource::Arxiv);
+                pinned += 1;
+            }
+        }
+        Ok(pinned)
+    }
+}
+
+/// Read `identifier -> latest version` from an arXiv API Atom feed
+fn parse_latest_versions(feed: &str) -> HashMap<String, u32> {
+    ENTRY_ID_RE.captures_iter(feed)
+        .filter_map(|caps| Some((caps[1].to_string(), caps[2].parse().ok()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_arxiv_id() {
+        let pinned = ArxivId::parse("2301.12345v2").unwrap();
+        assert_eq!(pinned, ArxivId { id: "2301.12345".to_string(), version: Some(2) });
+        assert_eq!(pinned.to_string(), "2301.12345v2");
+        assert_eq!(pinned.unversioned().to_string(), "2301.12345");
+
+        assert_eq!(ArxivId::parse("arXiv:hep-th/9711200").unwrap(), ArxivId { id: "hep-th/9711200".to_string(), version: None });
+        assert_eq!(ArxivId::parse("https://arxiv.org/abs/math.AG/0601001v3").unwrap().version, Some(3));
+        assert!(ArxivId::parse("10.1103/PhysRevD.98.030001").is_none());
+        assert!("2301.12345v".parse::<ArxivId>().is_err());
+    }
+
+    #[test]
+    fn test_parse_latest_versions() {
+        let feed = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <id>http://arxiv.org/api/abc</id>
+  <entry>
+    <id>http://arxiv.org/abs/2301.12345v3</id>
+  </entry>
+  <entry>
+    <id>http://arxiv.org/abs/hep-th/9711200v3</id>
+  </entry>
+</feed>"#;
+        let versions = parse_latest_versions(feed);
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions["2301.12345"], 3);
+        assert_eq!(versions["hep-th/9711200"], 3);
+    }
+}
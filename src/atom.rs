@@ -0,0 +1,265 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::models::{Paper, Reference};
+
+/// A single Atom feed entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtomEntry {
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    pub updated: String,
+    pub authors: Vec<String>,
+    pub summary: Option<String>,
+    /// License of the work (Atom `<rights>`)
+    pub rights: Option<String>,
+}
+
+impl AtomEntry {
+    /// Entry for a paper, linking to arXiv when possible and INSPIRE otherwise
+    pub fn from_paper(paper: &Paper, updated: &str) -> Self {
+        let link = match &paper.arxiv_id {
+            Some(arxiv_id) => format!("https://arxiv.org/abs/{}", arxiv_id),
+            None => format!("https://inspirehep.net/literature/{}", paper.id),
+        };
+
+        Self {
+            id: format!("https://inspirehep.net/literature/{}", paper.id),
+            title: paper.title.clone(),
+            link,
+            updated: updated.to_string(),
+            authors: paper.authors.clone(),
+            summary: paper.abstract_text.clone(),
+            rights: paper.license.as_ref().and_then(|license| license.name.clone()),
+        }
+    }
+
+    /// Entry for a reference, linking to arXiv when possible and INSPIRE otherwise
+    pub fn from_reference(reference: &Reference, updated: &str) -> Self {
+        let inspire_link = reference.inspire_id.as_ref()
+            .map(|id| format!("https://inspirehep.net/literature/{}", id));
+        let arxiv_link = reference.arxiv_id.as_ref()
+            .map(|id| format!("https://arxiv.org/abs/{}", id));
+
+        let id = inspire_link.clone()
+            .or_else(|| arxiv_link.clone())
+            .unwrap_or_else(|| format!("urn:reference_tool:{}", reference.bibtex_key()));
+        let link = arxiv_link.or(inspire_link).unwrap_or_else(|| id.clone());
+
+        Self {
+            id,
+            title: reference.title.clone(),
+            link,
+            updated: updated.to_string(),
+            authors: reference.authors.clone(),
+            summary: reference.abstract_text.clone(),
+            rights: reference.license.as_ref().and_then(|license| license.name.clone()),
+        }
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = String::from("  <entry>\n");
+        xml.push_str(&format!("    <id>{}</id>\n", escape_xml(&self.id)));
+        xml.push_str(&format!("    <title>{}</title>\n", escape_xml(&self.title)));
+        xml.push_str(&format!("    <link href=\"{}\"/>\n", escape_xml(&self.link)));
+        xml.push_str(&format!("    <updated>{}</updated>\n", self.updated));
+        for author in &self.authors {
+            xml.push_str(&format!("    <author><name>{}</name></author>\n", escape_xml(author)));
+        }
+        if let Some(summary) = &self.summary {
+            xml.push_str(&format!("    <summary>{}</summary>\n", escape_xml(summary)));
+        }
+        if let Some(rights) = &self.rights {
+            xml.push_str(&format!("    <rights>{}</rights>\n", escape_xml(rights)));
+        }
+        xml.push_str("  </entry>\n");
+        xml
+    }
+}
+
+/// Render a complete Atom feed
+pub fn render_feed(feed_id: &str, title: &str, updated: &str, entries: &[AtomEntry]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <id>{}</id>\n", escape_xml(feed_id)));
+    xml.push_str(&format!("  <title>{}</title>\n", escape_xml(title)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated));
+    xml.push_str("  <author><name>reference_tool</name></author>\n");
+    for entry in entries {
+        xml.push_str(&entry.to_xml());
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// Add entries to an existing feed, newest first, bumping its `<updated>` time.
+///
+/// Returns `None` if `existing` does not look like a feed written by `render_feed`.
+pub fn prepend_entries(existing: &str, updated: &str, entries: &[AtomEntry]) -> Option<String> {
+    let updated_start = existing.find("  <updated>")?;
+    let updated_end = updated_start + existing[updated_start..].find('\n')? + 1;
+    existing.find("</feed>")?;
+
+    let insert_at = existing.find("  <entry>")
+        .unwrap_or_else(|| existing.rfind("</feed>").unwrap_or(existing.len()));
+    let insert_at = insert_at.max(updated_end);
+
+    let mut xml = String::with_capacity(existing.len() + entries.len() * 512);
+    xml.push_str(&existing[..updated_start]);
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated));
+    xml.push_str(&existing[updated_end..insert_at]);
+    for entry in entries {
+        xml.push_str(&entry.to_xml());
+    }
+    xml.push_str(&existing[insert_at..]);
+    Some(xml)
+}
+
+/// Escape text for inclusion in XML content or attributes
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Current time as an RFC 3339 timestamp in UTC
+pub fn now_rfc3339() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format_rfc3339(seconds)
+}
+
+/// Format seconds since the Unix epoch as an RFC 3339 timestamp in UTC
+pub fn format_rfc3339(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64;
+    let remainder = seconds % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day,
+        remainder / 3600, (remainder % 3600) / 60, remainder % 60
+    )
+}
+
+/// Parse a UTC timestamp as written by `format_rfc3339` into seconds since the Unix epoch
+pub fn parse_rfc3339(timestamp: &str) -> Option<u64> {
+    let number = |range: std::ops::Range<usize>| timestamp.get(range)?.parse::<u32>().ok();
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    let days = days_from_civil(i64::from(year), month, day);
+    u64::try_from(days * 86_400 + i64::from(hour * 3600 + minute * 60 + second)).ok()
+}
+
+/// Convert a (year, month, day) civil date to days since the Unix epoch
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_index = i64::from(if month > 2 { month - 3 } else { month + 9 });
+    let day_of_year = (153 * month_index + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Convert days since the Unix epoch to a (year, month, day) civil date
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_entry(id: &str) -> AtomEntry {
+        AtomEntry {
+            id: format!("https://inspirehep.net/literature/{}", id),
+            title: format!("Paper {} <draft>", id),
+            link: format!("https://inspirehep.net/literature/{}", id),
+            updated: "2024-01-01T00:00:00Z".to_string(),
+            authors: vec!["Doe, John".to_string()],
+            summary: None,
+            rights: None,
+        }
+    }
+
+    #[test]
+    fn test_format_rfc3339() {
+        assert_eq!(format_rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_rfc3339(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_rfc3339(1_700_000_000), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn test_parse_rfc3339() {
+        for seconds in [0, 951_782_400, 1_700_000_000] {
+            assert_eq!(parse_rfc3339(&format_rfc3339(seconds)), Some(seconds));
+        }
+        assert_eq!(parse_rfc3339("2024-01-01T00:00:00+00:00"), Some(1_704_067_200));
+        assert_eq!(parse_rfc3339("yesterday"), None);
+    }
+
+    #[test]
+    fn test_render_feed() {
+        let feed = render_feed("urn:test", "New citations", "2024-01-01T00:00:00Z", &[create_entry("1")]);
+
+        assert!(feed.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+        assert!(feed.contains("<title>Paper 1 &lt;draft&gt;</title>"));
+        assert!(feed.contains("<author><name>Doe, John</name></author>"));
+        assert!(feed.trim_end().ends_with("</feed>"));
+    }
+
+    #[test]
+    fn test_entry_from_reference() {
+        let reference = Reference {
+            title: "Some Paper".to_string(),
+            authors: vec!["Doe, John".to_string()],
+            arxiv_id: Some("2301.12345".to_string()),
+            inspire_id: Some("42".to_string()),
+            abstract_text: Some("We find things.".to_string()),
+            license: Some(crate::models::License { name: Some("CC BY 4.0".to_string()), ..Default::default() }),
+            ..Default::default()
+        };
+
+        let entry = AtomEntry::from_reference(&reference, "2024-01-01T00:00:00Z");
+        assert_eq!(entry.id, "https://inspirehep.net/literature/42");
+        assert_eq!(entry.link, "https://arxiv.org/abs/2301.12345");
+        assert_eq!(entry.summary, Some("We find things.".to_string()));
+        assert_eq!(entry.rights, Some("CC BY 4.0".to_string()));
+        assert!(entry.to_xml().contains("<rights>CC BY 4.0</rights>"));
+
+        let bare = AtomEntry::from_reference(&Reference { title: "Bare".to_string(), ..Default::default() }, "2024-01-01T00:00:00Z");
+        assert!(bare.id.starts_with("urn:reference_tool:"));
+        assert_eq!(bare.link, bare.id);
+    }
+
+    #[test]
+    fn test_prepend_entries() {
+        let feed = render_feed("urn:test", "New citations", "2024-01-01T00:00:00Z", &[create_entry("1")]);
+        let updated = prepend_entries(&feed, "2024-02-01T00:00:00Z", &[create_entry("2")]).unwrap();
+
+        assert!(updated.contains("  <updated>2024-02-01T00:00:00Z</updated>\n  <author>"));
+        let first = updated.find("literature/2</id>").unwrap();
+        let second = updated.find("literature/1</id>").unwrap();
+        assert!(first < second);
+        assert_eq!(updated.matches("<entry>").count(), 2);
+
+        let empty = render_feed("urn:test", "New citations", "2024-01-01T00:00:00Z", &[]);
+        let updated = prepend_entries(&empty, "2024-02-01T00:00:00Z", &[create_entry("3")]).unwrap();
+        assert!(updated.contains("literature/3</id>"));
+        assert!(updated.trim_end().ends_with("</feed>"));
+
+        assert!(prepend_entries("not a feed", "2024-02-01T00:00:00Z", &[]).is_none());
+    }
+}
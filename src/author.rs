@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::api::InspireClient;
+use crate::models::Paper;
+use crate::report::{ReportFormat, Table};
+
+/// Citation summary of an author's papers, from INSPIRE citation counts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuthorMetrics {
+    pub author: String,
+    pub papers: usize,
+    pub total_citations: u64,
+    pub h_index: usize,
+    /// Total citations divided by the years since the first paper (inclusive)
+    pub citations_per_year: f64,
+    /// Citations of the papers published in each year
+    pub citations_by_year: BTreeMap<u32, u64>,
+    /// Most cited papers, most cited first
+    pub most_cited: Vec<CitedPaper>,
+}
+
+/// One of an author's papers with its citation count
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CitedPaper {
+    pub inspire_id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arxiv_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<u32>,
+    pub citations: u32,
+}
+
+impl AuthorMetrics {
+    /// Compute the metrics of an author's papers, keeping the `top` most cited.
+    /// `current_year` ends the span used for citations per year.
+    pub fn compute(author: &str, papers: &[Paper], top: usize, current_year: u32) -> Self {
+        let counts: Vec<usize> = papers.iter()
+            .map(|paper| paper.citation_count.unwrap_or(0) as usize)
+            .collect();
+        let total_citations: u64 = counts.iter().map(|&count| count as u64).sum();
+
+        let mut citations_by_year = BTreeMap::new();
+        for paper in papers {
+            if let Some(year) = paper.year {
+                *citations_by_year.entry(year).or_insert(0) += u64::from(paper.citation_count.unwrap_or(0));
+            }
+        }
+        let citations_per_year = match citations_by_year.keys().next() {
+            Some(&first) => total_citations as f64 / f64::from(current_year.max(first) - first + 1),
+            None => 0.0,
+        };
+
+        let mut most_cited: Vec<CitedPaper> = papers.iter()
+            .map(|paper| CitedPaper {
+                inspire_id: paper.id.clone(),
+                title: paper.title.clone(),
+                arxiv_id: paper.arxiv_id.clone(),
+                year: paper.year,
+                citations: paper.citation_count.unwrap_or(0),
+            })
+            .collect();
+        most_cited.sort_by(|a, b| b.citations.cmp(&a.citations).then_with(|| a.title.cmp(&b.title)));
+        most_cited.truncate(top);
+
+        Self {
+            author: author.to_string(),
+            papers: papers.len(),
+            total_citations,
+            h_index: h_index(&counts),
+            citations_per_year,
+            citations_by_year,
+            most_cited,
+        }
+    }
+
+    /// Headline numbers as a two-column table
+    pub fn summary_table(&self) -> Table {
+        let mut table = Table::new(&["metric", "value"]);
+        table.push_row(vec!["papers".to_string(), self.papers.to_string()]);
+        table.push_row(vec!["citations".to_string(), self.total_citations.to_string()]);
+        table.push_row(vec!["h_index".to_string(), self.h_index.to_string()]);
+        table.push_row(vec!["citations_per_year".to_string(), format!("{:.1}", self.citations_per_year)]);
+        table
+    }
+
+    /// Citations of the papers published in each year, oldest first
+    pub fn by_year_table(&self) -> Table {
+        let mut table = Table::new(&["year", "citations"]);
+        for (year, citations) in &self.citations_by_year {
+            table.push_row(vec![year.to_string(), citations.to_string()]);
+        }
+        table
+    }
+
+    /// The most cited papers
+    pub fn most_cited_table(&self) -> Table {
+        let mut table = Table::new(&["title", "year", "arxiv_id", "citations"]);
+        for paper in &self.most_cited {
+            table.push_row(vec![
+                paper.title.clone(),
+                paper.year.map(|year| year.to_string()).unwrap_or_default(),
+                paper.arxiv_id.clone().unwrap_or_default(),
+                paper.citations.to_string(),
+            ]);
+        }
+        table
+    }
+
+    /// Render the summary, per-year and most-cited tables; Markdown gets headings
+    pub fn render(&self, format: ReportFormat) -> String {
+        let tables = [
+            ("Summary", self.summary_table()),
+            ("Citations by year of publication", self.by_year_table()),
+            ("Most cited papers", self.most_cited_table()),
+        ];
+        let sections: Vec<String> = tables.iter()
+            .map(|(heading, table)| match format {
+                ReportFormat::Markdown => format!("## {}\n\n{}", heading, table.to_markdown()),
+                ReportFormat::Csv => table.to_csv(),
+            })
+            .collect();
+        match format {
+            ReportFormat::Markdown => format!("# Citation metrics for {}\n\n{}", self.author, sections.join("\n")),
+            ReportFormat::Csv => sections.join("\n"),
+        }
+    }
+}
+
+/// Fetch up to `max_papers` of an author's papers and compute their metrics
+pub async fn author_metrics(client: &InspireClient, author: &str, max_papers: usize, top: usize) -> Result<AuthorMetrics> {
+    let papers = client.get_author_papers(author, None, max_papers).await?;
+    let current_year = crate::atom::now_rfc3339()[..4].parse().unwrap_or(0);
+    Ok(AuthorMetrics::compute(author, &papers, top, current_year))
+}
+
+/// Largest h such that h of the given citation counts are at least h
+pub fn h_index(citation_counts: &[usize]) -> usize {
+    let mut counts = citation_counts.to_vec();
+    counts.sort_unstable_by(|a, b| b.cmp(a));
+    counts.iter()
+        .enumerate()
+        .take_while(|(index, &count)| count > *index)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_paper(id: &str, year: u32, citations: u32) -> Paper {
+        Paper {
+            id: id.to_string(),
+            title: format!("Paper {}", id),
+            year: Some(year),
+            citation_count: Some(citations),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_h_index() {
+        assert_eq!(h_index(&[]), 0);
+        assert_eq!(h_index(&[0, 0]), 0);
+        assert_eq!(h_index(&[10, 8, 5, 4, 3]), 4);
+        assert_eq!(h_index(&[25, 8, 5, 3, 3]), 3);
+        assert_eq!(h_index(&[1, 1, 1]), 1);
+    }
+
+    #[test]
+    fn test_compute_metrics() {
+        let papers = vec![
+            create_paper("1", 2020, 10),
+            create_paper("2", 2020, 3),
+            create_paper("3", 2022, 5),
+            create_paper("4", 2023, 2),
+        ];
+        let metrics = AuthorMetrics::compute("Doe, J.", &papers, 2, 2024);
+
+        assert_eq!(metrics.papers, 4);
+        assert_eq!(metrics.total_citations, 20);
+        assert_eq!(metrics.h_index, 3);
+        assert_eq!(metrics.citations_per_year, 4.0);
+        assert_eq!(metrics.citations_by_year, BTreeMap::from([(2020, 13), (2022, 5), (2023, 2)]));
+        let titles: Vec<&str> = metrics.most_cited.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["Paper 1", "Paper 3"]);
+
+        let markdown = metrics.render(ReportFormat::Markdown);
+        assert!(markdown.starts_with("# Citation metrics for Doe, J.\n\n## Summary\n\n| metric | value |\n"));
+        assert!(markdown.contains("| h_index | 3 |\n"));
+        assert!(markdown.contains("## Most cited papers\n\n| title | year | arxiv_id | citations |\n"));
+        assert!(metrics.render(ReportFormat::Csv).starts_with("metric,value\npapers,4\n"));
+    }
+}
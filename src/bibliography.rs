@@ -0,0 +1,255 @@
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::models::Reference;
+use crate::updates::normalize;
+
+/// Differences between two versions of a bibliography
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BibDiff {
+    /// Entries only present in the new bibliography
+    pub added: Vec<Reference>,
+    /// Entries only present in the old bibliography
+    pub removed: Vec<Reference>,
+    /// Entries present in both whose metadata differs
+    pub changed: Vec<ChangedEntry>,
+}
+
+/// An entry whose metadata changed between two bibliographies
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangedEntry {
+    /// Identifier of the entry (see `Reference::identifier`)
+    pub identifier: String,
+    pub old: Reference,
+    pub new: Reference,
+}
+
+impl BibDiff {
+    /// Whether the two bibliographies are identical
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare two bibliographies entry by entry.
+///
+/// Entries are matched by INSPIRE ID, then arXiv ID, then generated BibTeX key,
+/// so an entry that gained an INSPIRE ID between versions is still reported as
+/// changed rather than removed and re-added.
+pub fn diff(old: &[Reference], new: &[Reference]) -> BibDiff {
+    let mut by_inspire = HashMap::new();
+    let mut by_arxiv = HashMap::new();
+    let mut by_identifier = HashMap::new();
+
+    for (index, reference) in old.iter().enumerate() {
+        if let Some(inspire_id) = &reference.inspire_id {
+            by_inspire.entry(inspire_id.as_str()).or_insert(index);
+        }
+        if let Some(arxiv_id) = &reference.arxiv_id {
+            by_arxiv.entry(arxiv_id.as_str()).or_insert(index);
+        }
+        by_identifier.entry(reference.identifier()).or_insert(index);
+    }
+
+    let mut matched = HashSet::new();
+    let mut result = BibDiff::default();
+
+    for reference in new {
+        let candidate = reference.inspire_id.as_deref()
+            .and_then(|id| by_inspire.get(id))
+            .or_else(|| reference.arxiv_id.as_deref().and_then(|id| by_arxiv.get(id)))
+            .or_else(|| by_identifier.get(&reference.identifier()))
+            .copied()
+            .filter(|index| !matched.contains(index));
+
+        match candidate {
+            Some(index) => {
+                matched.insert(index);
+                if old[index] != *reference {
+                    result.changed.push(ChangedEntry {
+                        identifier: reference.identifier(),
+                        old: old[index].clone(),
+                        new: reference.clone(),
+                    });
+                }
+            }
+            None => result.added.push(reference.clone()),
+        }
+    }
+
+    result.removed = old.iter()
+        .enumerate()
+        .filter(|(index, _)| !matched.contains(index))
+        .map(|(_, reference)| reference.clone())
+        .collect();
+
+    result
+}
+
+/// A generated key that was taken by a different work and renamed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyRename {
+    pub original: String,
+    pub renamed: String,
+}
+
+/// Outcome of adding references to an existing bibliography
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BibMerge {
+    /// References to add, with collision-free keys
+    pub added: Vec<Reference>,
+    /// Keys of existing entries that already cover one of the new references
+    pub duplicates: Vec<String>,
+    /// Keys renamed because a different work already used them
+    pub renamed: Vec<KeyRename>,
+}
+
+/// Prepare references for adding to an existing bibliography.
+///
+/// References already present as the same work (see [`same_work`]) are
+/// skipped. A reference whose key belongs to a different existing entry is
+/// renamed with a numeric suffix (`Smith:2020ab` becomes `Smith:2020ab_2`).
+pub fn merge_into(existing: &[Reference], new: Vec<Reference>) -> BibMerge {
+    let mut used: HashSet<String> = existing.iter().map(|r| r.bibtex_key()).collect();
+    let mut result = BibMerge::default();
+
+    for mut reference in new {
+        if let Some(duplicate) = existing.iter().chain(&result.added).find(|r| same_work(r, &reference)) {
+            result.duplicates.push(duplicate.bibtex_key());
+            continue;
+        }
+
+        let key = reference.bibtex_key();
+        if used.contains(&key) {
+            let renamed = (2..)
+                .map(|suffix| format!("{}_{}", key, suffix))
+                .find(|candidate| !used.contains(candidate))
+                .unwrap_or_default();
+            reference.texkey = Some(renamed.clone());
+            result.renamed.push(KeyRename { original: key, renamed: renamed.clone() });
+            used.insert(renamed);
+        } else {
+            used.insert(key);
+        }
+        result.added.push(reference);
+    }
+
+    result
+}
+
+/// Whether two entries describe the same work: same INSPIRE record, arXiv ID
+/// or DOI, or (when neither has identifiers in common) the same title and year
+pub fn same_work(a: &Reference, b: &Reference) -> bool {
+    let same = |x: &Option<String>, y: &Option<String>| match (x, y) {
+        (Some(x), Some(y)) => Some(x.eq_ignore_ascii_case(y)),
+        _ => None,
+    };
+    let by_identifier = same(&a.inspire_id, &b.inspire_id)
+        .or_else(|| same(&a.arxiv_id, &b.arxiv_id))
+        .or_else(|| same(&a.doi, &b.doi));
+    match by_identifier {
+        Some(same) => same,
+        None => !a.title.is_empty() && normalize(&a.title) == normalize(&b.title) && a.year == b.year,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_reference(title: &str, arxiv_id: Option<&str>, inspire_id: Option<&str>) -> Reference {
+        Reference {
+            title: title.to_string(),
+            authors: vec!["Alice Smith".to_string()],
+            arxiv_id: arxiv_id.map(|s| s.to_string()),
+            inspire_id: inspire_id.map(|s| s.to_string()),
+            categories: vec!["hep-th".to_string()],
+            year: Some(2023),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let refs = vec![
+            create_reference("First", Some("2301.00001"), Some("1")),
+            create_reference("Second", None, Some("2")),
+        ];
+
+        let result = diff(&refs, &refs);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_diff_added_and_removed() {
+        let old = vec![
+            create_reference("Kept", None, Some("1")),
+            create_reference("Dropped", None, Some("2")),
+        ];
+        let new = vec![
+            create_reference("Kept", None, Some("1")),
+            create_reference("New", None, Some("3")),
+        ];
+
+        let result = diff(&old, &new);
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.added[0].title, "New");
+        assert_eq!(result.removed.len(), 1);
+        assert_eq!(result.removed[0].title, "Dropped");
+        assert!(result.changed.is_empty());
+    }
+
+    #[test]
+    fn test_diff_changed_metadata() {
+        let old = vec![create_reference("Old Title", Some("2301.00001"), Some("1"))];
+        let new = vec![create_reference("Fixed Title", Some("2301.00001"), Some("1"))];
+
+        let result = diff(&old, &new);
+        assert!(result.added.is_empty());
+        assert!(result.removed.is_empty());
+        assert_eq!(result.changed.len(), 1);
+        assert_eq!(result.changed[0].identifier, "inspire:1");
+        assert_eq!(result.changed[0].old.title, "Old Title");
+        assert_eq!(result.changed[0].new.title, "Fixed Title");
+    }
+
+    #[test]
+    fn test_diff_matches_on_arxiv_when_inspire_id_appears() {
+        let old = vec![create_reference("Preprint", Some("2301.00001"), None)];
+        let new = vec![create_reference("Preprint", Some("2301.00001"), Some("42"))];
+
+        let result = diff(&old, &new);
+        assert!(result.added.is_empty());
+        assert!(result.removed.is_empty());
+        assert_eq!(result.changed.len(), 1);
+        assert_eq!(result.changed[0].identifier, "inspire:42");
+    }
+
+    #[test]
+    fn test_merge_into_renames_colliding_keys() {
+        let existing = vec![
+            Reference { texkey: Some("Smith:2023ab".to_string()), ..create_reference("Existing", Some("2301.00001"), None) },
+            Reference { texkey: Some("Smith:2023ab_2".to_string()), ..create_reference("Also existing", Some("2301.00002"), None) },
+        ];
+        let new = vec![
+            Reference { texkey: Some("Smith:2023ab".to_string()), ..create_reference("Existing", Some("2301.00001"), Some("1")) },
+            Reference { texkey: Some("Smith:2023ab".to_string()), ..create_reference("Different", Some("2301.00003"), Some("3")) },
+            Reference { texkey: Some("Jones:2023".to_string()), ..create_reference("Unrelated", None, Some("4")) },
+        ];
+
+        let result = merge_into(&existing, new);
+        assert_eq!(result.duplicates, ["Smith:2023ab"]);
+        assert_eq!(result.renamed, [KeyRename { original: "Smith:2023ab".to_string(), renamed: "Smith:2023ab_3".to_string() }]);
+        let keys: Vec<String> = result.added.iter().map(|r| r.bibtex_key()).collect();
+        assert_eq!(keys, ["Smith:2023ab_3", "Jones:2023"]);
+    }
+
+    #[test]
+    fn test_same_work() {
+        let preprint = create_reference("Holography", Some("2301.00001"), None);
+        let record = create_reference("Holography revisited", Some("2301.00001"), Some("1"));
+        assert!(same_work(&preprint, &record));
+        assert!(!same_work(&preprint, &create_reference("Holography", Some("2301.00002"), None)));
+        assert!(same_work(&create_reference("{Holography}", None, None), &create_reference("holography", None, Some("1"))));
+    }
+}
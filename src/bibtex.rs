@@ -0,0 +1,482 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::arxiv::ArxivId;
+use crate::dates::{self, PartialDate};
+use crate::models::{normalize_isbn, normalize_issn, Pages, PublicationInfo, Reference, ThesisInfo, RETRACTED_NOTE};
+use crate::names;
+
+/// A single entry from a BibTeX file, with its fields kept in source order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BibEntry {
+    /// Entry type in lowercase (`article`, `inproceedings`, ...)
+    pub entry_type: String,
+    /// Citation key
+    pub key: String,
+    /// Field names (lowercase) and values without their outer delimiters
+    pub fields: Vec<(String, String)>,
+}
+
+impl BibEntry {
+    /// Look up a field value by (case-insensitive) name
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.iter()
+            .find(|(field_name, _)| field_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Convert this entry into a `Reference`
+    pub fn to_reference(&self) -> Reference {
+        let title = self.field("title")
+            .map(strip_braces)
+            .unwrap_or_else(|| "Unknown Title".to_string());
+
+        let authors = self.field("author")
+            .map(|authors| {
+                authors.split(" and ")
+                    .map(|name| names::to_last_first(&strip_braces(name.trim())))
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // A pinned eprint (`2301.12345v2`) keeps its version separately
+        let eprint = self.field("eprint").map(|s| s.trim().to_string());
+        let pinned = eprint.as_deref().and_then(ArxivId::parse);
+        let arxiv_version = pinned.as_ref().and_then(|id| id.version);
+        let arxiv_id = pinned.map(|id| id.id).or(eprint);
+
+        let categories = self.field("primaryclass")
+            .map(|class| vec![class.trim().to_string()])
+            .unwrap_or_default();
+
+        // biblatex `date`, else BibTeX `year` and `month` (a macro like `jan` or a number)
+        let year: Option<u32> = self.field("year").and_then(|year| year.trim().parse().ok());
+        let date = self.field("date").and_then(PartialDate::parse).or_else(|| {
+            let month = self.field("month").and_then(|month| {
+                let month = month.trim();
+                month.parse().ok().or_else(|| dates::month_from_name(month))
+            });
+            PartialDate::parse(&format!("{}-{:02}", year?, month?))
+        });
+        let year = year.or(date.map(|date| date.year));
+
+        let abstract_text = self.field("abstract").map(|s| s.to_string());
+
+        let document_type = match self.entry_type.as_str() {
+            "phdthesis" | "mastersthesis" => vec!["thesis".to_string()],
+            "inproceedings" | "conference" => vec!["conference paper".to_string()],
+            "proceedings" => vec!["proceedings".to_string()],
+            "book" => vec!["book".to_string()],
+            "incollection" | "inbook" => vec!["book chapter".to_string()],
+            _ => Vec::new(),
+        };
+
+        let (page_start, page_end) = match self.field("pages").and_then(Pages::parse) {
+            Some(Pages::Range(start, end)) => (Some(start), Some(end)),
+            Some(pages) => (Some(pages.first().to_string()), None),
+            None => (None, None),
+        };
+        let publication_info = PublicationInfo {
+            journal_title: self.field("journal").map(strip_braces),
+            journal_volume: self.field("volume").map(strip_braces),
+            journal_issue: self.field("number").map(strip_braces),
+            page_start,
+            page_end,
+            parent_title: self.field("booktitle").map(strip_braces),
+            isbn: self.field("isbn").and_then(normalize_isbn),
+            issn: self.field("issn").and_then(normalize_issn),
+            ..Default::default()
+        };
+
+        let thesis_info = match self.entry_type.as_str() {
+            "phdthesis" | "mastersthesis" => Some(ThesisInfo {
+                institution: self.field("school").map(strip_braces),
+                degree_type: Some(if self.entry_type == "phdthesis" { "phd" } else { "master" }.to_string()),
+                year: None,
+            }),
+            _ => None,
+        };
+
+        Reference {
+            title,
+            authors,
+            arxiv_id,
+            arxiv_version,
+            inspire_id: None,
+            categories,
+            year,
+            date,
+            texkey: Some(self.key.clone()),
+            abstract_text,
+            document_type,
+            doi: self.field("doi").map(|s| s.trim().to_string()),
+            publication_info: (publication_info != PublicationInfo::default()).then_some(publication_info),
+            thesis_info,
+            retracted: self.field("note").is_some_and(|note| note.contains(RETRACTED_NOTE)),
+            ..Default::default()
+        }
+    }
+
+    /// Render this entry back to BibTeX, preserving type, key and field order
+    pub fn to_bibtex(&self) -> String {
+        let mut bibtex = format!("@{}{{{},\n", self.entry_type, self.key);
+        for (name, value) in &self.fields {
+            bibtex.push_str(&format!("  {} = {{{}}},\n", name, value));
+        }
+        bibtex.push_str("}\n");
+        bibtex
+    }
+}
+
+/// Parse BibTeX source into its entries.
+///
+/// `@comment`, `@preamble` and `@string` blocks are skipped, as is any text
+/// between entries.
+pub fn parse(input: &str) -> Result<Vec<BibEntry>> {
+    let mut parser = Parser { input, pos: 0 };
+    let mut entries = Vec::new();
+
+    while let Some(offset) = input[parser.pos..].find('@') {
+        parser.pos += offset + 1;
+
+        let entry_type = parser.identifier().to_lowercase();
+        if entry_type.is_empty() {
+            continue;
+        }
+        parser.skip_whitespace();
+
+        let close = match parser.peek() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => continue,
+        };
+        parser.pos += 1;
+
+        if matches!(entry_type.as_str(), "comment" | "preamble" | "string") {
+            parser.skip_block(close)?;
+            continue;
+        }
+
+        entries.push(parser.entry(entry_type, close)?);
+    }
+
+    Ok(entries)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn line(&self) -> usize {
+        self.input[..self.pos].matches('\n').count() + 1
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+    }
+
+    /// Read an entry type, key or field name
+    fn identifier(&mut self) -> &str {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || matches!(c, '{' | '}' | '(' | ')' | ',' | '=' | '#' | '"') {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(anyhow!("Expected '{}' on line {}", expected, self.line()))
+        }
+    }
+
+    /// Skip the rest of a block whose opening delimiter was already consumed
+    fn skip_block(&mut self, close: char) -> Result<()> {
+        let open = if close == '}' { '{' } else { '(' };
+        let mut depth = 1;
+        while let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+        }
+        Err(anyhow!("Unterminated block at end of input"))
+    }
+
+    fn entry(&mut self, entry_type: String, close: char) -> Result<BibEntry> {
+        self.skip_whitespace();
+        let key = self.identifier().to_string();
+        if key.is_empty() {
+            return Err(anyhow!("Missing citation key for @{} on line {}", entry_type, self.line()));
+        }
+
+        let mut fields = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.pos += 1;
+                }
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(_) => {
+                    let name = self.identifier().to_lowercase();
+                    if name.is_empty() {
+                        return Err(anyhow!("Invalid field in entry '{}' on line {}", key, self.line()));
+                    }
+                    self.expect('=')?;
+                    let value = self.value()?;
+                    fields.push((name, value));
+                }
+                None => return Err(anyhow!("Unterminated entry '{}'", key)),
+            }
+        }
+
+        Ok(BibEntry { entry_type, key, fields })
+    }
+
+    /// Read a field value, joining `#`-concatenated parts
+    fn value(&mut self) -> Result<String> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('{') => {
+                    self.pos += 1;
+                    let start = self.pos;
+                    self.skip_block('}')?;
+                    value.push_str(&self.input[start..self.pos - 1]);
+                }
+                Some('"') => {
+                    self.pos += 1;
+                    let start = self.pos;
+                    let mut depth = 0;
+                    loop {
+                        match self.peek() {
+                            Some('{') => depth += 1,
+                            Some('}') => depth -= 1,
+                            Some('"') if depth == 0 => break,
+                            Some(_) => {}
+                            None => return Err(anyhow!("Unterminated quoted value")),
+                        }
+                        self.pos += self.peek().map_or(1, char::len_utf8);
+                    }
+                    value.push_str(&self.input[start..self.pos]);
+                    self.pos += 1;
+                }
+                _ => {
+                    let bare = self.identifier();
+                    if bare.is_empty() {
+                        return Err(anyhow!("Missing field value on line {}", self.line()));
+                    }
+                    value.push_str(bare);
+                }
+            }
+
+            self.skip_whitespace();
+            if self.peek() == Some('#') {
+                self.pos += 1;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+}
+
+/// Make free text safe as a braced BibTeX field value: whitespace is collapsed,
+/// LaTeX specials `%`, `&` and `#` are escaped (math in `$…$` is left alone) and
+/// unmatched braces, which would end the field early, are dropped
+pub fn escape_field(text: &str) -> String {
+    let chars: Vec<char> = text.split_whitespace().collect::<Vec<_>>().join(" ").chars().collect();
+
+    // Find unmatched braces first; backslashes do not protect braces from BibTeX
+    let mut unmatched = vec![false; chars.len()];
+    let mut open = Vec::new();
+    for (i, &c) in chars.iter().enumerate() {
+        match c {
+            '{' => open.push(i),
+            '}' if open.pop().is_none() => unmatched[i] = true,
+            _ => {}
+        }
+    }
+    for i in open {
+        unmatched[i] = true;
+    }
+
+    let mut escaped = String::with_capacity(chars.len());
+    let mut in_math = false;
+    for (i, &c) in chars.iter().enumerate() {
+        let after_backslash = i > 0 && chars[i - 1] == '\\';
+        match c {
+            _ if unmatched[i] => {}
+            '$' if !after_backslash => {
+                in_math = !in_math;
+                escaped.push(c);
+            }
+            '%' | '&' | '#' if !in_math && !after_backslash => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Remove protective braces wrapping a whole value (`{{Title}}` -> `Title`)
+fn strip_braces(value: &str) -> String {
+    let mut value = value.trim();
+    while value.starts_with('{') && value.ends_with('}') && value.len() >= 2 {
+        value = &value[1..value.len() - 1];
+    }
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entry() {
+        let input = r#"
+@Article{Maldacena:1997re,
+    author = "Maldacena, Juan Martin",
+    title = {{The Large N limit of superconformal field theories and supergravity}},
+    eprint = "hep-th/9711200",
+    archivePrefix = "arXiv",
+    year = 1998
+}
+"#;
+
+        let entries = parse(input).unwrap();
+        assert_eq!(entries.len(), 1);
+
+        let entry = &entries[0];
+        assert_eq!(entry.entry_type, "article");
+        assert_eq!(entry.key, "Maldacena:1997re");
+        assert_eq!(entry.field("AUTHOR"), Some("Maldacena, Juan Martin"));
+        assert_eq!(entry.field("year"), Some("1998"));
+        assert_eq!(entry.fields[3].0, "archiveprefix");
+
+        let reference = entry.to_reference();
+        assert_eq!(reference.title, "The Large N limit of superconformal field theories and supergravity");
+        assert_eq!(reference.arxiv_id, Some("hep-th/9711200".to_string()));
+        assert_eq!(reference.arxiv_version, None);
+        assert_eq!(reference.year, Some(1998));
+
+        let pinned = &parse("@misc{pinned, eprint = {2301.12345v2}}").unwrap()[0];
+        let reference = pinned.to_reference();
+        assert_eq!(reference.arxiv_id, Some("2301.12345".to_string()));
+        assert_eq!(reference.arxiv_version, Some(2));
+    }
+
+    #[test]
+    fn test_parse_skips_comments_and_strings() {
+        let input = r#"
+This line is ignored.
+@comment{ nothing {to} see }
+@string{jhep = "JHEP"}
+@preamble{"\newcommand{\noop}[1]{}"}
+@misc{first, title = {One}}
+@book(second, title = "Two" # " and Three", note = {nested {braces} kept})
+"#;
+
+        let entries = parse(input).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].key, "first");
+        assert_eq!(entries[1].entry_type, "book");
+        assert_eq!(entries[1].field("title"), Some("Two and Three"));
+        assert_eq!(entries[1].field("note"), Some("nested {braces} kept"));
+    }
+
+    #[test]
+    fn test_parse_unterminated_entry() {
+        let result = parse("@article{broken, title = {Never closed}");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_round_trip_reference() {
+        let reference = Reference {
+            title: "A Study of Quantum Field Theory".to_string(),
+            authors: vec!["Doe, John".to_string(), "Smith, Jane".to_string()],
+            arxiv_id: Some("2301.12345".to_string()),
+            inspire_id: None,
+            categories: vec!["hep-th".to_string()],
+            year: Some(2023),
+            ..Default::default()
+        };
+
+        let entries = parse(&reference.to_bibtex()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, reference.bibtex_key());
+
+        let parsed = entries[0].to_reference();
+        assert_eq!(parsed.texkey, Some(reference.bibtex_key()));
+        assert_eq!(Reference { texkey: None, ..parsed }, reference);
+    }
+
+    #[test]
+    fn test_round_trip_thesis_reference() {
+        let reference = Reference {
+            title: "Aspects of Holography".to_string(),
+            authors: vec!["Smith, Jane".to_string()],
+            year: Some(2019),
+            document_type: vec!["thesis".to_string()],
+            thesis_info: Some(ThesisInfo {
+                institution: Some("Princeton U.".to_string()),
+                degree_type: Some("phd".to_string()),
+                year: None,
+            }),
+            ..Default::default()
+        };
+
+        let entries = parse(&reference.to_bibtex()).unwrap();
+        assert_eq!(entries[0].entry_type, "phdthesis");
+        assert_eq!(Reference { texkey: None, ..entries[0].to_reference() }, reference);
+    }
+
+    #[test]
+    fn test_round_trip_entry() {
+        let input = "@inproceedings{Key2020,\n  title = {Proceedings {QCD} Talk},\n  booktitle = {Some Conference},\n}\n";
+
+        let entries = parse(input).unwrap();
+        assert_eq!(entries[0].to_bibtex(), input);
+        assert_eq!(parse(&entries[0].to_bibtex()).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_escape_field() {
+        assert_eq!(escape_field("50% of  events\n& more"), "50\\% of events \\& more");
+        assert_eq!(escape_field("Already \\% escaped"), "Already \\% escaped");
+        assert_eq!(escape_field("Mass $m_{\\chi} \\# 1$ bound"), "Mass $m_{\\chi} \\# 1$ bound");
+        assert_eq!(escape_field("Unbalanced } brace {here"), "Unbalanced  brace here");
+        assert_eq!(escape_field("{SU(N)} gauge"), "{SU(N)} gauge");
+    }
+}
@@ -0,0 +1,536 @@
+use crate::atom::escape_xml;
+use crate::models::{Pages, Reference, RETRACTED_NOTE};
+use crate::names::PersonName;
+
+/// Style of human-readable citations (`--format citations --style …`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CitationStyle {
+    /// APA 7th edition
+    #[default]
+    Apa,
+    /// Chicago bibliography style
+    Chicago,
+    /// IEEE, numbered
+    Ieee,
+    /// JHEP (`JHEP.bst`), as `\bibitem` entries
+    Jhep,
+    /// Physical Review D, as `\bibitem` entries
+    Prd,
+    /// REVTeX (`apsrev4-2`), as `\bibitem` entries
+    Revtex,
+}
+
+impl CitationStyle {
+    /// Whether the style renders `\bibitem` entries for a `thebibliography` environment
+    pub fn is_bibitem(self) -> bool {
+        matches!(self, CitationStyle::Jhep | CitationStyle::Prd | CitationStyle::Revtex)
+    }
+}
+
+/// Render references as a citation list, one paragraph per reference
+pub fn format_citations(references: &[Reference], style: CitationStyle) -> String {
+    references.iter()
+        .enumerate()
+        .map(|(index, reference)| format_citation_entry(index + 1, reference, style))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Render the `number`th entry (from 1) of a citation list
+pub fn format_citation_entry(number: usize, reference: &Reference, style: CitationStyle) -> String {
+    match style {
+        CitationStyle::Ieee => format!("[{}] {}\n", number, format_citation(reference, style)),
+        _ if style.is_bibitem() => {
+            format!("\\bibitem{{{}}}\n{}\n", reference.bibtex_key(), format_citation(reference, style))
+        }
+        _ => format!("{}\n", format_citation(reference, style)),
+    }
+}
+
+/// Render references as a complete `thebibliography` environment. Journal styles
+/// (`jhep`, `prd`, `revtex`) are used as they are; any other style falls back to
+/// `\bibitem{key} Author, Title, Journal (Year), [arXiv:…].`
+pub fn format_thebibliography(references: &[Reference], style: CitationStyle) -> String {
+    // The widest label sets the indentation: `9` for up to nine entries, `99` up to 99…
+    let widest = "9".repeat(references.len().max(1).to_string().len());
+    let mut bibliography = format!("\\begin{{thebibliography}}{{{}}}\n\n", widest);
+    for reference in references {
+        let text = if style.is_bibitem() {
+            format_citation(reference, style)
+        } else {
+            format_bibitem_plain(reference)
+        };
+        bibliography.push_str(&format!("\\bibitem{{{}}}\n{}\n\n", reference.bibtex_key(), text));
+    }
+    bibliography.push_str("\\end{thebibliography}\n");
+    bibliography
+}
+
+/// Render references as an HTML list (e.g. a publication list for a web page),
+/// each entry linking to its INSPIRE record or arXiv page
+pub fn format_html(references: &[Reference], style: CitationStyle) -> String {
+    let mut html = String::from(HTML_LIST_START);
+    for reference in references {
+        html.push_str(&format_html_item(reference, style));
+    }
+    html.push_str(HTML_LIST_END);
+    html
+}
+
+/// Opening and closing tags of the list written by [`format_html`]
+pub const HTML_LIST_START: &str = "<ul class=\"publications\">\n";
+pub const HTML_LIST_END: &str = "</ul>\n";
+
+/// Render one `<li>` of the list written by [`format_html`]
+pub fn format_html_item(reference: &Reference, style: CitationStyle) -> String {
+    let mut item = format!("  <li>{}", escape_xml(&format_citation(reference, style)));
+    if let Some(url) = reference.url() {
+        item.push_str(&format!(" <a href=\"{}\">[link]</a>", escape_xml(&url)));
+    }
+    item.push_str("</li>\n");
+    item
+}
+
+/// Render a single reference in the given style, prefixed with `[RETRACTED]`
+/// for retracted or withdrawn works
+pub fn format_citation(reference: &Reference, style: CitationStyle) -> String {
+    let citation = match style {
+        CitationStyle::Apa => format_apa(reference),
+        CitationStyle::Chicago => format_chicago(reference),
+        CitationStyle::Ieee => format_ieee(reference),
+        CitationStyle::Jhep => format_jhep(reference),
+        CitationStyle::Prd => format_aps(reference, false),
+        CitationStyle::Revtex => format_aps(reference, true),
+    };
+    if reference.retracted {
+        format!("[{}] {}", RETRACTED_NOTE, citation)
+    } else {
+        citation
+    }
+}
+
+/// `Maldacena, J. M., & Witten, E. (1998). Title. Journal, 2(3), 231–252. https://doi.org/…`
+fn format_apa(reference: &Reference) -> String {
+    let names: Vec<String> = reference.authors.iter()
+        .map(|author| {
+            let name = PersonName::parse(author);
+            join_parts(&[&name.full_surname(), &name.initials()], ", ")
+        })
+        .collect();
+    // APA lists up to 20 authors, then elides all but the last
+    let authors = if names.len() > 20 {
+        format!("{}, … {}", names[..19].join(", "), names[names.len() - 1])
+    } else {
+        join_list(&names, ", ", ", & ", ", & ")
+    };
+
+    let year = reference.year.map_or("n.d.".to_string(), |y| y.to_string());
+    // Without authors, the title moves to the author position
+    let mut citation = if authors.is_empty() {
+        format!("{}. ({}).", reference.title, year)
+    } else {
+        format!("{} ({}). {}.", authors, year, reference.title)
+    };
+    if let Some(info) = &reference.publication_info {
+        if let Some(journal) = &info.journal_title {
+            citation.push_str(&format!(" {}", journal));
+            if let Some(volume) = &info.journal_volume {
+                citation.push_str(&format!(", {}", volume));
+                if let Some(issue) = &info.journal_issue {
+                    citation.push_str(&format!("({})", issue));
+                }
+            }
+            if let Some(pages) = pages(reference) {
+                citation.push_str(&format!(", {}", pages));
+            }
+            citation.push('.');
+        }
+    }
+    push_locator(&mut citation, reference);
+    citation
+}
+
+/// `Maldacena, Juan Martin, and Edward Witten. "Title." Journal 2, no. 3 (1998): 231–252. https://doi.org/…`
+fn format_chicago(reference: &Reference) -> String {
+    let mut names: Vec<String> = reference.authors.iter()
+        .enumerate()
+        .map(|(index, author)| {
+            let name = PersonName::parse(author);
+            if index == 0 { name.last_first() } else { name.first_last() }
+        })
+        .collect();
+    // Chicago lists up to ten authors, otherwise the first seven followed by et al.
+    let authors = if names.len() > 10 {
+        names.truncate(7);
+        format!("{}, et al.", names.join(", "))
+    } else {
+        join_list(&names, ", ", ", and ", " and ")
+    };
+
+    let mut citation = String::new();
+    if !authors.is_empty() {
+        citation.push_str(&authors);
+        if !authors.ends_with('.') {
+            citation.push('.');
+        }
+        citation.push(' ');
+    }
+    citation.push_str(&format!("\"{}.\"", reference.title));
+
+    let info = reference.publication_info.as_ref();
+    let journal = info.and_then(|info| info.journal_title.as_ref());
+    if let (Some(info), Some(journal)) = (info, journal) {
+        citation.push_str(&format!(" {}", journal));
+        if let Some(volume) = &info.journal_volume {
+            citation.push_str(&format!(" {}", volume));
+        }
+        if let Some(issue) = &info.journal_issue {
+            citation.push_str(&format!(", no. {}", issue));
+        }
+    }
+    match (reference.year, journal) {
+        (Some(year), Some(_)) => citation.push_str(&format!(" ({})", year)),
+        (Some(year), None) => citation.push_str(&format!(" {}", year)),
+        _ => {}
+    }
+    if let Some(pages) = pages(reference).filter(|_| journal.is_some()) {
+        citation.push_str(&format!(": {}", pages));
+    }
+    citation.push('.');
+    push_locator(&mut citation, reference);
+    citation
+}
+
+/// `J. M. Maldacena and E. Witten, "Title," Journal, vol. 2, no. 3, pp. 231–252, 1998, doi: ….`
+fn format_ieee(reference: &Reference) -> String {
+    let names: Vec<String> = reference.authors.iter()
+        .map(|author| {
+            let name = PersonName::parse(author);
+            join_parts(&[&name.initials(), &name.full_surname()], " ")
+        })
+        .collect();
+    // IEEE lists up to six authors, otherwise the first followed by et al.
+    let authors = if names.len() > 6 {
+        format!("{} et al.", names[0])
+    } else {
+        join_list(&names, ", ", ", and ", " and ")
+    };
+
+    let mut parts = Vec::new();
+    if let Some(info) = &reference.publication_info {
+        if let Some(journal) = &info.journal_title {
+            parts.push(journal.clone());
+        }
+        if let Some(volume) = &info.journal_volume {
+            parts.push(format!("vol. {}", volume));
+        }
+        if let Some(issue) = &info.journal_issue {
+            parts.push(format!("no. {}", issue));
+        }
+        match info.pages() {
+            Some(pages @ Pages::Range(..)) => parts.push(format!("pp. {}", pages.format("–"))),
+            Some(Pages::Single(page)) => parts.push(format!("p. {}", page)),
+            Some(Pages::ArticleId(artid)) => parts.push(format!("Art. no. {}", artid)),
+            None => {}
+        }
+    }
+    if let Some(year) = reference.year {
+        parts.push(year.to_string());
+    }
+    if let Some(doi) = &reference.doi {
+        parts.push(format!("doi: {}", doi));
+    } else if let Some(arxiv_id) = &reference.arxiv_id {
+        parts.push(format!("arXiv:{}", arxiv_id));
+    }
+
+    let title = if parts.is_empty() {
+        format!("\"{}.\"", reference.title)
+    } else {
+        format!("\"{},\" {}.", reference.title, parts.join(", "))
+    };
+    if authors.is_empty() {
+        title
+    } else {
+        format!("{}, {}", authors, title)
+    }
+}
+
+/// `J.M. Maldacena and E. Witten, \emph{Title}, \emph{Journal} \textbf{2} (1998) 231 [hep-th/9711200].`
+fn format_jhep(reference: &Reference) -> String {
+    let names: Vec<String> = reference.authors.iter()
+        .map(|author| {
+            let name = PersonName::parse(author);
+            join_parts(&[&name.initials().replace(". ", "."), &name.full_surname()], " ")
+        })
+        .collect();
+    let authors = join_list(&names, ", ", " and ", " and ");
+
+    let mut parts = vec![authors, format!("\\emph{{{}}}", reference.title)];
+    let journal = reference.publication_info.as_ref().and_then(|info| info.journal_title.as_ref());
+    if let Some(journal) = journal {
+        let info = reference.publication_info.as_ref();
+        let mut source = format!("\\emph{{{}}}", journal);
+        if let Some(volume) = info.and_then(|info| info.journal_volume.as_ref()) {
+            source.push_str(&format!(" \\textbf{{{}}}", volume));
+        }
+        if let Some(year) = reference.year {
+            source.push_str(&format!(" ({})", year));
+        }
+        if let Some(page) = first_page(reference) {
+            source.push_str(&format!(" {}", page));
+        }
+        parts.push(source);
+    } else if let (Some(year), None) = (reference.year, &reference.arxiv_id) {
+        parts.push(year.to_string());
+    }
+
+    let mut citation = join_parts(&parts.iter().map(String::as_str).collect::<Vec<_>>(), ", ");
+    if let Some(arxiv_id) = &reference.arxiv_id {
+        // New-style identifiers carry the `arXiv:` prefix, old ones already name their archive
+        if arxiv_id.contains('/') {
+            citation.push_str(&format!(" [{}]", arxiv_id));
+        } else {
+            citation.push_str(&format!(" [arXiv:{}]", arxiv_id));
+        }
+    }
+    citation.push('.');
+    citation
+}
+
+/// APS styles: `J. M. Maldacena and E. Witten, Journal \textbf{2}, 231 (1998), arXiv:hep-th/9711200.`
+///
+/// PRD leaves out the titles of published articles; REVTeX always includes them.
+fn format_aps(reference: &Reference, with_title: bool) -> String {
+    let names: Vec<String> = reference.authors.iter()
+        .map(|author| {
+            let name = PersonName::parse(author);
+            join_parts(&[&name.initials(), &name.full_surname()], " ")
+        })
+        .collect();
+    let mut parts = vec![join_list(&names, ", ", ", and ", " and ")];
+
+    let journal = reference.publication_info.as_ref().and_then(|info| info.journal_title.as_ref());
+    if with_title || journal.is_none() {
+        parts.push(format!("\\emph{{{}}}", reference.title));
+    }
+    if let Some(journal) = journal {
+        let info = reference.publication_info.as_ref();
+        let mut source = journal.clone();
+        if let Some(volume) = info.and_then(|info| info.journal_volume.as_ref()) {
+            source.push_str(&format!(" \\textbf{{{}}}", volume));
+        }
+        if let Some(page) = first_page(reference) {
+            source.push_str(&format!(", {}", page));
+        }
+        if let Some(year) = reference.year {
+            source.push_str(&format!(" ({})", year));
+        }
+        parts.push(source);
+    }
+    if let Some(arxiv_id) = &reference.arxiv_id {
+        match (journal, reference.year) {
+            (None, Some(year)) => parts.push(format!("arXiv:{} ({})", arxiv_id, year)),
+            _ => parts.push(format!("arXiv:{}", arxiv_id)),
+        }
+    } else if let (None, Some(year)) = (journal, reference.year) {
+        parts.push(format!("({})", year));
+    }
+
+    let mut citation = join_parts(&parts.iter().map(String::as_str).collect::<Vec<_>>(), ", ");
+    citation.push('.');
+    citation
+}
+
+/// `J. M. Maldacena and E. Witten, Title, Journal (1998), [arXiv:hep-th/9711200].`
+fn format_bibitem_plain(reference: &Reference) -> String {
+    let names: Vec<String> = reference.authors.iter()
+        .map(|author| {
+            let name = PersonName::parse(author);
+            join_parts(&[&name.initials(), &name.full_surname()], " ")
+        })
+        .collect();
+    let mut parts = vec![join_list(&names, ", ", " and ", " and "), reference.title.clone()];
+
+    let journal = reference.publication_info.as_ref().and_then(|info| info.journal_title.as_ref());
+    match (journal, reference.year) {
+        (Some(journal), Some(year)) => parts.push(format!("{} ({})", journal, year)),
+        (Some(journal), None) => parts.push(journal.clone()),
+        (None, Some(year)) => parts.push(format!("({})", year)),
+        (None, None) => {}
+    }
+    if let Some(arxiv_id) = &reference.arxiv_id {
+        parts.push(format!("[arXiv:{}]", arxiv_id));
+    }
+
+    let mut citation = join_parts(&parts.iter().map(String::as_str).collect::<Vec<_>>(), ", ");
+    citation.push('.');
+    citation
+}
+
+/// First page, or the article ID for journals that number articles instead
+fn first_page(reference: &Reference) -> Option<String> {
+    let pages = reference.publication_info.as_ref()?.pages()?;
+    Some(pages.first().to_string())
+}
+
+/// Page range (with an en dash), single page or article ID
+fn pages(reference: &Reference) -> Option<String> {
+    let pages = reference.publication_info.as_ref()?.pages()?;
+    Some(pages.format("–"))
+}
+
+/// Append the DOI link, or the arXiv ID for preprints
+fn push_locator(citation: &mut String, reference: &Reference) {
+    if let Some(doi) = &reference.doi {
+        citation.push_str(&format!(" https://doi.org/{}", doi));
+    } else if let Some(arxiv_id) = &reference.arxiv_id {
+        citation.push_str(&format!(" arXiv:{}", arxiv_id));
+    }
+}
+
+/// Join names as `A`, `A and B`, or `A, B, and C` with the given separators
+fn join_list(items: &[String], separator: &str, last_separator: &str, pair_separator: &str) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [first, second] => format!("{}{}{}", first, pair_separator, second),
+        [rest @ .., last] => format!("{}{}{}", rest.join(separator), last_separator, last),
+    }
+}
+
+fn join_parts(parts: &[&str], separator: &str) -> String {
+    parts.iter().filter(|part| !part.is_empty()).copied().collect::<Vec<_>>().join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PublicationInfo;
+
+    fn create_reference() -> Reference {
+        Reference {
+            title: "The Large N limit of superconformal field theories and supergravity".to_string(),
+            authors: vec!["Maldacena, Juan Martin".to_string()],
+            arxiv_id: Some("hep-th/9711200".to_string()),
+            year: Some(1998),
+            doi: Some("10.4310/ATMP.1998.v2.n2.a1".to_string()),
+            publication_info: Some(PublicationInfo {
+                journal_title: Some("Adv. Theor. Math. Phys.".to_string()),
+                journal_volume: Some("2".to_string()),
+                journal_issue: Some("2".to_string()),
+                page_start: Some("231".to_string()),
+                page_end: Some("252".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_format_apa() {
+        let mut reference = create_reference();
+        assert_eq!(
+            format_citation(&reference, CitationStyle::Apa),
+            "Maldacena, J. M. (1998). The Large N limit of superconformal field theories and supergravity. \
+             Adv. Theor. Math. Phys., 2(2), 231–252. https://doi.org/10.4310/ATMP.1998.v2.n2.a1"
+        );
+
+        reference.authors.push("Witten, Edward".to_string());
+        assert!(format_citation(&reference, CitationStyle::Apa).starts_with("Maldacena, J. M., & Witten, E. (1998)."));
+
+        let anonymous = Reference { title: "Anonymous".to_string(), ..Default::default() };
+        assert_eq!(format_citation(&anonymous, CitationStyle::Apa), "Anonymous. (n.d.).");
+    }
+
+    #[test]
+    fn test_format_chicago() {
+        let mut reference = create_reference();
+        reference.authors.push("Witten, Edward".to_string());
+        assert_eq!(
+            format_citation(&reference, CitationStyle::Chicago),
+            "Maldacena, Juan Martin and Edward Witten. \"The Large N limit of superconformal field theories and supergravity.\" \
+             Adv. Theor. Math. Phys. 2, no. 2 (1998): 231–252. https://doi.org/10.4310/ATMP.1998.v2.n2.a1"
+        );
+    }
+
+    #[test]
+    fn test_format_ieee() {
+        let reference = Reference {
+            title: "A preprint".to_string(),
+            authors: (1..=7).map(|i| format!("Author{}, A.", i)).collect(),
+            arxiv_id: Some("2301.12345".to_string()),
+            year: Some(2023),
+            ..Default::default()
+        };
+        let citations = format_citations(&[create_reference(), reference], CitationStyle::Ieee);
+        assert!(citations.starts_with(
+            "[1] J. M. Maldacena, \"The Large N limit of superconformal field theories and supergravity,\" \
+             Adv. Theor. Math. Phys., vol. 2, no. 2, pp. 231–252, 1998, doi: 10.4310/ATMP.1998.v2.n2.a1.\n"
+        ));
+        assert!(citations.ends_with("\n[2] A. Author1 et al., \"A preprint,\" 2023, arXiv:2301.12345.\n"));
+    }
+
+    #[test]
+    fn test_format_journal_styles() {
+        let mut reference = create_reference();
+        reference.authors.push("Witten, Edward".to_string());
+        reference.texkey = Some("Maldacena:1997re".to_string());
+        assert_eq!(
+            format_citation(&reference, CitationStyle::Jhep),
+            "J.M. Maldacena and E. Witten, \\emph{The Large N limit of superconformal field theories and supergravity}, \
+             \\emph{Adv. Theor. Math. Phys.} \\textbf{2} (1998) 231 [hep-th/9711200]."
+        );
+        assert_eq!(
+            format_citation(&reference, CitationStyle::Prd),
+            "J. M. Maldacena and E. Witten, Adv. Theor. Math. Phys. \\textbf{2}, 231 (1998), arXiv:hep-th/9711200."
+        );
+        assert!(format_citation(&reference, CitationStyle::Revtex)
+            .starts_with("J. M. Maldacena and E. Witten, \\emph{The Large N limit"));
+
+        let preprint = Reference {
+            title: "A preprint".to_string(),
+            authors: vec!["Doe, John".to_string()],
+            arxiv_id: Some("2301.12345".to_string()),
+            year: Some(2023),
+            ..Default::default()
+        };
+        assert_eq!(format_citation(&preprint, CitationStyle::Jhep), "J. Doe, \\emph{A preprint} [arXiv:2301.12345].");
+        assert_eq!(format_citation(&preprint, CitationStyle::Prd), "J. Doe, \\emph{A preprint}, arXiv:2301.12345 (2023).");
+        let retracted = Reference { retracted: true, ..preprint };
+        assert_eq!(format_citation(&retracted, CitationStyle::Jhep), "[RETRACTED] J. Doe, \\emph{A preprint} [arXiv:2301.12345].");
+
+        let entries = format_citations(&[reference], CitationStyle::Jhep);
+        assert!(entries.starts_with("\\bibitem{Maldacena:1997re}\nJ.M. Maldacena"));
+    }
+
+    #[test]
+    fn test_format_thebibliography() {
+        let mut reference = create_reference();
+        reference.texkey = Some("Maldacena:1997re".to_string());
+        let references = vec![reference; 10];
+
+        let bibliography = format_thebibliography(&references, CitationStyle::Apa);
+        assert!(bibliography.starts_with("\\begin{thebibliography}{99}\n\n\\bibitem{Maldacena:1997re}\n\
+            J. M. Maldacena, The Large N limit of superconformal field theories and supergravity, \
+            Adv. Theor. Math. Phys. (1998), [arXiv:hep-th/9711200].\n\n"));
+        assert!(bibliography.ends_with("\\end{thebibliography}\n"));
+        assert_eq!(bibliography.matches("\\bibitem").count(), 10);
+
+        let bibliography = format_thebibliography(&references[..1], CitationStyle::Prd);
+        assert!(bibliography.starts_with("\\begin{thebibliography}{9}\n\n\\bibitem{Maldacena:1997re}\n\
+            J. M. Maldacena, Adv. Theor. Math. Phys."));
+    }
+    #[test]
+    fn test_format_html() {
+        let mut reference = create_reference();
+        reference.authors.push("Witten & Co".to_string());
+        reference.inspire_id = Some("452933".to_string());
+
+        let html = format_html(&[reference, Reference { title: "Untitled".to_string(), ..Default::default() }], CitationStyle::Apa);
+        assert!(html.starts_with("<ul class=\"publications\">\n  <li>Maldacena, J. M., &amp; Co, W. &amp;"));
+        assert!(html.contains(" <a href=\"https://inspirehep.net/literature/452933\">[link]</a></li>\n"));
+        assert!(html.contains("  <li>Untitled. (n.d.).</li>\n"));
+        assert!(html.ends_with("</ul>\n"));
+    }
+}
@@ -0,0 +1,650 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Result;
+use futures::{Stream, TryStreamExt};
+use log::{info, warn};
+use serde::Serialize;
+
+use crate::api::InspireClient;
+use crate::crossref::CrossrefClient;
+use crate::unpaywall::UnpaywallClient;
+use crate::filter::{IdentifierFilter, TextFilter};
+use crate::models::{normalize_doi, DataSource, DatasetListing, OpenAccessSource, Paper, Reference};
+use crate::progress::{self, Progress, ProgressObserver};
+use crate::query::{LiteratureQuery, SearchSort};
+use crate::report::Table;
+use crate::warnings::{Warning, WarningKind, Warnings};
+#[cfg(feature = "network-analysis")]
+use crate::network::{BuildStatus, CitationNetwork, NetworkBuildOptions, PartialBuildError};
+
+/// Order of a reference list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ReferenceSort {
+    /// As listed in the paper
+    #[default]
+    Original,
+    /// Newest first
+    Year,
+    /// Most cited first (looks up each reference's record)
+    Citations,
+    /// Alphabetically by title
+    Title,
+}
+
+/// How a reference list is filtered, ordered and completed (`refs` options)
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceOptions {
+    /// Keep references in at least one of these arXiv categories
+    pub categories: Option<Vec<String>>,
+    /// Keep references whose title or abstract matches
+    pub filter: Option<TextFilter>,
+    /// Keep references with an author at a matching institution; reference
+    /// lists carry no affiliations, so each reference's record is looked up
+    pub affiliation: Option<TextFilter>,
+    /// Drop or keep references by identifier (`--exclude-file`, `--include-file`)
+    pub identifiers: IdentifierFilter,
+    /// Order of the returned references
+    pub sort: ReferenceSort,
+    /// Return at most this many references, after sorting
+    pub limit: Option<usize>,
+    /// Look up each reference's record to include its abstract
+    pub with_abstract: bool,
+}
+
+/// A paper's prepared references and the soft issues met while fetching them
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchResult {
+    pub paper: Paper,
+    pub references: Vec<Reference>,
+    pub warnings: Warnings,
+}
+
+/// Fetch a paper by arXiv ID (or DOI or record ID, see `fetch_paper`) and its
+/// references, prepared according to `options`
+pub async fn fetch_references(client: &InspireClient, arxiv_id: &str, options: &ReferenceOptions) -> Result<FetchResult> {
+    info!("Fetching references for paper: {}", arxiv_id);
+
+    let paper = fetch_paper(client, arxiv_id).await?;
+    let (references, mut warnings) = client.get_paper_references_with_warnings(&paper.id).await?;
+    let (references, prepare_warnings) = prepare_references(client, references, options).await;
+    warnings.extend(prepare_warnings);
+
+    info!("Found {} references", references.len());
+    Ok(FetchResult { paper, references, warnings })
+}
+
+/// Filter, sort, limit and complete an already fetched reference list.
+///
+/// Records are looked up for every reference when filtering by affiliation or
+/// text (which also matches abstracts), for every remaining reference when
+/// sorting by citations, and otherwise only for abstracts of those kept after
+/// the limit. Failed lookups and references without a year are returned as warnings.
+pub async fn prepare_references(client: &InspireClient, mut references: Vec<Reference>, options: &ReferenceOptions) -> (Vec<Reference>, Warnings) {
+    let mut warnings = Warnings::new();
+    let mut completed = false;
+    if options.affiliation.is_some() || options.filter.is_some() {
+        let with_abstract = options.with_abstract || options.filter.is_some();
+        complete_references(client, &mut references, with_abstract, &mut warnings).await;
+        completed = true;
+    }
+    let mut references = filter_references(references, options);
+    if options.filter.is_some() && !options.with_abstract {
+        // Abstracts were only fetched for the text filter
+        for reference in &mut references {
+            reference.abstract_text = None;
+            reference.sources.abstract_source = None;
+        }
+    }
+
+    if options.sort == ReferenceSort::Citations && !completed {
+        complete_references(client, &mut references, options.with_abstract, &mut warnings).await;
+        completed = true;
+    }
+    sort_references(&mut references, options.sort);
+    if let Some(limit) = options.limit {
+        references.truncate(limit);
+    }
+    if options.with_abstract && !completed {
+        complete_references(client, &mut references, true, &mut warnings).await;
+    }
+
+    warnings.extend(references.iter()
+        .filter(|r| r.year.is_none())
+        .map(|r| {
+            let warning = Warning::new(WarningKind::MissingYear, format!("No year for \"{}\"", r.title));
+            match reference_identifier(r) {
+                Some(id) => warning.with_item(id),
+                None => warning,
+            }
+        }));
+
+    (references, warnings)
+}
+
+/// The most specific identifier of a reference, for pointing at it in warnings
+fn reference_identifier(reference: &Reference) -> Option<String> {
+    reference.inspire_id.clone()
+        .or_else(|| reference.arxiv_id.clone())
+        .or_else(|| reference.doi.clone())
+        .or_else(|| reference.label.as_ref().map(|label| format!("[{}]", label)))
+}
+
+/// Fetch a paper by INSPIRE record ID (all digits), DOI or arXiv ID
+pub async fn fetch_paper(client: &InspireClient, id: &str) -> Result<Paper> {
+    if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) {
+        client.get_paper_by_id(id).await
+    } else if normalize_doi(id).is_some() {
+        client.get_paper_by_doi(id).await
+    } else {
+        client.get_paper_by_arxiv(id).await
+    }
+}
+
+/// Run a literature search page by page, yielding each page as references with
+/// the filters in `options` applied, so only one page is held at a time
+pub fn search_references<'a>(
+    client: &'a InspireClient,
+    query: &'a LiteratureQuery,
+    limit: usize,
+    sort: SearchSort,
+    options: &'a ReferenceOptions,
+) -> impl Stream<Item = Result<Vec<Reference>>> + 'a {
+    client.search_literature_pages(query, limit, sort)
+        .map_ok(move |papers| filter_references(papers.into_iter().map(Reference::from).collect(), options))
+}
+
+/// Apply the category, text, affiliation and identifier filters to a reference list
+pub fn filter_references(references: Vec<Reference>, options: &ReferenceOptions) -> Vec<Reference> {
+    references.into_iter()
+        .filter(|r| match &options.categories {
+            Some(categories) => r.categories.iter().any(|c| categories.contains(c)),
+            None => true,
+        })
+        .filter(|r| match &options.filter {
+            Some(filter) => filter.matches_reference(r),
+            None => true,
+        })
+        .filter(|r| match &options.affiliation {
+            Some(filter) => filter.matches_any(&r.affiliations),
+            None => true,
+        })
+        .filter(|r| options.identifiers.allows_reference(r))
+        .collect()
+}
+
+/// One row per reference, for listings shown as CSV or Markdown (`--table`)
+pub fn reference_table(references: &[Reference]) -> Table {
+    let mut table = Table::new(&["title", "authors", "year", "arxiv_id", "citations"]);
+    for reference in references {
+        let authors = match reference.authors.as_slice() {
+            [] => String::new(),
+            [author] => author.clone(),
+            [author, ..] => format!("{} et al.", author),
+        };
+        table.push_row(vec![
+            reference.title.clone(),
+            authors,
+            reference.year.map(|year| year.to_string()).unwrap_or_default(),
+            reference.arxiv_id.clone().unwrap_or_default(),
+            reference.citation_count.map(|count| count.to_string()).unwrap_or_default(),
+        ]);
+    }
+    table
+}
+
+/// Sort references in place; ties keep their original order
+pub fn sort_references(references: &mut [Reference], sort: ReferenceSort) {
+    match sort {
+        ReferenceSort::Original => {}
+        ReferenceSort::Year => references.sort_by_key(|r| std::cmp::Reverse(r.year)),
+        ReferenceSort::Citations => references.sort_by_key(|r| std::cmp::Reverse(r.citation_count)),
+        ReferenceSort::Title => references.sort_by_key(|r| r.title.to_lowercase()),
+    }
+}
+
+/// Fill in citation counts, affiliations (and abstracts) from each reference's INSPIRE record
+async fn complete_references(client: &InspireClient, references: &mut [Reference], with_abstract: bool, warnings: &mut Warnings) {
+    for reference in references.iter_mut() {
+        let Some(inspire_id) = reference.inspire_id.clone() else { continue };
+        match client.get_paper_by_id(&inspire_id).await {
+            Ok(record) => {
+                let sources = &mut reference.sources;
+                sources.citation_count_source = record.citation_count.and(Some(DataSource::InspireRecord));
+                sources.affiliations_source = (!record.affiliations.is_empty()).then_some(DataSource::InspireRecord);
+                reference.citation_count = record.citation_count;
+                reference.affiliations = record.affiliations;
+                if with_abstract {
+                    sources.abstract_source = record.abstract_text.as_ref().and(Some(DataSource::InspireRecord));
+                    reference.abstract_text = record.abstract_text;
+                }
+            }
+            Err(e) => {
+                warn!("Failed to fetch record {}: {}", inspire_id, e);
+                warnings.push(Warning::new(
+                    WarningKind::LookupFailed,
+                    format!("Could not fetch record {}: {}", inspire_id, e),
+                ).with_item(inspire_id));
+            }
+        }
+    }
+}
+
+/// Datasets of a paper and of each of its references that has any (`--with-datasets`)
+pub async fn dataset_listings(
+    client: &InspireClient,
+    paper: &Paper,
+    references: &[Reference],
+    observer: Option<&dyn ProgressObserver>,
+) -> Vec<DatasetListing> {
+    let mut progress = Progress {
+        stage: "datasets",
+        total: Some(references.iter().filter(|r| r.inspire_id.is_some()).count()),
+        ..Default::default()
+    };
+    let mut listings = Vec::new();
+    if !paper.datasets.is_empty() {
+        listings.push(DatasetListing {
+            inspire_id: paper.id.clone(),
+            title: paper.title.clone(),
+            datasets: paper.datasets.clone(),
+        });
+    }
+
+    for inspire_id in references.iter().filter_map(|r| r.inspire_id.as_ref()) {
+        match client.get_paper_by_id(inspire_id).await {
+            Ok(record) if !record.datasets.is_empty() => listings.push(DatasetListing {
+                inspire_id: record.id,
+                title: record.title,
+                datasets: record.datasets,
+            }),
+            Ok(_) => {}
+            Err(e) => warn!("Failed to fetch record {}: {}", inspire_id, e),
+        }
+        progress.requests_completed += 1;
+        progress.papers_discovered = listings.len();
+        progress.current_item = Some(inspire_id.clone());
+        progress::report(observer, &progress);
+    }
+
+    progress::finish(observer, &progress);
+    listings
+}
+
+/// Flag references withdrawn on INSPIRE or retracted according to CrossRef
+/// (which includes the Retraction Watch database). Returns the number flagged.
+pub async fn flag_retractions(client: &InspireClient, crossref: &CrossrefClient, references: &mut [Reference]) -> usize {
+    let ids: Vec<String> = references.iter().filter_map(|r| r.inspire_id.clone()).collect();
+    if let Err(e) = client.prefetch_records(&ids).await {
+        warn!("Failed to prefetch records: {}", e);
+    }
+
+    for reference in references.iter_mut() {
+        // Reference entries often lack the DOI the record has
+        let mut doi = reference.doi.clone();
+        if let Some(inspire_id) = reference.inspire_id.clone() {
+            match client.get_paper_by_id(&inspire_id).await {
+                Ok(record) => {
+                    if record.retracted {
+                        reference.retracted = true;
+                        reference.sources.retracted_source = Some(DataSource::InspireRecord);
+                    }
+                    doi = doi.or(record.doi);
+                }
+                Err(e) => warn!("Failed to fetch record {}: {}", inspire_id, e),
+            }
+        }
+        if let Some(doi) = doi.filter(|_| !reference.retracted) {
+            match crossref.is_retracted(&doi).await {
+                Ok(true) => {
+                    reference.retracted = true;
+                    reference.sources.retracted_source = Some(DataSource::Crossref);
+                }
+                Ok(false) => {}
+                Err(e) => warn!("Could not check {} for retractions: {}", doi, e),
+            }
+        }
+    }
+
+    references.iter().filter(|r| r.retracted).count()
+}
+
+/// Open-access counts over a reference list
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct OpenAccessSummary {
+    pub references: usize,
+    pub open: usize,
+    pub closed: usize,
+    /// Neither on arXiv nor openly licensed, and not looked up on Unpaywall
+    pub unknown: usize,
+    /// Open references by where their status was read from
+    pub open_by_source: BTreeMap<OpenAccessSource, usize>,
+}
+
+impl OpenAccessSummary {
+    pub fn from_references(references: &[Reference]) -> Self {
+        let mut summary = Self { references: references.len(), ..Default::default() };
+        for reference in references {
+            match &reference.open_access {
+                Some(status) if status.is_open => {
+                    summary.open += 1;
+                    *summary.open_by_source.entry(status.source).or_insert(0) += 1;
+                }
+                Some(_) => summary.closed += 1,
+                None => summary.unknown += 1,
+            }
+        }
+        summary
+    }
+
+    /// Counts as a two-column report table
+    pub fn table(&self) -> Table {
+        let mut table = Table::new(&["status", "references"]);
+        table.push_row(vec!["open".to_string(), self.open.to_string()]);
+        for (source, count) in &self.open_by_source {
+            table.push_row(vec![format!("open ({})", source.as_str()), count.to_string()]);
+        }
+        table.push_row(vec!["closed".to_string(), self.closed.to_string()]);
+        table.push_row(vec!["unknown".to_string(), self.unknown.to_string()]);
+        table
+    }
+}
+
+/// Set the open-access status of each reference: open when it is on arXiv or
+/// openly licensed, otherwise as Unpaywall reports it for the DOI when an
+/// Unpaywall client is given. Failed lookups leave the status unknown.
+pub async fn annotate_open_access(unpaywall: Option<&UnpaywallClient>, references: &mut [Reference]) -> OpenAccessSummary {
+    for reference in references.iter_mut() {
+        reference.open_access = reference.known_open_access();
+        let (Some(unpaywall), None, Some(doi)) = (unpaywall, &reference.open_access, &reference.doi) else {
+            continue;
+        };
+        match unpaywall.lookup(doi).await {
+            Ok(status) => reference.open_access = status,
+            Err(e) => warn!("Could not look up {} on Unpaywall: {}", doi, e),
+        }
+    }
+    OpenAccessSummary::from_references(references)
+}
+
+/// Country codes of institutions (by affiliation name) from their INSPIRE
+/// institution records; institutions without a known country are left out
+/// and returned as warnings
+pub async fn institution_countries<'a>(client: &InspireClient, institutions: impl IntoIterator<Item = &'a str>) -> (HashMap<String, String>, Warnings) {
+    let mut countries = HashMap::new();
+    let mut warnings = Warnings::new();
+    for institution in institutions {
+        match client.get_institution(institution).await {
+            Ok(record) => match record.country_code() {
+                Some(country) => {
+                    countries.insert(institution.to_string(), country.to_string());
+                }
+                None => warnings.push(Warning::new(
+                    WarningKind::LookupFailed,
+                    format!("No country known for institution \"{}\"", institution),
+                ).with_item(institution)),
+            },
+            Err(e) => {
+                warn!("Failed to look up institution {}: {}", institution, e);
+                warnings.push(Warning::new(
+                    WarningKind::LookupFailed,
+                    format!("Could not look up institution \"{}\": {}", institution, e),
+                ).with_item(institution));
+            }
+        }
+    }
+    (countries, warnings)
+}
+
+/// Build the citation network around a paper.
+///
+/// A cancelled build still returns the papers found before cancellation. A
+/// build failing after finding papers returns a `PartialBuildError` holding them.
+#[cfg(feature = "network-analysis")]
+pub async fn build_network(client: &InspireClient, arxiv_id: &str, options: &NetworkBuildOptions) -> Result<(CitationNetwork, BuildStatus)> {
+    info!("Building citation network for paper: {} with depth: {}", arxiv_id, options.depth);
+
+    let mut network = CitationNetwork::new();
+    let status = match network.build_with_options(client, arxiv_id, options).await {
+        Ok(status) => status,
+        Err(error) if network.paper_count() > 0 => {
+            network.build_error = Some(error.to_string());
+            return Err(PartialBuildError { network, source: error }.into());
+        }
+        Err(error) => return Err(error),
+    };
+
+    info!("Built network with {} papers", network.paper_count());
+    Ok((network, status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::License;
+
+    fn create_reference(title: &str, categories: &[&str]) -> Reference {
+        Reference {
+            title: title.to_string(),
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filter_references() {
+        let references = vec![
+            create_reference("Holography and AdS/CFT", &["hep-th"]),
+            create_reference("Collider phenomenology", &["hep-ph"]),
+            create_reference("Black hole entropy", &["hep-th", "gr-qc"]),
+        ];
+
+        assert_eq!(filter_references(references.clone(), &ReferenceOptions::default()).len(), 3);
+
+        let options = ReferenceOptions {
+            categories: Some(vec!["hep-th".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(filter_references(references.clone(), &options).len(), 2);
+
+        let options = ReferenceOptions {
+            categories: Some(vec!["hep-th".to_string()]),
+            filter: Some(TextFilter::new("black hole").unwrap()),
+            ..Default::default()
+        };
+        let filtered = filter_references(references.clone(), &options);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].title, "Black hole entropy");
+
+        let mut affiliated = references;
+        affiliated[1].affiliations = vec!["CERN".to_string(), "DESY".to_string()];
+        let options = ReferenceOptions {
+            affiliation: Some(TextFilter::new("desy").unwrap()),
+            ..Default::default()
+        };
+        let filtered = filter_references(affiliated, &options);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].title, "Collider phenomenology");
+    }
+
+    #[test]
+    fn test_sort_references() {
+        let mut references = vec![
+            Reference { title: "beta".to_string(), year: Some(2001), citation_count: Some(5), ..Default::default() },
+            Reference { title: "Alpha".to_string(), year: Some(2010), citation_count: None, ..Default::default() },
+            Reference { title: "gamma".to_string(), year: None, citation_count: Some(50), ..Default::default() },
+        ];
+        let titles = |refs: &[Reference]| refs.iter().map(|r| r.title.clone()).collect::<Vec<_>>();
+
+        sort_references(&mut references, ReferenceSort::Year);
+        assert_eq!(titles(&references), ["Alpha", "beta", "gamma"]);
+
+        sort_references(&mut references, ReferenceSort::Citations);
+        assert_eq!(titles(&references), ["gamma", "beta", "Alpha"]);
+
+        sort_references(&mut references, ReferenceSort::Title);
+        assert_eq!(titles(&references), ["Alpha", "beta", "gamma"]);
+    }
+
+    #[test]
+    fn test_reference_table() {
+        let references = vec![Reference {
+            title: "Holography".to_string(),
+            authors: vec!["Doe, J.".to_string(), "Roe, R.".to_string()],
+            year: Some(2024),
+            arxiv_id: Some("2401.00001".to_string()),
+            citation_count: Some(3),
+            ..Default::default()
+        }];
+        assert_eq!(
+            reference_table(&references).to_csv(),
+            "title,authors,year,arxiv_id,citations\nHolography,\"Doe, J. et al.\",2024,2401.00001,3\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_open_access_without_unpaywall() {
+        let mut references = vec![
+            Reference { arxiv_id: Some("2301.12345".to_string()), ..create_reference("Preprint", &[]) },
+            Reference {
+                doi: Some("10.1007/JHEP01(2024)001".to_string()),
+                license: Some(License { name: Some("CC BY 4.0".to_string()), ..Default::default() }),
+                ..create_reference("Licensed", &[])
+            },
+            Reference { doi: Some("10.1016/0370-2693(86)90001-0".to_string()), ..create_reference("Paywalled", &[]) },
+        ];
+        let summary = annotate_open_access(None, &mut references).await;
+        assert_eq!(references[0].open_access.as_ref().unwrap().url.as_deref(), Some("https://arxiv.org/pdf/2301.12345"));
+        assert_eq!(references[1].open_access.as_ref().unwrap().source, OpenAccessSource::License);
+        assert_eq!(references[2].open_access, None);
+        assert_eq!((summary.open, summary.closed, summary.unknown), (2, 0, 1));
+        assert_eq!(summary.table().rows[1], ["open (arxiv)", "1"]);
+    }
+
+    #[tokio::test]
+    async fn test_prepare_references_limits_without_requests() {
+        let references = vec![
+            create_reference("Holography and AdS/CFT", &["hep-th"]),
+            create_reference("Black hole entropy", &["hep-th"]),
+            create_reference("Collider phenomenology", &["hep-ph"]),
+        ];
+        let options = ReferenceOptions {
+            sort: ReferenceSort::Title,
+            limit: Some(2),
+            ..Default::default()
+        };
+
+        let (prepared, warnings) = prepare_references(&InspireClient::new(), references, &options).await;
+        assert_eq!(prepared.len(), 2);
+        assert_eq!(prepared[0].title, "Black hole entropy");
+        // Only the references kept after the limit are reported
+        assert_eq!(warnings.count(WarningKind::MissingYear), 2);
+        assert_eq!(warnings.iter().next().unwrap().message, "No year for \"Black hole entropy\"");
+    }
+
+    /// A client whose every record lookup gets `record`
+    #[cfg(feature = "native")]
+    async fn serve_record(record: serde_json::Value) -> InspireClient {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let body = record.to_string();
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = [0; 1024];
+                let _ = stream.read(&mut buffer).await;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(), body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        InspireClient::new().with_base_urls(&[format!("http://{}/api", addr)])
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn test_text_filter_matches_fetched_abstracts() {
+        let client = serve_record(serde_json::json!({"metadata": {
+            "control_number": 7,
+            "titles": [{"title": "Large N"}],
+            "abstracts": [{"value": "A holographic duality."}]
+        }})).await;
+
+        let references = vec![
+            Reference { inspire_id: Some("7".to_string()), ..create_reference("Large N", &["hep-th"]) },
+            create_reference("Collider phenomenology", &["hep-ph"]),
+        ];
+        let options = ReferenceOptions { filter: Some(TextFilter::new("holograph").unwrap()), ..Default::default() };
+        let (prepared, _) = prepare_references(&client, references.clone(), &options).await;
+        assert_eq!(prepared.len(), 1);
+        assert_eq!(prepared[0].title, "Large N");
+        assert_eq!(prepared[0].abstract_text, None);
+
+        let options = ReferenceOptions { with_abstract: true, ..options };
+        let (prepared, _) = prepare_references(&client, references, &options).await;
+        assert_eq!(prepared[0].abstract_text.as_deref(), Some("A holographic duality."));
+        assert_eq!(prepared[0].sources.abstract_source, Some(DataSource::InspireRecord));
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn test_sources_only_for_filled_fields() {
+        // No citation count and no abstract on this record
+        let client = serve_record(serde_json::json!({"metadata": {
+            "control_number": 7,
+            "titles": [{"title": "Large N"}],
+            "authors": [{"full_name": "Maldacena, Juan", "affiliations": [{"value": "Harvard U."}]}]
+        }})).await;
+
+        let references = vec![Reference { inspire_id: Some("7".to_string()), ..create_reference("Large N", &["hep-th"]) }];
+        let options = ReferenceOptions { sort: ReferenceSort::Citations, with_abstract: true, ..Default::default() };
+        let (prepared, _) = prepare_references(&client, references, &options).await;
+        assert_eq!(prepared[0].affiliations, ["Harvard U."]);
+        assert_eq!(prepared[0].sources.affiliations_source, Some(DataSource::InspireRecord));
+        assert_eq!(prepared[0].sources.citation_count_source, None);
+        assert_eq!(prepared[0].sources.abstract_source, None);
+    }
+
+    #[cfg(all(feature = "native", feature = "network-analysis"))]
+    #[tokio::test]
+    async fn test_failed_build_returns_partial_network() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Answers the root lookup and its record, then goes away
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let reference = |id: u32| serde_json::json!({"record": {"$ref": format!("https://inspirehep.net/api/literature/{}", id)}});
+            let bodies = [
+                serde_json::json!({"hits": {"hits": [{"metadata": {"control_number": 1, "titles": [{"title": "Root"}]}}]}}),
+                serde_json::json!({"metadata": {"control_number": 1, "titles": [{"title": "Root"}], "references": [reference(2), reference(3)]}}),
+            ];
+            for body in bodies {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = [0; 1024];
+                let _ = stream.read(&mut buffer).await;
+                let body = body.to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(), body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let client = InspireClient::new()
+            .with_base_urls(&[format!("http://{}/api", addr)])
+            .with_connect_retries(0);
+        let options = NetworkBuildOptions { depth: 2, ..Default::default() };
+        let error = build_network(&client, "2301.12345", &options).await.unwrap_err();
+
+        let partial = error.downcast::<PartialBuildError>().unwrap();
+        assert_eq!(partial.network.paper_count(), 3);
+        assert!(partial.network.citations.contains_key("1"));
+        assert!(partial.network.build_error.as_deref().unwrap().starts_with("cannot reach 127.0.0.1"));
+    }
+}
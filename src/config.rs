@@ -1,7 +1,14 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
-use anyhow::Result;
-use crate::output::OutputFormat;
+use anyhow::{anyhow, Result};
+use log::{debug, info};
+use crate::api::{ConnectionOptions, MAX_RETRIES};
+use crate::dates::DateOrder;
+use crate::hooks::HookConfig;
+use crate::notify::NotificationConfig;
+use crate::models::BibtexOptions;
+use crate::output::{BibSort, OutputFormat};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
@@ -25,6 +32,37 @@ pub struct Config {
     
     /// UI settings
     pub ui: UiConfig,
+    
+    /// Notification settings for watch mode
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+    
+    /// BibTeX output settings
+    #[serde(default)]
+    pub bibtex: BibtexConfig,
+    
+    /// Commands run after output is written
+    #[serde(default)]
+    pub hooks: HookConfig,
+    
+    /// Contact address sent to Unpaywall for open-access lookups; without it
+    /// only arXiv and license information is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unpaywall_email: Option<String>,
+    
+    /// Named sets of defaults selected with `--profile` (`[profiles.thesis]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Defaults overridden by a profile; unset fields keep the top-level value
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Profile {
+    pub default_format: Option<OutputFormat>,
+    pub default_output_dir: Option<PathBuf>,
+    pub default_categories: Option<Vec<String>>,
+    pub verbose: Option<bool>,
+    pub default_network_depth: Option<u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -32,14 +70,43 @@ pub struct ApiConfig {
     /// Custom INSPIRE API base URL
     pub base_url: Option<String>,
     
-    /// Request timeout in seconds
+    /// API base URLs in order of preference, failing over from each to the
+    /// next; replaces `base_url` when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_urls: Option<Vec<String>>,
+    
+    /// Seconds to wait for the response headers of a request
     pub timeout_seconds: Option<u64>,
     
-    /// Maximum retries for failed requests
+    /// Retries of requests that cannot reach the server (DNS, TLS, refused);
+    /// `CONNECT_RETRIES` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_retries: Option<u32>,
+    
+    /// Retries of requests that are rate limited (429) or find the server
+    /// temporarily unavailable (502-504)
     pub max_retries: Option<u32>,
     
     /// Delay between requests (in milliseconds) to avoid rate limiting
     pub request_delay_ms: Option<u64>,
+    
+    /// How ambiguous numeric dates in records are read (`day-first` or `month-first`)
+    pub date_order: Option<DateOrder>,
+    
+    /// Idle connections kept open per host
+    pub pool_max_idle_per_host: Option<usize>,
+    
+    /// Seconds an idle connection is kept open
+    pub pool_idle_timeout_seconds: Option<u64>,
+    
+    /// Use HTTP/2 without negotiating it first
+    pub http2_prior_knowledge: Option<bool>,
+    
+    /// Largest response accepted, in MiB
+    pub max_response_mb: Option<usize>,
+    
+    /// Seconds to wait for a whole response body
+    pub body_timeout_seconds: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -54,6 +121,33 @@ pub struct UiConfig {
     pub progress_style: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct BibtexConfig {
+    /// Order of generated entries (`key`, `year`, `author` or `appearance`)
+    pub sort: Option<BibSort>,
+    
+    /// Add `keywords` from the arXiv categories
+    pub keywords: Option<bool>,
+    
+    /// Add a `url` to the INSPIRE record
+    pub url: Option<bool>,
+    
+    /// Add this text as `note` to every entry
+    pub note: Option<String>,
+    
+    /// Add the abstract as `abstract` (looked up for `refs` even without `--with-abstract`)
+    pub include_abstract: Option<bool>,
+    
+    /// Pin `eprint` to the current arXiv version (`2301.12345v2`)
+    pub pin_versions: Option<bool>,
+    
+    /// Add `month` (`jan` ... `dec`) when the publication month is known
+    pub month: Option<bool>,
+    
+    /// Write biblatex's `date` instead of `year` and `month`
+    pub biblatex: Option<bool>,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -64,6 +158,11 @@ impl Default for Config {
             default_network_depth: Some(1),
             api: ApiConfig::default(),
             ui: UiConfig::default(),
+            notifications: NotificationConfig::default(),
+            bibtex: BibtexConfig::default(),
+            hooks: HookConfig::default(),
+            unpaywall_email: None,
+            profiles: BTreeMap::new(),
         }
     }
 }
@@ -72,9 +171,36 @@ impl Default for ApiConfig {
     fn default() -> Self {
         Self {
             base_url: Some("https://inspirehep.net/api".to_string()),
+            base_urls: None,
             timeout_seconds: Some(30),
-            max_retries: Some(3),
+            connect_retries: None,
+            max_retries: Some(MAX_RETRIES),
             request_delay_ms: Some(100),
+            date_order: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout_seconds: None,
+            http2_prior_knowledge: None,
+            max_response_mb: None,
+            body_timeout_seconds: None,
+        }
+    }
+}
+
+impl ApiConfig {
+    /// API base URLs, primary first: `base_urls` when set, otherwise `base_url`
+    pub fn base_urls(&self) -> Vec<String> {
+        match &self.base_urls {
+            Some(urls) if !urls.is_empty() => urls.clone(),
+            _ => self.base_url.iter().cloned().collect(),
+        }
+    }
+    
+    /// The HTTP client's pool and protocol settings
+    pub fn connection_options(&self) -> ConnectionOptions {
+        ConnectionOptions {
+            pool_max_idle_per_host: self.pool_max_idle_per_host,
+            pool_idle_timeout: self.pool_idle_timeout_seconds.map(std::time::Duration::from_secs),
+            http2_prior_knowledge: self.http2_prior_knowledge.unwrap_or(false),
         }
     }
 }
@@ -90,7 +216,8 @@ impl Default for UiConfig {
 }
 
 impl Config {
-    /// Load configuration from file, creating default if it doesn't exist
+    /// Load configuration from file, or the defaults when there is none yet
+    /// (`init-config` writes one)
     pub fn load() -> Result<Self> {
         let config_path = Self::config_file_path()?;
         
@@ -99,15 +226,13 @@ impl Config {
             let config: Config = toml::from_str(&content)?;
             Ok(config)
         } else {
-            // Create default config
-            let config = Config::default();
-            config.save()?;
-            Ok(config)
+            debug!("No configuration at {}; using defaults", config_path.display());
+            Ok(Config::default())
         }
     }
     
-    /// Save configuration to file
-    pub fn save(&self) -> Result<()> {
+    /// Save configuration to file, returning its path
+    pub fn save(&self) -> Result<PathBuf> {
         let config_path = Self::config_file_path()?;
         
         if let Some(parent) = config_path.parent() {
@@ -117,8 +242,8 @@ impl Config {
         let content = toml::to_string_pretty(self)?;
         std::fs::write(&config_path, content)?;
         
-        println!("Configuration saved to: {}", config_path.display());
-        Ok(())
+        info!("Configuration saved to: {}", config_path.display());
+        Ok(config_path)
     }
     
     /// Get the path to the configuration file
@@ -129,12 +254,27 @@ impl Config {
         Ok(config_dir.join("reference_tool").join("config.toml"))
     }
     
-    /// Show current configuration
-    pub fn show(&self) -> Result<()> {
-        let content = toml::to_string_pretty(self)?;
-        println!("Current configuration:");
-        println!("{}", content);
-        Ok(())
+    /// This configuration with the defaults of profile `name` applied
+    pub fn with_profile(mut self, name: &str) -> Result<Self> {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            return Err(if known.is_empty() {
+                anyhow!("Unknown profile '{}': the config defines no [profiles]", name)
+            } else {
+                anyhow!("Unknown profile '{}' (available: {})", name, known.join(", "))
+            });
+        };
+        self.default_format = profile.default_format.or(self.default_format);
+        self.default_output_dir = profile.default_output_dir.or(self.default_output_dir);
+        self.default_categories = profile.default_categories.or(self.default_categories);
+        self.verbose = profile.verbose.or(self.verbose);
+        self.default_network_depth = profile.default_network_depth.or(self.default_network_depth);
+        Ok(self)
+    }
+    
+    /// Current configuration as TOML
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
     }
     
     /// Get effective output format (CLI arg or config default)
@@ -156,6 +296,29 @@ impl Config {
             .or_else(|| self.default_categories.clone())
     }
     
+    /// Get effective network depth
+    pub fn effective_network_depth(&self, cli_depth: Option<u32>) -> u32 {
+        cli_depth.or(self.default_network_depth).unwrap_or(1)
+    }
+    
+    /// Get effective BibTeX entry order
+    pub fn effective_bib_sort(&self, cli_sort: Option<BibSort>) -> BibSort {
+        cli_sort.or(self.bibtex.sort).unwrap_or_default()
+    }
+    
+    /// Get effective optional BibTeX fields; CLI flags can only turn fields on
+    pub fn effective_bibtex_options(&self, cli_keywords: bool, cli_url: bool, cli_note: Option<String>, cli_pin_versions: bool, cli_month: bool, cli_biblatex: bool) -> BibtexOptions {
+        BibtexOptions {
+            keywords: cli_keywords || self.bibtex.keywords.unwrap_or(false),
+            url: cli_url || self.bibtex.url.unwrap_or(false),
+            note: cli_note.or_else(|| self.bibtex.note.clone()),
+            include_abstract: self.bibtex.include_abstract.unwrap_or(false),
+            pin_versions: cli_pin_versions || self.bibtex.pin_versions.unwrap_or(false),
+            month: cli_month || self.bibtex.month.unwrap_or(false),
+            biblatex: cli_biblatex || self.bibtex.biblatex.unwrap_or(false),
+        }
+    }
+    
     /// Get effective verbosity
     pub fn effective_verbose(&self, cli_verbose: bool) -> bool {
         cli_verbose || self.verbose.unwrap_or(false)
@@ -165,7 +328,6 @@ impl Config {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::tempdir;
     
     #[test]
     fn test_default_config() {
@@ -183,6 +345,108 @@ mod tests {
         
         assert_eq!(config.default_format, parsed.default_format);
         assert_eq!(config.verbose, parsed.verbose);
+        assert!(toml_str.contains("default_format = \"json\""));
+        
+        // Configs saved before the format names were lowercased
+        let parsed: Config = toml::from_str("default_format = \"Bibtex\"\n[api]\n[ui]\n").unwrap();
+        assert_eq!(parsed.default_format, Some(OutputFormat::Bibtex));
+    }
+    
+    #[test]
+    fn test_notifications_section() {
+        let toml_str = r#"
+            [api]
+            [ui]
+            [notifications]
+            webhook_url = "https://example.org/hook"
+            atom_file = "/tmp/citations.atom"
+        "#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        
+        assert_eq!(parsed.notifications.webhook_url, Some("https://example.org/hook".to_string()));
+        assert!(parsed.notifications.slack_webhook_url.is_none());
+        assert!(parsed.notifications.is_enabled());
+        
+        let without: Config = toml::from_str("[api]\n[ui]\n").unwrap();
+        assert!(!without.notifications.is_enabled());
+    }
+    
+    #[test]
+    fn test_bibtex_section() {
+        let parsed: Config = toml::from_str("[api]\n[ui]\n[bibtex]\nsort = \"author\"\n").unwrap();
+        assert_eq!(parsed.effective_bib_sort(None), BibSort::Author);
+        assert_eq!(parsed.effective_bib_sort(Some(BibSort::Key)), BibSort::Key);
+        
+        let without: Config = toml::from_str("[api]\n[ui]\n").unwrap();
+        assert_eq!(without.effective_bib_sort(None), BibSort::Appearance);
+        assert_eq!(without.effective_bibtex_options(false, false, None, false, false, false), BibtexOptions::default());
+        
+        let parsed: Config = toml::from_str("[api]\n[ui]\n[bibtex]\nkeywords = true\nnote = \"Group A\"\n").unwrap();
+        let options = parsed.effective_bibtex_options(false, true, None, false, false, false);
+        assert!(options.keywords && options.url);
+        assert_eq!(options.note, Some("Group A".to_string()));
+        assert!(!options.biblatex);
+        
+        let parsed: Config = toml::from_str("[api]\ndate_order = \"month-first\"\n[ui]\n[bibtex]\nmonth = true\nbiblatex = true\n").unwrap();
+        assert_eq!(parsed.api.date_order, Some(DateOrder::MonthFirst));
+        let options = parsed.effective_bibtex_options(false, false, None, false, false, false);
+        assert!(options.month && options.biblatex);
+        assert!(without.effective_bibtex_options(false, false, None, false, true, false).month);
+    }
+    
+    #[test]
+    fn test_connection_options() {
+        assert_eq!(Config::default().api.connection_options(), ConnectionOptions::default());
+        
+        let parsed: Config = toml::from_str("[api]\npool_max_idle_per_host = 8\npool_idle_timeout_seconds = 30\nhttp2_prior_knowledge = true\n[ui]\n").unwrap();
+        let options = parsed.api.connection_options();
+        assert_eq!(options.pool_max_idle_per_host, Some(8));
+        assert_eq!(options.pool_idle_timeout, Some(std::time::Duration::from_secs(30)));
+        assert!(options.http2_prior_knowledge);
+    }
+    
+    #[test]
+    fn test_base_urls() {
+        assert_eq!(Config::default().api.base_urls(), vec!["https://inspirehep.net/api"]);
+        
+        let parsed: Config = toml::from_str("[api]\nbase_url = \"https://a.example/api\"\nbase_urls = [\"https://b.example/api\", \"https://c.example/api\"]\n[ui]\n").unwrap();
+        assert_eq!(parsed.api.base_urls(), vec!["https://b.example/api", "https://c.example/api"]);
+        
+        let without: Config = toml::from_str("[api]\n[ui]\n").unwrap();
+        assert!(without.api.base_urls().is_empty());
+    }
+    
+    #[test]
+    fn test_profiles() {
+        let toml_str = r#"
+            default_format = "json"
+            default_categories = ["hep-th"]
+            default_network_depth = 1
+            [api]
+            [ui]
+            [profiles.thesis]
+            default_format = "bibtex"
+            default_output_dir = "thesis"
+            default_network_depth = 2
+            [profiles.quick]
+            default_categories = ["hep-ph", "hep-ex"]
+        "#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        
+        let thesis = parsed.clone().with_profile("thesis").unwrap();
+        assert_eq!(thesis.effective_format(None), OutputFormat::Bibtex);
+        assert_eq!(thesis.effective_output_dir(None), Some(PathBuf::from("thesis")));
+        assert_eq!(thesis.effective_network_depth(None), 2);
+        assert_eq!(thesis.effective_categories(None), Some(vec!["hep-th".to_string()]));
+        
+        let quick = parsed.clone().with_profile("quick").unwrap();
+        assert_eq!(quick.effective_format(None), OutputFormat::Json);
+        assert_eq!(quick.effective_categories(None), Some(vec!["hep-ph".to_string(), "hep-ex".to_string()]));
+        assert_eq!(quick.effective_network_depth(Some(3)), 3);
+        
+        let error = parsed.with_profile("talk").unwrap_err().to_string();
+        assert!(error.contains("available: quick, thesis"), "{}", error);
+        assert!(Config::default().with_profile("thesis").is_err());
     }
     
     #[test]
@@ -194,8 +458,8 @@ mod tests {
         assert_eq!(config.effective_format(None), OutputFormat::Json);
         
         // Test verbosity
-        assert_eq!(config.effective_verbose(true), true);
-        assert_eq!(config.effective_verbose(false), false);
+        assert!(config.effective_verbose(true));
+        assert!(!config.effective_verbose(false));
         
         // Test categories
         let categories = config.effective_categories(Some("hep-th,hep-ph".to_string()));
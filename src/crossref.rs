@@ -0,0 +1,96 @@
+use anyhow::{anyhow, Result};
+use log::debug;
+use reqwest::Client;
+use serde::Deserialize;
+
+/// Kinds of CrossRef update notices that take a work back (retraction notices,
+/// including those imported from the Retraction Watch database)
+const RETRACTION_TYPES: [&str; 3] = ["retraction", "withdrawal", "removal"];
+
+/// Client for the CrossRef REST API, used to find retraction notices for DOIs
+pub struct CrossrefClient {
+    client: Client,
+    base_url: String,
+}
+
+impl Default for CrossrefClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Deserialize)]
+struct WorksResponse {
+    message: WorksMessage,
+}
+
+#[derive(Deserialize)]
+struct WorksMessage {
+    #[serde(default)]
+    items: Vec<Notice>,
+}
+
+#[derive(Deserialize)]
+struct Notice {
+    #[serde(default, rename = "update-to")]
+    update_to: Vec<Update>,
+}
+
+#[derive(Deserialize)]
+struct Update {
+    #[serde(rename = "DOI")]
+    doi: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+impl CrossrefClient {
+    pub fn new() -> Self {
+        Self {
+            client: Client::new(),
+            base_url: "https://api.crossref.org".to_string(),
+        }
+    }
+
+    /// Whether CrossRef lists a retraction or withdrawal notice for this DOI
+    pub async fn is_retracted(&self, doi: &str) -> Result<bool> {
+        debug!("Looking up retraction notices for {}", doi);
+        let filter = format!("updates:{}", doi);
+        let response = self.client.get(format!("{}/works", self.base_url))
+            .query(&[("filter", filter.as_str()), ("rows", "20"), ("select", "DOI,update-to")])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to query CrossRef: {}", response.status()));
+        }
+
+        let works: WorksResponse = response.json().await?;
+        Ok(retracts(&works.message.items, doi))
+    }
+}
+
+/// Whether any of the notices retracts or withdraws `doi`
+fn retracts(notices: &[Notice], doi: &str) -> bool {
+    notices.iter()
+        .flat_map(|notice| &notice.update_to)
+        .any(|update| update.doi.eq_ignore_ascii_case(doi) && RETRACTION_TYPES.contains(&update.kind.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retraction_notices() {
+        let body = r#"{"status": "ok", "message": {"items": [
+            {"DOI": "10.1000/correction", "update-to": [{"DOI": "10.1000/XYZ", "type": "correction", "label": "Correction"}]},
+            {"DOI": "10.1000/notice", "update-to": [{"DOI": "10.1000/xyz", "type": "retraction", "source": "retraction-watch"}]},
+            {"DOI": "10.1000/unrelated"}
+        ]}}"#;
+        let works: WorksResponse = serde_json::from_str(body).unwrap();
+        assert!(retracts(&works.message.items, "10.1000/XYZ"));
+        assert!(!retracts(&works.message.items[..1], "10.1000/XYZ"));
+        assert!(!retracts(&works.message.items, "10.1000/other"));
+    }
+}
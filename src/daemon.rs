@@ -0,0 +1,147 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use anyhow::{Result, anyhow};
+use log::{debug, info, warn};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::api::InspireClient;
+use crate::models::{Paper, Reference};
+use crate::protocol::{self, ProtocolRequest};
+
+/// Default socket path, in the runtime directory when the platform has one
+pub fn default_socket_path() -> Result<PathBuf> {
+    let dir = dirs::runtime_dir()
+        .or_else(dirs::data_dir)
+        .ok_or_else(|| anyhow!("Could not find runtime or data directory"))?;
+    Ok(dir.join("reference_tool").join("daemon.sock"))
+}
+
+/// Accept connections on a Unix socket, answering each with the stdin protocol.
+///
+/// All connections share one client, so its connection pool stays warm between
+/// CLI invocations. A stale socket left by a crashed daemon is replaced.
+pub async fn run(client: Arc<InspireClient>, socket_path: &Path) -> Result<()> {
+    if socket_path.exists() {
+        if UnixStream::connect(socket_path).await.is_ok() {
+            return Err(anyhow!("A daemon is already listening on {}", socket_path.display()));
+        }
+        std::fs::remove_file(socket_path)?;
+    }
+    if let Some(parent) = socket_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let listener = UnixListener::bind(socket_path)?;
+    info!("Daemon listening on {}", socket_path.display());
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let client = Arc::clone(&client);
+        tokio::spawn(async move {
+            let (read, write) = stream.into_split();
+            if let Err(e) = protocol::run(&client, BufReader::new(read), write).await {
+                warn!("Daemon connection failed: {}", e);
+            }
+        });
+    }
+}
+
+/// Connection to a running daemon
+pub struct DaemonClient {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+    next_seq: u64,
+}
+
+impl DaemonClient {
+    /// Connect to the daemon, returning `None` if none is listening
+    pub async fn connect(socket_path: &Path) -> Option<Self> {
+        let stream = UnixStream::connect(socket_path).await.ok()?;
+        debug!("Connected to daemon at {}", socket_path.display());
+        let (read, writer) = stream.into_split();
+        Some(Self {
+            lines: BufReader::new(read).lines(),
+            writer,
+            next_seq: 0,
+        })
+    }
+
+    /// Send a request and wait for its result
+    pub async fn request(&mut self, mut request: ProtocolRequest) -> Result<Value> {
+        self.next_seq += 1;
+        request.seq = Some(Value::from(self.next_seq));
+
+        let mut line = serde_json::to_string(&request)?;
+        line.push('\n');
+        self.writer.write_all(line.as_bytes()).await?;
+
+        let response = self.lines.next_line().await?
+            .ok_or_else(|| anyhow!("Daemon closed the connection"))?;
+        let mut response: Value = serde_json::from_str(&response)?;
+
+        if response["seq"] != self.next_seq {
+            return Err(anyhow!("Daemon response out of sequence"));
+        }
+        if response["ok"] == Value::Bool(true) {
+            Ok(response["result"].take())
+        } else {
+            Err(anyhow!("{}", response["error"].as_str().unwrap_or("Daemon request failed")))
+        }
+    }
+
+    /// Get paper information by arXiv ID or TeX key
+    pub async fn get_paper(&mut self, id: &str) -> Result<Paper> {
+        let result = self.request(ProtocolRequest {
+            op: "paper".to_string(),
+            id: Some(id.to_string()),
+            ..Default::default()
+        }).await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Get references for a paper by INSPIRE record ID
+    pub async fn get_paper_references(&mut self, paper_id: &str) -> Result<Vec<Reference>> {
+        let result = self.request(ProtocolRequest {
+            op: "references".to_string(),
+            id: Some(paper_id.to_string()),
+            ..Default::default()
+        }).await?;
+        Ok(serde_json::from_value(result)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_daemon_round_trip() {
+        let dir = tempdir().unwrap();
+        let socket_path = dir.path().join("daemon.sock");
+
+        assert!(DaemonClient::connect(&socket_path).await.is_none());
+
+        let path = socket_path.clone();
+        tokio::spawn(async move { run(Arc::new(InspireClient::new()), &path).await });
+        let mut daemon = loop {
+            if let Some(daemon) = DaemonClient::connect(&socket_path).await {
+                break daemon;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        };
+
+        let error = daemon.request(ProtocolRequest { op: "bogus".to_string(), ..Default::default() })
+            .await.unwrap_err();
+        assert_eq!(error.to_string(), "Unknown op: bogus");
+
+        let error = daemon.request(ProtocolRequest { op: "references".to_string(), ..Default::default() })
+            .await.unwrap_err();
+        assert_eq!(error.to_string(), "Missing id for references");
+
+        assert!(run(Arc::new(InspireClient::new()), &socket_path).await.is_err());
+    }
+}
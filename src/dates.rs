@@ -0,0 +1,247 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::atom;
+
+/// BibTeX month macros, January first
+const MONTH_MACROS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/// How numeric dates with the year last are read when both other parts could be
+/// the month (`03-04-2023`); dates with the year first are always year-month-day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DateOrder {
+    /// `15-01-2023` (most of the world)
+    #[default]
+    DayFirst,
+    /// `01-15-2023` (US)
+    MonthFirst,
+}
+
+/// A date known to year, month or day precision (`2023`, `2023-01`, `2023-01-15`).
+///
+/// Serialized as its ISO 8601 form, which is also the biblatex `date` format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PartialDate {
+    pub year: u32,
+    pub month: Option<u32>,
+    /// Only set together with `month`
+    pub day: Option<u32>,
+}
+
+impl PartialDate {
+    /// Parse a date, reading ambiguous numeric dates day first
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::parse_with(text, DateOrder::default())
+    }
+
+    /// Parse a date in any of the forms found in INSPIRE records and BibTeX
+    /// files: ISO 8601 with or without a time (`2023-01-15T10:00:00Z`),
+    /// `2023/01/15`, `15.01.2023`, `01-2023`, and English month names
+    /// (`15 January 2023`, `Jan. 15, 2023`, `January 2023`)
+    pub fn parse_with(text: &str, order: DateOrder) -> Option<Self> {
+        let text = text.trim();
+        // Drop the time of an ISO 8601 timestamp
+        let text = text.split_once('T')
+            .filter(|(date, _)| date.starts_with(|c: char| c.is_ascii_digit()))
+            .map_or(text, |(date, _)| date);
+
+        let parts: Vec<&str> = text
+            .split(|c: char| matches!(c, '-' | '/' | '.' | ',') || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .collect();
+        if parts.iter().any(|part| part.chars().any(|c| c.is_ascii_alphabetic())) {
+            return Self::from_named_month(&parts);
+        }
+
+        let numbers: Vec<u32> = parts.iter().map(|part| part.parse().ok()).collect::<Option<_>>()?;
+        let is_year = |part: &str| part.len() == 4;
+        match (parts.as_slice(), numbers.as_slice()) {
+            ([year], [y]) if is_year(year) => Self::new(*y, None, None),
+            ([year, _], [y, m]) if is_year(year) => Self::new(*y, Some(*m), None),
+            ([_, year], [m, y]) if is_year(year) => Self::new(*y, Some(*m), None),
+            ([year, _, _], [y, m, d]) if is_year(year) => Self::new(*y, Some(*m), Some(*d)),
+            ([_, _, year], [a, b, y]) if is_year(year) => {
+                let (day, month) = match order {
+                    // A part above 12 can only be the day, whatever the order
+                    _ if *a > 12 => (*a, *b),
+                    _ if *b > 12 => (*b, *a),
+                    DateOrder::DayFirst => (*a, *b),
+                    DateOrder::MonthFirst => (*b, *a),
+                };
+                Self::new(*y, Some(month), Some(day))
+            }
+            _ => None,
+        }
+    }
+
+    /// A date from parts with one month name, in any order (`15 Jan 2023`,
+    /// `January 15 2023`, `Jan 2023`)
+    fn from_named_month(parts: &[&str]) -> Option<Self> {
+        let mut month = None;
+        let mut year = None;
+        let mut day = None;
+        for part in parts {
+            if let Some(number) = month_from_name(part) {
+                if month.replace(number).is_some() {
+                    return None;
+                }
+            } else if part.len() == 4 {
+                year = Some(part.parse().ok()?);
+            } else {
+                // Ordinal suffixes: `1st`, `22nd`
+                let digits = part.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+                if day.replace(digits.parse().ok()?).is_some() {
+                    return None;
+                }
+            }
+        }
+        Self::new(year?, Some(month?), day)
+    }
+
+    /// A validated date; `None` for impossible months or days
+    fn new(year: u32, month: Option<u32>, day: Option<u32>) -> Option<Self> {
+        if !(1000..=9999).contains(&year) {
+            return None;
+        }
+        if let Some(month) = month {
+            if !(1..=12).contains(&month) {
+                return None;
+            }
+        }
+        if let Some(day) = day {
+            let month = month?;
+            if day < 1 || day > days_in_month(year, month) {
+                return None;
+            }
+        }
+        Some(Self { year, month, day })
+    }
+
+    /// The BibTeX month macro (`jan` ... `dec`), when the month is known
+    pub fn bibtex_month(&self) -> Option<&'static str> {
+        self.month.map(|month| MONTH_MACROS[month as usize - 1])
+    }
+}
+
+impl fmt::Display for PartialDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}", self.year)?;
+        if let Some(month) = self.month {
+            write!(f, "-{:02}", month)?;
+            if let Some(day) = self.day {
+                write!(f, "-{:02}", day)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PartialDate {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Self::parse(text).ok_or_else(|| anyhow!("Not a date: {}", text))
+    }
+}
+
+impl TryFrom<String> for PartialDate {
+    type Error = anyhow::Error;
+
+    fn try_from(text: String) -> Result<Self> {
+        text.parse()
+    }
+}
+
+impl From<PartialDate> for String {
+    fn from(date: PartialDate) -> Self {
+        date.to_string()
+    }
+}
+
+/// Month number of an English month name or abbreviation (`January`, `jan`, `Sept`)
+pub fn month_from_name(name: &str) -> Option<u32> {
+    let name = name.trim().trim_end_matches('.').to_lowercase();
+    if name.len() < 3 {
+        return None;
+    }
+    let full_names = [
+        "january", "february", "march", "april", "may", "june",
+        "july", "august", "september", "october", "november", "december",
+    ];
+    full_names.iter()
+        .position(|full| full.starts_with(&name))
+        .map(|index| index as u32 + 1)
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    let days = atom::days_from_civil(i64::from(next_year), next_month, 1) - atom::days_from_civil(i64::from(year), month, 1);
+    days as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: u32, month: Option<u32>, day: Option<u32>) -> Option<PartialDate> {
+        Some(PartialDate { year, month, day })
+    }
+
+    #[test]
+    fn test_parse_numeric_dates() {
+        assert_eq!(PartialDate::parse("2023"), date(2023, None, None));
+        assert_eq!(PartialDate::parse("2023-01"), date(2023, Some(1), None));
+        assert_eq!(PartialDate::parse("2023-01-15"), date(2023, Some(1), Some(15)));
+        assert_eq!(PartialDate::parse("2023/1/5"), date(2023, Some(1), Some(5)));
+        assert_eq!(PartialDate::parse("2023-01-15T10:30:00+00:00"), date(2023, Some(1), Some(15)));
+        assert_eq!(PartialDate::parse("01-2023"), date(2023, Some(1), None));
+        assert_eq!(PartialDate::parse("15-01-2023"), date(2023, Some(1), Some(15)));
+        assert_eq!(PartialDate::parse("15.01.2023"), date(2023, Some(1), Some(15)));
+
+        assert_eq!(PartialDate::parse(""), None);
+        assert_eq!(PartialDate::parse("2023-13"), None);
+        assert_eq!(PartialDate::parse("2023-02-29"), None);
+        assert_eq!(PartialDate::parse("2024-02-29"), date(2024, Some(2), Some(29)));
+        assert_eq!(PartialDate::parse("23-01-15"), None);
+        assert_eq!(PartialDate::parse("in press"), None);
+    }
+
+    #[test]
+    fn test_date_order() {
+        assert_eq!(PartialDate::parse_with("03-04-2023", DateOrder::DayFirst), date(2023, Some(4), Some(3)));
+        assert_eq!(PartialDate::parse_with("03-04-2023", DateOrder::MonthFirst), date(2023, Some(3), Some(4)));
+        // Unambiguous either way
+        assert_eq!(PartialDate::parse_with("01/15/2023", DateOrder::DayFirst), date(2023, Some(1), Some(15)));
+        assert_eq!(PartialDate::parse_with("15/01/2023", DateOrder::MonthFirst), date(2023, Some(1), Some(15)));
+    }
+
+    #[test]
+    fn test_parse_month_names() {
+        assert_eq!(PartialDate::parse("15 January 2023"), date(2023, Some(1), Some(15)));
+        assert_eq!(PartialDate::parse("Jan. 15, 2023"), date(2023, Some(1), Some(15)));
+        assert_eq!(PartialDate::parse("September 2019"), date(2019, Some(9), None));
+        assert_eq!(PartialDate::parse("1st Sept 2019"), date(2019, Some(9), Some(1)));
+        assert_eq!(PartialDate::parse("January"), None);
+        assert_eq!(month_from_name("dec"), Some(12));
+        assert_eq!(month_from_name("ma"), None);
+    }
+
+    #[test]
+    fn test_format_dates() {
+        let day = PartialDate::parse("2023-01-05").unwrap();
+        assert_eq!(day.to_string(), "2023-01-05");
+        assert_eq!(day.bibtex_month(), Some("jan"));
+        let year = PartialDate::parse("1997").unwrap();
+        assert_eq!(year.to_string(), "1997");
+        assert_eq!(year.bibtex_month(), None);
+
+        assert_eq!(serde_json::to_string(&day).unwrap(), "\"2023-01-05\"");
+        assert_eq!(serde_json::from_str::<PartialDate>("\"2019-06\"").unwrap(), PartialDate::parse("2019-06").unwrap());
+        assert!(serde_json::from_str::<PartialDate>("\"soon\"").is_err());
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::api::InspireClient;
+use crate::atom::{self, AtomEntry};
+use crate::models::Paper;
+use crate::watch;
+
+/// How often an author digest is produced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum DigestPeriod {
+    Daily,
+    #[default]
+    Weekly,
+    Monthly,
+}
+
+impl DigestPeriod {
+    /// Length of the period in seconds (a month counts as 30 days)
+    pub fn seconds(self) -> u64 {
+        match self {
+            DigestPeriod::Daily => 86_400,
+            DigestPeriod::Weekly => 7 * 86_400,
+            DigestPeriod::Monthly => 30 * 86_400,
+        }
+    }
+
+    fn adjective(self) -> &'static str {
+        match self {
+            DigestPeriod::Daily => "Daily",
+            DigestPeriod::Weekly => "Weekly",
+            DigestPeriod::Monthly => "Monthly",
+        }
+    }
+}
+
+/// How a digest is rendered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum DigestFormat {
+    #[default]
+    Markdown,
+    Atom,
+    /// Plain text with a `Subject:` line, ready to pipe into `sendmail`
+    Email,
+}
+
+/// A new citation of one of the author's papers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestItem {
+    /// INSPIRE record ID and title of the author's paper
+    pub cited_id: String,
+    pub cited_title: String,
+    pub citing: Paper,
+    /// When the citation was found (RFC 3339)
+    pub found: String,
+}
+
+/// Persisted state of a watched author: citations already seen per paper, and
+/// the citations collected for the next digest
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DigestState {
+    pub author: String,
+    /// INSPIRE record IDs of citing papers already seen, by the author's paper they cite
+    #[serde(default)]
+    pub seen: BTreeMap<String, BTreeSet<String>>,
+    /// Citations found since the last digest
+    #[serde(default)]
+    pub pending: Vec<DigestItem>,
+    /// When the author was last checked (RFC 3339)
+    pub last_checked: Option<String>,
+    /// When the last digest was produced, or watching started (RFC 3339)
+    pub last_digest: Option<String>,
+}
+
+/// Citations of an author's papers over one digest period
+#[derive(Debug, Clone)]
+pub struct Digest {
+    pub author: String,
+    pub period: DigestPeriod,
+    /// Start and end of the period (RFC 3339)
+    pub since: String,
+    pub until: String,
+    pub items: Vec<DigestItem>,
+}
+
+/// Result of checking a watched author
+#[derive(Debug, Clone)]
+pub struct DigestCheck {
+    /// Number of the author's papers checked
+    pub papers: usize,
+    /// Citations found in this check
+    pub new_citations: usize,
+    /// Whether this was the first check, which only records a baseline
+    pub first_run: bool,
+    /// The digest, when a period has ended
+    pub digest: Option<Digest>,
+}
+
+impl DigestState {
+    /// Load state from a file, returning `None` if it does not exist yet
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Save state to a file, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Record the current citations of one of the author's papers, queueing
+    /// unseen ones for the digest. A paper seen for the first time only records
+    /// a baseline. Returns the number of citations queued.
+    pub fn record_citations(&mut self, paper: &Paper, citing: Vec<Paper>, now: &str) -> usize {
+        let baseline = !self.seen.contains_key(&paper.id);
+        let seen = self.seen.entry(paper.id.clone()).or_default();
+        let mut queued = 0;
+        for citing in citing {
+            if seen.insert(citing.id.clone()) && !baseline {
+                self.pending.push(DigestItem {
+                    cited_id: paper.id.clone(),
+                    cited_title: paper.title.clone(),
+                    citing,
+                    found: now.to_string(),
+                });
+                queued += 1;
+            }
+        }
+        queued
+    }
+
+    /// Take the pending citations as a digest if the period since the last one has ended
+    pub fn take_digest(&mut self, period: DigestPeriod, now: &str) -> Option<Digest> {
+        let since = self.last_digest.clone()?;
+        let elapsed = atom::parse_rfc3339(now)?.saturating_sub(atom::parse_rfc3339(&since)?);
+        if elapsed < period.seconds() {
+            return None;
+        }
+        self.last_digest = Some(now.to_string());
+        Some(Digest {
+            author: self.author.clone(),
+            period,
+            since,
+            until: now.to_string(),
+            items: std::mem::take(&mut self.pending),
+        })
+    }
+}
+
+impl Digest {
+    /// Render the digest in the given format
+    pub fn render(&self, format: DigestFormat) -> String {
+        match format {
+            DigestFormat::Markdown => self.to_markdown(),
+            DigestFormat::Atom => self.to_atom(),
+            DigestFormat::Email => self.to_email(),
+        }
+    }
+
+    /// One-line summary, used as heading and email subject
+    pub fn title(&self) -> String {
+        format!(
+            "{} citation digest for {}: {} new citation{}",
+            self.period.adjective(), self.author, self.items.len(),
+            if self.items.len() == 1 { "" } else { "s" }
+        )
+    }
+
+    /// Markdown, one section per cited paper
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n\n{} to {}\n", self.title(), date(&self.since), date(&self.until));
+        for (cited, items) in self.by_paper() {
+            markdown.push_str(&format!("\n## {}\n\n", cited));
+            for item in items {
+                markdown.push_str(&format!("- [{}]({}){}\n", item.citing.title, link(&item.citing), byline(&item.citing)));
+            }
+        }
+        markdown
+    }
+
+    /// Atom feed with one entry per citing paper
+    pub fn to_atom(&self) -> String {
+        let entries: Vec<AtomEntry> = self.items.iter()
+            .map(|item| {
+                let mut entry = AtomEntry::from_paper(&item.citing, &item.found);
+                entry.summary = Some(format!("Cites {}", item.cited_title));
+                entry
+            })
+            .collect();
+        atom::render_feed(
+            &format!("urn:reference_tool:digest:{}", self.author),
+            &format!("Citations of {}", self.author),
+            &self.until,
+            &entries,
+        )
+    }
+
+    /// Plain-text email body, headed by its `Subject:` line
+    pub fn to_email(&self) -> String {
+        let mut email = format!("Subject: {}\n\n", self.title());
+        email.push_str(&format!("New citations of papers by {} from {} to {}.\n", self.author, date(&self.since), date(&self.until)));
+        if self.items.is_empty() {
+            email.push_str("\nNo new citations this time.\n");
+        }
+        for (cited, items) in self.by_paper() {
+            email.push_str(&format!("\n{}\n", cited));
+            for item in items {
+                email.push_str(&format!("  * {}{}\n    {}\n", item.citing.title, byline(&item.citing), link(&item.citing)));
+            }
+        }
+        email
+    }
+
+    /// Items grouped by the cited paper's title, in order of first appearance
+    fn by_paper(&self) -> Vec<(&str, Vec<&DigestItem>)> {
+        let mut groups: Vec<(&str, Vec<&DigestItem>)> = Vec::new();
+        for item in &self.items {
+            match groups.iter_mut().find(|(cited, _)| *cited == item.cited_title) {
+                Some((_, items)) => items.push(item),
+                None => groups.push((&item.cited_title, vec![item])),
+            }
+        }
+        groups
+    }
+}
+
+fn link(paper: &Paper) -> String {
+    AtomEntry::from_paper(paper, "").link
+}
+
+/// ` — Doe, J. et al. (2024)`
+fn byline(paper: &Paper) -> String {
+    let authors = match paper.authors.as_slice() {
+        [] => return String::new(),
+        [author] => author.clone(),
+        [author, ..] => format!("{} et al.", author),
+    };
+    match paper.year {
+        Some(year) => format!(" — {} ({})", authors, year),
+        None => format!(" — {}", authors),
+    }
+}
+
+fn date(timestamp: &str) -> &str {
+    timestamp.get(..10).unwrap_or(timestamp)
+}
+
+/// Default state file for a watched author, next to the per-paper watch state
+pub fn default_state_path(author: &str) -> Result<PathBuf> {
+    watch::default_state_path(&format!("author-{}", author))
+}
+
+/// Check an author's most recent papers for new citations, and produce the
+/// digest when the period since the last one has ended.
+///
+/// The first check records all current citations as a baseline and starts
+/// the first period.
+pub async fn check_author(
+    client: &InspireClient,
+    author: &str,
+    state_path: &Path,
+    period: DigestPeriod,
+    max_papers: usize,
+    limit: usize,
+) -> Result<DigestCheck> {
+    let papers = client.get_author_papers(author, None, max_papers).await?;
+    let now = atom::now_rfc3339();
+
+    let existing = DigestState::load(state_path)?
+        .filter(|state| state.author == author);
+    let first_run = existing.is_none();
+    let mut state = existing.unwrap_or_else(|| DigestState {
+        author: author.to_string(),
+        last_digest: Some(now.clone()),
+        ..Default::default()
+    });
+
+    let mut new_citations = 0;
+    for paper in &papers {
+        match client.get_recent_citing_papers(&paper.id, limit).await {
+            Ok(citing) => new_citations += state.record_citations(paper, citing, &now),
+            Err(e) => warn!("Could not fetch citations of {}: {}", paper.title, e),
+        }
+    }
+    if first_run {
+        info!("Recorded baseline citations of {} papers by {}", papers.len(), author);
+    }
+
+    let digest = state.take_digest(period, &now);
+    state.last_checked = Some(now);
+    state.save(state_path)?;
+
+    Ok(DigestCheck { papers: papers.len(), new_citations, first_run, digest })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn create_paper(id: &str) -> Paper {
+        Paper {
+            id: id.to_string(),
+            title: format!("Paper {}", id),
+            authors: vec!["Doe, J.".to_string(), "Roe, R.".to_string()],
+            year: Some(2024),
+            ..Default::default()
+        }
+    }
+
+    fn create_state() -> DigestState {
+        DigestState {
+            author: "Doe, J.".to_string(),
+            last_digest: Some("2024-01-01T00:00:00Z".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_record_citations() {
+        let mut state = create_state();
+        let paper = create_paper("1");
+
+        assert_eq!(state.record_citations(&paper, vec![create_paper("10")], "2024-01-02T00:00:00Z"), 0);
+        assert_eq!(state.record_citations(&paper, vec![create_paper("10"), create_paper("11")], "2024-01-03T00:00:00Z"), 1);
+        assert_eq!(state.pending.len(), 1);
+        assert_eq!(state.pending[0].citing.id, "11");
+        assert_eq!(state.pending[0].cited_title, "Paper 1");
+    }
+
+    #[test]
+    fn test_take_digest_after_period() {
+        let mut state = create_state();
+        let paper = create_paper("1");
+        state.record_citations(&paper, Vec::new(), "2024-01-01T00:00:00Z");
+        state.record_citations(&paper, vec![create_paper("10")], "2024-01-03T00:00:00Z");
+
+        assert!(state.take_digest(DigestPeriod::Weekly, "2024-01-07T00:00:00Z").is_none());
+        let digest = state.take_digest(DigestPeriod::Weekly, "2024-01-08T00:00:00Z").unwrap();
+        assert_eq!(digest.items.len(), 1);
+        assert_eq!(digest.since, "2024-01-01T00:00:00Z");
+        assert!(state.pending.is_empty());
+        assert_eq!(state.last_digest.as_deref(), Some("2024-01-08T00:00:00Z"));
+        assert!(state.take_digest(DigestPeriod::Weekly, "2024-01-09T00:00:00Z").is_none());
+    }
+
+    #[test]
+    fn test_render_digest() {
+        let digest = Digest {
+            author: "Doe, J.".to_string(),
+            period: DigestPeriod::Weekly,
+            since: "2024-01-01T00:00:00Z".to_string(),
+            until: "2024-01-08T00:00:00Z".to_string(),
+            items: vec![DigestItem {
+                cited_id: "1".to_string(),
+                cited_title: "Paper 1".to_string(),
+                citing: create_paper("10"),
+                found: "2024-01-03T00:00:00Z".to_string(),
+            }],
+        };
+
+        let markdown = digest.render(DigestFormat::Markdown);
+        assert!(markdown.starts_with("# Weekly citation digest for Doe, J.: 1 new citation\n\n2024-01-01 to 2024-01-08\n"));
+        assert!(markdown.contains("## Paper 1\n\n- [Paper 10](https://inspirehep.net/literature/10) — Doe, J. et al. (2024)\n"));
+
+        let email = digest.render(DigestFormat::Email);
+        assert!(email.starts_with("Subject: Weekly citation digest for Doe, J.: 1 new citation\n\n"));
+        assert!(email.contains("  * Paper 10 — Doe, J. et al. (2024)\n    https://inspirehep.net/literature/10\n"));
+
+        let feed = digest.render(DigestFormat::Atom);
+        assert!(feed.contains("<summary>Cites Paper 1</summary>"));
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("author.json");
+        assert!(DigestState::load(&path).unwrap().is_none());
+
+        let mut state = create_state();
+        state.record_citations(&create_paper("1"), vec![create_paper("10")], "2024-01-02T00:00:00Z");
+        state.save(&path).unwrap();
+        let loaded = DigestState::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.author, "Doe, J.");
+        assert_eq!(loaded.seen["1"], BTreeSet::from(["10".to_string()]));
+    }
+}
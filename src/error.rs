@@ -0,0 +1,137 @@
+use std::fmt;
+
+use reqwest::StatusCode;
+use thiserror::Error;
+
+/// Why a request to INSPIRE failed.
+///
+/// Returned inside `anyhow::Error` by `InspireClient`; callers that need to
+/// tell the cases apart can `downcast_ref::<ApiError>()`.
+#[derive(Debug, Error)]
+pub enum ApiError {
+    /// No connection could be made: DNS lookup, TLS handshake or a refused connection
+    #[error("cannot reach {host} — are you offline?")]
+    Unreachable {
+        host: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    /// The connection was made but no response came in time
+    #[error("{host} did not respond in time")]
+    Timeout {
+        host: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    /// No response headers arrived within the client's request timeout
+    #[error("{host} did not respond within {seconds} s")]
+    ResponseTimeout {
+        host: String,
+        seconds: u64,
+    },
+    /// The server answered with an error status
+    #[error("{action}: {status}")]
+    Status {
+        /// What was attempted (`Failed to fetch literature record 42`)
+        action: String,
+        status: StatusCode,
+    },
+    /// The response body was larger than the client's limit
+    #[error("{what} is larger than the {limit}-byte response limit")]
+    TooLarge {
+        /// What was being read (`literature record 42`)
+        what: String,
+        limit: usize,
+    },
+    /// The response body did not arrive within the client's body timeout
+    #[error("{what} did not arrive within {seconds} s")]
+    BodyTimeout {
+        what: String,
+        seconds: u64,
+    },
+    /// A record that failed too often in this run is no longer requested
+    #[error("{what} skipped after {failures} failed attempts")]
+    Skipped {
+        what: String,
+        failures: u32,
+    },
+    /// Any other failure while sending a request or reading its response
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+}
+
+impl ApiError {
+    /// Classify a failed request; `fallback_host` names the server when the
+    /// error does not carry a URL
+    pub fn from_request(error: reqwest::Error, fallback_host: &str) -> Self {
+        let host = error.url()
+            .and_then(|url| url.host_str())
+            .unwrap_or(fallback_host)
+            .to_string();
+        if is_connect(&error) {
+            ApiError::Unreachable { host, source: error }
+        } else if error.is_timeout() {
+            ApiError::Timeout { host, source: error }
+        } else {
+            ApiError::Request(error)
+        }
+    }
+
+    /// An error status returned for `action`
+    pub fn status(action: impl fmt::Display, status: StatusCode) -> Self {
+        ApiError::Status { action: action.to_string(), status }
+    }
+
+    /// Whether the server was never reached, so retrying cannot have done
+    /// anything on the server side
+    pub fn is_connect(&self) -> bool {
+        matches!(self, ApiError::Unreachable { .. })
+    }
+
+    /// Whether a response was refused for its size
+    pub fn is_too_large(&self) -> bool {
+        matches!(self, ApiError::TooLarge { .. })
+    }
+
+    /// HTTP status of the response, when there was one
+    pub fn http_status(&self) -> Option<StatusCode> {
+        match self {
+            ApiError::Status { status, .. } => Some(*status),
+            ApiError::Request(error) => error.status(),
+            ApiError::Unreachable { .. } | ApiError::Timeout { .. } | ApiError::ResponseTimeout { .. } => None,
+            ApiError::TooLarge { .. } | ApiError::BodyTimeout { .. } => None,
+            ApiError::Skipped { .. } => None,
+        }
+    }
+}
+
+// The fetch backend on wasm32 cannot tell connection failures apart
+#[cfg(not(target_arch = "wasm32"))]
+fn is_connect(error: &reqwest::Error) -> bool {
+    error.is_connect()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn is_connect(_error: &reqwest::Error) -> bool {
+    false
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_classify_request_errors() {
+        // Nothing listens on port 9 of the loopback address
+        let error = reqwest::get("http://127.0.0.1:9/api").await.unwrap_err();
+        let error = ApiError::from_request(error, "inspirehep.net");
+        assert!(error.is_connect());
+        assert_eq!(error.to_string(), "cannot reach 127.0.0.1 — are you offline?");
+        assert_eq!(error.http_status(), None);
+
+        let error = ApiError::status("Failed to fetch literature record 42", StatusCode::NOT_FOUND);
+        assert!(!error.is_connect());
+        assert_eq!(error.to_string(), "Failed to fetch literature record 42: 404 Not Found");
+        assert_eq!(error.http_status(), Some(StatusCode::NOT_FOUND));
+    }
+}
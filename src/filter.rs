@@ -0,0 +1,221 @@
+use std::collections::HashSet;
+use anyhow::Result;
+use regex::{Regex, RegexBuilder};
+
+use crate::models::{Paper, Reference};
+
+/// Case-insensitive text filter over titles and abstracts.
+///
+/// The pattern is used as a regular expression when it is valid and as a
+/// plain substring otherwise, so `--grep "N=4"` and `--grep "AdS.*CFT"` both work.
+#[derive(Debug, Clone)]
+pub struct TextFilter {
+    regex: Regex,
+}
+
+impl TextFilter {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .or_else(|_| {
+                RegexBuilder::new(&regex::escape(pattern))
+                    .case_insensitive(true)
+                    .build()
+            })?;
+
+        Ok(Self { regex })
+    }
+
+    /// Whether the text matches the filter
+    pub fn matches(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// Whether any of the texts matches the filter, e.g. one of a record's
+    /// affiliations for `--affiliation`
+    pub fn matches_any(&self, texts: &[String]) -> bool {
+        texts.iter().any(|text| self.matches(text))
+    }
+
+    /// Whether a reference's title or abstract matches the filter
+    pub fn matches_reference(&self, reference: &Reference) -> bool {
+        self.matches(&reference.title)
+            || reference.abstract_text.as_deref().is_some_and(|text| self.matches(text))
+    }
+
+    /// Whether a paper's title or abstract matches the filter
+    pub fn matches_paper(&self, paper: &Paper) -> bool {
+        self.matches(&paper.title)
+            || paper.abstract_text.as_deref().is_some_and(|text| self.matches(text))
+    }
+}
+
+/// Records to always leave out, or the only records to keep, by identifier
+/// (arXiv ID, DOI or INSPIRE record ID), from `--exclude-file` and `--include-file`
+#[derive(Debug, Clone, Default)]
+pub struct IdentifierFilter {
+    include: Option<HashSet<String>>,
+    exclude: HashSet<String>,
+}
+
+impl IdentifierFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep only records with one of the identifiers listed in `content`
+    pub fn with_include_list(mut self, content: &str) -> Self {
+        self.include.get_or_insert_with(HashSet::new).extend(parse_identifier_list(content));
+        self
+    }
+
+    /// Leave out records with any of the identifiers listed in `content`
+    pub fn with_exclude_list(mut self, content: &str) -> Self {
+        self.exclude.extend(parse_identifier_list(content));
+        self
+    }
+
+    /// Whether the filter lets every record through
+    pub fn is_empty(&self) -> bool {
+        self.include.is_none() && self.exclude.is_empty()
+    }
+
+    /// Whether a reference passes the lists
+    pub fn allows_reference(&self, reference: &Reference) -> bool {
+        self.allows([&reference.inspire_id, &reference.arxiv_id, &reference.doi])
+    }
+
+    /// Whether a paper passes the lists
+    pub fn allows_paper(&self, paper: &Paper) -> bool {
+        self.allows([&Some(paper.id.clone()), &paper.arxiv_id, &paper.doi])
+    }
+
+    fn allows(&self, ids: [&Option<String>; 3]) -> bool {
+        let ids: Vec<String> = ids.into_iter().flatten().map(|id| normalize_identifier(id)).collect();
+        if ids.iter().any(|id| self.exclude.contains(id)) {
+            return false;
+        }
+        match &self.include {
+            Some(include) => ids.iter().any(|id| include.contains(id)),
+            None => true,
+        }
+    }
+}
+
+/// Parse an identifier list: one arXiv ID, DOI or INSPIRE record ID per line,
+/// ignoring blank lines and `#` comments
+pub fn parse_identifier_list(content: &str) -> HashSet<String> {
+    content.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(normalize_identifier)
+        .collect()
+}
+
+/// Lowercase an identifier and drop URL or scheme prefixes and arXiv version
+/// suffixes, so `arXiv:2301.12345v2` matches `2301.12345`
+fn normalize_identifier(id: &str) -> String {
+    let mut id = id.trim().to_lowercase();
+    for prefix in ["https://doi.org/", "http://doi.org/", "doi:", "https://arxiv.org/abs/", "arxiv:", "recid:"] {
+        if let Some(rest) = id.strip_prefix(prefix) {
+            id = rest.to_string();
+        }
+    }
+    if !id.starts_with("10.") {
+        if let Some((base, version)) = id.rsplit_once('v') {
+            if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) && base.ends_with(|c: char| c.is_ascii_digit()) {
+                id.truncate(base.len());
+            }
+        }
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_reference(title: &str, abstract_text: Option<&str>) -> Reference {
+        Reference {
+            title: title.to_string(),
+            abstract_text: abstract_text.map(|s| s.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_substring_match_is_case_insensitive() {
+        let filter = TextFilter::new("holography").unwrap();
+
+        assert!(filter.matches_reference(&create_reference("Holography and Entanglement", None)));
+        assert!(filter.matches_reference(&create_reference("Black holes", Some("A holography dictionary"))));
+        assert!(!filter.matches_reference(&create_reference("Lattice QCD", Some("Quark masses"))));
+    }
+
+    #[test]
+    fn test_regex_match() {
+        let filter = TextFilter::new("AdS.*CFT").unwrap();
+
+        assert!(filter.matches("Notes on AdS3/CFT2"));
+        assert!(!filter.matches("CFT without AdS"));
+    }
+
+    #[test]
+    fn test_invalid_regex_falls_back_to_substring() {
+        let filter = TextFilter::new("N=(4").unwrap();
+
+        assert!(filter.matches("Super Yang-Mills with N=(4,4) supersymmetry"));
+        assert!(!filter.matches("N=4 SYM"));
+    }
+
+    #[test]
+    fn test_matches_paper() {
+        let paper = Paper {
+            title: "Scattering amplitudes".to_string(),
+            abstract_text: Some("We compute holographic correlators.".to_string()),
+            ..Default::default()
+        };
+
+        assert!(TextFilter::new("holographic").unwrap().matches_paper(&paper));
+        assert!(!TextFilter::new("lattice").unwrap().matches_paper(&paper));
+    }
+
+    #[test]
+    fn test_matches_any() {
+        let filter = TextFilter::new("cern").unwrap();
+        let affiliations = vec!["DESY".to_string(), "CERN, Geneva".to_string()];
+
+        assert!(filter.matches_any(&affiliations));
+        assert!(!filter.matches_any(&affiliations[..1]));
+        assert!(!filter.matches_any(&[]));
+    }
+
+    #[test]
+    fn test_identifier_filter() {
+        let paper = |id: &str, arxiv_id: Option<&str>, doi: Option<&str>| Paper {
+            id: id.to_string(),
+            arxiv_id: arxiv_id.map(|s| s.to_string()),
+            doi: doi.map(|s| s.to_string()),
+            ..Default::default()
+        };
+        let known = paper("451647", Some("hep-th/9711200"), Some("10.1023/A:1026654312961"));
+        let other = paper("1", Some("2301.12345"), None);
+
+        let filter = IdentifierFilter::new().with_exclude_list("# irrelevant\narXiv:hep-th/9711200v3\n");
+        assert!(!filter.allows_paper(&known));
+        assert!(filter.allows_paper(&other));
+
+        let filter = IdentifierFilter::new().with_exclude_list("https://doi.org/10.1023/a:1026654312961");
+        assert!(!filter.allows_paper(&known));
+
+        let filter = IdentifierFilter::new().with_include_list("451647\n");
+        assert!(filter.allows_paper(&known));
+        assert!(!filter.allows_paper(&other));
+        assert!(!filter.is_empty());
+
+        let reference = Reference { arxiv_id: Some("2301.12345".to_string()), ..Default::default() };
+        assert!(IdentifierFilter::new().with_include_list("2301.12345v1").allows_reference(&reference));
+        assert!(IdentifierFilter::new().is_empty());
+    }
+}
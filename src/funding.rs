@@ -0,0 +1,98 @@
+use std::collections::{BTreeSet, HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::models::Paper;
+use crate::report::Table;
+
+/// Papers acknowledging one funding agency, for grant impact statements
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingStats {
+    pub agency: String,
+    pub papers: usize,
+    /// Share of all papers, in percent
+    pub percentage: f64,
+    /// Distinct grant numbers acknowledged, sorted
+    pub grants: Vec<String>,
+}
+
+/// Count the papers acknowledging each agency, most frequent first. A paper
+/// with several grants from one agency counts once for it.
+pub fn funding_stats<'a>(papers: impl IntoIterator<Item = &'a Paper>) -> Vec<FundingStats> {
+    let mut agencies: HashMap<&str, (usize, BTreeSet<&str>)> = HashMap::new();
+    let mut total = 0;
+    for paper in papers {
+        total += 1;
+        let mut seen = BTreeSet::new();
+        for grant in &paper.funding {
+            let (papers, grants) = agencies.entry(grant.agency.as_str()).or_default();
+            if seen.insert(grant.agency.as_str()) {
+                *papers += 1;
+            }
+            grants.extend(grant.grant_number.as_deref());
+        }
+    }
+
+    let mut stats: Vec<FundingStats> = agencies.into_iter()
+        .map(|(agency, (papers, grants))| FundingStats {
+            agency: agency.to_string(),
+            papers,
+            percentage: 100.0 * papers as f64 / total as f64,
+            grants: grants.into_iter().map(str::to_string).collect(),
+        })
+        .collect();
+    stats.sort_by(|a, b| b.papers.cmp(&a.papers).then_with(|| a.agency.cmp(&b.agency)));
+    stats
+}
+
+/// Per-agency counts as a report table, grant numbers separated by `; `
+pub fn funding_table(stats: &[FundingStats]) -> Table {
+    let mut table = Table::new(&["agency", "papers", "percentage", "grants"]);
+    for agency in stats {
+        table.push_row(vec![
+            agency.agency.clone(),
+            agency.papers.to_string(),
+            format!("{:.1}", agency.percentage),
+            agency.grants.join("; "),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Funding;
+
+    fn funded_by(grants: &[(&str, Option<&str>)]) -> Paper {
+        Paper {
+            funding: grants.iter()
+                .map(|(agency, grant_number)| Funding {
+                    agency: agency.to_string(),
+                    grant_number: grant_number.map(str::to_string),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_funding_stats() {
+        let papers = [
+            funded_by(&[("DOE", Some("DE-SC0011632")), ("ERC", Some("101002846"))]),
+            funded_by(&[("DOE", Some("DE-SC0011632")), ("DOE", Some("DE-AC02-05CH11231"))]),
+            funded_by(&[("NSF", None)]),
+            funded_by(&[]),
+        ];
+        let stats = funding_stats(&papers);
+        let rows: Vec<(&str, usize)> = stats.iter().map(|s| (s.agency.as_str(), s.papers)).collect();
+        assert_eq!(rows, [("DOE", 2), ("ERC", 1), ("NSF", 1)]);
+        assert_eq!(stats[0].percentage, 50.0);
+        assert_eq!(stats[0].grants, ["DE-AC02-05CH11231", "DE-SC0011632"]);
+
+        let table = funding_table(&stats);
+        assert_eq!(table.rows[0], ["DOE", "2", "50.0", "DE-AC02-05CH11231; DE-SC0011632"]);
+        assert_eq!(table.rows[2], ["NSF", "1", "25.0", ""]);
+        assert!(funding_stats([]).is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::atom::escape_xml;
+use crate::models::Paper;
+use crate::network::{CitationNetwork, EdgeKind};
+
+/// DOT attributes that set seed papers apart from the papers found from them
+const SEED_STYLE: &str = ", is_seed=true, shape=doubleoctagon, style=filled, fillcolor=\"gold\"";
+
+/// One exported citation edge
+struct Edge<'a> {
+    citing: &'a str,
+    cited: &'a str,
+    weight: u32,
+    kind: EdgeKind,
+    /// Reference labels in the citing paper, e.g. `[42]`
+    labels: &'a [String],
+}
+
+impl Edge<'_> {
+    /// Labels as written in the citing paper: `[3], [17]`
+    fn label_text(&self) -> String {
+        self.labels.iter().map(|label| format!("[{}]", label)).collect::<Vec<_>>().join(", ")
+    }
+}
+
+fn edges(network: &CitationNetwork) -> impl Iterator<Item = Edge<'_>> {
+    network.citations.iter().flat_map(move |(citing, cited_ids)| {
+        cited_ids.iter().map(move |cited| Edge {
+            citing,
+            cited,
+            weight: network.edge_weight(citing, cited),
+            kind: network.edge_kind(citing, cited),
+            labels: network.edge_labels(citing, cited),
+        })
+    })
+}
+
+/// Render a network as GraphML, with paper metadata on nodes and weights on edges
+pub fn to_graphml(network: &CitationNetwork) -> String {
+    let mut graphml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    graphml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    graphml.push_str("  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n");
+    graphml.push_str("  <key id=\"year\" for=\"node\" attr.name=\"year\" attr.type=\"int\"/>\n");
+    graphml.push_str("  <key id=\"arxiv_id\" for=\"node\" attr.name=\"arxiv_id\" attr.type=\"string\"/>\n");
+    graphml.push_str("  <key id=\"depth\" for=\"node\" attr.name=\"depth\" attr.type=\"int\"/>\n");
+    graphml.push_str("  <key id=\"is_seed\" for=\"node\" attr.name=\"is_seed\" attr.type=\"boolean\">\n    <default>false</default>\n  </key>\n");
+    graphml.push_str("  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"int\"/>\n");
+    graphml.push_str("  <key id=\"labels\" for=\"edge\" attr.name=\"labels\" attr.type=\"string\"/>\n");
+    graphml.push_str("  <key id=\"kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n");
+    graphml.push_str("  <graph id=\"citations\" edgedefault=\"directed\">\n");
+
+    for paper in network.papers.values() {
+        graphml.push_str(&format!("    <node id=\"{}\">\n", escape_xml(&paper.id)));
+        graphml.push_str(&format!("      <data key=\"title\">{}</data>\n", escape_xml(&paper.title)));
+        if let Some(year) = paper.year {
+            graphml.push_str(&format!("      <data key=\"year\">{}</data>\n", year));
+        }
+        if let Some(arxiv_id) = &paper.arxiv_id {
+            graphml.push_str(&format!("      <data key=\"arxiv_id\">{}</data>\n", escape_xml(arxiv_id)));
+        }
+        if let Some(depth) = network.depth(&paper.id) {
+            graphml.push_str(&format!("      <data key=\"depth\">{}</data>\n", depth));
+        }
+        if network.is_seed(&paper.id) {
+            graphml.push_str("      <data key=\"is_seed\">true</data>\n");
+        }
+        graphml.push_str("    </node>\n");
+    }
+
+    for edge in edges(network) {
+        graphml.push_str(&format!(
+            "    <edge source=\"{}\" target=\"{}\">\n      <data key=\"weight\">{}</data>\n      <data key=\"kind\">{}</data>\n",
+            escape_xml(edge.citing), escape_xml(edge.cited), edge.weight, edge.kind.as_str()
+        ));
+        if !edge.labels.is_empty() {
+            graphml.push_str(&format!("      <data key=\"labels\">{}</data>\n", escape_xml(&edge.label_text())));
+        }
+        graphml.push_str("    </edge>\n");
+    }
+
+    graphml.push_str("  </graph>\n</graphml>\n");
+    graphml
+}
+
+/// Render a network in Graphviz DOT; edges cited several times are drawn thicker,
+/// each edge's tooltip lists its reference labels, edges found by citation search
+/// are dashed, nodes carry their depth, and seed papers are drawn as filled
+/// double octagons
+pub fn to_dot(network: &CitationNetwork) -> String {
+    let mut dot = String::from("digraph citations {\n");
+    for paper in network.papers.values() {
+        let mut attributes = format!("label=\"{}\"", escape_dot(&node_label(paper)));
+        if let Some(depth) = network.depth(&paper.id) {
+            attributes.push_str(&format!(", depth={}", depth));
+        }
+        if network.is_seed(&paper.id) {
+            attributes.push_str(SEED_STYLE);
+        }
+        dot.push_str(&format!("  \"{}\" [{}];\n", escape_dot(&paper.id), attributes));
+    }
+    for edge in edges(network) {
+        let mut attributes = format!("weight={}, penwidth={}, kind={}", edge.weight, edge.weight, edge.kind.as_str());
+        if edge.kind == EdgeKind::CitedBy {
+            attributes.push_str(", style=dashed");
+        }
+        if !edge.labels.is_empty() {
+            attributes.push_str(&format!(", tooltip=\"{}\"", escape_dot(&edge.label_text())));
+        }
+        dot.push_str(&format!("  \"{}\" -> \"{}\" [{}];\n", escape_dot(edge.citing), escape_dot(edge.cited), attributes));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Render a network as Cytoscape.js JSON (`{"elements": {"nodes": …, "edges": …}}`)
+pub fn to_cytoscape(network: &CitationNetwork) -> Result<String> {
+    let nodes: Vec<Value> = network.papers.values()
+        .map(|paper| json!({
+            "data": {
+                "id": paper.id,
+                "label": node_label(paper),
+                "title": paper.title,
+                "year": paper.year,
+                "arxiv_id": paper.arxiv_id,
+                "depth": network.depth(&paper.id),
+                "is_seed": network.is_seed(&paper.id),
+            },
+            "classes": if network.is_seed(&paper.id) { "seed" } else { "" },
+        }))
+        .collect();
+    let edges: Vec<Value> = edges(network)
+        .map(|edge| json!({
+            "data": {
+                "id": format!("{}->{}", edge.citing, edge.cited),
+                "source": edge.citing,
+                "target": edge.cited,
+                "weight": edge.weight,
+                "kind": edge.kind,
+                "labels": edge.labels,
+            }
+        }))
+        .collect();
+
+    Ok(serde_json::to_string_pretty(&json!({ "elements": { "nodes": nodes, "edges": edges } }))?)
+}
+
+/// Short node label: first author and year, falling back to the title
+fn node_label(paper: &Paper) -> String {
+    match (paper.authors.first(), paper.year) {
+        (Some(author), Some(year)) => format!("{} ({})", author, year),
+        (Some(author), None) => author.clone(),
+        _ => paper.title.clone(),
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_network() -> CitationNetwork {
+        let mut network = CitationNetwork::new();
+        network.add_paper(Paper {
+            id: "1".to_string(),
+            title: "Large N & \"holography\"".to_string(),
+            authors: vec!["Maldacena".to_string()],
+            year: Some(1997),
+            ..Default::default()
+        });
+        network.add_paper(Paper { id: "2".to_string(), title: "Cited".to_string(), ..Default::default() });
+        network.add_labeled_citations("1", vec![("2".to_string(), "4".to_string()), ("2".to_string(), "9".to_string())]);
+        network.add_cited_by("2", vec!["3".to_string()]);
+        network.record_depth("1", 0);
+        network.mark_seed("1");
+        network
+    }
+
+    #[test]
+    fn test_to_graphml() {
+        let graphml = to_graphml(&create_network());
+        assert!(graphml.contains("<data key=\"title\">Large N &amp; &quot;holography&quot;</data>"));
+        assert!(graphml.contains("<data key=\"depth\">0</data>\n      <data key=\"is_seed\">true</data>"));
+        assert_eq!(graphml.matches("key=\"is_seed\">true").count(), 1);
+        assert!(graphml.contains("<edge source=\"1\" target=\"2\">\n      <data key=\"weight\">2</data>\n      <data key=\"kind\">cites</data>\n      <data key=\"labels\">[4], [9]</data>"));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = to_dot(&create_network());
+        assert!(dot.contains("\"1\" [label=\"Maldacena (1997)\", depth=0, is_seed=true, shape=doubleoctagon, style=filled, fillcolor=\"gold\"];"));
+        assert!(dot.contains("\"2\" [label=\"Cited\"];"));
+        assert!(dot.contains("\"1\" -> \"2\" [weight=2, penwidth=2, kind=cites, tooltip=\"[4], [9]\"];"));
+        assert!(dot.contains("\"3\" -> \"2\" [weight=1, penwidth=1, kind=cited_by, style=dashed];"));
+    }
+
+    #[test]
+    fn test_to_cytoscape() {
+        let cytoscape: Value = serde_json::from_str(&to_cytoscape(&create_network()).unwrap()).unwrap();
+        let edges = cytoscape["elements"]["edges"].as_array().unwrap();
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[0]["data"]["weight"], 2);
+        assert_eq!(edges[0]["data"]["kind"], "cites");
+        assert_eq!(edges[1]["data"]["kind"], "cited_by");
+        assert_eq!(edges[0]["data"]["labels"], json!(["4", "9"]));
+        let nodes = cytoscape["elements"]["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0]["data"]["depth"], 0);
+        assert!(nodes[1]["data"]["depth"].is_null());
+        assert_eq!(nodes[0]["data"]["is_seed"], true);
+        assert_eq!(nodes[0]["classes"], "seed");
+        assert_eq!(nodes[1]["data"]["is_seed"], false);
+    }
+}
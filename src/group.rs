@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+use log::{info, warn};
+
+use crate::api::InspireClient;
+use crate::models::Reference;
+
+/// Combined publication list of a group
+#[derive(Debug, Clone, Default)]
+pub struct GroupBibliography {
+    /// Every paper by at least one member, once, newest first
+    pub references: Vec<Reference>,
+    /// Number of papers found per member, in the order of the member list
+    pub per_author: Vec<(String, usize)>,
+    /// Members whose papers could not be fetched
+    pub failed: Vec<String>,
+}
+
+/// Parse a member list: one INSPIRE author name (e.g. `Doe, J.`) per line,
+/// ignoring blank lines and `#` comments
+pub fn parse_author_list(content: &str) -> Vec<String> {
+    content.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Fetch the papers of every member and merge them into one list, so that a
+/// paper written by several members appears only once.
+///
+/// A member whose papers cannot be fetched is reported in `failed` rather than
+/// failing the whole list.
+pub async fn build_group_bibliography(
+    client: &InspireClient,
+    authors: &[String],
+    since: Option<u32>,
+    max_per_author: usize,
+) -> GroupBibliography {
+    let mut bibliography = GroupBibliography::default();
+    let mut seen = HashSet::new();
+
+    for author in authors {
+        match client.get_author_papers(author, since, max_per_author).await {
+            Ok(papers) => {
+                info!("Found {} papers by {}", papers.len(), author);
+                bibliography.per_author.push((author.clone(), papers.len()));
+                for paper in papers {
+                    if seen.insert(paper.id.clone()) {
+                        bibliography.references.push(Reference::from(paper));
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("Could not fetch the papers of {}: {}", author, e);
+                bibliography.failed.push(author.clone());
+            }
+        }
+    }
+
+    sort_newest_first(&mut bibliography.references);
+    bibliography
+}
+
+/// Newest first, then by title; papers without a year go last
+fn sort_newest_first(references: &mut [Reference]) {
+    references.sort_by(|a, b| {
+        b.year.unwrap_or(0).cmp(&a.year.unwrap_or(0))
+            .then_with(|| a.title.cmp(&b.title))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_author_list() {
+        let content = "# Theory group\nDoe, J.\n\n  Roe, R.  # postdoc\n";
+        assert_eq!(parse_author_list(content), ["Doe, J.", "Roe, R."]);
+    }
+
+    #[test]
+    fn test_sort_newest_first() {
+        let reference = |title: &str, year: Option<u32>| Reference { title: title.to_string(), year, ..Default::default() };
+        let mut references = vec![reference("B", Some(2021)), reference("Undated", None), reference("C", Some(2023)), reference("A", Some(2021))];
+        sort_newest_first(&mut references);
+        let titles: Vec<&str> = references.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, ["C", "A", "B", "Undated"]);
+    }
+}
@@ -0,0 +1,119 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, anyhow};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+/// One recorded command invocation, enough to run it again exactly
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// When the command was run (RFC 3339)
+    pub timestamp: String,
+    /// Command-line arguments, without the program name
+    pub args: Vec<String>,
+    /// Working directory, so relative paths resolve the same way again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory: Option<PathBuf>,
+    /// Paper the command was about (arXiv ID, record ID or input file), if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    /// Where the output was written, if not to stdout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+}
+
+impl HistoryEntry {
+    /// The command as it could be typed again, quoting arguments that need it
+    pub fn command_line(&self) -> String {
+        std::iter::once("reference_tool".to_string())
+            .chain(self.args.iter().map(|arg| quote(arg)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn quote(arg: &str) -> String {
+    let plain = !arg.is_empty() && arg.chars().all(|c| c.is_alphanumeric() || "-_./:=,@+".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Append an entry to the history file (one JSON object per line)
+pub fn append(path: &Path, entry: &HistoryEntry) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Load the history, oldest first; unreadable lines are skipped with a warning
+pub fn load(path: &Path) -> Result<Vec<HistoryEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(content.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(index, line)| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!("Skipping line {} of {}: {}", index + 1, path.display(), e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Default history file, under the user's data directory
+pub fn default_history_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| anyhow!("Could not find data directory"))?;
+
+    Ok(data_dir.join("reference_tool").join("history.jsonl"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn create_entry(args: &[&str]) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            directory: Some(PathBuf::from("/home/me/thesis")),
+            identifier: Some("hep-th/9905104".to_string()),
+            output: None,
+        }
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("history.jsonl");
+        assert!(load(&path).unwrap().is_empty());
+
+        let first = create_entry(&["refs", "hep-th/9905104"]);
+        let second = create_entry(&["network", "build", "hep-th/9905104", "--depth", "2"]);
+        append(&path, &first).unwrap();
+        append(&path, &second).unwrap();
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"not json\n").unwrap();
+
+        assert_eq!(load(&path).unwrap(), vec![first, second]);
+    }
+
+    #[test]
+    fn test_command_line_quotes() {
+        let entry = create_entry(&["refs", "hep-th/9905104", "--grep", "black hole", "--bib-note", "it's"]);
+        assert_eq!(
+            entry.command_line(),
+            "reference_tool refs hep-th/9905104 --grep 'black hole' --bib-note 'it'\\''s'"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+/// User commands run after output is written, configured under `[hooks]`
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct HookConfig {
+    /// Shell command run after each export to a file (e.g. `latexmk`, a git
+    /// commit of the .bib, an rsync). `{output_path}` and `{format}` are replaced
+    /// by the file and output format; a command using neither gets both appended
+    pub post_output: Option<String>,
+}
+
+/// Fill in the `{output_path}` and `{format}` placeholders of a hook command,
+/// quoted for the shell
+pub fn expand_command(template: &str, output_path: &Path, format: &str) -> String {
+    let path = shell_quote(&output_path.to_string_lossy());
+    let format = shell_quote(format);
+    if template.contains("{output_path}") || template.contains("{format}") {
+        template.replace("{output_path}", &path).replace("{format}", &format)
+    } else {
+        format!("{} {} {}", template, path, format)
+    }
+}
+
+/// Run a post-output hook for a file just written, failing if the command
+/// cannot be started or exits unsuccessfully
+pub async fn run_post_output(template: &str, output_path: &Path, format: &str) -> Result<()> {
+    let command = expand_command(template, output_path, format);
+    info!("Running post-output hook: {}", command);
+
+    let status = shell(&command).status().await?;
+    if !status.success() {
+        return Err(anyhow!("Hook `{}` exited with {}", command, status));
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[cfg(windows)]
+fn shell_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_expand_command() {
+        let path = Path::new("/tmp/my refs.bib");
+        assert_eq!(
+            expand_command("latexmk -pdf paper.tex && cp {output_path} backup/", path, "bibtex"),
+            "latexmk -pdf paper.tex && cp '/tmp/my refs.bib' backup/"
+        );
+        assert_eq!(expand_command("./publish.sh", path, "bibtex"), "./publish.sh '/tmp/my refs.bib' 'bibtex'");
+        assert_eq!(expand_command("echo {format}", Path::new("it's.json"), "json"), "echo 'json'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[tokio::test]
+    async fn test_run_post_output() {
+        let dir = tempdir().unwrap();
+        let output = dir.path().join("refs.bib");
+        let marker = dir.path().join("done");
+
+        let hook = format!("echo {{format}} > '{}'", marker.display());
+        run_post_output(&hook, &output, "bibtex").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&marker).unwrap(), "bibtex\n");
+
+        assert!(run_post_output("exit 3", &output, "bibtex").await.is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use std::path::Path;
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+
+use crate::bibtex;
+use crate::models::Reference;
+#[cfg(feature = "network-analysis")]
+use crate::models::EXPORT_SCHEMA_VERSION;
+#[cfg(feature = "network-analysis")]
+use crate::network::CitationNetwork;
+
+/// Read a reference list written with `--format json`. A saved citation network
+/// is accepted too, its papers becoming the references.
+pub async fn read_references(path: &Path) -> Result<Vec<Reference>> {
+    let json = read(path).await?;
+    parse_references(&json).with_context(|| format!("Could not read references from {}", path.display()))
+}
+
+/// Read a saved bibliography: a `.bib` file, or a JSON reference list (or network)
+pub async fn read_bibliography(path: &Path) -> Result<Vec<Reference>> {
+    if path.extension().is_some_and(|ext| ext == "bib") {
+        let entries = bibtex::parse(&read(path).await?)
+            .with_context(|| format!("Could not parse {}", path.display()))?;
+        Ok(entries.iter().map(|entry| entry.to_reference()).collect())
+    } else {
+        read_references(path).await
+    }
+}
+
+/// Read a citation network written with `network build --format json`
+#[cfg(feature = "network-analysis")]
+pub async fn read_network(path: &Path) -> Result<CitationNetwork> {
+    let json = read(path).await?;
+    parse_network(&json).with_context(|| format!("Could not read a citation network from {}", path.display()))
+}
+
+/// Parse an exported reference list (or citation network) back into references
+pub fn parse_references(json: &str) -> Result<Vec<Reference>> {
+    let value: Value = serde_json::from_str(json)?;
+    match value {
+        Value::Array(_) => Ok(serde_json::from_value(value)?),
+        #[cfg(feature = "network-analysis")]
+        Value::Object(ref object) if object.contains_key("papers") => {
+            let network = network_from_value(value)?;
+            Ok(network.get_all_papers().into_iter().map(|paper| Reference::from(paper.clone())).collect())
+        }
+        _ => Err(anyhow!("expected a JSON array of references")),
+    }
+}
+
+/// Parse an exported citation network, checking its schema version
+#[cfg(feature = "network-analysis")]
+pub fn parse_network(json: &str) -> Result<CitationNetwork> {
+    network_from_value(serde_json::from_str(json)?)
+}
+
+#[cfg(feature = "network-analysis")]
+fn network_from_value(value: Value) -> Result<CitationNetwork> {
+    if !value.is_object() {
+        return Err(anyhow!("expected a JSON object with the network's papers and citations"));
+    }
+    check_schema_version(&value)?;
+    CitationNetwork::from_value(value)
+}
+
+/// Exports from before versioning have no `schema_version` and count as version 1
+#[cfg(feature = "network-analysis")]
+fn check_schema_version(value: &Value) -> Result<()> {
+    let version = match value.get("schema_version") {
+        None => 1,
+        Some(version) => version.as_u64()
+            .ok_or_else(|| anyhow!("invalid schema_version {}", version))?,
+    };
+    if version > u64::from(EXPORT_SCHEMA_VERSION) {
+        return Err(anyhow!(
+            "export has schema version {}, but this version of reference_tool reads up to {}; please upgrade",
+            version, EXPORT_SCHEMA_VERSION
+        ));
+    }
+    Ok(())
+}
+
+async fn read(path: &Path) -> Result<String> {
+    tokio::fs::read_to_string(path).await
+        .with_context(|| format!("Could not read {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "network-analysis")]
+    use crate::models::Paper;
+    use crate::output::{OutputFormat, OutputWriter};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_round_trip_references() {
+        let references = vec![Reference {
+            title: "First Test Paper".to_string(),
+            authors: vec!["Alice Smith".to_string()],
+            arxiv_id: Some("2301.12345".to_string()),
+            year: Some(2023),
+            ..Default::default()
+        }];
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("references.json");
+        OutputWriter::new(OutputFormat::Json, Some(path.clone())).write_references(&references).await.unwrap();
+
+        assert_eq!(read_references(&path).await.unwrap(), references);
+        assert_eq!(read_bibliography(&path).await.unwrap(), references);
+        assert!(parse_references("{\"title\": \"Not a list\"}").is_err());
+    }
+
+    #[cfg(feature = "network-analysis")]
+    #[test]
+    fn test_parse_network_checks_schema_version() {
+        let mut network = CitationNetwork::new();
+        network.add_paper(Paper { id: "1".to_string(), title: "Root".to_string(), ..Default::default() });
+        let json = network.to_json().unwrap();
+
+        assert_eq!(parse_network(&json).unwrap().papers.len(), 1);
+        assert_eq!(parse_references(&json).unwrap()[0].title, "Root");
+
+        let mut value: Value = serde_json::from_str(&json).unwrap();
+        value.as_object_mut().unwrap().remove("schema_version");
+        assert!(parse_network(&value.to_string()).is_ok());
+
+        value["schema_version"] = (EXPORT_SCHEMA_VERSION + 1).into();
+        let error = parse_network(&value.to_string()).unwrap_err();
+        assert!(error.to_string().contains("please upgrade"));
+    }
+}
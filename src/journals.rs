@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::models::PublicationInfo;
+use crate::report::Table;
+
+/// Row label for entries not published in a journal (preprints, theses, ...)
+pub const NO_JOURNAL: &str = "(no journal)";
+
+/// How many entries of a bibliography or network appeared in one journal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalStats {
+    /// Journal title as INSPIRE abbreviates it (`JHEP`, `Phys.Rev.D`), or `NO_JOURNAL`
+    pub journal: String,
+    pub papers: usize,
+    /// Share of all entries, in percent
+    pub percentage: f64,
+}
+
+/// Count the entries per journal, most frequent first; entries without a
+/// journal come last as one `NO_JOURNAL` row
+pub fn journal_stats<'a>(publications: impl IntoIterator<Item = Option<&'a PublicationInfo>>) -> Vec<JournalStats> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    let mut unpublished = 0;
+    let mut total = 0;
+    for publication in publications {
+        total += 1;
+        match publication.and_then(|info| info.journal_title.as_deref()) {
+            Some(journal) => *counts.entry(journal).or_insert(0) += 1,
+            None => unpublished += 1,
+        }
+    }
+
+    let percentage = |papers: usize| 100.0 * papers as f64 / total as f64;
+    let mut stats: Vec<JournalStats> = counts.into_iter()
+        .map(|(journal, papers)| JournalStats { journal: journal.to_string(), papers, percentage: percentage(papers) })
+        .collect();
+    stats.sort_by(|a, b| b.papers.cmp(&a.papers).then_with(|| a.journal.cmp(&b.journal)));
+    if unpublished > 0 {
+        stats.push(JournalStats { journal: NO_JOURNAL.to_string(), papers: unpublished, percentage: percentage(unpublished) });
+    }
+    stats
+}
+
+/// Per-journal counts as a report table
+pub fn journal_table(stats: &[JournalStats]) -> Table {
+    let mut table = Table::new(&["journal", "papers", "percentage"]);
+    for journal in stats {
+        table.push_row(vec![
+            journal.journal.clone(),
+            journal.papers.to_string(),
+            format!("{:.1}", journal.percentage),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn published_in(journal: &str) -> Option<PublicationInfo> {
+        Some(PublicationInfo { journal_title: Some(journal.to_string()), ..Default::default() })
+    }
+
+    #[test]
+    fn test_journal_stats() {
+        let publications = [
+            published_in("JHEP"),
+            published_in("Phys.Rev.D"),
+            published_in("JHEP"),
+            None,
+            Some(PublicationInfo { cnum: Some("C17-07-05".to_string()), ..Default::default() }),
+            published_in("JHEP"),
+            published_in("Nucl.Phys.B"),
+            published_in("Phys.Rev.D"),
+        ];
+        let stats = journal_stats(publications.iter().map(Option::as_ref));
+        let rows: Vec<(&str, usize)> = stats.iter().map(|s| (s.journal.as_str(), s.papers)).collect();
+        assert_eq!(rows, [("JHEP", 3), ("Phys.Rev.D", 2), ("Nucl.Phys.B", 1), (NO_JOURNAL, 2)]);
+        assert_eq!(stats[0].percentage, 37.5);
+
+        let table = journal_table(&stats);
+        assert_eq!(table.rows[0], ["JHEP", "3", "37.5"]);
+        assert_eq!(table.rows[3], [NO_JOURNAL, "2", "25.0"]);
+        assert!(journal_stats([]).is_empty());
+    }
+}
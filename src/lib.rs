@@ -2,36 +2,169 @@
 //!
 //! This crate provides functionality to:
 //! - Fetch paper information and references from INSPIRE-HEP API
+//! - List the papers of an experiment or collaboration
 //! - Generate BibTeX bibliographies
-//! - Build citation networks
+//! - Build combined publication lists for a group of authors
+//! - Summarize an author's citations (h-index, citations per year, most cited papers)
+//! - Report how old the references of a paper are
+//! - Count which journals a bibliography or network draws from
+//! - Count the funding agencies acknowledged across a network
+//! - Report which references are open access (arXiv, licenses, Unpaywall)
+//! - Build citation networks and export them as GraphML, DOT or Cytoscape JSON
+//! - Recommend related papers by bibliographic coupling
+//! - Attach the sentences citing each reference, from Semantic Scholar
+//! - Watch papers for new citations, with webhook/chat/Atom notifications
+//! - Collect periodic digests of new citations of an author's papers
 //! - Export data in JSON and BibTeX formats
+//! - Group a bibliography by the sections of the citing paper
+//! - Read existing BibTeX files
+//! - Extract citation keys from LaTeX sources
+//! - Compare bibliographies entry by entry
+//! - Check saved bibliographies for records updated on INSPIRE
+//! - Check the bibliography printed in a PDF against a .bib file
+//! - Serve papers, references and networks over a local REST API
+//! - Answer newline-delimited JSON requests on stdin for editor integration
+//! - Keep a warm client in a daemon that the CLI forwards to over a Unix socket
+//!
+//! # Features
+//!
+//! - `cli` (default): the command-line binary; implies `native`, `network-analysis`,
+//!   `cache`, `export-graph` and `pdf`
+//! - `native`: everything that needs Tokio or the file system
+//! - `network-analysis`: citation networks and related-paper recommendation
+//! - `cache`: an in-memory LRU cache of the literature records a client fetched
+//! - `export-graph`: GraphML, DOT and Cytoscape.js export of networks; implies `network-analysis`
+//! - `pdf`: reading bibliographies out of PDF files; implies `native`
+//! - `wasm`: JavaScript bindings for a browser build
+//!
+//! With `default-features = false` only `InspireClient`, the models and the
+//! BibTeX/bibliography helpers are built. Without `native`, the crate
+//! (including `network-analysis`) compiles to `wasm32-unknown-unknown`.
 //!
 //! # Example
 //!
 //! ```rust,no_run
-//! use reference_tool::{api::InspireClient, output::{OutputWriter, OutputFormat}};
+//! use reference_tool::prelude::*;
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     let client = InspireClient::new();
-//!     let paper = client.get_paper_by_arxiv("2301.12345").await?;
-//!     let references = client.get_paper_references(&paper.id).await?;
+//!     let options = ReferenceOptions::default();
+//!     let result = commands::fetch_references(&client, "2301.12345", &options).await?;
+//!     for warning in &result.warnings {
+//!         eprintln!("warning: {}", warning);
+//!     }
 //!     
 //!     let writer = OutputWriter::new(OutputFormat::Json, None);
-//!     writer.write_references(&references).await?;
+//!     writer.write_references(&result.references).await?;
 //!     
 //!     Ok(())
 //! }
 //! ```
 
 pub mod api;
+pub mod arxiv;
+pub mod dates;
+pub mod error;
+pub mod query;
+pub mod records;
 pub mod models;
-pub mod output;
+pub mod bibliography;
+pub mod bibtex;
+pub mod names;
+pub mod citation;
+pub mod crossref;
+pub mod unpaywall;
+pub mod sections;
+pub mod semantic_scholar;
+pub mod updates;
+pub mod group;
+pub mod author;
+pub mod aging;
+pub mod journals;
+pub mod funding;
+pub mod filter;
+pub mod report;
+pub mod commands;
+pub mod progress;
+pub mod atom;
+pub mod metrics;
+pub mod warnings;
+
+#[cfg(feature = "network-analysis")]
 pub mod network;
+#[cfg(feature = "export-graph")]
+pub mod graph;
+#[cfg(feature = "network-analysis")]
+pub mod similarity;
+
+#[cfg(feature = "native")]
+pub mod throttle;
+#[cfg(feature = "native")]
+pub mod output;
+#[cfg(feature = "native")]
+pub mod input;
+#[cfg(feature = "native")]
 pub mod config;
+#[cfg(feature = "native")]
+pub mod wizard;
+#[cfg(feature = "native")]
+pub mod texscan;
+#[cfg(feature = "native")]
+pub mod sync;
+#[cfg(feature = "native")]
+pub mod notify;
+#[cfg(feature = "native")]
+pub mod hooks;
+#[cfg(feature = "pdf")]
+pub mod pdfcheck;
+#[cfg(feature = "native")]
+pub mod watch;
+#[cfg(feature = "native")]
+pub mod digest;
+#[cfg(feature = "native")]
+pub mod library;
+#[cfg(feature = "native")]
+pub mod history;
+#[cfg(all(feature = "native", feature = "network-analysis"))]
+pub mod server;
+#[cfg(all(feature = "native", feature = "network-analysis"))]
+pub mod protocol;
+#[cfg(all(feature = "native", feature = "network-analysis"))]
+pub mod manifest;
+#[cfg(all(feature = "native", feature = "network-analysis", unix))]
+pub mod daemon;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+/// Commonly used types and the CLI's command functions.
+///
+/// `use reference_tool::prelude::*;` is the supported entry point for
+/// downstream crates; items outside the prelude may change between releases.
+pub mod prelude {
+    pub use crate::api::{ConnectionOptions, InspireClient};
+    pub use crate::error::ApiError;
+    pub use crate::models::{Address, BibtexOptions, Conference, Dataset, DatasetListing, Experiment, Institution, Job, License, Paper, PublicationInfo, Reference, ThesisInfo};
+    pub use crate::bibliography::BibDiff;
+    pub use crate::filter::TextFilter;
+    pub use crate::records::{Endpoint, InspireRecord};
+    pub use crate::report::{ReportFormat, Table};
+    pub use crate::commands::{self, ReferenceOptions};
+    pub use crate::progress::{Progress, ProgressObserver};
+    pub use crate::warnings::{Warning, WarningKind, Warnings};
+    #[cfg(feature = "network-analysis")]
+    pub use crate::network::{BuildStatus, CitationNetwork, EdgeKind, NetworkBuildOptions, PartialBuildError};
+    pub use tokio_util::sync::CancellationToken;
+    #[cfg(feature = "native")]
+    pub use crate::output::{BibSort, OutputFormat, OutputWriter};
+}
 
 // Re-export commonly used types
 pub use api::InspireClient;
 pub use models::{Paper, Reference};
+#[cfg(feature = "native")]
 pub use output::{OutputWriter, OutputFormat};
+#[cfg(feature = "network-analysis")]
 pub use network::CitationNetwork;
+pub use bibliography::BibDiff;
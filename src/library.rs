@@ -0,0 +1,240 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::atom;
+use crate::models::Reference;
+
+/// Where a paper is on the reading list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ReadingStatus {
+    ToRead,
+    Reading,
+    Read,
+}
+
+impl fmt::Display for ReadingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ReadingStatus::ToRead => "to-read",
+            ReadingStatus::Reading => "reading",
+            ReadingStatus::Read => "read",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The user's own data about papers (tags, notes and the reading list), kept in
+/// a JSON file under the data directory and keyed by arXiv ID or INSPIRE record ID
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Library {
+    #[serde(default)]
+    pub papers: BTreeMap<String, LibraryEntry>,
+}
+
+/// What the user attached to one paper
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// Title, remembered when the paper is put on the reading list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Reading-list status; `None` for papers not on the list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ReadingStatus>,
+    /// When the paper was put on the reading list (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queued: Option<String>,
+}
+
+impl LibraryEntry {
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.notes.is_empty() && self.status.is_none()
+    }
+}
+
+impl Library {
+    /// Load the library from a file, empty if it does not exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Save the library to a file, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Tag a paper, returning whether the tag is new
+    pub fn add_tag(&mut self, paper: &str, tag: &str) -> bool {
+        self.papers.entry(normalize_id(paper)).or_default().tags.insert(tag.to_string())
+    }
+
+    /// Remove a tag from a paper, returning whether it was there
+    pub fn remove_tag(&mut self, paper: &str, tag: &str) -> bool {
+        let id = normalize_id(paper);
+        let Some(entry) = self.papers.get_mut(&id) else { return false };
+        let removed = entry.tags.remove(tag);
+        if entry.is_empty() {
+            self.papers.remove(&id);
+        }
+        removed
+    }
+
+    /// Attach a free-text note to a paper
+    pub fn add_note(&mut self, paper: &str, note: &str) {
+        self.papers.entry(normalize_id(paper)).or_default().notes.push(note.to_string());
+    }
+
+    /// What the user attached to a paper, if anything
+    pub fn entry(&self, paper: &str) -> Option<&LibraryEntry> {
+        self.papers.get(&normalize_id(paper))
+    }
+
+    /// Put a paper on the reading list, or change its status if it already is
+    pub fn queue(&mut self, paper: &str, title: Option<String>, status: ReadingStatus) {
+        let entry = self.papers.entry(normalize_id(paper)).or_default();
+        if title.is_some() {
+            entry.title = title;
+        }
+        entry.status = Some(status);
+        entry.queued.get_or_insert_with(atom::now_rfc3339);
+    }
+
+    /// Change the status of a paper on the reading list, returning whether it is on the list
+    pub fn set_status(&mut self, paper: &str, status: ReadingStatus) -> bool {
+        match self.papers.get_mut(&normalize_id(paper)) {
+            Some(entry) if entry.status.is_some() => {
+                entry.status = Some(status);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Papers on the reading list, optionally only those with one status, by
+    /// status and then in the order they were queued
+    pub fn reading_list(&self, status: Option<ReadingStatus>) -> Vec<(&str, &LibraryEntry)> {
+        let mut list: Vec<(&str, &LibraryEntry)> = self.papers.iter()
+            .filter(|(_, entry)| entry.status.is_some() && (status.is_none() || entry.status == status))
+            .map(|(id, entry)| (id.as_str(), entry))
+            .collect();
+        list.sort_by(|(_, a), (_, b)| (a.status, &a.queued).cmp(&(b.status, &b.queued)));
+        list
+    }
+
+    /// Papers carrying a tag
+    pub fn tagged<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.papers.iter()
+            .filter(move |(_, entry)| entry.tags.contains(tag))
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Entry for a reference, looked up by arXiv ID, then INSPIRE record ID
+    pub fn entry_for(&self, reference: &Reference) -> Option<&LibraryEntry> {
+        reference.arxiv_id.iter()
+            .chain(reference.inspire_id.iter())
+            .find_map(|id| self.entry(id))
+    }
+
+    /// Copy tags and notes onto references, so they are included in exports
+    pub fn annotate(&self, references: &mut [Reference]) {
+        for reference in references {
+            if let Some(entry) = self.entry_for(reference) {
+                reference.tags = entry.tags.iter().cloned().collect();
+                reference.notes = entry.notes.clone();
+            }
+        }
+    }
+}
+
+/// `arXiv:2301.12345` and `2301.12345` name the same paper
+fn normalize_id(paper: &str) -> String {
+    let paper = paper.trim();
+    match paper.get(..6) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("arxiv:") => paper[6..].to_string(),
+        _ => paper.to_string(),
+    }
+}
+
+/// Default library file, under the user's data directory
+pub fn default_library_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| anyhow!("Could not find data directory"))?;
+
+    Ok(data_dir.join("reference_tool").join("library.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_tags_and_notes() {
+        let mut library = Library::default();
+        assert!(library.add_tag("arXiv:2301.12345", "to-read"));
+        assert!(!library.add_tag("2301.12345", "to-read"));
+        library.add_tag("1234567", "to-read");
+        library.add_note("2301.12345", "Compare with section 4");
+        assert_eq!(library.tagged("to-read").collect::<Vec<_>>(), ["1234567", "2301.12345"]);
+
+        let mut references = vec![
+            Reference { arxiv_id: Some("2301.12345".to_string()), ..Default::default() },
+            Reference { inspire_id: Some("1234567".to_string()), ..Default::default() },
+            Reference { arxiv_id: Some("2302.00001".to_string()), ..Default::default() },
+        ];
+        library.annotate(&mut references);
+        assert_eq!(references[0].tags, ["to-read"]);
+        assert_eq!(references[0].notes, ["Compare with section 4"]);
+        assert_eq!(references[1].tags, ["to-read"]);
+        assert!(references[2].tags.is_empty());
+
+        assert!(library.remove_tag("1234567", "to-read"));
+        assert!(library.entry("1234567").is_none());
+    }
+
+    #[test]
+    fn test_reading_list() {
+        let mut library = Library::default();
+        library.queue("2301.12345", Some("First".to_string()), ReadingStatus::ToRead);
+        library.queue("2302.00001", None, ReadingStatus::Reading);
+        library.add_tag("2303.00002", "thesis");
+        assert!(!library.set_status("2303.00002", ReadingStatus::Read));
+
+        let ids = |list: Vec<(&str, &LibraryEntry)>| list.into_iter().map(|(id, _)| id.to_string()).collect::<Vec<_>>();
+        assert_eq!(ids(library.reading_list(None)), ["2301.12345", "2302.00001"]);
+
+        assert!(library.set_status("arXiv:2301.12345", ReadingStatus::Read));
+        assert_eq!(ids(library.reading_list(None)), ["2302.00001", "2301.12345"]);
+        assert_eq!(ids(library.reading_list(Some(ReadingStatus::Read))), ["2301.12345"]);
+        assert_eq!(library.entry("2301.12345").unwrap().title.as_deref(), Some("First"));
+        assert_eq!(serde_json::to_value(ReadingStatus::ToRead).unwrap(), "to-read");
+    }
+
+    #[test]
+    fn test_library_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("library.json");
+        assert_eq!(Library::load(&path).unwrap(), Library::default());
+
+        let mut library = Library::default();
+        library.add_tag("2301.12345", "thesis");
+        library.save(&path).unwrap();
+        assert_eq!(Library::load(&path).unwrap(), library);
+    }
+}
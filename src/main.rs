@@ -1,17 +1,48 @@
 use clap::{Args, Parser, Subcommand};
-use log::info;
+use futures::{Stream, TryStreamExt};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
-mod api;
-mod models;
-mod output;
-mod network;
-mod config;
-
-use crate::api::InspireClient;
-use crate::output::{OutputFormat, OutputWriter};
-use crate::network::CitationNetwork;
-use crate::config::Config;
+use reference_tool::api::InspireClient;
+use reference_tool::aging::ReferenceAging;
+use reference_tool::arxiv::ArxivClient;
+use reference_tool::crossref::CrossrefClient;
+use reference_tool::unpaywall::UnpaywallClient;
+use reference_tool::atom;
+use reference_tool::history::{self, HistoryEntry};
+use reference_tool::input;
+use reference_tool::journals;
+use reference_tool::manifest::NetworkManifest;
+use reference_tool::library::{self, Library, ReadingStatus};
+use reference_tool::output::{BibSort, OutputFormat, OutputWriter};
+use reference_tool::network::{BuildStatus, NetworkBuildOptions, PartialBuildError};
+use reference_tool::report::ReportFormat;
+use reference_tool::config::Config;
+use reference_tool::wizard;
+use reference_tool::filter::{IdentifierFilter, TextFilter};
+use reference_tool::progress::{Progress, ProgressObserver, SharedObserver};
+use reference_tool::pdfcheck;
+use reference_tool::sections::{self, SectionGroup};
+use reference_tool::semantic_scholar::{self, SemanticScholarClient};
+use reference_tool::similarity::{self, MapOptions, SimilarityOptions};
+use reference_tool::sync;
+use reference_tool::digest::{self, DigestFormat, DigestPeriod};
+use reference_tool::{notify, protocol, server, watch};
+use reference_tool::commands::{self, FetchResult, ReferenceOptions, ReferenceSort};
+use reference_tool::models::{DataSource, FieldSources, Paper, Reference};
+use reference_tool::texscan;
+use reference_tool::warnings::{WarningKind, Warnings};
+use reference_tool::updates;
+use reference_tool::group;
+use reference_tool::author;
+use reference_tool::query::{LiteratureQuery, SearchSort};
+use reference_tool::citation::CitationStyle;
+#[cfg(unix)]
+use reference_tool::daemon;
 
 #[derive(Parser)]
 #[command(name = "reference_tool")]
@@ -25,9 +56,21 @@ struct Cli {
     #[arg(long, global = true)]
     arxiv_id: Option<String>,
     
-    /// Output format
-    #[arg(long, value_enum, default_value_t = OutputFormat::Json, global = true)]
-    format: OutputFormat,
+    /// DOI of the paper, for papers not on arXiv (`refs` and `network build`)
+    #[arg(long, global = true, conflicts_with = "arxiv_id")]
+    doi: Option<String>,
+    
+    /// Output format [default: default_format from the config, or json]
+    #[arg(long, value_enum, global = true)]
+    format: Option<OutputFormat>,
+    
+    /// Apply the defaults of this `[profiles.NAME]` section of the config
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    
+    /// Citation style for `--format citations`, `bibitem` and `html`
+    #[arg(long, value_enum, default_value_t = CitationStyle::Apa, global = true)]
+    style: CitationStyle,
     
     /// Output file path
     #[arg(long, global = true)]
@@ -37,31 +80,651 @@ struct Cli {
     #[arg(long, global = true)]
     categories: Option<String>,
     
+    /// List the datasets (HEPData, INSPIRE data records) associated with the paper and its references
+    #[arg(long, global = true)]
+    with_datasets: bool,
+    
+    /// Only keep entries whose title or abstract matches this pattern (regex or substring,
+    /// case-insensitive); `refs` looks up each reference's record for its abstract
+    #[arg(long, global = true)]
+    grep: Option<String>,
+    
+    /// Only keep papers with an author at a matching institution (regex or substring,
+    /// case-insensitive); for `refs`, looks up each reference's record
+    #[arg(long, global = true)]
+    affiliation: Option<String>,
+    
+    /// Order of BibTeX entries (default from the config, otherwise as returned by INSPIRE)
+    #[arg(long, value_enum, global = true)]
+    bib_sort: Option<BibSort>,
+    
+    /// Add a `keywords` field from the arXiv categories to BibTeX entries
+    #[arg(long, global = true)]
+    bib_keywords: bool,
+    
+    /// Add a `url` field linking the INSPIRE record to BibTeX entries
+    #[arg(long, global = true)]
+    bib_url: bool,
+    
+    /// Add this text as a `note` field to BibTeX entries
+    #[arg(long, global = true)]
+    bib_note: Option<String>,
+    
+    /// Pin BibTeX `eprint` fields to the current arXiv version (e.g. 2301.12345v2)
+    #[arg(long, global = true)]
+    bib_pin_versions: bool,
+    
+    /// Add a `month` field (jan ... dec) to BibTeX entries whose publication month is known
+    #[arg(long, global = true)]
+    bib_month: bool,
+    
+    /// Write BibTeX for biblatex: a full `date` (e.g. 2023-01-15) instead of `year` and `month`
+    #[arg(long, global = true)]
+    biblatex: bool,
+    
+    /// Write author, experiment and journal listings page by page as they arrive,
+    /// keeping memory use bounded for tens of thousands of records
+    #[arg(long, global = true)]
+    stream: bool,
+    
+    /// Add BibTeX entries to the existing --output file instead of overwriting it,
+    /// renaming keys already taken by a different entry
+    #[arg(long, global = true)]
+    append: bool,
+    
+    /// Leave out records listed in this file (one arXiv ID, DOI or INSPIRE record ID per line)
+    #[arg(long, global = true)]
+    exclude_file: Option<PathBuf>,
+    
+    /// Keep only records listed in this file (one arXiv ID, DOI or INSPIRE record ID per line)
+    #[arg(long, global = true)]
+    include_file: Option<PathBuf>,
+    
+    /// Only keep papers carrying this tag in the local library
+    #[arg(long, global = true)]
+    tag: Option<String>,
+    
+    /// Include the untouched INSPIRE metadata of each paper and reference in JSON output
+    #[arg(long, global = true)]
+    raw: bool,
+    
+    /// Name the source of each field: `*_source` fields in the JSON of `refs`, and a
+    /// `country_source` column in `network countries`
+    #[arg(long, global = true)]
+    provenance: bool,
+    
+    /// Answer newline-delimited JSON requests on stdin (for editor plugins) instead of running once
+    #[arg(long)]
+    stdin_protocol: bool,
+    
+    /// How progress of long operations is shown on stderr (json: one event object per line)
+    #[arg(long, value_enum, default_value_t = ProgressStyle::Human, global = true)]
+    progress: ProgressStyle,
+    
     /// Enable verbose logging
     #[arg(short, long, global = true)]
     verbose: bool,
 }
 
+/// Progress display on stderr (`--progress`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+enum ProgressStyle {
+    /// Status lines for network builds
+    #[default]
+    Human,
+    /// NDJSON events (stage, completed, total, current_item) for every long operation
+    Json,
+}
+
 #[derive(Subcommand)]
 enum Commands {
-    /// Build citation network
+    /// Fetch the references of a paper
+    Refs(RefsArgs),
+    /// Build and analyze citation networks
     Network(NetworkArgs),
+    /// Resolve the citation keys used by a LaTeX document
+    Texscan(TexscanArgs),
+    /// Find related papers sharing references with a paper
+    Similar(SimilarArgs),
+    /// Group a paper's references into clusters that cite the same literature
+    Map(MapArgs),
+    /// Filter a saved JSON reference list (or network) and write it in another format
+    Filter(FilterArgs),
+    /// Report entries of a saved bibliography whose INSPIRE record changed, without rewriting it
+    CheckUpdates(CheckUpdatesArgs),
+    /// Check the bibliography printed in a PDF against a .bib file before submission
+    VerifyPdf(VerifyPdfArgs),
+    /// Build one publication list from the papers of a group's members
+    GroupBib(GroupBibArgs),
+    /// List an author's papers, or summarize their citations with --metrics
+    Author(AuthorArgs),
+    /// Show an institution's INSPIRE record: address, ROR and GRID IDs, number of papers
+    Institution(InstitutionArgs),
+    /// List the papers of an INSPIRE experiment (or collaboration)
+    Experiment(ExperimentArgs),
+    /// List the contributions to a conference, or the conferences of a series with --series
+    Conference(ConferenceArgs),
+    /// List the papers in a journal volume or issue
+    Journal(JournalArgs),
+    /// Search INSPIRE for papers by title, author or any INSPIRE query
+    Search(SearchArgs),
+    /// List the papers of the last few days in an arXiv category
+    Recent(RecentArgs),
+    /// List the most cited papers of an arXiv category, optionally from one year
+    Top(TopArgs),
+    /// Tag papers in the local library
+    Tag(TagArgs),
+    /// Attach a note to a paper, or show its notes
+    Note(NoteArgs),
+    /// Keep a reading list of papers to read, being read and read
+    List(ListArgs),
+    /// Show previously run commands
+    History(HistoryArgs),
+    /// Run a command from the history again, exactly as before
+    Rerun(RerunArgs),
+    /// Update the .bib files of all LaTeX projects in a workspace
+    Sync(SyncArgs),
+    /// Report new citations of a paper (or an author's papers) since the last check
+    Watch(WatchArgs),
+    /// Serve a local REST API for web frontends and notebooks
+    Serve(ServeArgs),
+    /// Keep a warm client running and answer CLI requests over a Unix socket
+    #[cfg(unix)]
+    Daemon(DaemonArgs),
+    /// Check the configuration and which INSPIRE endpoints answer
+    Doctor,
     /// Show current configuration
     Config,
     /// Initialize configuration file
-    InitConfig,
+    InitConfig(InitConfigArgs),
+}
+
+#[derive(Args, Default)]
+struct RefsArgs {
+    /// ArXiv ID, DOI or INSPIRE record ID of the paper (can also be given
+    /// globally with --arxiv-id or --doi)
+    #[arg(value_name = "ARXIV_ID")]
+    paper: Option<String>,
+    /// Return at most this many references
+    #[arg(long)]
+    limit: Option<usize>,
+    /// Order of the references
+    #[arg(long, value_enum, default_value_t = ReferenceSort::Original)]
+    sort: ReferenceSort,
+    /// Look up and include each reference's abstract
+    #[arg(long)]
+    with_abstract: bool,
+    /// Include the sentences citing each reference, where Semantic Scholar has them
+    #[arg(long)]
+    contexts: bool,
+    /// Flag references withdrawn on INSPIRE or retracted according to CrossRef
+    #[arg(long)]
+    check_retractions: bool,
+    /// Annotate each reference with its open-access status, from arXiv,
+    /// its license and (with an email address configured) Unpaywall
+    #[arg(long)]
+    open_access: bool,
+    /// Keep only the references that are open access (implies --open-access)
+    #[arg(long)]
+    oa_only: bool,
+    /// Contact email for Unpaywall lookups [default: unpaywall_email from the config]
+    #[arg(long, value_name = "EMAIL")]
+    unpaywall_email: Option<String>,
+    /// Group the references by the section of the paper citing them, using the
+    /// paper's .tex source or a file of `Section: 1-12, 15` reference number ranges
+    #[arg(long, value_name = "FILE")]
+    group_by_section: Option<PathBuf>,
+    /// List the papers citing the paper instead of its references, most recent first
+    #[arg(long, conflicts_with_all = ["report", "group_by_section", "contexts", "sort"])]
+    citations: bool,
+    /// Report the age distribution of the references instead of listing them
+    #[arg(long, group = "report")]
+    aging: bool,
+    /// Count the references per journal instead of listing them
+    #[arg(long, group = "report")]
+    journals: bool,
+    /// Age in years under which --aging counts a reference as recent
+    #[arg(long, value_name = "YEARS", default_value_t = 5, requires = "aging")]
+    recent_years: u32,
+    /// Write the --aging or --journals report in this format instead of JSON
+    /// (the aging report's Markdown includes a histogram)
+    #[arg(long, value_enum, requires = "report")]
+    table: Option<ReportFormat>,
+}
+
+#[derive(Args)]
+struct FilterArgs {
+    /// JSON file written by `refs --format json` or `network build --format json`
+    file: PathBuf,
+}
+
+#[derive(Args)]
+struct CheckUpdatesArgs {
+    /// BibTeX file, or JSON file written by `refs --format json`
+    file: PathBuf,
+}
+
+#[derive(Args)]
+struct VerifyPdfArgs {
+    /// PDF of the draft
+    pdf: PathBuf,
+    /// BibTeX file (or JSON written by `refs --format json`) the draft was built from
+    #[arg(long)]
+    bib: PathBuf,
+    /// Do not look up entries missing from the .bib file on INSPIRE
+    #[arg(long)]
+    offline: bool,
+}
+
+#[derive(Args)]
+struct GroupBibArgs {
+    /// Text file with one INSPIRE author name (e.g. "Doe, J.") per line
+    authors: PathBuf,
+    /// Only papers from this year onwards
+    #[arg(long)]
+    since: Option<u32>,
+    /// Number of most recent papers fetched per member
+    #[arg(long, default_value_t = 500)]
+    max_per_author: usize,
+}
+
+#[derive(Args)]
+struct AuthorArgs {
+    /// INSPIRE author name (e.g. "Doe, J.")
+    author: String,
+    /// Report h-index, total citations, citations per year and the most cited papers
+    #[arg(long)]
+    metrics: bool,
+    /// Write the metrics as tables in this format instead of JSON
+    #[arg(long, value_enum, requires = "metrics")]
+    table: Option<ReportFormat>,
+    /// Number of most cited papers in the metrics
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+    /// Number of most recent papers fetched
+    #[arg(long, default_value_t = 1000)]
+    max_papers: usize,
+}
+
+#[derive(Args)]
+struct InstitutionArgs {
+    /// Institution as named in author affiliations (e.g. CERN), or its INSPIRE record ID
+    name: String,
+}
+
+#[derive(Args)]
+struct ExperimentArgs {
+    /// INSPIRE experiment name (e.g. CERN-LHC-ATLAS), or collaboration name with --collaboration
+    name: String,
+    /// Match papers signed by a collaboration of this name (e.g. ATLAS) instead
+    #[arg(long)]
+    collaboration: bool,
+    /// Only papers from this year onwards
+    #[arg(long)]
+    since: Option<u32>,
+    /// Number of papers fetched (before --categories and --grep are applied)
+    #[arg(long, default_value_t = 100)]
+    limit: usize,
+    /// Order of the papers
+    #[arg(long, value_enum, default_value_t = SearchSort::MostRecent)]
+    sort: SearchSort,
+}
+
+#[derive(Args)]
+struct ConferenceArgs {
+    /// Conference number (e.g. C17-07-05), acronym (e.g. EPS-HEP2017) or INSPIRE record ID;
+    /// a series name (e.g. EPS-HEP) with --series
+    conference: String,
+    /// List the conferences of this series instead, newest first
+    #[arg(long)]
+    series: bool,
+    /// Number of contributions (or conferences of the series) fetched
+    #[arg(long, default_value_t = 500)]
+    limit: usize,
+    /// Order of the contributions
+    #[arg(long, value_enum, default_value_t = SearchSort::MostCited)]
+    sort: SearchSort,
+}
+
+#[derive(Args)]
+struct JournalArgs {
+    /// Journal title as INSPIRE abbreviates it (e.g. JHEP, "Phys. Rev. D")
+    title: String,
+    /// Volume (for JHEP and JCAP, the year)
+    #[arg(long)]
+    volume: Option<String>,
+    /// Issue within the volume
+    #[arg(long, requires = "volume")]
+    issue: Option<String>,
+    /// Number of papers fetched (before --categories and --grep are applied)
+    #[arg(long, default_value_t = 250)]
+    limit: usize,
+    /// Order of the papers
+    #[arg(long, value_enum, default_value_t = SearchSort::MostRecent)]
+    sort: SearchSort,
+}
+
+#[derive(Args)]
+struct InitConfigArgs {
+    /// Ask for the default format, output directory, categories and rate limits
+    /// instead of writing the defaults
+    #[arg(long)]
+    interactive: bool,
+}
+
+#[derive(Args)]
+struct SearchArgs {
+    /// INSPIRE search query, e.g. `t "black hole" and a Maldacena`, or free text
+    query: String,
+    /// Number of papers fetched (before --categories and --grep are applied)
+    #[arg(long, default_value_t = 25)]
+    limit: usize,
+    /// Write a table in this format instead of using --format
+    #[arg(long, value_enum)]
+    table: Option<ReportFormat>,
+}
+
+#[derive(Args)]
+struct RecentArgs {
+    /// ArXiv category (e.g. hep-th)
+    #[arg(long)]
+    category: String,
+    /// Number of days to look back
+    #[arg(long, default_value_t = 7)]
+    days: u64,
+    /// Order of the papers
+    #[arg(long, value_enum, default_value_t = SearchSort::MostRecent)]
+    sort: SearchSort,
+    /// Number of papers fetched (before --grep is applied)
+    #[arg(long, default_value_t = 100)]
+    limit: usize,
+    /// Write a table in this format instead of using --format
+    #[arg(long, value_enum)]
+    table: Option<ReportFormat>,
+}
+
+#[derive(Args)]
+struct TopArgs {
+    /// ArXiv category (e.g. hep-ex)
+    #[arg(long)]
+    category: String,
+    /// Only papers from this year
+    #[arg(long)]
+    year: Option<u32>,
+    /// Number of papers fetched (before --grep is applied)
+    #[arg(long, default_value_t = 50)]
+    limit: usize,
+    /// Write a table in this format instead of using --format
+    #[arg(long, value_enum)]
+    table: Option<ReportFormat>,
+}
+
+#[derive(Args)]
+struct TagArgs {
+    #[command(subcommand)]
+    action: TagAction,
+}
+
+#[derive(Subcommand)]
+enum TagAction {
+    /// Add tags to a paper
+    Add(TagEditArgs),
+    /// Remove tags from a paper
+    Remove(TagEditArgs),
+    /// List tagged papers, or the papers carrying one tag
+    List {
+        tag: Option<String>,
+    },
+}
+
+#[derive(Args)]
+struct TagEditArgs {
+    /// ArXiv ID or INSPIRE record ID of the paper
+    paper: String,
+    /// Tags to add or remove
+    #[arg(required = true)]
+    tags: Vec<String>,
+}
+
+#[derive(Args)]
+struct NoteArgs {
+    /// ArXiv ID or INSPIRE record ID of the paper
+    paper: String,
+    /// Text of the note; without it, the paper's notes are shown
+    text: Option<String>,
+}
+
+#[derive(Args)]
+struct ListArgs {
+    #[command(subcommand)]
+    action: ListAction,
+}
+
+#[derive(Subcommand)]
+enum ListAction {
+    /// Put a paper on the reading list (or change its status)
+    Add(ListAddArgs),
+    /// Mark a paper on the reading list as read
+    Done(ListPaperArgs),
+    /// Show the reading list
+    Show(ListShowArgs),
+    /// Write BibTeX for the papers on the reading list
+    Bibtex(ListShowArgs),
+    /// Open a paper's arXiv (or INSPIRE) page in the browser and mark it as being read
+    Open(ListPaperArgs),
+}
+
+#[derive(Args)]
+struct ListAddArgs {
+    /// ArXiv ID or INSPIRE record ID of the paper
+    paper: String,
+    /// Status of the paper
+    #[arg(long, value_enum, default_value_t = ReadingStatus::ToRead)]
+    status: ReadingStatus,
+}
+
+#[derive(Args)]
+struct ListPaperArgs {
+    /// ArXiv ID or INSPIRE record ID of the paper
+    paper: String,
+}
+
+#[derive(Args)]
+struct ListShowArgs {
+    /// Only papers with this status
+    #[arg(long, value_enum)]
+    status: Option<ReadingStatus>,
+}
+
+#[derive(Args)]
+struct HistoryArgs {
+    /// Show only the most recent commands
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+}
+
+#[derive(Args)]
+struct RerunArgs {
+    /// Number of the command, as shown by `history`
+    number: usize,
 }
 
 #[derive(Args)]
 struct NetworkArgs {
+    #[command(subcommand)]
+    action: NetworkAction,
+}
+
+#[derive(Subcommand)]
+enum NetworkAction {
+    /// Build the citation network around a paper
+    Build(NetworkBuildArgs),
+    /// Summary statistics for a saved network
+    Stats(NetworkTableArgs),
+    /// Per-author statistics for a saved network
+    Authors(NetworkTableArgs),
+    /// Papers per institution for a saved network
+    Institutions(NetworkTableArgs),
+    /// Papers per country and shared between countries for a saved network
+    /// (looks up each institution's country on INSPIRE)
+    Countries(NetworkTableArgs),
+    /// Papers per journal for a saved network
+    Journals(NetworkTableArgs),
+    /// Papers per funding agency for a saved network
+    Funding(NetworkTableArgs),
+    /// Convert a saved network to another output format
+    Export(NetworkExportArgs),
+    /// Shortest citation chain between two papers of a saved network
+    Path(NetworkPathArgs),
+    /// Check a saved network for dangling or inconsistent citations
+    Check(NetworkFileArgs),
+    /// Build a network again from the manifest written next to an earlier one
+    Rebuild(NetworkRebuildArgs),
+}
+
+#[derive(Args)]
+struct NetworkBuildArgs {
+    /// ArXiv ID, DOI or INSPIRE record ID of the paper (can also be given
+    /// globally with --arxiv-id or --doi)
+    #[arg(value_name = "ARXIV_ID")]
+    paper: Option<String>,
+    /// Depth of the citation network [default: default_network_depth from the config, or 1]
+    #[arg(long)]
+    depth: Option<u32>,
+    /// Expand only the N most cited references of each paper at the next depth
+    #[arg(long, value_name = "N")]
+    max_per_level: Option<usize>,
+}
+
+#[derive(Args)]
+struct NetworkFileArgs {
+    /// Network JSON file written by `network build --format json`
+    file: PathBuf,
+}
+
+#[derive(Args)]
+struct NetworkExportArgs {
+    /// Network JSON file written by `network build --format json`
+    file: PathBuf,
+    /// Keep only papers at most this many citation steps from the seed paper
+    #[arg(long, value_name = "DEPTH")]
+    export_max_depth: Option<u32>,
+}
+
+#[derive(Args)]
+struct NetworkRebuildArgs {
+    /// Manifest written by `network build --output` (e.g. network.manifest.json)
+    manifest: PathBuf,
+}
+
+#[derive(Args)]
+struct NetworkTableArgs {
+    /// Network JSON file written by `network build --format json`
+    file: PathBuf,
+    /// Table format
+    #[arg(long, value_enum, default_value_t = ReportFormat::Markdown)]
+    table: ReportFormat,
+}
+
+#[derive(Args)]
+struct NetworkPathArgs {
+    /// Network JSON file written by `network build --format json`
+    file: PathBuf,
+    /// Citing paper (INSPIRE record ID or arXiv ID)
+    from: String,
+    /// Cited paper (INSPIRE record ID or arXiv ID)
+    to: String,
+}
+
+#[derive(Args)]
+struct TexscanArgs {
+    /// LaTeX source (.tex) or auxiliary (.aux) file; a .tex file's .aux is used when present
+    path: PathBuf,
+}
+
+#[derive(Args)]
+struct SimilarArgs {
+    /// ArXiv ID of the paper (can also be specified globally)
+    arxiv_id: Option<String>,
+    /// Number of related papers to return
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+    /// Number of the paper's references to examine
+    #[arg(long, default_value_t = 25)]
+    max_references: usize,
+    /// Number of citing papers fetched per reference
+    #[arg(long, default_value_t = 50)]
+    citing_per_reference: usize,
+}
+
+#[derive(Args)]
+struct MapArgs {
     /// ArXiv ID of the paper (can also be specified globally)
     arxiv_id: Option<String>,
-    /// Depth of the citation network
-    #[arg(long, default_value_t = 1)]
-    depth: u32,
-    /// Build the network
+    /// Number of the paper's references to cluster
+    #[arg(long, default_value_t = 50)]
+    max_references: usize,
+    /// Lowest coupling strength (0 to 1) at which references are grouped
+    #[arg(long, default_value_t = 0.1)]
+    threshold: f64,
+    /// Write the map as grouped Markdown or as CSV instead of JSON
+    #[arg(long, value_enum)]
+    table: Option<ReportFormat>,
+}
+
+#[derive(Args)]
+struct SyncArgs {
+    /// Directory whose child directories are LaTeX projects
+    #[arg(long)]
+    workspace: PathBuf,
+    /// Report what would be added without writing any .bib file
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args)]
+struct WatchArgs {
+    /// ArXiv ID of the paper (can also be specified globally)
+    arxiv_id: Option<String>,
+    /// Watch all papers of an author (e.g. "Doe, J.") and produce digests instead
+    #[arg(long, conflicts_with = "arxiv_id")]
+    author: Option<String>,
+    /// How often to produce an author digest
+    #[arg(long, value_enum, requires = "author", default_value_t = DigestPeriod::Weekly)]
+    digest: DigestPeriod,
+    /// Format of author digests
+    #[arg(long, value_enum, requires = "author", default_value_t = DigestFormat::Markdown)]
+    digest_format: DigestFormat,
+    /// Number of the author's most recent papers to watch
+    #[arg(long, requires = "author", default_value_t = 50)]
+    max_papers: usize,
+    /// State file remembering already-reported citations
+    #[arg(long)]
+    state: Option<PathBuf>,
+    /// Number of most recent citing papers to check (per paper)
+    #[arg(long, default_value_t = 100)]
+    limit: usize,
+    /// Keep running, checking again every this many minutes
+    #[arg(long)]
+    interval: Option<u64>,
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+}
+
+#[cfg(unix)]
+#[derive(Args)]
+struct DaemonArgs {
+    /// Socket path [default: reference_tool/daemon.sock in the runtime directory]
+    #[arg(long)]
+    socket: Option<PathBuf>,
+    /// Also serve Prometheus metrics over HTTP at this address
     #[arg(long)]
-    build_network: bool,
+    metrics_listen: Option<SocketAddr>,
 }
 
 #[tokio::main]
@@ -73,6 +736,10 @@ async fn main() -> Result<(), anyhow::Error> {
         eprintln!("Warning: Could not load configuration, using defaults");
         Config::default()
     });
+    let config = match &cli.profile {
+        Some(profile) => config.with_profile(profile)?,
+        None => config,
+    };
     
     // Initialize logger with effective verbosity
     let verbose = config.effective_verbose(cli.verbose);
@@ -86,62 +753,1219 @@ async fn main() -> Result<(), anyhow::Error> {
             .init();
     }
     
-    let client = InspireClient::new();
+    let client = InspireClient::from_config(&config.api)?.with_raw(cli.raw);
+    
+    if cli.stdin_protocol {
+        let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+        return protocol::run(&client, stdin, tokio::io::stdout()).await;
+    }
     
     // Use config defaults for CLI options
-    let format = config.effective_format(Some(cli.format));
+    let format = config.effective_format(cli.format);
     let output_path = cli.output.or_else(|| config.effective_output_dir(None));
-    let output_writer = OutputWriter::new(format, output_path);
+    let writes_to_file = output_path.is_some();
+    let recorded_output = output_path.clone();
+    let bibtex_options = config.effective_bibtex_options(cli.bib_keywords, cli.bib_url, cli.bib_note, cli.bib_pin_versions, cli.bib_month, cli.biblatex);
+    let bibtex_abstracts = format == OutputFormat::Bibtex && bibtex_options.include_abstract;
+    let bibtex_versions = format == OutputFormat::Bibtex && bibtex_options.pin_versions;
+    let output_writer = OutputWriter::new(format, output_path)
+        .with_citation_style(cli.style)
+        .with_bib_sort(config.effective_bib_sort(cli.bib_sort))
+        .with_bibtex_options(bibtex_options)
+        .with_post_output_hook(config.hooks.post_output.clone());
+    let text_filter = cli.grep.as_deref().map(TextFilter::new).transpose()?;
+    let affiliation_filter = cli.affiliation.as_deref().map(TextFilter::new).transpose()?;
+    let identifiers = identifier_filter(cli.include_file.as_deref(), cli.exclude_file.as_deref())?;
+    
+    // The bare form (`reference_tool --arxiv-id X`) is kept as an alias of `refs` for one release
+    let command = cli.command.unwrap_or_else(|| {
+        if cli.arxiv_id.is_some() || cli.doi.is_some() {
+            warn!("Fetching references without a subcommand is deprecated; use `reference_tool refs`");
+        }
+        Commands::Refs(RefsArgs::default())
+    });
+    
+    if !matches!(command, Commands::History(_) | Commands::Rerun(_)) {
+        record_history(&command, cli.arxiv_id.clone().or_else(|| cli.doi.clone()), recorded_output.clone());
+    }
     
-    match cli.command {
-        Some(Commands::Config) => {
-            config.show()?;
+    match command {
+        Commands::Config => {
+            println!("Current configuration:");
+            println!("{}", config.to_toml()?);
+        }
+        Commands::Doctor => {
+            println!("🩺 reference_tool {}", env!("CARGO_PKG_VERSION"));
+            if let Ok(path) = Config::config_file_path() {
+                println!("⚙️  Configuration: {}", path.display());
+            }
+            let checks = client.check_endpoints().await;
+            for check in &checks {
+                match &check.outcome {
+                    Ok(latency) => println!("✅ {} ({} ms)", check.base_url, latency.as_millis()),
+                    Err(error) => println!("❌ {}: {}", check.base_url, error),
+                }
+            }
+            let role = if client.active_base_url() == client.base_urls().next().unwrap_or_default() { "primary" } else { "mirror" };
+            if checks.iter().all(|check| check.outcome.is_err()) {
+                anyhow::bail!("No INSPIRE endpoint answered");
+            }
+            println!("🔗 Using {} ({})", client.active_base_url(), role);
+        }
+        Commands::InitConfig(args) => {
+            let new_config = if args.interactive {
+                // Starting from defaults would overwrite a file that only failed to parse
+                let current = Config::load()
+                    .map_err(|e| anyhow::anyhow!("Could not read the existing configuration ({}); fix or remove it first", e))?;
+                wizard::run(current, std::io::stdin().lock(), std::io::stdout())?
+            } else {
+                Config::default()
+            };
+            let path = new_config.save()?;
+            println!("Configuration saved to: {}", path.display());
+        }
+        Commands::Network(NetworkArgs { action: NetworkAction::Build(args) }) => {
+            let arxiv_id = args.paper.or(cli.arxiv_id).or(cli.doi)
+                .ok_or_else(|| anyhow::anyhow!("ArXiv ID or DOI is required"))?;
+            if text_filter.is_some() {
+                anyhow::bail!("--grep does not apply to `network build`; use `filter --grep` on the built network instead");
+            }
+            
+            let options = NetworkBuildOptions {
+                depth: config.effective_network_depth(args.depth),
+                max_per_level: args.max_per_level,
+                identifiers,
+                cancel: cancel_on_ctrl_c(),
+                observer: Some(network_observer(cli.progress)),
+            };
+            build_network(&client, &arxiv_id, &options, &output_writer, recorded_output.as_deref()).await?;
+        }
+        Commands::Network(NetworkArgs { action: NetworkAction::Rebuild(args) }) => {
+            if text_filter.is_some() {
+                anyhow::bail!("--grep does not apply to `network rebuild`; use `filter --grep` on the rebuilt network instead");
+            }
+            let original = NetworkManifest::load(&args.manifest)?;
+            if original.tool_version != env!("CARGO_PKG_VERSION") {
+                warn!("Network was built with reference_tool {}, rebuilding with {}", original.tool_version, env!("CARGO_PKG_VERSION"));
+            }
+            let options = NetworkBuildOptions {
+                depth: original.depth,
+                max_per_level: original.max_per_level,
+                identifiers,
+                cancel: cancel_on_ctrl_c(),
+                observer: Some(network_observer(cli.progress)),
+            };
+            let rebuilt = build_network(&client, &original.paper, &options, &output_writer, recorded_output.as_deref()).await?;
+            
+            let diff = original.diff(&rebuilt);
+            if diff.is_empty() {
+                println!("♻️  Rebuilt network matches the manifest ({} papers)", rebuilt.records.len());
+            } else {
+                println!(
+                    "♻️  Rebuilt network differs from the manifest: {} records revised since, {} papers no longer reached, {} new",
+                    diff.revised.len(), diff.missing.len(), diff.added.len()
+                );
+                for id in &diff.revised {
+                    println!("  • revised: {}", id);
+                }
+            }
+        }
+        Commands::Network(NetworkArgs { action: NetworkAction::Stats(args) }) => {
+            let network = input::read_network(&args.file).await?;
+            output_writer.write_table(&network.stats_table(), args.table).await?;
+        }
+        Commands::Network(NetworkArgs { action: NetworkAction::Authors(args) }) => {
+            let network = input::read_network(&args.file).await?;
+            output_writer.write_table(&network.author_stats_table(), args.table).await?;
         }
-        Some(Commands::InitConfig) => {
-            let default_config = Config::default();
-            default_config.save()?;
+        Commands::Network(NetworkArgs { action: NetworkAction::Institutions(args) }) => {
+            let network = input::read_network(&args.file).await?;
+            let mut table = network.institution_stats_table();
+            if let Some(filter) = &affiliation_filter {
+                table.rows.retain(|row| filter.matches(&row[0]));
+            }
+            output_writer.write_table(&table, args.table).await?;
+        }
+        Commands::Network(NetworkArgs { action: NetworkAction::Journals(args) }) => {
+            let network = input::read_network(&args.file).await?;
+            output_writer.write_table(&network.journal_stats_table(), args.table).await?;
+        }
+        Commands::Network(NetworkArgs { action: NetworkAction::Funding(args) }) => {
+            let network = input::read_network(&args.file).await?;
+            output_writer.write_table(&network.funding_stats_table(), args.table).await?;
+            let funded = network.papers.values().filter(|paper| !paper.funding.is_empty()).count();
+            println!("💰 {} of {} papers acknowledge funding", funded, network.papers.len());
+        }
+        Commands::Network(NetworkArgs { action: NetworkAction::Countries(args) }) => {
+            let network = input::read_network(&args.file).await?;
+            let (countries, warnings) = commands::institution_countries(&client, network.institutions()).await;
+            report_warnings(&warnings, cli.progress, verbose);
+            output_writer.write_table(&network.country_stats_table(&countries, cli.provenance), args.table).await?;
+            let (known, international) = network.international_share(&countries);
+            println!("🌍 {} of {} papers with a known country have authors in more than one country", international, known);
+        }
+        Commands::Network(NetworkArgs { action: NetworkAction::Export(args) }) => {
+            let mut network = input::read_network(&args.file).await?;
+            if let Some(max_depth) = args.export_max_depth {
+                if network.depths.is_empty() {
+                    anyhow::bail!("{} records no depths; build the network again to trim it by depth", args.file.display());
+                }
+                network = network.trimmed_to_depth(max_depth);
+            }
+            output_writer.write_network(&network).await?;
+        }
+        Commands::Network(NetworkArgs { action: NetworkAction::Path(args) }) => {
+            let network = input::read_network(&args.file).await?;
+            let find = |id: &str| network.find_paper(id)
+                .ok_or_else(|| anyhow::anyhow!("Paper {} is not in the network", id));
+            let (from, to) = (find(&args.from)?, find(&args.to)?);
+            
+            match network.shortest_path(&from.id, &to.id) {
+                Some(path) => {
+                    println!("🔗 {} steps from {} to {}", path.len() - 1, from.title, to.title);
+                    for id in &path {
+                        let title = network.papers.get(id).map_or("", |p| p.title.as_str());
+                        println!("  • [{}] {}", id, title);
+                    }
+                }
+                None => println!("⚠️  {} does not lead to {} through citations in this network", from.title, to.title),
+            }
+        }
+        Commands::Network(NetworkArgs { action: NetworkAction::Check(args) }) => {
+            let network = input::read_network(&args.file).await?;
+            let issues = network.validate();
+            if issues.is_empty() {
+                println!("✅ {} papers, no integrity issues", network.paper_count());
+            } else {
+                for issue in &issues {
+                    println!("⚠️  {}", issue);
+                }
+                return Err(anyhow::anyhow!("Found {} integrity issues in {}", issues.len(), args.file.display()));
+            }
+        }
+        Commands::Texscan(args) => {
+            let keys = texscan::citation_keys(&args.path)?;
+            info!("Found {} citation keys in {}", keys.len(), args.path.display());
+            
+            let (mut references, unresolved) = texscan::resolve_keys(&client, &keys).await;
+            
+            if let Some(filter) = &text_filter {
+                references.retain(|r| filter.matches_reference(r));
+            }
+            
+            write_references(&output_writer, &references, cli.append).await?;
+            println!("✅ Resolved {} citation keys", references.len());
+            if !unresolved.is_empty() {
+                println!("⚠️  Unresolved keys: {}", unresolved.join(", "));
+            }
         }
-        Some(Commands::Network(args)) => {
+        Commands::Similar(args) => {
             let arxiv_id = args.arxiv_id.or(cli.arxiv_id)
                 .ok_or_else(|| anyhow::anyhow!("ArXiv ID is required"))?;
             
-            if !args.build_network {
-                return Err(anyhow::anyhow!("--build-network flag is required for network command"));
+            let options = SimilarityOptions {
+                max_references: args.max_references,
+                citing_per_reference: args.citing_per_reference,
+                limit: args.limit,
+                cancel: cancel_on_ctrl_c(),
+                observer: json_observer(cli.progress),
+            };
+            
+            let (seed, mut similar) = similarity::find_similar(&client, &arxiv_id, &options).await?;
+            println!("📄 Seed paper: {}", seed.title);
+            
+            if let Some(filter) = &text_filter {
+                similar.retain(|s| filter.matches_paper(&s.paper));
             }
             
-            info!("Building citation network for paper: {} with depth: {}", arxiv_id, args.depth);
+            output_writer.write_similar(&similar).await?;
+            println!("✅ Found {} related papers", similar.len());
+        }
+        Commands::Map(args) => {
+            let arxiv_id = args.arxiv_id.or(cli.arxiv_id)
+                .ok_or_else(|| anyhow::anyhow!("ArXiv ID is required"))?;
             
-            let mut network = CitationNetwork::new();
-            network.build(&client, &arxiv_id, args.depth).await?;
+            let options = MapOptions {
+                max_references: args.max_references,
+                threshold: args.threshold,
+                cancel: cancel_on_ctrl_c(),
+                observer: json_observer(cli.progress),
+                ..Default::default()
+            };
             
-            output_writer.write_network(&network).await?;
-            info!("Built network with {} papers", network.paper_count());
+            let map = similarity::map_bibliography(&client, &arxiv_id, &options).await?;
+            println!("📄 Seed paper: {}", map.seed.title);
+            output_writer.write_bibliography_map(&map, args.table).await?;
+            println!("✅ Grouped references into {} clusters ({} unclustered)", map.clusters.len(), map.unclustered.len());
         }
-        None => {
-            // Default behavior: fetch references
-            let arxiv_id = cli.arxiv_id
+        Commands::Sync(args) => {
+            let projects = sync::discover_projects(&args.workspace)?;
+            println!("📂 Found {} LaTeX projects in {}", projects.len(), args.workspace.display());
+            
+            let mut summaries = Vec::new();
+            for project in &projects {
+                match sync::sync_project(&client, project, args.dry_run).await {
+                    Ok(summary) => summaries.push(summary),
+                    Err(e) => warn!("Failed to sync {}: {}", project.dir.display(), e),
+                }
+            }
+            
+            print!("{}", sync::summary_table(&summaries).to_markdown());
+            let added: usize = summaries.iter().map(|s| s.added.len()).sum();
+            if args.dry_run {
+                println!("✅ Dry run: {} entries would be added", added);
+            } else {
+                println!("✅ Added {} entries across {} projects", added, summaries.len());
+            }
+        }
+        Commands::Watch(args) if args.author.is_some() => {
+            let author = args.author.unwrap_or_default();
+            let state_path = match args.state {
+                Some(path) => path,
+                None => digest::default_state_path(&author)?,
+            };
+            
+            loop {
+                let check = match (digest::check_author(&client, &author, &state_path, args.digest, args.max_papers, args.limit).await, args.interval) {
+                    (Ok(check), _) => check,
+                    (Err(e), Some(minutes)) => {
+                        warn!("Check of {} failed, retrying in {} minutes: {}", author, minutes, e);
+                        tokio::time::sleep(std::time::Duration::from_secs(minutes * 60)).await;
+                        continue;
+                    }
+                    (Err(e), None) => return Err(e),
+                };
+                
+                if check.first_run {
+                    println!("👀 Now watching {} papers by {} (state in {})", check.papers, author, state_path.display());
+                } else {
+                    println!("📄 {}: {} new citations across {} papers", author, check.new_citations, check.papers);
+                }
+                if let Some(mut digest) = check.digest {
+                    digest.items.retain(|item| identifiers.allows_paper(&item.citing));
+                    let rendered = digest.render(args.digest_format);
+                    match &recorded_output {
+                        Some(path) => {
+                            tokio::fs::write(path, rendered).await?;
+                            println!("📬 Wrote digest of {} citations to {}", digest.items.len(), path.display());
+                        }
+                        None => print!("{}", rendered),
+                    }
+                }
+                
+                match args.interval {
+                    Some(minutes) => tokio::time::sleep(std::time::Duration::from_secs(minutes * 60)).await,
+                    None => break,
+                }
+            }
+        }
+        Commands::Watch(args) => {
+            let arxiv_id = args.arxiv_id.or(cli.arxiv_id)
                 .ok_or_else(|| anyhow::anyhow!("ArXiv ID is required"))?;
+            let state_path = match args.state {
+                Some(path) => path,
+                None => watch::default_state_path(&arxiv_id)?,
+            };
             
-            info!("Fetching references for paper: {}", arxiv_id);
+            loop {
+                let mut result = match (watch::check(&client, &arxiv_id, &state_path, args.limit).await, args.interval) {
+                    (Ok(result), _) => result,
+                    (Err(e), Some(minutes)) => {
+                        warn!("Check of {} failed, retrying in {} minutes: {}", arxiv_id, minutes, e);
+                        tokio::time::sleep(std::time::Duration::from_secs(minutes * 60)).await;
+                        continue;
+                    }
+                    (Err(e), None) => return Err(e),
+                };
+                result.new_citations.retain(|paper| identifiers.allows_paper(paper));
+                
+                if result.first_run {
+                    println!("👀 Now watching: {} (state in {})", result.paper.title, state_path.display());
+                } else {
+                    if result.paper_changed {
+                        println!("✏️  {}: record updated on INSPIRE ({})", result.paper.title,
+                            result.paper.last_updated.as_deref().unwrap_or_default());
+                    }
+                    println!("📄 {}: {} new citations", result.paper.title, result.new_citations.len());
+                    for citing in &result.new_citations {
+                        println!("  • {}", citing.title);
+                    }
+                    if writes_to_file && !result.new_citations.is_empty() {
+                        let references: Vec<Reference> = result.new_citations.iter()
+                            .cloned()
+                            .map(Reference::from)
+                            .collect();
+                        output_writer.write_references(&references).await?;
+                    }
+                    if let Err(e) = notify::notify_new_citations(&config.notifications, &result.paper, &result.new_citations).await {
+                        warn!("Some notifications failed: {}", e);
+                    }
+                }
+                
+                match args.interval {
+                    Some(minutes) => tokio::time::sleep(std::time::Duration::from_secs(minutes * 60)).await,
+                    None => break,
+                }
+            }
+        }
+        Commands::Serve(args) => {
+            println!("🌐 Serving on http://{}", args.listen);
+            server::serve(Arc::new(client), args.listen).await?;
+        }
+        #[cfg(unix)]
+        Commands::Daemon(args) => {
+            let socket_path = match args.socket {
+                Some(path) => path,
+                None => daemon::default_socket_path()?,
+            };
+            if let Some(addr) = args.metrics_listen {
+                println!("📈 Metrics on http://{}/metrics", addr);
+                tokio::spawn(async move {
+                    if let Err(e) = server::serve_metrics(addr).await {
+                        warn!("Metrics endpoint failed: {}", e);
+                    }
+                });
+            }
+            println!("🔌 Daemon listening on {}", socket_path.display());
+            daemon::run(Arc::new(client), &socket_path).await?;
+        }
+        Commands::Filter(args) => {
+            let references = input::read_references(&args.file).await?;
+            let total = references.len();
+            let options = ReferenceOptions {
+                categories: config.effective_categories(cli.categories),
+                filter: text_filter,
+                affiliation: affiliation_filter,
+                identifiers,
+                ..Default::default()
+            };
+            let references = commands::filter_references(references, &options);
+            let references = annotate(references, cli.tag.as_deref())?;
+            output_writer.write_references(&references).await?;
+            println!("✅ Kept {} of {} references", references.len(), total);
+        }
+        Commands::CheckUpdates(args) => {
+            let saved = input::read_bibliography(&args.file).await?;
+            println!("📚 Checking {} entries of {} against INSPIRE", saved.len(), args.file.display());
             
-            let paper = client.get_paper_by_arxiv(&arxiv_id).await?;
-            println!("📄 Found paper: {}", paper.title);
+            let report = updates::check_updates(&client, &ArxivClient::new(), &saved).await;
+            for update in &report.updates {
+                let icon = if update.current.retracted { "🚫" } else { "✏️ " };
+                println!("{} {}", icon, update.key);
+                for change in &update.changes {
+                    println!("  • {}", change);
+                }
+            }
+            if !report.unresolved.is_empty() {
+                println!("❓ Not found on INSPIRE: {}", report.unresolved.join(", "));
+            }
             
-            let references = client.get_paper_references(&paper.id).await?;
+            // Only an explicit --output gets the current entries; the input file is never touched
+            if writes_to_file && !report.updates.is_empty() {
+                let current: Vec<Reference> = report.updates.iter().map(|update| update.current.clone()).collect();
+                output_writer.write_references(&current).await?;
+            }
+            println!("✅ {} changed, {} unchanged", report.updates.len(), report.unchanged);
+        }
+        Commands::VerifyPdf(args) => {
+            let bibliography = input::read_bibliography(&args.bib).await?;
+            println!("📄 Checking the bibliography of {} against {} entries of {}", args.pdf.display(), bibliography.len(), args.bib.display());
             
-            let filtered_refs = if let Some(categories) = config.effective_categories(cli.categories) {
-                references.into_iter()
-                    .filter(|r| r.categories.iter().any(|c| categories.contains(c)))
-                    .collect()
+            let report = pdfcheck::verify_pdf(&client, &args.pdf, &bibliography, args.offline).await?;
+            for missing in &report.missing {
+                println!("❌ [{}] not in the .bib file: {}", missing.entry.label, missing.entry.text);
+                if let Some(reference) = &missing.inspire {
+                    println!("  • on INSPIRE: {} ({})", reference.title, reference.texkey.as_deref().unwrap_or_default());
+                }
+            }
+            for mismatch in &report.mismatched {
+                println!("⚠️  [{}] {}: {}", mismatch.label, mismatch.key, mismatch.reason);
+            }
+            if !report.uncited.is_empty() {
+                println!("❓ In the .bib file but not in the PDF: {}", report.uncited.join(", "));
+            }
+            if writes_to_file {
+                output_writer.write_pdf_report(&report).await?;
+            }
+            println!(
+                "✅ {} entries: {} matched, {} missing, {} mismatched",
+                report.entries, report.matched, report.missing.len(), report.mismatched.len()
+            );
+        }
+        Commands::GroupBib(args) => {
+            let content = tokio::fs::read_to_string(&args.authors).await?;
+            let authors = group::parse_author_list(&content);
+            println!("👥 Fetching papers of {} group members", authors.len());
+            
+            let bibliography = group::build_group_bibliography(&client, &authors, args.since, args.max_per_author).await;
+            for (author, count) in &bibliography.per_author {
+                println!("  • {}: {} papers", author, count);
+            }
+            if !bibliography.failed.is_empty() {
+                println!("❓ Could not fetch: {}", bibliography.failed.join(", "));
+            }
+            
+            let references = annotate(bibliography.references, cli.tag.as_deref())?;
+            output_writer.write_references(&references).await?;
+            println!("✅ {} distinct papers", references.len());
+        }
+        Commands::Author(args) if args.metrics => {
+            let metrics = author::author_metrics(&client, &args.author, args.max_papers, args.top).await?;
+            println!("📊 {}: {} papers, {} citations, h-index {}", metrics.author, metrics.papers, metrics.total_citations, metrics.h_index);
+            output_writer.write_author_metrics(&metrics, args.table).await?;
+        }
+        Commands::Author(args) if cli.stream => {
+            let query = LiteratureQuery::new().with_author(&args.author);
+            let options = ReferenceOptions::default();
+            let pages = commands::search_references(&client, &query, args.max_papers, SearchSort::MostRecent, &options);
+            let written = stream_search_results(&output_writer, pages, cli.tag.as_deref()).await?;
+            println!("✅ Found {} papers by {}", written, args.author);
+        }
+        Commands::Author(args) => {
+            let papers = client.get_author_papers(&args.author, None, args.max_papers).await?;
+            let references: Vec<Reference> = papers.into_iter().map(Reference::from).collect();
+            let references = annotate(references, cli.tag.as_deref())?;
+            output_writer.write_references(&references).await?;
+            println!("✅ Found {} papers by {}", references.len(), args.author);
+        }
+        Commands::Institution(args) => {
+            let institution = client.get_institution(&args.name).await?;
+            let location: Vec<&str> = institution.address.iter()
+                .flat_map(|address| [address.city.as_deref(), address.country_code.as_deref()])
+                .flatten()
+                .collect();
+            println!("🏛️  {} [{}] {}", institution.full_name.as_deref().unwrap_or(&institution.name), institution.name, location.join(", "));
+            if let Some(count) = institution.paper_count {
+                println!("📄 {} papers on INSPIRE", count);
+            }
+            output_writer.write_institution(&institution).await?;
+        }
+        Commands::Experiment(args) => {
+            let query = if args.collaboration {
+                LiteratureQuery::new().with_collaboration(&args.name)
+            } else {
+                LiteratureQuery::new().with_experiment(&args.name)
+            };
+            let query = query.with_min_year(args.since);
+            let options = ReferenceOptions {
+                categories: config.effective_categories(cli.categories),
+                filter: text_filter,
+                affiliation: affiliation_filter,
+                identifiers,
+                ..Default::default()
+            };
+            let found = if cli.stream {
+                let pages = commands::search_references(&client, &query, args.limit, args.sort, &options);
+                stream_search_results(&output_writer, pages, cli.tag.as_deref()).await?
+            } else {
+                let (papers, warnings) = client.search_literature_with_warnings(&query, args.limit, args.sort).await?;
+                report_warnings(&warnings, cli.progress, verbose);
+                let references = search_results(papers, &options, cli.tag.as_deref())?;
+                output_writer.write_references(&references).await?;
+                references.len()
+            };
+            println!("✅ Found {} papers of {}", found, args.name);
+        }
+        Commands::Conference(args) if args.series => {
+            let conferences = client.get_conference_series(&args.conference, args.limit).await?;
+            output_writer.write_conferences(&conferences).await?;
+            println!("✅ Found {} conferences of the {} series", conferences.len(), args.conference);
+        }
+        Commands::Conference(args) => {
+            let conference = client.get_conference(&args.conference).await?;
+            let cnum = conference.cnum.as_deref()
+                .ok_or_else(|| anyhow::anyhow!("Conference {} has no conference number to find contributions by", conference.id))?;
+            println!("🎤 {} ({})", conference.title, cnum);
+            let query = LiteratureQuery::new().with_conference(cnum);
+            let options = ReferenceOptions {
+                categories: config.effective_categories(cli.categories),
+                filter: text_filter,
+                affiliation: affiliation_filter,
+                identifiers,
+                ..Default::default()
+            };
+            let found = if cli.stream {
+                let pages = commands::search_references(&client, &query, args.limit, args.sort, &options);
+                stream_search_results(&output_writer, pages, cli.tag.as_deref()).await?
+            } else {
+                let (papers, warnings) = client.search_literature_with_warnings(&query, args.limit, args.sort).await?;
+                report_warnings(&warnings, cli.progress, verbose);
+                let references = search_results(papers, &options, cli.tag.as_deref())?;
+                write_references(&output_writer, &references, cli.append).await?;
+                references.len()
+            };
+            println!("✅ Found {} contributions to {}", found, conference.acronyms.first().unwrap_or(&conference.title));
+        }
+        Commands::Journal(args) => {
+            let mut query = LiteratureQuery::new().with_journal(&args.title, args.volume.as_deref());
+            if let Some(issue) = &args.issue {
+                query = query.with_journal_issue(issue);
+            }
+            let options = ReferenceOptions {
+                categories: config.effective_categories(cli.categories),
+                filter: text_filter,
+                affiliation: affiliation_filter,
+                identifiers,
+                ..Default::default()
+            };
+            let found = if cli.stream {
+                let pages = commands::search_references(&client, &query, args.limit, args.sort, &options);
+                stream_search_results(&output_writer, pages, cli.tag.as_deref()).await?
+            } else {
+                let (papers, warnings) = client.search_literature_with_warnings(&query, args.limit, args.sort).await?;
+                report_warnings(&warnings, cli.progress, verbose);
+                let references = search_results(papers, &options, cli.tag.as_deref())?;
+                output_writer.write_references(&references).await?;
+                references.len()
+            };
+            let issue: Vec<&str> = [Some(args.title.as_str()), args.volume.as_deref(), args.issue.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect();
+            println!("✅ Found {} papers in {}", found, issue.join(" "));
+        }
+        Commands::Search(args) => {
+            let papers = client.search(&args.query, args.limit).await?;
+            let options = ReferenceOptions {
+                categories: config.effective_categories(cli.categories),
+                filter: text_filter,
+                affiliation: affiliation_filter,
+                identifiers,
+                ..Default::default()
+            };
+            let references = search_results(papers, &options, cli.tag.as_deref())?;
+            match args.table {
+                Some(format) => output_writer.write_table(&commands::reference_table(&references), format).await?,
+                None => write_references(&output_writer, &references, cli.append).await?,
+            }
+            println!("✅ Found {} papers matching {}", references.len(), args.query);
+        }
+        Commands::Recent(args) => {
+            let now = atom::parse_rfc3339(&atom::now_rfc3339()).unwrap_or(0);
+            let since = atom::format_rfc3339(now.saturating_sub(args.days * 86_400));
+            let query = LiteratureQuery::new()
+                .with_category(&args.category)
+                .with_min_date(&since[..10]);
+            let (papers, warnings) = client.search_literature_with_warnings(&query, args.limit, args.sort).await?;
+            report_warnings(&warnings, cli.progress, verbose);
+            let options = ReferenceOptions { filter: text_filter, affiliation: affiliation_filter, identifiers, ..Default::default() };
+            let references = search_results(papers, &options, cli.tag.as_deref())?;
+            match args.table {
+                Some(format) => output_writer.write_table(&commands::reference_table(&references), format).await?,
+                None => output_writer.write_references(&references).await?,
+            }
+            println!("✅ Found {} papers in {} since {}", references.len(), args.category, &since[..10]);
+        }
+        Commands::Top(args) => {
+            let mut query = LiteratureQuery::new().with_category(&args.category);
+            if let Some(year) = args.year {
+                query = query.with_year(year);
+            }
+            let (papers, warnings) = client.search_literature_with_warnings(&query, args.limit, SearchSort::MostCited).await?;
+            report_warnings(&warnings, cli.progress, verbose);
+            let options = ReferenceOptions { filter: text_filter, affiliation: affiliation_filter, identifiers, ..Default::default() };
+            let references = search_results(papers, &options, cli.tag.as_deref())?;
+            match args.table {
+                Some(format) => output_writer.write_table(&commands::reference_table(&references), format).await?,
+                None => output_writer.write_references(&references).await?,
+            }
+            println!("✅ Found the {} most cited papers in {}", references.len(), args.category);
+        }
+        Commands::Tag(TagArgs { action }) => {
+            let path = library::default_library_path()?;
+            let mut library = Library::load(&path)?;
+            match action {
+                TagAction::Add(args) => {
+                    for tag in &args.tags {
+                        library.add_tag(&args.paper, tag);
+                    }
+                    library.save(&path)?;
+                    println!("🏷️  Tagged {}: {}", args.paper, args.tags.join(", "));
+                }
+                TagAction::Remove(args) => {
+                    let removed = args.tags.iter().filter(|tag| library.remove_tag(&args.paper, tag)).count();
+                    library.save(&path)?;
+                    println!("🏷️  Removed {} tags from {}", removed, args.paper);
+                }
+                TagAction::List { tag: Some(tag) } => {
+                    for paper in library.tagged(&tag) {
+                        println!("{}", paper);
+                    }
+                }
+                TagAction::List { tag: None } => {
+                    for (paper, entry) in &library.papers {
+                        if !entry.tags.is_empty() {
+                            println!("{}: {}", paper, entry.tags.iter().cloned().collect::<Vec<_>>().join(", "));
+                        }
+                    }
+                }
+            }
+        }
+        Commands::Note(args) => {
+            let path = library::default_library_path()?;
+            let mut library = Library::load(&path)?;
+            match args.text {
+                Some(text) => {
+                    library.add_note(&args.paper, &text);
+                    library.save(&path)?;
+                    println!("📝 Note added to {}", args.paper);
+                }
+                None => {
+                    for note in library.entry(&args.paper).map(|entry| entry.notes.as_slice()).unwrap_or_default() {
+                        println!("📝 {}", note);
+                    }
+                }
+            }
+        }
+        Commands::List(ListArgs { action }) => {
+            let path = library::default_library_path()?;
+            let mut library = Library::load(&path)?;
+            match action {
+                ListAction::Add(args) => {
+                    let title = match commands::fetch_paper(&client, &args.paper).await {
+                        Ok(paper) => Some(paper.title),
+                        Err(e) => {
+                            warn!("Could not look up {}: {}", args.paper, e);
+                            None
+                        }
+                    };
+                    library.queue(&args.paper, title, args.status);
+                    library.save(&path)?;
+                    println!("📚 Added {} to the reading list ({})", args.paper, args.status);
+                }
+                ListAction::Done(args) => {
+                    if !library.set_status(&args.paper, ReadingStatus::Read) {
+                        return Err(anyhow::anyhow!("{} is not on the reading list", args.paper));
+                    }
+                    library.save(&path)?;
+                    println!("✅ Marked {} as read", args.paper);
+                }
+                ListAction::Show(args) => {
+                    let list = library.reading_list(args.status);
+                    println!("📚 Reading list ({} papers)", list.len());
+                    for (paper, entry) in list {
+                        let status = entry.status.map(|s| s.to_string()).unwrap_or_default();
+                        match &entry.title {
+                            Some(title) => println!("  [{}] {}  {}", status, paper, title),
+                            None => println!("  [{}] {}", status, paper),
+                        }
+                    }
+                }
+                ListAction::Bibtex(args) => {
+                    let mut references = Vec::new();
+                    for (paper, _) in library.reading_list(args.status) {
+                        match commands::fetch_paper(&client, paper).await {
+                            Ok(record) => references.push(Reference::from(record)),
+                            Err(e) => warn!("Could not look up {}: {}", paper, e),
+                        }
+                    }
+                    library.annotate(&mut references);
+                    output_writer.with_format(OutputFormat::Bibtex).write_references(&references).await?;
+                    if writes_to_file {
+                        println!("✅ Wrote BibTeX for {} papers", references.len());
+                    }
+                }
+                ListAction::Open(args) => {
+                    let url = paper_url(&args.paper);
+                    open_in_browser(&url)?;
+                    if library.entry(&args.paper).is_some_and(|entry| entry.status == Some(ReadingStatus::ToRead)) {
+                        library.set_status(&args.paper, ReadingStatus::Reading);
+                        library.save(&path)?;
+                    }
+                    println!("🌐 Opened {}", url);
+                }
+            }
+        }
+        Commands::History(args) => {
+            let entries = history::load(&history::default_history_path()?)?;
+            let skip = entries.len().saturating_sub(args.limit);
+            for (number, entry) in entries.iter().enumerate().skip(skip) {
+                let output = entry.output.as_ref()
+                    .map(|path| format!("  → {}", path.display()))
+                    .unwrap_or_default();
+                println!("{:>4}  {}  {}{}", number + 1, entry.timestamp, entry.command_line(), output);
+            }
+        }
+        Commands::Rerun(args) => {
+            let entries = history::load(&history::default_history_path()?)?;
+            let entry = args.number.checked_sub(1)
+                .and_then(|index| entries.get(index))
+                .ok_or_else(|| anyhow::anyhow!("No command {} in the history", args.number))?;
+            println!("🔁 {}", entry.command_line());
+            let mut rerun = std::process::Command::new(std::env::current_exe()?);
+            rerun.args(&entry.args);
+            if let Some(directory) = &entry.directory {
+                rerun.current_dir(directory);
+            }
+            let status = rerun.status()?;
+            if !status.success() {
+                return Err(anyhow::anyhow!("Command {} failed ({})", args.number, status));
+            }
+        }
+        Commands::Refs(args) => {
+            let arxiv_id = args.paper.or(cli.arxiv_id).or(cli.doi)
+                .ok_or_else(|| anyhow::anyhow!("ArXiv ID or DOI is required"))?;
+            let options = ReferenceOptions {
+                categories: config.effective_categories(cli.categories),
+                filter: text_filter,
+                affiliation: affiliation_filter,
+                identifiers,
+                sort: args.sort,
+                limit: args.limit,
+                with_abstract: args.with_abstract || bibtex_abstracts,
+            };
+            
+            if args.citations {
+                let paper = commands::fetch_paper(&client, &arxiv_id).await?;
+                println!("📄 Found paper: {}", paper.title);
+                let citing = client.get_paper_citations(&paper.id).await?;
+                let total = citing.len();
+                let mut references = search_results(citing, &options, cli.tag.as_deref())?;
+                if let Some(limit) = args.limit {
+                    references.truncate(limit);
+                }
+                if cli.provenance {
+                    references.iter_mut().for_each(|r| r.mark_sources(DataSource::InspireRecord));
+                }
+                write_references(&output_writer, &references, cli.append).await?;
+                println!("✅ Found {} citing papers, listed {}", total, references.len());
+                return Ok(());
+            }
+            
+            // The daemon's client does not keep raw metadata
+            let FetchResult { paper, mut references, warnings } = if cli.raw {
+                commands::fetch_references(&client, &arxiv_id, &options).await?
             } else {
-                references
+                fetch_references(&client, &arxiv_id, &options).await?
             };
+            println!("📄 Found paper: {}", paper.title);
+            report_warnings(&warnings, cli.progress, verbose);
+            if let (true, Some(updated)) = (verbose, &paper.last_updated) {
+                println!("🕒 Last updated on INSPIRE: {}", updated);
+            }
             
-            output_writer.write_references(&filtered_refs).await?;
-            println!("✅ Successfully processed {} references", filtered_refs.len());
-            info!("Found {} references", filtered_refs.len());
+            if cli.with_datasets {
+                let listings = commands::dataset_listings(&client, &paper, &references, json_observer(cli.progress).as_deref()).await;
+                output_writer.write_datasets(&listings).await?;
+                println!("✅ Found datasets for {} papers", listings.len());
+                return Ok(());
+            }
+            
+            if args.aging {
+                let paper_year = paper.year
+                    .ok_or_else(|| anyhow::anyhow!("{} has no publication year to measure reference ages from", arxiv_id))?;
+                let aging = ReferenceAging::compute(paper_year, &references, args.recent_years);
+                if let Some(median) = aging.median_age {
+                    println!("⏳ Median reference age: {:.1} years", median);
+                }
+                output_writer.write_reference_aging(&aging, args.table).await?;
+                println!("✅ Measured the ages of {} references", references.len() - aging.undated);
+                return Ok(());
+            }
+            
+            if args.journals {
+                let stats = journals::journal_stats(references.iter().map(|r| r.publication_info.as_ref()));
+                output_writer.write_journal_stats(&stats, args.table).await?;
+                println!("✅ Counted {} references in {} journals", references.len(), stats.iter().filter(|s| s.journal != journals::NO_JOURNAL).count());
+                return Ok(());
+            }
+            
+            if args.contexts {
+                match SemanticScholarClient::new().get_citation_contexts(&arxiv_id).await {
+                    Ok(contexts) => {
+                        let attached = semantic_scholar::attach_contexts(&mut references, &contexts);
+                        println!("💬 Found citation contexts for {} references", attached);
+                    }
+                    Err(e) => warn!("Could not fetch citation contexts from Semantic Scholar: {}", e),
+                }
+            }
+            
+            if args.check_retractions {
+                let retracted = commands::flag_retractions(&client, &CrossrefClient::new(), &mut references).await;
+                for reference in references.iter().filter(|r| r.retracted) {
+                    println!("🚫 RETRACTED: {}", reference.title);
+                }
+                println!("🔎 Checked {} references for retractions, {} retracted", references.len(), retracted);
+            }
+            
+            if args.open_access || args.oa_only {
+                let unpaywall = args.unpaywall_email.as_deref()
+                    .or(config.unpaywall_email.as_deref())
+                    .map(UnpaywallClient::new);
+                let summary = commands::annotate_open_access(unpaywall.as_ref(), &mut references).await;
+                let by_source: Vec<String> = summary.open_by_source.iter()
+                    .map(|(source, count)| format!("{} {}", count, source.as_str()))
+                    .collect();
+                println!("📖 Open access: {} of {} ({}), {} unknown", summary.open, summary.references, by_source.join(", "), summary.unknown);
+                if args.oa_only {
+                    references.retain(|r| r.open_access.as_ref().is_some_and(|status| status.is_open));
+                }
+            }
+            
+            if bibtex_versions {
+                match ArxivClient::new().pin_versions(&mut references).await {
+                    Ok(pinned) => println!("📌 Pinned {} references to their current arXiv version", pinned),
+                    Err(e) => warn!("Could not look up arXiv versions: {}", e),
+                }
+            }
+            
+            for reference in &mut references {
+                if cli.provenance {
+                    reference.mark_sources(DataSource::InspireReference);
+                } else {
+                    reference.sources = FieldSources::default();
+                }
+            }
+            
+            let references = annotate(references, cli.tag.as_deref())?;
+            if let Some(path) = &args.group_by_section {
+                let groups = section_groups(&client, path, &references).await?;
+                output_writer.write_sections(&groups).await?;
+                println!("✅ Grouped {} references into {} sections", references.len(), groups.len());
+            } else {
+                write_references(&output_writer, &references, cli.append).await?;
+                println!("✅ Successfully processed {} references", references.len());
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// Write references, or with `--append` add them to the existing .bib file and
+/// report skipped duplicates and renamed keys
+async fn write_references(output_writer: &OutputWriter, references: &[Reference], append: bool) -> anyhow::Result<()> {
+    if !append {
+        return output_writer.write_references(references).await;
+    }
+    
+    let merge = output_writer.append_references(references).await?;
+    if !merge.duplicates.is_empty() {
+        println!("⏭️  Already in the bibliography: {}", merge.duplicates.join(", "));
+    }
+    for rename in &merge.renamed {
+        println!("🔀 {} → {} (key taken by a different entry)", rename.original, rename.renamed);
+    }
+    println!("📎 Appended {} entries", merge.added.len());
+    Ok(())
+}
+
+/// Progress display for long-running commands, on stderr so piped output stays clean
+struct CliProgress;
+
+impl ProgressObserver for CliProgress {
+    fn on_progress(&self, progress: &Progress) {
+        eprintln!(
+            "🔍 Processing depth {} ({} papers found, {} requests)",
+            progress.current_depth, progress.papers_discovered, progress.requests_completed
+        );
+    }
+    
+    fn on_finish(&self, progress: &Progress) {
+        eprintln!("✅ Network build complete! {} papers found", progress.papers_discovered);
+    }
+}
+
+/// Progress as NDJSON events on stderr, for wrappers rendering their own display (`--progress json`)
+struct JsonProgress;
+
+impl JsonProgress {
+    fn emit(progress: &Progress, event: &'static str) {
+        if let Ok(line) = serde_json::to_string(&progress.to_event(event)) {
+            eprintln!("{}", line);
+        }
+    }
+}
+
+impl ProgressObserver for JsonProgress {
+    fn on_progress(&self, progress: &Progress) {
+        Self::emit(progress, "progress");
+    }
+    
+    fn on_finish(&self, progress: &Progress) {
+        Self::emit(progress, "finish");
+    }
+}
+
+/// Observer for network builds, which show status lines unless `--progress json` is given
+fn network_observer(style: ProgressStyle) -> SharedObserver {
+    match style {
+        ProgressStyle::Human => Arc::new(CliProgress),
+        ProgressStyle::Json => Arc::new(JsonProgress),
+    }
+}
+
+/// Observer for operations that only report progress with `--progress json`
+fn json_observer(style: ProgressStyle) -> Option<SharedObserver> {
+    match style {
+        ProgressStyle::Json => Some(Arc::new(JsonProgress)),
+        ProgressStyle::Human => None,
+    }
+}
+
+/// Warnings listed one by one without `--verbose`; more are summarized per kind
+const MAX_LISTED_WARNINGS: usize = 5;
+
+/// Show the soft issues of a result: as `warning` events on stderr with
+/// `--progress json`, otherwise one line each (or a count per kind when there
+/// are many and `--verbose` is not given)
+fn report_warnings(warnings: &Warnings, style: ProgressStyle, verbose: bool) {
+    match style {
+        ProgressStyle::Json => {
+            for warning in warnings {
+                let event = serde_json::json!({
+                    "event": "warning",
+                    "kind": warning.kind,
+                    "message": warning.message,
+                    "item": warning.item,
+                });
+                eprintln!("{}", event);
+            }
         }
+        ProgressStyle::Human if verbose || warnings.len() <= MAX_LISTED_WARNINGS => {
+            for warning in warnings {
+                println!("⚠️  {}", warning);
+            }
+        }
+        ProgressStyle::Human => {
+            let kinds = [
+                (WarningKind::UnparsedReference, "references without title or identifier"),
+                (WarningKind::MissingYear, "references without a year"),
+                (WarningKind::TruncatedResults, "truncated searches"),
+                (WarningKind::LookupFailed, "failed record lookups"),
+            ];
+            for (kind, description) in kinds {
+                let count = warnings.count(kind);
+                if count > 0 {
+                    println!("⚠️  {} {} (run with --verbose to list them)", count, description);
+                }
+            }
+        }
+    }
+}
+
+/// Token cancelled on Ctrl-C, so long-running commands can stop and still write partial results
+fn cancel_on_ctrl_c() -> CancellationToken {
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            warn!("Interrupted, finishing with partial results");
+            cancel.cancel();
+        }
+    });
+    token
+}
+
+/// Build a network, write it, and write its manifest next to the output file
+async fn build_network(
+    client: &InspireClient,
+    arxiv_id: &str,
+    options: &NetworkBuildOptions,
+    output_writer: &OutputWriter,
+    output_path: Option<&std::path::Path>,
+) -> anyhow::Result<NetworkManifest> {
+    let started = atom::now_rfc3339();
+    let (network, status) = match commands::build_network(client, arxiv_id, options).await {
+        Ok(built) => built,
+        Err(error) => {
+            // Keep the papers already fetched, marked with the error, before failing
+            let partial = error.downcast::<PartialBuildError>()?;
+            output_writer.write_network(&partial.network).await?;
+            println!("⚠️  Build failed; wrote the {} papers found so far (marked with `build_error`)", partial.network.paper_count());
+            return Err(partial.source);
+        }
+    };
+    output_writer.write_network(&network).await?;
+    if status == BuildStatus::Cancelled {
+        println!("⚠️  Build interrupted; wrote the {} papers found so far", network.paper_count());
+    }
+    if !network.failed_records.is_empty() {
+        let ids: Vec<&str> = network.failed_records.keys().map(String::as_str).collect();
+        println!("⚠️  References of {} records could not be fetched: {}", ids.len(), ids.join(", "));
     }
     
+    let manifest = NetworkManifest::record(client, arxiv_id, options.depth, options.max_per_level, started, &network, status).await;
+    if let Some(output_path) = output_path {
+        let manifest_path = NetworkManifest::path_for(output_path);
+        manifest.save(&manifest_path)?;
+        info!("Manifest written to: {}", manifest_path.display());
+    }
+    Ok(manifest)
+}
+
+/// Record a command in the history file; failures only warn, never stop the command
+fn record_history(command: &Commands, global_arxiv_id: Option<String>, output: Option<PathBuf>) {
+    let file = |path: &PathBuf| Some(path.display().to_string());
+    let identifier = match command {
+        Commands::Refs(args) => args.paper.clone(),
+        Commands::Network(NetworkArgs { action }) => match action {
+            NetworkAction::Build(args) => args.paper.clone(),
+            NetworkAction::Stats(args) | NetworkAction::Authors(args) | NetworkAction::Institutions(args) | NetworkAction::Countries(args)
+                | NetworkAction::Journals(args) | NetworkAction::Funding(args) => file(&args.file),
+            NetworkAction::Export(args) => file(&args.file),
+            NetworkAction::Check(args) => file(&args.file),
+            NetworkAction::Path(args) => file(&args.file),
+            NetworkAction::Rebuild(args) => file(&args.manifest),
+        },
+        Commands::Texscan(args) => file(&args.path),
+        Commands::Similar(args) => args.arxiv_id.clone(),
+        Commands::Map(args) => args.arxiv_id.clone(),
+        Commands::Watch(args) => args.arxiv_id.clone().or_else(|| args.author.clone()),
+        Commands::Filter(args) => file(&args.file),
+        Commands::CheckUpdates(args) => file(&args.file),
+        Commands::VerifyPdf(args) => file(&args.pdf),
+        Commands::GroupBib(args) => file(&args.authors),
+        Commands::Author(args) => Some(args.author.clone()),
+        Commands::Institution(args) => Some(args.name.clone()),
+        Commands::Conference(args) => Some(args.conference.clone()),
+        Commands::Experiment(args) => Some(args.name.clone()),
+        Commands::Journal(args) => Some(args.title.clone()),
+        Commands::Recent(args) => Some(args.category.clone()),
+        Commands::Top(args) => Some(args.category.clone()),
+        _ => None,
+    }.or(global_arxiv_id);
+    
+    let entry = HistoryEntry {
+        timestamp: atom::now_rfc3339(),
+        args: std::env::args().skip(1).collect(),
+        directory: std::env::current_dir().ok(),
+        identifier,
+        output,
+    };
+    if let Err(e) = history::default_history_path().and_then(|path| history::append(&path, &entry)) {
+        warn!("Could not record command history: {}", e);
+    }
+}
+
+/// Landing page of a paper: INSPIRE for record IDs, arXiv otherwise
+fn paper_url(paper: &str) -> String {
+    if paper.chars().all(|c| c.is_ascii_digit()) {
+        format!("https://inspirehep.net/literature/{}", paper)
+    } else {
+        format!("https://arxiv.org/abs/{}", paper.trim_start_matches("arXiv:"))
+    }
+}
+
+/// Open a URL with the platform's default handler
+fn open_in_browser(url: &str) -> anyhow::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    let status = command.arg(url).status()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("Could not open {} ({})", url, status));
+    }
     Ok(())
 }
+
+/// Add the library's tags and notes to references, keeping only those tagged `tag` if given
+fn annotate(references: Vec<Reference>, tag: Option<&str>) -> anyhow::Result<Vec<Reference>> {
+    Ok(annotate_with(&load_library()?, references, tag))
+}
+
+fn annotate_with(library: &Library, mut references: Vec<Reference>, tag: Option<&str>) -> Vec<Reference> {
+    library.annotate(&mut references);
+    if let Some(tag) = tag {
+        references.retain(|reference| reference.tags.iter().any(|t| t == tag));
+    }
+    references
+}
+
+/// The local library, or an empty one when there is no data directory
+fn load_library() -> anyhow::Result<Library> {
+    match library::default_library_path() {
+        Ok(path) => Library::load(&path),
+        Err(_) => Ok(Library::default()),
+    }
+}
+
+/// Write search results page by page as they arrive (`--stream`), annotated
+/// like `annotate`. Returns the number of references written.
+async fn stream_search_results(
+    output_writer: &OutputWriter,
+    pages: impl Stream<Item = anyhow::Result<Vec<Reference>>>,
+    tag: Option<&str>,
+) -> anyhow::Result<usize> {
+    let library = load_library()?;
+    let mut stream = output_writer.stream_references().await?;
+    let mut pages = std::pin::pin!(pages);
+    while let Some(references) = pages.try_next().await? {
+        stream.write(&annotate_with(&library, references, tag)).await?;
+    }
+    stream.finish().await
+}
+
+/// Papers from a literature search as references, with --categories, --grep and --tag applied
+fn search_results(papers: Vec<Paper>, options: &ReferenceOptions, tag: Option<&str>) -> anyhow::Result<Vec<Reference>> {
+    let references = papers.into_iter().map(Reference::from).collect();
+    annotate(commands::filter_references(references, options), tag)
+}
+
+/// Identifier lists from `--include-file` and `--exclude-file`
+fn identifier_filter(include: Option<&std::path::Path>, exclude: Option<&std::path::Path>) -> anyhow::Result<IdentifierFilter> {
+    let mut filter = IdentifierFilter::new();
+    if let Some(path) = include {
+        filter = filter.with_include_list(&std::fs::read_to_string(path)?);
+    }
+    if let Some(path) = exclude {
+        filter = filter.with_exclude_list(&std::fs::read_to_string(path)?);
+    }
+    Ok(filter)
+}
+
+/// Group references by the sections of the citing paper: from its LaTeX source,
+/// resolving each citation key on INSPIRE, or from a file of reference number ranges
+async fn section_groups(client: &InspireClient, path: &std::path::Path, references: &[Reference]) -> anyhow::Result<Vec<SectionGroup>> {
+    let content = std::fs::read_to_string(path)?;
+    if path.extension().and_then(|ext| ext.to_str()) != Some("tex") {
+        return Ok(sections::group_by_labels(references, &sections::parse_label_ranges(&content)?));
+    }
+    
+    let tex_sections = texscan::scan_sections(&content);
+    let keys: Vec<String> = tex_sections.iter().flat_map(|section| section.keys.clone()).collect();
+    let (resolved, unresolved) = texscan::resolve_keys(client, &keys).await;
+    if !unresolved.is_empty() {
+        warn!("Could not resolve {} citation keys: {}", unresolved.len(), unresolved.join(", "));
+    }
+    
+    let identifiers: HashMap<&str, Vec<String>> = resolved.iter()
+        .filter_map(|reference| {
+            let ids = reference.inspire_id.iter().chain(&reference.arxiv_id).cloned().collect();
+            Some((reference.texkey.as_deref()?, ids))
+        })
+        .collect();
+    let cited: Vec<(String, Vec<String>)> = tex_sections.iter()
+        .map(|section| {
+            let ids = section.keys.iter()
+                .filter_map(|key| identifiers.get(key.as_str()))
+                .flatten()
+                .cloned()
+                .collect();
+            (section.title.clone(), ids)
+        })
+        .collect();
+    Ok(sections::group_by_identifiers(references, &cited))
+}
+
+/// Fetch a paper and its filtered references, through the daemon when one is running
+async fn fetch_references(client: &InspireClient, arxiv_id: &str, options: &ReferenceOptions) -> anyhow::Result<FetchResult> {
+    #[cfg(unix)]
+    if let Ok(socket_path) = daemon::default_socket_path() {
+        if let Some(mut daemon) = daemon::DaemonClient::connect(&socket_path).await {
+            info!("Forwarding to daemon at {}", socket_path.display());
+            let paper = daemon.get_paper(arxiv_id).await?;
+            let references = daemon.get_paper_references(&paper.id).await?;
+            let (references, warnings) = commands::prepare_references(client, references, options).await;
+            return Ok(FetchResult { paper, references, warnings });
+        }
+    }
+    
+    commands::fetch_references(client, arxiv_id, options).await
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::api::InspireClient;
+use crate::network::{BuildStatus, CitationNetwork};
+
+/// How a saved citation network was built, written next to it so the network
+/// can be rebuilt later (`network rebuild`), e.g. for a paper's appendix
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkManifest {
+    /// Version of reference_tool that built the network
+    pub tool_version: String,
+    /// ArXiv ID of the root paper
+    pub paper: String,
+    pub depth: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_per_level: Option<usize>,
+    /// When the build started and finished (RFC 3339)
+    pub started: String,
+    pub finished: String,
+    /// Whether the build ran to completion rather than being interrupted
+    pub complete: bool,
+    /// Every paper in the network, with the INSPIRE revision of its record when
+    /// it was fetched (papers only known from reference lists have none)
+    pub records: BTreeMap<String, Option<u64>>,
+    /// Records whose references could not be fetched, so the network lacks their citations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_records: Vec<String>,
+}
+
+/// Differences between a manifest's records and a rebuilt network
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ManifestDiff {
+    /// Records whose INSPIRE revision changed since the manifest was written
+    pub revised: Vec<String>,
+    /// Papers in the manifest but not in the rebuilt network
+    pub missing: Vec<String>,
+    /// Papers in the rebuilt network but not in the manifest
+    pub added: Vec<String>,
+}
+
+impl ManifestDiff {
+    /// Whether the rebuilt network matches the manifest exactly
+    pub fn is_empty(&self) -> bool {
+        self.revised.is_empty() && self.missing.is_empty() && self.added.is_empty()
+    }
+}
+
+impl NetworkManifest {
+    /// Describe a finished build. Revisions are looked up for the papers whose
+    /// records were fetched during the build, so they come from the client's cache.
+    pub async fn record(
+        client: &InspireClient,
+        paper: &str,
+        depth: u32,
+        max_per_level: Option<usize>,
+        started: String,
+        network: &CitationNetwork,
+        status: BuildStatus,
+    ) -> Self {
+        let mut records = BTreeMap::new();
+        for (id, paper) in &network.papers {
+            let revision = match paper.revision {
+                Some(revision) => Some(revision),
+                None if network.citations.contains_key(id) => match client.get_record_revision(id).await {
+                    Ok(revision) => revision,
+                    Err(e) => {
+                        warn!("Could not look up the revision of record {}: {}", id, e);
+                        None
+                    }
+                },
+                None => None,
+            };
+            records.insert(id.clone(), revision);
+        }
+
+        Self {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            paper: paper.to_string(),
+            depth,
+            max_per_level,
+            started,
+            finished: crate::atom::now_rfc3339(),
+            complete: status == BuildStatus::Complete,
+            records,
+            failed_records: network.failed_records.keys().cloned().collect(),
+        }
+    }
+
+    /// Manifest file for a network output: `network.json` gets `network.manifest.json`
+    pub fn path_for(output: &Path) -> PathBuf {
+        output.with_extension("manifest.json")
+    }
+
+    /// Load a manifest from a file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Save the manifest to a file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Compare with the manifest of a rebuild
+    pub fn diff(&self, rebuilt: &NetworkManifest) -> ManifestDiff {
+        let mut diff = ManifestDiff::default();
+        for (id, revision) in &self.records {
+            match rebuilt.records.get(id) {
+                None => diff.missing.push(id.clone()),
+                Some(new) if revision.is_some() && new.is_some() && new != revision => diff.revised.push(id.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.added = rebuilt.records.keys()
+            .filter(|id| !self.records.contains_key(*id))
+            .cloned()
+            .collect();
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Paper;
+    use tempfile::tempdir;
+
+    fn create_manifest(records: &[(&str, Option<u64>)]) -> NetworkManifest {
+        NetworkManifest {
+            tool_version: "0.1.0".to_string(),
+            paper: "hep-th/9905104".to_string(),
+            depth: 2,
+            max_per_level: Some(5),
+            started: "2024-01-01T00:00:00Z".to_string(),
+            finished: "2024-01-01T00:05:00Z".to_string(),
+            complete: true,
+            records: records.iter().map(|(id, revision)| (id.to_string(), *revision)).collect(),
+            failed_records: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_uses_known_revisions() {
+        let mut network = CitationNetwork::new();
+        network.add_paper(Paper { id: "1".to_string(), revision: Some(4), ..Default::default() });
+        network.add_paper(Paper { id: "2".to_string(), ..Default::default() });
+
+        let client = InspireClient::new();
+        let manifest = NetworkManifest::record(&client, "hep-th/9905104", 1, None, String::new(), &network, BuildStatus::Complete).await;
+        assert_eq!(manifest.records, BTreeMap::from([("1".to_string(), Some(4)), ("2".to_string(), None)]));
+        assert!(manifest.complete);
+    }
+
+    #[test]
+    fn test_diff_and_round_trip() {
+        let original = create_manifest(&[("1", Some(4)), ("2", Some(1)), ("3", None)]);
+        let rebuilt = create_manifest(&[("1", Some(5)), ("2", Some(1)), ("4", None)]);
+        let diff = original.diff(&rebuilt);
+        assert_eq!(diff.revised, ["1"]);
+        assert_eq!(diff.missing, ["3"]);
+        assert_eq!(diff.added, ["4"]);
+        assert!(original.diff(&original).is_empty());
+
+        let dir = tempdir().unwrap();
+        let path = NetworkManifest::path_for(&dir.path().join("network.json"));
+        assert!(path.ends_with("network.manifest.json"));
+        original.save(&path).unwrap();
+        assert_eq!(NetworkManifest::load(&path).unwrap(), original);
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use lazy_static::lazy_static;
+
+/// Upper bounds (seconds) of the INSPIRE latency histogram buckets
+pub const LATENCY_BUCKETS: [f64; 8] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+lazy_static! {
+    /// Process-wide metrics, exposed by the server and daemon at `/metrics`
+    pub static ref METRICS: Metrics = Metrics::default();
+}
+
+/// Counters and histograms in the Prometheus data model
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Requests answered, by endpoint (route or protocol op) and status
+    requests: Mutex<BTreeMap<(String, String), u64>>,
+    /// Cumulative counts per `LATENCY_BUCKETS` entry
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    latency_sum_micros: AtomicU64,
+    latency_count: AtomicU64,
+    /// INSPIRE responses with HTTP 429
+    rate_limited: AtomicU64,
+}
+
+impl Metrics {
+    /// Count a request answered by the server or daemon
+    pub fn record_request(&self, endpoint: &str, status: &str) {
+        let mut requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        *requests.entry((endpoint.to_string(), status.to_string())).or_default() += 1;
+    }
+
+    /// Record the latency of one INSPIRE API call
+    pub fn record_inspire_latency(&self, latency: Duration) {
+        let seconds = latency.as_secs_f64();
+        for (bucket, bound) in self.latency_buckets.iter().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.latency_sum_micros.fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+        self.latency_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a rate-limited (HTTP 429) INSPIRE response
+    pub fn record_rate_limited(&self) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+
+        text.push_str("# HELP reference_tool_requests_total Requests answered by the server or daemon.\n");
+        text.push_str("# TYPE reference_tool_requests_total counter\n");
+        let requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        for ((endpoint, status), count) in requests.iter() {
+            let _ = writeln!(
+                text,
+                "reference_tool_requests_total{{endpoint=\"{}\",status=\"{}\"}} {}",
+                endpoint, status, count
+            );
+        }
+
+        text.push_str("# HELP reference_tool_inspire_request_duration_seconds Latency of INSPIRE API calls.\n");
+        text.push_str("# TYPE reference_tool_inspire_request_duration_seconds histogram\n");
+        for (bucket, bound) in self.latency_buckets.iter().zip(LATENCY_BUCKETS) {
+            let _ = writeln!(
+                text,
+                "reference_tool_inspire_request_duration_seconds_bucket{{le=\"{}\"}} {}",
+                bound, bucket.load(Ordering::Relaxed)
+            );
+        }
+        let count = self.latency_count.load(Ordering::Relaxed);
+        let _ = writeln!(text, "reference_tool_inspire_request_duration_seconds_bucket{{le=\"+Inf\"}} {}", count);
+        let _ = writeln!(
+            text,
+            "reference_tool_inspire_request_duration_seconds_sum {}",
+            self.latency_sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+        );
+        let _ = writeln!(text, "reference_tool_inspire_request_duration_seconds_count {}", count);
+
+        text.push_str("# HELP reference_tool_inspire_rate_limited_total INSPIRE responses rejected with HTTP 429.\n");
+        text.push_str("# TYPE reference_tool_inspire_rate_limited_total counter\n");
+        let _ = writeln!(text, "reference_tool_inspire_rate_limited_total {}", self.rate_limited.load(Ordering::Relaxed));
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        metrics.record_request("paper", "200");
+        metrics.record_request("paper", "200");
+        metrics.record_request("cite", "error");
+        metrics.record_inspire_latency(Duration::from_millis(300));
+        metrics.record_inspire_latency(Duration::from_secs(20));
+        metrics.record_rate_limited();
+
+        let text = metrics.render();
+        assert!(text.contains("reference_tool_requests_total{endpoint=\"paper\",status=\"200\"} 2\n"));
+        assert!(text.contains("reference_tool_requests_total{endpoint=\"cite\",status=\"error\"} 1\n"));
+        assert!(text.contains("reference_tool_inspire_request_duration_seconds_bucket{le=\"0.25\"} 0\n"));
+        assert!(text.contains("reference_tool_inspire_request_duration_seconds_bucket{le=\"0.5\"} 1\n"));
+        assert!(text.contains("reference_tool_inspire_request_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("reference_tool_inspire_request_duration_seconds_sum 20.3\n"));
+        assert!(text.contains("reference_tool_inspire_rate_limited_total 1\n"));
+    }
+}
@@ -1,44 +1,848 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::arxiv::ArxivId;
+use crate::bibtex;
+use crate::dates::PartialDate;
+use crate::names::{self, PersonName};
+
+/// Layout version written into JSON exports (`schema_version`) and checked when
+/// they are read back; bump it when the exported structure changes incompatibly
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Paper {
     pub id: String,
     pub title: String,
     pub authors: Vec<String>,
+    /// Institutions the authors are affiliated with, each listed once in author order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub affiliations: Vec<String>,
     pub arxiv_id: Option<String>,
     pub categories: Vec<String>,
     pub year: Option<u32>,
+    /// Publication date, to the precision known (`2023`, `2023-01`, `2023-01-15`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<PartialDate>,
+    /// INSPIRE TeX key (e.g. `Maldacena:1997re`)
+    #[serde(default)]
+    pub texkey: Option<String>,
+    #[serde(default, rename = "abstract", skip_serializing_if = "Option::is_none")]
+    pub abstract_text: Option<String>,
+    /// INSPIRE document types (`article`, `thesis`, `conference paper`, ...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub document_type: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publication_info: Option<PublicationInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thesis_info: Option<ThesisInfo>,
+    /// Data records (HEPData, INSPIRE data) associated with the paper
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub datasets: Vec<Dataset>,
+    /// License the work is distributed under
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<License>,
+    /// Grants acknowledged by the paper
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub funding: Vec<Funding>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citation_count: Option<u32>,
+    /// Whether this is likely a review rather than primary literature
+    #[serde(default)]
+    pub is_review: bool,
+    /// Withdrawn from arXiv or retracted by the publisher
+    #[serde(default)]
+    pub retracted: bool,
+    /// INSPIRE revision of the record this was parsed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u64>,
+    /// When the INSPIRE record was last updated (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
+    /// Untouched INSPIRE metadata this was parsed from, kept when the client
+    /// is built `with_raw(true)` (`--raw`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<serde_json::Value>,
+}
+
+/// Optional fields added to generated BibTeX entries (`[bibtex]` config section)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BibtexOptions {
+    /// Add `keywords` from the arXiv categories, e.g. for JabRef groups
+    pub keywords: bool,
+    /// Add a `url` to the INSPIRE record (or arXiv page)
+    pub url: bool,
+    /// Add this text as `note`
+    pub note: Option<String>,
+    /// Add the abstract, when known, as `abstract`
+    pub include_abstract: bool,
+    /// Write the pinned arXiv version, when known, into `eprint` (`2301.12345v2`)
+    pub pin_versions: bool,
+    /// Add `month` as a BibTeX month macro (`jan`) when the publication month is known
+    pub month: bool,
+    /// Write biblatex's `date` (`2023-01-15`) instead of `year` and `month`
+    /// when the publication date is known
+    pub biblatex: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Reference {
     pub title: String,
     pub authors: Vec<String>,
+    /// Institutions the authors are affiliated with, each listed once in author order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub affiliations: Vec<String>,
     pub arxiv_id: Option<String>,
+    /// arXiv version the citation is pinned to (`2` for `2301.12345v2`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arxiv_version: Option<u32>,
     pub inspire_id: Option<String>,
     pub categories: Vec<String>,
     pub year: Option<u32>,
+    /// Publication date, to the precision known (`2023`, `2023-01`, `2023-01-15`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<PartialDate>,
+    /// Citation key to use instead of a generated one
+    #[serde(default)]
+    pub texkey: Option<String>,
+    #[serde(default, rename = "abstract", skip_serializing_if = "Option::is_none")]
+    pub abstract_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub document_type: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publication_info: Option<PublicationInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thesis_info: Option<ThesisInfo>,
+    /// License the work is distributed under
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<License>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citation_count: Option<u32>,
+    /// Whether this is likely a review rather than primary literature
+    #[serde(default)]
+    pub is_review: bool,
+    /// Withdrawn from arXiv or retracted by the publisher
+    #[serde(default)]
+    pub retracted: bool,
+    /// Label of the entry in the citing paper's reference list (e.g. `42` for `[42]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Sentences of the citing paper in which this reference is cited
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contexts: Vec<String>,
+    /// Untouched INSPIRE reference entry this was parsed from, kept when the client
+    /// is built `with_raw(true)` (`--raw`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<serde_json::Value>,
+    /// The user's tags from the local library
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// The user's notes from the local library
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// Open-access status, when looked up (`--open-access`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_access: Option<OpenAccess>,
+    /// Where fields came from, written as `title_source` etc. (`--provenance`)
+    #[serde(flatten)]
+    pub sources: FieldSources,
+}
+
+/// BibTeX `note` marking a retracted or withdrawn work
+pub const RETRACTED_NOTE: &str = "RETRACTED";
+
+/// Citation count above which a paper titled like a review is treated as one
+pub const REVIEW_CITATION_THRESHOLD: u32 = 1000;
+
+/// Heuristic review detection: an explicit `review` document type, or an
+/// extremely highly cited paper with "review" or "lectures" in its title
+pub fn looks_like_review(document_type: &[String], citation_count: Option<u32>, title: &str) -> bool {
+    if document_type.iter().any(|t| t == "review") {
+        return true;
+    }
+    
+    let title = title.to_lowercase();
+    let review_title = title.contains("review") || title.contains("lectures");
+    review_title && citation_count.is_some_and(|count| count >= REVIEW_CITATION_THRESHOLD)
+}
+
+/// Where a work was published: journal, or proceedings for conference contributions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PublicationInfo {
+    pub journal_title: Option<String>,
+    pub journal_volume: Option<String>,
+    pub journal_issue: Option<String>,
+    pub page_start: Option<String>,
+    pub page_end: Option<String>,
+    pub artid: Option<String>,
+    pub year: Option<u32>,
+    /// INSPIRE conference number (e.g. `C17-07-05`)
+    pub cnum: Option<String>,
+    /// Conference acronym (e.g. `EPS-HEP2017`)
+    pub conf_acronym: Option<String>,
+    /// Title of the proceedings volume containing the work
+    pub parent_title: Option<String>,
+    /// ISBN of the book, or of the book or proceedings volume containing the work
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isbn: Option<String>,
+    /// ISSN of the journal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issn: Option<String>,
+}
+
+/// Range separators found in page fields: BibTeX's `--`, en and em dashes,
+/// minus and hyphen (longest first, so `--` is not split as `-`)
+const PAGE_DASHES: [&str; 6] = ["--", "\u{2014}", "\u{2013}", "\u{2212}", "\u{2010}", "-"];
+
+/// Where in its journal a work is, from INSPIRE's `page_start`, `page_end` and `artid`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pages {
+    /// First and last page
+    Range(String, String),
+    /// A single page
+    Single(String),
+    /// Article number, for journals that number articles instead of pages
+    /// (`030001` in Physical Review D, `001` in JHEP)
+    ArticleId(String),
+}
+
+impl Pages {
+    /// Read a page field as written in BibTeX or by hand (`123--145`, `123–45`, `12`)
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        Some(match split_page_range(text) {
+            Some((start, end)) => Self::range(start, end),
+            None => Self::Single(text.to_string()),
+        })
+    }
+
+    /// A range, with an abbreviated last page written out (`123`–`45` is
+    /// `123`–`145`) and a range ending where it starts made a single page
+    fn range(start: &str, end: &str) -> Self {
+        let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+        let end = if digits(start) && digits(end) && end.len() < start.len() {
+            format!("{}{}", &start[..start.len() - end.len()], end)
+        } else {
+            end.to_string()
+        };
+        if end == start {
+            Self::Single(end)
+        } else {
+            Self::Range(start.to_string(), end)
+        }
+    }
+
+    /// First page or article number, for styles that only cite where a work starts
+    pub fn first(&self) -> &str {
+        match self {
+            Self::Range(start, _) | Self::Single(start) | Self::ArticleId(start) => start,
+        }
+    }
+
+    /// The pages with `dash` between the ends of a range: `--` in BibTeX, where
+    /// styles typeset it as an en dash, and `–` in formatted citations
+    pub fn format(&self, dash: &str) -> String {
+        match self {
+            Self::Range(start, end) => format!("{}{}{}", start, dash, end),
+            Self::Single(page) | Self::ArticleId(page) => page.clone(),
+        }
+    }
+}
+
+/// Split `123--145` (or any other range separator) into its two ends
+fn split_page_range(text: &str) -> Option<(&str, &str)> {
+    PAGE_DASHES.iter()
+        .find_map(|dash| text.split_once(dash))
+        .map(|(start, end)| (start.trim(), end.trim()))
+        .filter(|(start, end)| !start.is_empty() && !end.is_empty())
+}
+
+impl PublicationInfo {
+    /// Normalized pages: the article number for journals that number articles
+    /// (when there is no page, or the page just repeats it or restarts at 1
+    /// in every article), otherwise the page range or single page. A range
+    /// given entirely in `page_start` (`123-145`) is split.
+    pub fn pages(&self) -> Option<Pages> {
+        fn field(value: &Option<String>) -> Option<&str> {
+            value.as_deref().map(str::trim).filter(|value| !value.is_empty())
+        }
+        let artid = field(&self.artid);
+        let start = field(&self.page_start);
+        match (start, artid) {
+            (Some(start), Some(artid)) if start == artid || start == "1" => Some(Pages::ArticleId(artid.to_string())),
+            (Some(start), _) => Some(match (split_page_range(start), field(&self.page_end)) {
+                (Some((start, end)), _) => Pages::range(start, end),
+                (None, Some(end)) => Pages::range(start, end),
+                (None, None) => Pages::Single(start.to_string()),
+            }),
+            (None, Some(artid)) => Some(Pages::ArticleId(artid.to_string())),
+            (None, None) => None,
+        }
+    }
+}
+
+/// A DOI without `doi:` or `https://doi.org/` prefixes (`10.1023/A:1026654312961`),
+/// or `None` when the text is not a DOI
+pub fn normalize_doi(text: &str) -> Option<String> {
+    let text = text.trim();
+    let lowercase = text.to_ascii_lowercase();
+    let doi = ["https://doi.org/", "http://doi.org/", "https://dx.doi.org/", "http://dx.doi.org/", "doi:"]
+        .iter()
+        .find(|prefix| lowercase.starts_with(*prefix))
+        .map_or(text, |prefix| text[prefix.len()..].trim_start());
+    let (registrant, suffix) = doi.split_once('/')?;
+    let valid = registrant.strip_prefix("10.").is_some_and(|code| !code.is_empty() && code.chars().all(|c| c.is_ascii_digit() || c == '.'))
+        && !suffix.is_empty();
+    valid.then(|| doi.to_string())
+}
+
+/// An ISBN-10 or ISBN-13 without hyphens or spaces (`9780521670531`), or
+/// `None` when the check digit does not match
+pub fn normalize_isbn(text: &str) -> Option<String> {
+    let compact: String = text.trim()
+        .trim_start_matches("ISBN")
+        .trim_start_matches(':')
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let digits: Vec<u32> = compact.chars()
+        .enumerate()
+        .map(|(position, c)| match c {
+            'X' if position == 9 && compact.len() == 10 => Some(10),
+            _ => c.to_digit(10),
+        })
+        .collect::<Option<_>>()?;
+    let valid = match digits.len() {
+        10 => digits.iter().zip((1..=10).rev()).map(|(digit, weight)| digit * weight).sum::<u32>() % 11 == 0,
+        13 => digits.iter().zip([1, 3].iter().cycle()).map(|(digit, weight)| digit * weight).sum::<u32>() % 10 == 0,
+        _ => false,
+    };
+    valid.then_some(compact)
+}
+
+/// An ISSN in its standard `1234-5679` form, or `None` when the check digit
+/// does not match
+pub fn normalize_issn(text: &str) -> Option<String> {
+    let compact: Vec<char> = text.trim()
+        .trim_start_matches("ISSN")
+        .trim_start_matches(':')
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if compact.len() != 8 {
+        return None;
+    }
+    let sum: u32 = compact[..7].iter()
+        .zip((2..=8).rev())
+        .map(|(c, weight)| c.to_digit(10).map(|digit| digit * weight))
+        .sum::<Option<u32>>()?;
+    let check = match (11 - sum % 11) % 11 {
+        10 => 'X',
+        digit => char::from_digit(digit, 10)?,
+    };
+    (compact[7] == check).then(|| format!("{}-{}", compact[..4].iter().collect::<String>(), compact[4..].iter().collect::<String>()))
+}
+
+/// License information from an INSPIRE record
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct License {
+    /// License name (e.g. `CC BY 4.0`, `arXiv nonexclusive-distrib 1.0`)
+    pub name: Option<String>,
+    pub url: Option<String>,
+    /// Which version of the work it covers (`preprint` or `publication`)
+    pub material: Option<String>,
+}
+
+impl License {
+    /// Whether this is a Creative Commons license, allowing redistribution
+    pub fn is_open_access(&self) -> bool {
+        let cc_name = self.name.as_deref().is_some_and(|name| name.to_uppercase().starts_with("CC"));
+        let cc_url = self.url.as_deref().is_some_and(|url| url.contains("creativecommons.org"));
+        cc_name || cc_url
+    }
+}
+
+/// A grant acknowledged by a paper, from the `funding_info` of its INSPIRE record
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Funding {
+    /// Funding agency (e.g. `European Research Council`)
+    pub agency: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grant_number: Option<String>,
+    /// Project name or number under which the grant was awarded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_number: Option<String>,
+}
+
+/// Where the open-access status of a work was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OpenAccessSource {
+    /// The work is on arXiv
+    Arxiv,
+    /// INSPIRE lists a Creative Commons license
+    License,
+    /// Unpaywall's record for the DOI
+    Unpaywall,
+}
+
+impl OpenAccessSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OpenAccessSource::Arxiv => "arxiv",
+            OpenAccessSource::License => "license",
+            OpenAccessSource::Unpaywall => "unpaywall",
+        }
+    }
+}
+
+/// Service a field of a reference was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSource {
+    /// The entry in the citing paper's reference list on INSPIRE
+    InspireReference,
+    /// The cited work's own INSPIRE literature record
+    InspireRecord,
+    /// INSPIRE institution records
+    InspireInstitution,
+    /// Semantic Scholar's citation contexts
+    SemanticScholar,
+    /// CrossRef update notices
+    Crossref,
+    /// The arXiv API
+    Arxiv,
+}
+
+impl DataSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DataSource::InspireReference => "inspire_reference",
+            DataSource::InspireRecord => "inspire_record",
+            DataSource::InspireInstitution => "inspire_institution",
+            DataSource::SemanticScholar => "semantic_scholar",
+            DataSource::Crossref => "crossref",
+            DataSource::Arxiv => "arxiv",
+        }
+    }
+}
+
+/// Source of each field of a reference that was filled in, so conflicting
+/// metadata can be traced back; only set fields are serialized
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldSources {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_source: Option<DataSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authors_source: Option<DataSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub affiliations_source: Option<DataSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arxiv_id_source: Option<DataSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arxiv_version_source: Option<DataSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year_source: Option<DataSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abstract_source: Option<DataSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doi_source: Option<DataSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publication_info_source: Option<DataSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citation_count_source: Option<DataSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retracted_source: Option<DataSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contexts_source: Option<DataSource>,
+}
+
+/// Whether a work can be read for free, and where
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenAccess {
+    pub is_open: bool,
+    pub source: OpenAccessSource,
+    /// Unpaywall's classification (`gold`, `green`, `hybrid`, `bronze` or `closed`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oa_status: Option<String>,
+    /// A free full text, a PDF where one is known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// An INSPIRE institution record
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Institution {
+    /// INSPIRE record ID
+    pub id: String,
+    /// Name used in author affiliations (the INSPIRE ICN, e.g. `CERN`)
+    pub name: String,
+    /// Full name, from the top of the institution's hierarchy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    /// Research Organization Registry ID (`https://ror.org/01ggx4157`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ror: Option<String>,
+    /// GRID ID (`grid.9132.9`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid: Option<String>,
+    /// Number of INSPIRE literature records affiliated with the institution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paper_count: Option<u64>,
+}
+
+impl Institution {
+    /// ISO 3166-1 country code of the address, e.g. `CH`
+    pub fn country_code(&self) -> Option<&str> {
+        self.address.as_ref()?.country_code.as_deref()
+    }
+}
+
+/// An INSPIRE conference record
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Conference {
+    /// INSPIRE record ID
+    pub id: String,
+    /// INSPIRE conference number (`C17-07-05`), which contributions' publication info refers to
+    pub cnum: Option<String>,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub acronyms: Vec<String>,
+    /// Names of the series the conference belongs to (`EPS-HEP`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub series: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opening_date: Option<PartialDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closing_date: Option<PartialDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    /// Number of INSPIRE literature records contributed to the conference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contribution_count: Option<u64>,
+}
+
+/// An INSPIRE experiment record
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Experiment {
+    /// INSPIRE record ID
+    pub id: String,
+    /// Name papers are linked by (e.g. `CERN-LHC-ATLAS`)
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub long_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collaboration: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accelerator: Option<String>,
+    /// Host institutions, as named in author affiliations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub institutions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_started: Option<PartialDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_completed: Option<PartialDate>,
+    /// Number of INSPIRE literature records linked to the experiment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paper_count: Option<u64>,
+}
+
+/// An INSPIRE job listing
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    /// INSPIRE record ID
+    pub id: String,
+    pub position: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub institutions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub regions: Vec<String>,
+    /// Career stages (`POSTDOC`, `JUNIOR`, ...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranks: Vec<String>,
+    /// arXiv categories of the field
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    /// Application deadline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<PartialDate>,
+    /// `open`, `closed` or `pending`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Postal address of an institution or conference venue
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Address {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+    pub country_code: Option<String>,
+}
+
+/// A dataset linked from a paper record
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Dataset {
+    /// Where the link came from (`HEPData`, ...)
+    pub source: String,
+    pub doi: Option<String>,
+    pub url: Option<String>,
+}
+
+/// Datasets associated with one paper, as listed by `--with-datasets`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DatasetListing {
+    pub inspire_id: String,
+    pub title: String,
+    pub datasets: Vec<Dataset>,
+}
+
+/// Degree information for theses
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThesisInfo {
+    pub institution: Option<String>,
+    /// INSPIRE degree type (`phd`, `master`, `bachelor`, `habilitation`, ...)
+    pub degree_type: Option<String>,
+    pub year: Option<u32>,
+}
+
+impl From<Paper> for Reference {
+    fn from(paper: Paper) -> Self {
+        Self {
+            title: paper.title,
+            authors: paper.authors,
+            affiliations: paper.affiliations,
+            arxiv_id: paper.arxiv_id,
+            arxiv_version: None,
+            inspire_id: Some(paper.id),
+            categories: paper.categories,
+            year: paper.year,
+            date: paper.date,
+            texkey: paper.texkey,
+            abstract_text: paper.abstract_text,
+            document_type: paper.document_type,
+            doi: paper.doi,
+            publication_info: paper.publication_info,
+            thesis_info: paper.thesis_info,
+            license: paper.license,
+            citation_count: paper.citation_count,
+            is_review: paper.is_review,
+            retracted: paper.retracted,
+            label: None,
+            contexts: Vec::new(),
+            raw: paper.raw,
+            tags: Vec::new(),
+            notes: Vec::new(),
+            open_access: None,
+            sources: FieldSources::default(),
+        }
+    }
+}
+
+impl Paper {
+    /// Build a network node for a reference resolved to an INSPIRE record
+    pub fn from_reference(id: String, reference: &Reference) -> Self {
+        Self {
+            id,
+            title: reference.title.clone(),
+            authors: reference.authors.clone(),
+            affiliations: reference.affiliations.clone(),
+            arxiv_id: reference.arxiv_id.clone(),
+            categories: reference.categories.clone(),
+            year: reference.year,
+            date: reference.date,
+            texkey: reference.texkey.clone(),
+            abstract_text: reference.abstract_text.clone(),
+            document_type: reference.document_type.clone(),
+            doi: reference.doi.clone(),
+            publication_info: reference.publication_info.clone(),
+            thesis_info: reference.thesis_info.clone(),
+            datasets: Vec::new(),
+            license: reference.license.clone(),
+            funding: Vec::new(),
+            citation_count: reference.citation_count,
+            is_review: reference.is_review,
+            retracted: reference.retracted,
+            revision: None,
+            last_updated: None,
+            raw: reference.raw.clone(),
+        }
+    }
 }
 
 impl Reference {
+    /// Attribute every filled-in field without a recorded source to `source`,
+    /// e.g. the INSPIRE reference entry the reference was parsed from
+    pub fn mark_sources(&mut self, source: DataSource) {
+        let sources = &mut self.sources;
+        let mark = |filled: bool, field: &mut Option<DataSource>| {
+            if filled && field.is_none() {
+                *field = Some(source);
+            }
+        };
+        mark(!self.title.is_empty(), &mut sources.title_source);
+        mark(!self.authors.is_empty(), &mut sources.authors_source);
+        mark(!self.affiliations.is_empty(), &mut sources.affiliations_source);
+        mark(self.arxiv_id.is_some(), &mut sources.arxiv_id_source);
+        mark(self.arxiv_version.is_some(), &mut sources.arxiv_version_source);
+        mark(self.year.is_some(), &mut sources.year_source);
+        mark(self.abstract_text.is_some(), &mut sources.abstract_source);
+        mark(self.doi.is_some(), &mut sources.doi_source);
+        mark(self.publication_info.is_some(), &mut sources.publication_info_source);
+        mark(self.citation_count.is_some(), &mut sources.citation_count_source);
+        mark(self.retracted, &mut sources.retracted_source);
+        mark(!self.contexts.is_empty(), &mut sources.contexts_source);
+    }
+    
+    /// Open-access status known without asking another service: open when the
+    /// work is on arXiv or under a Creative Commons license, otherwise unknown
+    pub fn known_open_access(&self) -> Option<OpenAccess> {
+        if let Some(arxiv_id) = &self.arxiv_id {
+            return Some(OpenAccess {
+                is_open: true,
+                source: OpenAccessSource::Arxiv,
+                oa_status: None,
+                url: Some(format!("https://arxiv.org/pdf/{}", arxiv_id)),
+            });
+        }
+        let license = self.license.as_ref().filter(|license| license.is_open_access())?;
+        Some(OpenAccess {
+            is_open: true,
+            source: OpenAccessSource::License,
+            oa_status: None,
+            url: self.doi.as_ref().map(|doi| format!("https://doi.org/{}", doi)).or_else(|| license.url.clone()),
+        })
+    }
+
+    /// Stable identifier for this reference: the INSPIRE record ID when known,
+    /// then the arXiv ID, falling back to the generated BibTeX key
+    pub fn identifier(&self) -> String {
+        if let Some(inspire_id) = &self.inspire_id {
+            format!("inspire:{}", inspire_id)
+        } else if let Some(arxiv_id) = &self.arxiv_id {
+            format!("arxiv:{}", arxiv_id)
+        } else {
+            format!("key:{}", self.generate_bibtex_key())
+        }
+    }
+
+    /// BibTeX entry type for this reference, based on its INSPIRE document type
+    pub fn bibtex_entry_type(&self) -> &'static str {
+        let has_type = |t: &str| self.document_type.iter().any(|d| d == t);
+        
+        if has_type("thesis") || self.thesis_info.is_some() {
+            match self.thesis_info.as_ref().and_then(|t| t.degree_type.as_deref()) {
+                Some("master") | Some("diploma") | Some("bachelor") | Some("laurea") => "mastersthesis",
+                _ => "phdthesis",
+            }
+        } else if has_type("conference paper") {
+            "inproceedings"
+        } else if has_type("proceedings") {
+            "proceedings"
+        } else if has_type("book") {
+            "book"
+        } else if has_type("book chapter") {
+            "incollection"
+        } else {
+            "article"
+        }
+    }
+    
     /// Generate BibTeX entry for this reference
     pub fn to_bibtex(&self) -> String {
-        let key = self.generate_bibtex_key();
-        let authors_str = self.authors.join(" and ");
+        self.to_bibtex_with(&BibtexOptions::default())
+    }
+    
+    /// Generate BibTeX entry for this reference, adding the optional fields in `options`
+    pub fn to_bibtex_with(&self, options: &BibtexOptions) -> String {
+        let key = self.bibtex_key();
+        let authors_str = self.authors.iter()
+            .map(|name| names::to_last_first(name))
+            .collect::<Vec<_>>()
+            .join(" and ");
+        let entry_type = self.bibtex_entry_type();
         
-        let mut bibtex = format!("@article{{{},\n", key);
+        let mut bibtex = format!("@{}{{{},\n", entry_type, key);
         bibtex.push_str(&format!("  title = {{{}}},\n", self.title));
         
         if !authors_str.is_empty() {
             bibtex.push_str(&format!("  author = {{{}}},\n", authors_str));
         }
         
-        if let Some(year) = self.year {
-            bibtex.push_str(&format!("  year = {{{}}},\n", year));
+        if let Some(thesis) = &self.thesis_info {
+            if let Some(institution) = &thesis.institution {
+                bibtex.push_str(&format!("  school = {{{}}},\n", institution));
+            }
         }
         
-        if let Some(arxiv_id) = &self.arxiv_id {
-            bibtex.push_str(&format!("  eprint = {{{}}},\n", arxiv_id));
+        if let Some(info) = &self.publication_info {
+            match entry_type {
+                "inproceedings" | "incollection" => {
+                    let booktitle = info.parent_title.as_ref()
+                        .or(info.conf_acronym.as_ref())
+                        .or(info.journal_title.as_ref());
+                    if let Some(booktitle) = booktitle {
+                        bibtex.push_str(&format!("  booktitle = {{{}}},\n", booktitle));
+                    }
+                }
+                _ => {
+                    if let Some(journal) = &info.journal_title {
+                        bibtex.push_str(&format!("  journal = {{{}}},\n", journal));
+                    }
+                }
+            }
+            if let Some(volume) = &info.journal_volume {
+                bibtex.push_str(&format!("  volume = {{{}}},\n", volume));
+            }
+            if let Some(issue) = &info.journal_issue {
+                bibtex.push_str(&format!("  number = {{{}}},\n", issue));
+            }
+            if let Some(pages) = info.pages() {
+                bibtex.push_str(&format!("  pages = {{{}}},\n", pages.format("--")));
+            }
+            if let Some(isbn) = &info.isbn {
+                bibtex.push_str(&format!("  isbn = {{{}}},\n", isbn));
+            }
+            // An ISSN identifies the journal, so only articles carry it
+            if let Some(issn) = info.issn.as_ref().filter(|_| entry_type == "article") {
+                bibtex.push_str(&format!("  issn = {{{}}},\n", issn));
+            }
+        }
+        
+        match self.date.filter(|_| options.biblatex) {
+            Some(date) => bibtex.push_str(&format!("  date = {{{}}},\n", date)),
+            None => {
+                let year = self.year.or_else(|| self.thesis_info.as_ref().and_then(|t| t.year));
+                if let Some(year) = year {
+                    bibtex.push_str(&format!("  year = {{{}}},\n", year));
+                }
+                // Month macros are left unbraced so styles can localize them
+                if let Some(month) = self.date.filter(|_| options.month).and_then(|date| date.bibtex_month()) {
+                    bibtex.push_str(&format!("  month = {},\n", month));
+                }
+            }
+        }
+        
+        if let Some(doi) = &self.doi {
+            bibtex.push_str(&format!("  doi = {{{}}},\n", doi));
+        }
+        
+        if let Some(eprint) = self.arxiv_eprint() {
+            let eprint = if options.pin_versions { eprint } else { eprint.unversioned() };
+            bibtex.push_str(&format!("  eprint = {{{}}},\n", eprint));
             bibtex.push_str("  archivePrefix = {arXiv},\n");
         }
         
@@ -46,15 +850,63 @@ impl Reference {
             bibtex.push_str(&format!("  primaryClass = {{{}}},\n", self.categories[0]));
         }
         
+        // Library tags always become keywords, for reference-manager groups
+        let keywords: Vec<&str> = self.categories.iter()
+            .filter(|_| options.keywords)
+            .chain(&self.tags)
+            .map(String::as_str)
+            .collect();
+        if !keywords.is_empty() {
+            bibtex.push_str(&format!("  keywords = {{{}}},\n", keywords.join(", ")));
+        }
+        
+        if options.url {
+            if let Some(url) = self.url() {
+                bibtex.push_str(&format!("  url = {{{}}},\n", url));
+            }
+        }
+        
+        // Retracted works are flagged in the note, where every style prints it
+        let note = match (self.retracted, &options.note) {
+            (true, Some(note)) => Some(format!("{}. {}", RETRACTED_NOTE, note)),
+            (true, None) => Some(RETRACTED_NOTE.to_string()),
+            (false, note) => note.clone(),
+        };
+        if let Some(note) = note {
+            bibtex.push_str(&format!("  note = {{{}}},\n", note));
+        }
+        
+        if let Some(abstract_text) = self.abstract_text.as_ref().filter(|_| options.include_abstract) {
+            bibtex.push_str(&format!("  abstract = {{{}}},\n", bibtex::escape_field(abstract_text)));
+        }
+        
         bibtex.push_str("}\n");
         bibtex
     }
     
+    /// The arXiv identifier with the version the citation is pinned to, if any
+    pub fn arxiv_eprint(&self) -> Option<ArxivId> {
+        self.arxiv_id.as_ref().map(|id| ArxivId { id: id.clone(), version: self.arxiv_version })
+    }
+    
+    /// Landing page: the INSPIRE record, otherwise the arXiv abstract page
+    pub fn url(&self) -> Option<String> {
+        self.inspire_id.as_ref()
+            .map(|id| format!("https://inspirehep.net/literature/{}", id))
+            .or_else(|| self.arxiv_id.as_ref().map(|id| format!("https://arxiv.org/abs/{}", id)))
+    }
+    
+    /// BibTeX key for this reference: its TeX key if known, otherwise a generated one
+    pub fn bibtex_key(&self) -> String {
+        self.texkey.clone().unwrap_or_else(|| self.generate_bibtex_key())
+    }
+
     /// Generate a unique BibTeX key for this reference
     fn generate_bibtex_key(&self) -> String {
         let first_author = self.authors.first()
-            .map(|name| name.split_whitespace().last().unwrap_or("Unknown"))
-            .unwrap_or("Unknown");
+            .map(|name| PersonName::parse(name).key_surname())
+            .filter(|surname| !surname.is_empty())
+            .unwrap_or_else(|| "Unknown".to_string());
             
         let year = self.year.map(|y| y.to_string()).unwrap_or_else(|| "YYYY".to_string());
         
@@ -85,6 +937,7 @@ mod tests {
             arxiv_id: Some("2301.12345".to_string()),
             categories: vec!["hep-th".to_string(), "hep-ph".to_string()],
             year: Some(2023),
+            ..Default::default()
         };
 
         assert_eq!(paper.id, "123456");
@@ -104,13 +957,14 @@ mod tests {
             inspire_id: Some("789012".to_string()),
             categories: vec!["hep-th".to_string()],
             year: Some(2023),
+            ..Default::default()
         };
 
         let bibtex = reference.to_bibtex();
         
         assert!(bibtex.contains("@article{"));
         assert!(bibtex.contains("title = {A Study of Quantum Field Theory}"));
-        assert!(bibtex.contains("author = {John Doe and Jane Smith}"));
+        assert!(bibtex.contains("author = {Doe, John and Smith, Jane}"));
         assert!(bibtex.contains("year = {2023}"));
         assert!(bibtex.contains("eprint = {2301.12345}"));
         assert!(bibtex.contains("archivePrefix = {arXiv}"));
@@ -126,6 +980,7 @@ mod tests {
             inspire_id: None,
             categories: vec![],
             year: None,
+            ..Default::default()
         };
 
         let bibtex = reference.to_bibtex();
@@ -137,6 +992,242 @@ mod tests {
         assert!(!bibtex.contains("eprint ="));
     }
 
+    #[test]
+    fn test_reference_to_bibtex_with_options() {
+        let reference = Reference {
+            title: "A Study of Quantum Field Theory".to_string(),
+            inspire_id: Some("789012".to_string()),
+            categories: vec!["hep-th".to_string(), "gr-qc".to_string()],
+            ..Default::default()
+        };
+        assert!(!reference.to_bibtex().contains("keywords ="));
+
+        let options = BibtexOptions {
+            keywords: true,
+            url: true,
+            note: Some("Reviewed for chapter 3".to_string()),
+            ..Default::default()
+        };
+        let bibtex = reference.to_bibtex_with(&options);
+        assert!(bibtex.contains("  keywords = {hep-th, gr-qc},\n"));
+        assert!(bibtex.contains("  url = {https://inspirehep.net/literature/789012},\n"));
+        assert!(bibtex.contains("  note = {Reviewed for chapter 3},\n"));
+
+        let reference = Reference { abstract_text: Some("We find 95% agreement.".to_string()), ..reference };
+        assert!(!reference.to_bibtex_with(&options).contains("abstract ="));
+        let options = BibtexOptions { include_abstract: true, ..options };
+        assert!(reference.to_bibtex_with(&options).contains("  abstract = {We find 95\\% agreement.},\n"));
+
+        let tagged = Reference { tags: vec!["to-read".to_string()], ..reference.clone() };
+        assert!(tagged.to_bibtex().contains("  keywords = {to-read},\n"));
+        assert!(tagged.to_bibtex_with(&options).contains("  keywords = {hep-th, gr-qc, to-read},\n"));
+
+        let preprint = Reference { arxiv_id: Some("2301.12345".to_string()), ..Default::default() };
+        assert_eq!(preprint.url(), Some("https://arxiv.org/abs/2301.12345".to_string()));
+
+        let pinned = Reference { arxiv_version: Some(2), ..preprint };
+        assert!(pinned.to_bibtex().contains("  eprint = {2301.12345},\n"));
+        let options = BibtexOptions { pin_versions: true, ..Default::default() };
+        assert!(pinned.to_bibtex_with(&options).contains("  eprint = {2301.12345v2},\n"));
+
+        let retracted = Reference { retracted: true, ..pinned };
+        assert!(retracted.to_bibtex().contains("  note = {RETRACTED},\n"));
+        let options = BibtexOptions { note: Some("Chapter 3".to_string()), ..Default::default() };
+        let bibtex = retracted.to_bibtex_with(&options);
+        assert!(bibtex.contains("  note = {RETRACTED. Chapter 3},\n"));
+        let entry = &bibtex::parse(&bibtex).unwrap()[0];
+        assert!(entry.to_reference().retracted);
+    }
+
+    #[test]
+    fn test_bibtex_dates() {
+        let reference = Reference {
+            title: "Dated".to_string(),
+            year: Some(2023),
+            date: PartialDate::parse("2023-01-15"),
+            ..Default::default()
+        };
+        assert!(!reference.to_bibtex().contains("month ="));
+        let options = BibtexOptions { month: true, ..Default::default() };
+        let bibtex = reference.to_bibtex_with(&options);
+        assert!(bibtex.contains("  year = {2023},\n  month = jan,\n"));
+        assert!(!bibtex.contains("date ="));
+        assert_eq!(bibtex::parse(&bibtex).unwrap()[0].to_reference().date, PartialDate::parse("2023-01"));
+
+        let options = BibtexOptions { biblatex: true, ..options };
+        let biblatex = reference.to_bibtex_with(&options);
+        assert!(biblatex.contains("  date = {2023-01-15},\n"));
+        assert!(!biblatex.contains("year =") && !biblatex.contains("month ="));
+        let parsed = bibtex::parse(&biblatex).unwrap()[0].to_reference();
+        assert_eq!((parsed.year, parsed.date), (Some(2023), reference.date));
+
+        // Without a known date biblatex output falls back to `year`
+        let undated = Reference { date: None, ..reference };
+        assert!(undated.to_bibtex_with(&options).contains("  year = {2023},\n"));
+    }
+
+    #[test]
+    fn test_thesis_to_bibtex() {
+        let reference = Reference {
+            title: "Aspects of Holography".to_string(),
+            authors: vec!["Jane Smith".to_string()],
+            document_type: vec!["thesis".to_string()],
+            thesis_info: Some(ThesisInfo {
+                institution: Some("Princeton U.".to_string()),
+                degree_type: Some("phd".to_string()),
+                year: Some(2019),
+            }),
+            ..Default::default()
+        };
+
+        let bibtex = reference.to_bibtex();
+        assert!(bibtex.starts_with("@phdthesis{"));
+        assert!(bibtex.contains("school = {Princeton U.}"));
+        assert!(bibtex.contains("year = {2019}"));
+
+        let masters = Reference {
+            thesis_info: Some(ThesisInfo { degree_type: Some("master".to_string()), ..Default::default() }),
+            ..reference
+        };
+        assert!(masters.to_bibtex().starts_with("@mastersthesis{"));
+    }
+
+    #[test]
+    fn test_conference_paper_to_bibtex() {
+        let reference = Reference {
+            title: "Status of the Experiment".to_string(),
+            authors: vec!["John Doe".to_string()],
+            year: Some(2017),
+            document_type: vec!["conference paper".to_string()],
+            doi: Some("10.22323/1.314.0001".to_string()),
+            publication_info: Some(PublicationInfo {
+                journal_title: Some("PoS".to_string()),
+                journal_volume: Some("EPS-HEP2017".to_string()),
+                cnum: Some("C17-07-05".to_string()),
+                conf_acronym: Some("EPS-HEP2017".to_string()),
+                isbn: Some("9780521670531".to_string()),
+                issn: Some("1824-8039".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let bibtex = reference.to_bibtex();
+        assert!(bibtex.starts_with("@inproceedings{"));
+        assert!(bibtex.contains("booktitle = {EPS-HEP2017}"));
+        assert!(!bibtex.contains("journal ="));
+        assert!(bibtex.contains("doi = {10.22323/1.314.0001}"));
+        assert!(bibtex.contains("  isbn = {9780521670531},\n"));
+        assert!(!bibtex.contains("issn ="));
+    }
+
+    #[test]
+    fn test_journal_article_to_bibtex() {
+        let reference = Reference {
+            title: "Anti de Sitter space and holography".to_string(),
+            authors: vec!["Edward Witten".to_string()],
+            year: Some(1998),
+            document_type: vec!["article".to_string()],
+            publication_info: Some(PublicationInfo {
+                journal_title: Some("Adv.Theor.Math.Phys.".to_string()),
+                journal_volume: Some("2".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let bibtex = reference.to_bibtex();
+        assert!(bibtex.starts_with("@article{"));
+        assert!(bibtex.contains("journal = {Adv.Theor.Math.Phys.}"));
+        assert!(bibtex.contains("volume = {2}"));
+        assert!(!bibtex.contains("pages ="));
+
+        let mut paged = reference.clone();
+        let info = paged.publication_info.as_mut().unwrap();
+        info.page_start = Some("253".to_string());
+        info.page_end = Some("291".to_string());
+        let bibtex = paged.to_bibtex();
+        assert!(bibtex.contains("  pages = {253--291},\n"));
+        let parsed = bibtex::parse(&bibtex).unwrap()[0].to_reference().publication_info.unwrap();
+        assert_eq!((parsed.page_start.as_deref(), parsed.page_end.as_deref()), (Some("253"), Some("291")));
+
+        paged.publication_info.as_mut().unwrap().issn = Some("1095-0761".to_string());
+        let bibtex = paged.to_bibtex();
+        assert!(bibtex.contains("  issn = {1095-0761},\n"));
+        let parsed = bibtex::parse(&bibtex).unwrap()[0].to_reference().publication_info.unwrap();
+        assert_eq!(parsed.issn.as_deref(), Some("1095-0761"));
+    }
+
+    #[test]
+    fn test_normalize_doi() {
+        assert_eq!(normalize_doi("10.1023/A:1026654312961").as_deref(), Some("10.1023/A:1026654312961"));
+        assert_eq!(normalize_doi(" doi:10.1103/PhysRevD.1.1").as_deref(), Some("10.1103/PhysRevD.1.1"));
+        assert_eq!(normalize_doi("https://doi.org/10.1016/0370-2693(86)90001-0").as_deref(), Some("10.1016/0370-2693(86)90001-0"));
+        assert_eq!(normalize_doi("DOI: 10.1088/1475-7516/2022/01/001").as_deref(), Some("10.1088/1475-7516/2022/01/001"));
+        assert_eq!(normalize_doi("hep-th/9711200"), None);
+        assert_eq!(normalize_doi("2301.12345"), None);
+        assert_eq!(normalize_doi("10./x"), None);
+    }
+
+    #[test]
+    fn test_normalize_isbn_and_issn() {
+        assert_eq!(normalize_isbn("978-0-521-67053-1").as_deref(), Some("9780521670531"));
+        assert_eq!(normalize_isbn("ISBN 0-521-67053-5").as_deref(), Some("0521670535"));
+        assert_eq!(normalize_isbn("0-8044-2957-x").as_deref(), Some("080442957X"));
+        assert_eq!(normalize_isbn("978-0-521-67053-2"), None);
+        assert_eq!(normalize_isbn("97805216705"), None);
+        assert_eq!(normalize_isbn("X804429570"), None);
+
+        assert_eq!(normalize_issn("0020-7748").as_deref(), Some("0020-7748"));
+        assert_eq!(normalize_issn("ISSN: 1050124x").as_deref(), Some("1050-124X"));
+        assert_eq!(normalize_issn("0020-7749"), None);
+        assert_eq!(normalize_issn("0020-774"), None);
+    }
+
+    #[test]
+    fn test_publication_pages() {
+        let info = |start: Option<&str>, end: Option<&str>, artid: Option<&str>| PublicationInfo {
+            page_start: start.map(str::to_string),
+            page_end: end.map(str::to_string),
+            artid: artid.map(str::to_string),
+            ..Default::default()
+        };
+        let range = |start: &str, end: &str| Some(Pages::Range(start.to_string(), end.to_string()));
+        let single = |page: &str| Some(Pages::Single(page.to_string()));
+        let artid = |id: &str| Some(Pages::ArticleId(id.to_string()));
+
+        assert_eq!(info(Some("123"), Some("145"), None).pages(), range("123", "145"));
+        assert_eq!(info(Some("123"), Some("45"), None).pages(), range("123", "145"));
+        assert_eq!(info(Some("123\u{2014}145"), None, None).pages(), range("123", "145"));
+        assert_eq!(info(Some("123--145"), None, None).pages(), range("123", "145"));
+        assert_eq!(info(Some("L12"), Some("L15"), None).pages(), range("L12", "L15"));
+        assert_eq!(info(Some("12"), Some("12"), None).pages(), single("12"));
+        assert_eq!(info(Some(" 12 "), Some(""), None).pages(), single("12"));
+        // PRD-style article numbers win over per-article pagination
+        assert_eq!(info(None, None, Some("030001")).pages(), artid("030001"));
+        assert_eq!(info(Some("030001"), None, Some("030001")).pages(), artid("030001"));
+        assert_eq!(info(Some("1"), Some("24"), Some("030001")).pages(), artid("030001"));
+        assert_eq!(info(Some("231"), Some("252"), Some("4")).pages(), range("231", "252"));
+        assert_eq!(info(None, Some("12"), None).pages(), None);
+
+        assert_eq!(Pages::parse("1113--1133").unwrap().format("\u{2013}"), "1113\u{2013}1133");
+        assert_eq!(Pages::parse("e123"), single("e123"));
+        assert_eq!(Pages::parse("  "), None);
+    }
+
+    #[test]
+    fn test_looks_like_review() {
+        let review = vec!["review".to_string()];
+        let article = vec!["article".to_string()];
+
+        assert!(looks_like_review(&review, None, "Anything"));
+        assert!(looks_like_review(&article, Some(5000), "TASI Lectures on Holography"));
+        assert!(looks_like_review(&article, Some(1000), "A Review of Particle Physics"));
+        assert!(!looks_like_review(&article, Some(50), "Lectures on Holography"));
+        assert!(!looks_like_review(&article, None, "Lectures on Holography"));
+        assert!(!looks_like_review(&article, Some(20000), "The Large N limit"));
+    }
+
     #[test]
     fn test_generate_bibtex_key() {
         let reference = Reference {
@@ -146,10 +1237,11 @@ mod tests {
             inspire_id: None,
             categories: vec![],
             year: Some(2023),
+            ..Default::default()
         };
 
         let key = reference.generate_bibtex_key();
-        assert!(key.contains("Doe"));
+        assert!(key.starts_with("vonDoe2023"));
         assert!(key.contains("2023"));
         assert!(key.contains("Quantum"));
         assert!(key.chars().all(|c| c.is_alphanumeric()));
@@ -164,6 +1256,7 @@ mod tests {
             inspire_id: None,
             categories: vec![],
             year: Some(2023),
+            ..Default::default()
         };
 
         let key = reference.generate_bibtex_key();
@@ -172,6 +1265,45 @@ mod tests {
         assert!(key.contains("Anonymous"));
     }
 
+    #[test]
+    fn test_reference_from_paper_uses_texkey() {
+        let paper = Paper {
+            id: "451647".to_string(),
+            title: "The Large N limit of superconformal field theories and supergravity".to_string(),
+            authors: vec!["Maldacena, Juan Martin".to_string()],
+            arxiv_id: Some("hep-th/9711200".to_string()),
+            categories: vec!["Theory-HEP".to_string()],
+            year: Some(1997),
+            texkey: Some("Maldacena:1997re".to_string()),
+            ..Default::default()
+        };
+
+        let reference = Reference::from(paper);
+        assert_eq!(reference.inspire_id, Some("451647".to_string()));
+        assert_eq!(reference.bibtex_key(), "Maldacena:1997re");
+        assert!(reference.to_bibtex().starts_with("@article{Maldacena:1997re,"));
+    }
+
+    #[test]
+    fn test_identifier_priority() {
+        let mut reference = Reference {
+            title: "Some Paper".to_string(),
+            authors: vec!["John Doe".to_string()],
+            arxiv_id: Some("2301.12345".to_string()),
+            inspire_id: Some("789012".to_string()),
+            categories: vec![],
+            year: Some(2023),
+            ..Default::default()
+        };
+        assert_eq!(reference.identifier(), "inspire:789012");
+
+        reference.inspire_id = None;
+        assert_eq!(reference.identifier(), "arxiv:2301.12345");
+
+        reference.arxiv_id = None;
+        assert_eq!(reference.identifier(), "key:Doe2023SomePaper");
+    }
+
     #[test]
     fn test_serialize_deserialize() {
         let paper = Paper {
@@ -181,6 +1313,7 @@ mod tests {
             arxiv_id: Some("2301.12345".to_string()),
             categories: vec!["hep-th".to_string()],
             year: Some(2023),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&paper).unwrap();
@@ -193,4 +1326,26 @@ mod tests {
         assert_eq!(paper.categories, deserialized.categories);
         assert_eq!(paper.year, deserialized.year);
     }
+
+    #[test]
+    fn test_field_sources() {
+        let mut reference = Reference {
+            title: "Test Paper".to_string(),
+            year: Some(2023),
+            contexts: vec!["as shown in [1]".to_string()],
+            ..Default::default()
+        };
+        assert!(!serde_json::to_string(&reference).unwrap().contains("_source"));
+
+        reference.sources.contexts_source = Some(DataSource::SemanticScholar);
+        reference.mark_sources(DataSource::InspireReference);
+        let json = serde_json::to_value(&reference).unwrap();
+        assert_eq!(json["title_source"], "inspire_reference");
+        assert_eq!(json["year_source"], "inspire_reference");
+        assert_eq!(json["contexts_source"], "semantic_scholar");
+        assert!(json.get("doi_source").is_none());
+
+        let deserialized: Reference = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.sources, reference.sources);
+    }
 }
@@ -0,0 +1,153 @@
+/// A person's name split into BibTeX's four parts; particles such as `von`
+/// are recognized by their lowercase first letter, as BibTeX does
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PersonName {
+    /// Given names, e.g. `Juan Martin`
+    pub first: String,
+    /// Lowercase particles before the surname, e.g. `van der`
+    pub von: String,
+    /// Surname, e.g. `Maldacena`
+    pub last: String,
+    /// Suffix, e.g. `Jr.`
+    pub jr: String,
+}
+
+impl PersonName {
+    /// Parse "Last, First", "von Last, Jr., First" or "First von Last"
+    pub fn parse(name: &str) -> Self {
+        let parts: Vec<&str> = name.split(',').map(str::trim).collect();
+        match parts.as_slice() {
+            [whole] => Self::parse_first_last(whole),
+            [surname, first] => Self { first: first.to_string(), ..Self::parse_surname(surname) },
+            [surname, jr, first, ..] => Self {
+                first: first.to_string(),
+                jr: jr.to_string(),
+                ..Self::parse_surname(surname)
+            },
+            [] => Self::default(),
+        }
+    }
+
+    /// "First von Last": the surname is the last word, plus any particles before it
+    fn parse_first_last(name: &str) -> Self {
+        let words: Vec<&str> = name.split_whitespace().collect();
+        let Some((last, rest)) = words.split_last() else { return Self::default() };
+
+        let von_start = rest.iter().position(|word| is_particle(word)).unwrap_or(rest.len());
+        let von_end = rest.iter().rposition(|word| is_particle(word)).map_or(von_start, |i| i + 1);
+        Self {
+            first: rest[..von_start].join(" "),
+            von: rest[von_start..von_end].join(" "),
+            last: rest[von_end..].iter().chain(std::iter::once(last)).copied().collect::<Vec<_>>().join(" "),
+            jr: String::new(),
+        }
+    }
+
+    /// "von Last": leading particles, then the surname (always at least one word)
+    fn parse_surname(surname: &str) -> Self {
+        let words: Vec<&str> = surname.split_whitespace().collect();
+        let von_len = words.iter()
+            .take(words.len().saturating_sub(1))
+            .take_while(|word| is_particle(word))
+            .count();
+        Self {
+            von: words[..von_len].join(" "),
+            last: words[von_len..].join(" "),
+            ..Self::default()
+        }
+    }
+
+    /// Surname with its particles, e.g. `van der Berg`
+    pub fn full_surname(&self) -> String {
+        join_nonempty(&[&self.von, &self.last], " ")
+    }
+
+    /// "von Last, Jr., First", the unambiguous BibTeX form
+    pub fn last_first(&self) -> String {
+        join_nonempty(&[&self.full_surname(), &self.jr, &self.first], ", ")
+    }
+
+    /// "First von Last, Jr." as written in prose
+    pub fn first_last(&self) -> String {
+        let name = join_nonempty(&[&self.first, &self.full_surname()], " ");
+        join_nonempty(&[&name, &self.jr], ", ")
+    }
+
+    /// Initials of the given names, e.g. `J. M.` (hyphenated names keep the hyphen: `J.-P.`)
+    pub fn initials(&self) -> String {
+        self.first.split_whitespace()
+            .map(|word| {
+                word.split('-')
+                    .filter_map(|part| part.chars().next())
+                    .map(|c| format!("{}.", c))
+                    .collect::<Vec<_>>()
+                    .join("-")
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Surname as used in generated citation keys: particles kept, spaces and
+    /// punctuation dropped (`van der Berg` becomes `vanderBerg`)
+    pub fn key_surname(&self) -> String {
+        self.full_surname().chars().filter(|c| c.is_alphanumeric()).collect()
+    }
+}
+
+/// A particle is a word starting (after any apostrophe) with a lowercase letter, like `von` or `'t`
+fn is_particle(word: &str) -> bool {
+    word.chars().find(|c| c.is_alphabetic()).is_some_and(char::is_lowercase)
+}
+
+fn join_nonempty(parts: &[&str], separator: &str) -> String {
+    parts.iter().filter(|part| !part.is_empty()).copied().collect::<Vec<_>>().join(separator)
+}
+
+/// Normalize a name to "von Last, First"
+pub fn to_last_first(name: &str) -> String {
+    PersonName::parse(name).last_first()
+}
+
+/// Normalize a name to "First von Last"
+pub fn to_first_last(name: &str) -> String {
+    PersonName::parse(name).first_last()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_forms() {
+        let expected = PersonName {
+            first: "John".to_string(),
+            von: "von".to_string(),
+            last: "Doe".to_string(),
+            jr: String::new(),
+        };
+        assert_eq!(PersonName::parse("John von Doe"), expected);
+        assert_eq!(PersonName::parse("von Doe, John"), expected);
+
+        let name = PersonName::parse("Maldacena, Juan Martin");
+        assert_eq!((name.first.as_str(), name.last.as_str()), ("Juan Martin", "Maldacena"));
+
+        let name = PersonName::parse("King, Jr., Martin Luther");
+        assert_eq!(name.jr, "Jr.");
+        assert_eq!(name.first_last(), "Martin Luther King, Jr.");
+
+        let name = PersonName::parse("Gerard 't Hooft");
+        assert_eq!(name.von, "'t");
+        assert_eq!(name.key_surname(), "tHooft");
+
+        assert_eq!(PersonName::parse("Plato"), PersonName { last: "Plato".to_string(), ..Default::default() });
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(to_last_first("Ludwig van der Berg"), "van der Berg, Ludwig");
+        assert_eq!(to_first_last("van der Berg, Ludwig"), "Ludwig van der Berg");
+        assert_eq!(to_last_first("Maldacena, Juan Martin"), "Maldacena, Juan Martin");
+        assert_eq!(PersonName::parse("Witten, Edward").key_surname(), "Witten");
+        assert_eq!(PersonName::parse("Derendinger, Jean-Pierre Paul").initials(), "J.-P. P.");
+    }
+}
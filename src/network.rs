@@ -1,94 +1,365 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use anyhow::Result;
 use serde::{Serialize, Deserialize};
-use log::{info, debug};
+use serde_json::Value;
+use log::{info, debug, warn};
+use tokio_util::sync::CancellationToken;
 
 use crate::api::InspireClient;
-use crate::models::Paper;
+use crate::commands;
+use crate::error::ApiError;
+use crate::filter::IdentifierFilter;
+use crate::funding;
+use crate::journals;
+pub use crate::author::h_index;
+use crate::models::{DataSource, Paper, Reference, EXPORT_SCHEMA_VERSION};
+use crate::progress::{self, Progress, SharedObserver};
+use crate::report::Table;
 
+/// Per-author statistics computed within a citation network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthorStats {
+    pub author: String,
+    /// Number of papers in the network with this author
+    pub papers: usize,
+    /// Citations received from other papers in the network
+    pub citations: usize,
+    /// h-index counting only citations within the network
+    pub h_index: usize,
+}
+
+/// Per-institution statistics computed within a citation network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstitutionStats {
+    pub institution: String,
+    /// Number of papers in the network with an author at this institution
+    pub papers: usize,
+    /// Citations those papers received from other papers in the network
+    pub citations: usize,
+}
+
+/// Per-country statistics computed within a citation network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CountryStats {
+    /// ISO 3166-1 country code
+    pub country: String,
+    /// Number of papers in the network with an author in this country
+    pub papers: usize,
+    /// Of those, papers with authors in other countries too
+    pub international_papers: usize,
+    /// Other countries on those papers with the number of papers shared, most frequent first
+    pub partners: Vec<(String, usize)>,
+}
+
+/// Settings for `CitationNetwork::build_with_options`
+#[derive(Clone)]
+pub struct NetworkBuildOptions {
+    /// Depth of the citation network
+    pub depth: u32,
+    /// Expand only this many references of each paper at the next depth,
+    /// most cited first (list order when citation counts are unknown)
+    pub max_per_level: Option<usize>,
+    /// References left out of the network and not expanded
+    pub identifiers: IdentifierFilter,
+    /// Stops the build at the next request; the papers found so far are kept
+    pub cancel: CancellationToken,
+    /// Receives progress after each request
+    pub observer: Option<SharedObserver>,
+}
+
+impl Default for NetworkBuildOptions {
+    fn default() -> Self {
+        Self {
+            depth: 1,
+            max_per_level: None,
+            identifiers: IdentifierFilter::default(),
+            cancel: CancellationToken::new(),
+            observer: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for NetworkBuildOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NetworkBuildOptions")
+            .field("depth", &self.depth)
+            .field("max_per_level", &self.max_per_level)
+            .field("identifiers", &self.identifiers)
+            .field("cancel", &self.cancel)
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
+}
+
+/// How a network build ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildStatus {
+    Complete,
+    /// Cancelled through the options' token; the network holds what was built so far
+    Cancelled,
+}
+
+/// A network build that failed partway, with the papers found before the failure
+#[derive(Debug, thiserror::Error)]
+#[error("network build failed after finding {} papers: {source}", .network.paper_count())]
+pub struct PartialBuildError {
+    /// The partial network, with `build_error` set
+    pub network: CitationNetwork,
+    #[source]
+    pub source: anyhow::Error,
+}
+
+/// How a citation edge was found.
+///
+/// Edges always point from the citing to the cited paper, in `citations` as in
+/// every export; the kind only says which side's record they were read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    /// Read from the citing paper's reference list
+    #[default]
+    Cites,
+    /// Found among the papers citing the cited paper; carries no reference labels
+    CitedBy,
+}
+
+impl EdgeKind {
+    /// Name used in exports: `cites` or `cited_by`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EdgeKind::Cites => "cites",
+            EdgeKind::CitedBy => "cited_by",
+        }
+    }
+}
+
+/// What is known about one citation edge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EdgeInfo {
+    /// Number of reference entries of the citing paper resolving to the cited record
+    pub weight: u32,
+    /// Labels of those entries in the citing paper (e.g. `42` for `[42]`), or
+    /// their 1-based positions in its reference list when unlabeled
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// How the edge was found; `cites` in networks saved before kinds were recorded
+    #[serde(default)]
+    pub kind: EdgeKind,
+}
+
+/// An inconsistency found by `CitationNetwork::validate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkIssue {
+    /// A citation whose citing or cited paper is not in the network
+    DanglingEdge { citing: String, cited: String },
+    /// A reverse-citation entry without the matching citation
+    OrphanedReverseCitation { cited: String, citing: String },
+    /// A citation without the matching reverse-citation entry
+    MissingReverseCitation { citing: String, cited: String },
+    /// A paper stored under another ID, or an ID listed twice among a paper's citations
+    DuplicateId { id: String },
+}
+
+impl std::fmt::Display for NetworkIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkIssue::DanglingEdge { citing, cited } => {
+                write!(f, "citation {} -> {} points outside the network", citing, cited)
+            }
+            NetworkIssue::OrphanedReverseCitation { cited, citing } => {
+                write!(f, "{} is listed as citing {} but has no such citation", citing, cited)
+            }
+            NetworkIssue::MissingReverseCitation { citing, cited } => {
+                write!(f, "{} cites {} but is not listed among its citing papers", citing, cited)
+            }
+            NetworkIssue::DuplicateId { id } => write!(f, "duplicate paper ID {}", id),
+        }
+    }
+}
+
+/// Papers and the citations between them.
+///
+/// Maps are ordered by paper ID so that serialized networks and exports are
+/// identical from run to run and diff cleanly.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CitationNetwork {
-    pub papers: HashMap<String, Paper>,
-    pub citations: HashMap<String, Vec<String>>, // paper_id -> [referenced_paper_ids]
-    pub reverse_citations: HashMap<String, Vec<String>>, // paper_id -> [citing_paper_ids]
+    pub papers: BTreeMap<String, Paper>,
+    pub citations: BTreeMap<String, Vec<String>>, // paper_id -> [referenced_paper_ids]
+    pub reverse_citations: BTreeMap<String, Vec<String>>, // paper_id -> [citing_paper_ids]
+    /// citing paper_id -> referenced paper_id -> edge details; absent in older saved networks
+    #[serde(default)]
+    pub edge_info: BTreeMap<String, BTreeMap<String, EdgeInfo>>,
+    /// Fewest citation steps from the seed paper (depth 0) at which each paper
+    /// was found; absent in older saved networks
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub depths: BTreeMap<String, u32>,
+    /// Records whose references could not be fetched during the build, with
+    /// the last error; their citations are missing from the network
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub failed_records: BTreeMap<String, String>,
+    /// Why the build stopped early, for a network saved after a failed build;
+    /// it then holds only the papers found before the failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_error: Option<String>,
+    /// Papers the build started from; serialized as `"is_seed": true` on
+    /// their entries in `papers`
+    #[serde(skip)]
+    pub seeds: BTreeSet<String>,
+}
+
+impl Default for CitationNetwork {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CitationNetwork {
     pub fn new() -> Self {
         Self {
-            papers: HashMap::new(),
-            citations: HashMap::new(),
-            reverse_citations: HashMap::new(),
+            papers: BTreeMap::new(),
+            citations: BTreeMap::new(),
+            reverse_citations: BTreeMap::new(),
+            edge_info: BTreeMap::new(),
+            depths: BTreeMap::new(),
+            failed_records: BTreeMap::new(),
+            build_error: None,
+            seeds: BTreeSet::new(),
         }
     }
     
     /// Build citation network starting from a paper with given depth
     pub async fn build(&mut self, client: &InspireClient, arxiv_id: &str, depth: u32) -> Result<()> {
+        let options = NetworkBuildOptions { depth, ..Default::default() };
+        self.build_with_options(client, arxiv_id, &options).await?;
+        Ok(())
+    }
+    
+    /// Build citation network starting from a paper, stopping early if cancelled.
+    ///
+    /// Records that cannot be fetched are skipped, but an unreachable server
+    /// ends the build with an error; the network then keeps what was found.
+    pub async fn build_with_options(&mut self, client: &InspireClient, arxiv_id: &str, options: &NetworkBuildOptions) -> Result<BuildStatus> {
+        let depth = options.depth;
+        let cancel = &options.cancel;
         let mut to_process = Vec::new();
         let mut processed = HashSet::new();
+        // Records that failed once, tried again after everything else
+        let mut retry_later = Vec::new();
         
         // Get the root paper
-        let root_paper = client.get_paper_by_arxiv(arxiv_id).await?;
+        let root_paper = match cancel.run_until_cancelled(commands::fetch_paper(client, arxiv_id)).await {
+            Some(paper) => paper?,
+            None => {
+                progress::finish(options.observer.as_deref(), &Progress::default());
+                return Ok(BuildStatus::Cancelled);
+            }
+        };
         let root_id = root_paper.id.clone();
         
         info!("Starting network build from paper: {}", root_paper.title);
-        println!("📄 Root paper: {}", root_paper.title);
         
         self.add_paper(root_paper);
+        self.record_depth(&root_id, 0);
+        self.mark_seed(&root_id);
         to_process.push((root_id.clone(), 0));
         
-        let mut processed_count = 0;
+        let mut progress = Progress {
+            papers_discovered: self.papers.len(),
+            requests_completed: 1,
+            stage: "network",
+            ..Default::default()
+        };
+        progress::report(options.observer.as_deref(), &progress);
         
-        while let Some((paper_id, current_depth)) = to_process.pop() {
+        while let Some((paper_id, current_depth)) = to_process.pop().or_else(|| retry_later.pop()) {
             if processed.contains(&paper_id) || current_depth >= depth {
                 continue;
             }
             
             processed.insert(paper_id.clone());
-            processed_count += 1;
             
             debug!("Processing paper at depth {}: {}", current_depth, paper_id);
-            println!("🔍 Processing depth {} (paper {})", current_depth, processed_count);
             
             // Get references for this paper
-            match client.get_paper_references(&paper_id).await {
-                Ok(references) => {
+            let references = match cancel.run_until_cancelled(client.get_paper_references(&paper_id)).await {
+                Some(references) => references,
+                None => {
+                    info!("Network build cancelled. {} papers found so far.", self.papers.len());
+                    progress::finish(options.observer.as_deref(), &progress);
+                    return Ok(BuildStatus::Cancelled);
+                }
+            };
+            progress.requests_completed += 1;
+            progress.current_depth = current_depth;
+            progress.current_item = Some(paper_id.clone());
+            match references {
+                Ok(mut references) => {
+                    // Labels default to the position in the full list, before excluded entries are dropped
+                    for (index, reference) in references.iter_mut().enumerate() {
+                        reference.label.get_or_insert_with(|| (index + 1).to_string());
+                    }
+                    references.retain(|reference| options.identifiers.allows_reference(reference));
                     let mut ref_ids = Vec::new();
                     
+                    // Add to processing queue for next depth level
+                    if current_depth + 1 < depth {
+                        for inspire_id in expansion_candidates(&references, options.max_per_level) {
+                            to_process.push((inspire_id.to_string(), current_depth + 1));
+                        }
+                    }
+                    
                     for reference in references {
                         // Try to find the paper in INSPIRE if we have an ID
                         if let Some(inspire_id) = &reference.inspire_id {
-                            // Convert reference to paper (simplified)
-                            let ref_paper = Paper {
-                                id: inspire_id.clone(),
-                                title: reference.title.clone(),
-                                authors: reference.authors.clone(),
-                                arxiv_id: reference.arxiv_id.clone(),
-                                categories: reference.categories.clone(),
-                                year: reference.year,
-                            };
+                            let ref_paper = Paper::from_reference(inspire_id.clone(), &reference);
+                            let label = reference.label.clone().unwrap_or_default();
                             
                             self.add_paper(ref_paper);
-                            ref_ids.push(inspire_id.clone());
-                            
-                            // Add to processing queue for next depth level
-                            if current_depth + 1 < depth {
-                                to_process.push((inspire_id.clone(), current_depth + 1));
-                            }
+                            self.record_depth(inspire_id, current_depth + 1);
+                            ref_ids.push((inspire_id.clone(), label));
                         }
                     }
                     
-                    self.add_citations(&paper_id, ref_ids);
+                    self.add_labeled_citations(&paper_id, ref_ids);
+                    self.failed_records.remove(&paper_id);
+                    
+                    // Reference lists carry no affiliations or funding; the record
+                    // just fetched for the references does, and is served from the cache
+                    if let Ok(record) = client.get_paper_by_id(&paper_id).await {
+                        if let Some(paper) = self.papers.get_mut(&paper_id) {
+                            paper.affiliations = record.affiliations;
+                            paper.funding = record.funding;
+                        }
+                    }
+                }
+                Err(e) if e.downcast_ref::<ApiError>().is_some_and(ApiError::is_connect) => {
+                    progress::finish(options.observer.as_deref(), &progress);
+                    return Err(e);
                 }
                 Err(e) => {
-                    debug!("Failed to get references for {}: {}", paper_id, e);
+                    let too_large = e.downcast_ref::<ApiError>().is_some_and(ApiError::is_too_large);
+                    if too_large {
+                        warn!("Skipping record {}: {}", paper_id, e);
+                    } else {
+                        debug!("Failed to get references for {}: {}", paper_id, e);
+                    }
+                    // Oversized records stay oversized, and the client stops
+                    // requesting a record once it failed repeatedly
+                    if !too_large && !client.is_record_skipped(&paper_id) {
+                        processed.remove(&paper_id);
+                        retry_later.push((paper_id.clone(), current_depth));
+                    }
+                    self.failed_records.insert(paper_id.clone(), e.to_string());
                 }
             }
+            
+            progress.papers_discovered = self.papers.len();
+            progress::report(options.observer.as_deref(), &progress);
         }
         
-        println!("✅ Network build complete! {} papers processed", self.papers.len());
+        progress::finish(options.observer.as_deref(), &progress);
         info!("Network build complete. {} papers processed.", self.papers.len());
-        Ok(())
+        Ok(BuildStatus::Complete)
     }
     
     /// Add a paper to the network
@@ -96,17 +367,166 @@ impl CitationNetwork {
         self.papers.insert(paper.id.clone(), paper);
     }
     
-    /// Add citation relationships
+    /// Note that a paper was reached `depth` citation steps from the seed,
+    /// keeping the smallest depth seen
+    pub fn record_depth(&mut self, paper_id: &str, depth: u32) {
+        self.depths.entry(paper_id.to_string())
+            .and_modify(|known| *known = (*known).min(depth))
+            .or_insert(depth);
+    }
+    
+    /// Mark a paper as one the network was built from
+    pub fn mark_seed(&mut self, paper_id: &str) {
+        self.seeds.insert(paper_id.to_string());
+    }
+    
+    /// Whether the network was built from this paper
+    pub fn is_seed(&self, paper_id: &str) -> bool {
+        self.seeds.contains(paper_id)
+    }
+    
+    /// Citation steps from the seed paper to a paper, when recorded
+    pub fn depth(&self, paper_id: &str) -> Option<u32> {
+        self.depths.get(paper_id).copied()
+    }
+    
+    /// The papers at most `max_depth` citation steps from the seed, and the
+    /// citations between them. Papers of unknown depth are left out.
+    pub fn trimmed_to_depth(&self, max_depth: u32) -> CitationNetwork {
+        let keep = |id: &String| self.depth(id).is_some_and(|depth| depth <= max_depth);
+        let kept_ids = |ids: &Vec<String>| ids.iter().filter(|id| keep(id)).cloned().collect::<Vec<_>>();
+        
+        let mut network = CitationNetwork::new();
+        for (citing, cited_ids) in self.citations.iter().filter(|(id, _)| keep(id)) {
+            network.citations.insert(citing.clone(), kept_ids(cited_ids));
+            if let Some(edges) = self.edge_info.get(citing) {
+                let edges = edges.iter().filter(|(id, _)| keep(id)).map(|(id, edge)| (id.clone(), edge.clone())).collect();
+                network.edge_info.insert(citing.clone(), edges);
+            }
+        }
+        for (cited, citing_ids) in self.reverse_citations.iter().filter(|(id, _)| keep(id)) {
+            let citing_ids = kept_ids(citing_ids);
+            if !citing_ids.is_empty() {
+                network.reverse_citations.insert(cited.clone(), citing_ids);
+            }
+        }
+        network.papers = self.papers.iter().filter(|(id, _)| keep(id)).map(|(id, paper)| (id.clone(), paper.clone())).collect();
+        network.depths = self.depths.iter().filter(|(id, _)| keep(id)).map(|(id, depth)| (id.clone(), *depth)).collect();
+        network.failed_records = self.failed_records.iter().filter(|(id, _)| keep(id)).map(|(id, error)| (id.clone(), error.clone())).collect();
+        network.build_error = self.build_error.clone();
+        network.seeds = self.seeds.iter().filter(|id| keep(id)).cloned().collect();
+        network
+    }
+    
+    /// Add citation relationships, labelling each entry by its 1-based position.
+    ///
+    /// A record listed several times becomes one edge whose weight counts the entries.
     pub fn add_citations(&mut self, citing_paper_id: &str, referenced_paper_ids: Vec<String>) {
-        self.citations.insert(citing_paper_id.to_string(), referenced_paper_ids.clone());
+        let entries = referenced_paper_ids.into_iter()
+            .enumerate()
+            .map(|(index, id)| (id, (index + 1).to_string()))
+            .collect();
+        self.add_labeled_citations(citing_paper_id, entries);
+    }
+    
+    /// Add citation relationships as (referenced paper ID, reference label) entries.
+    ///
+    /// The entries are the citing paper's reference list: they replace the
+    /// paper's earlier `cites` edges, while `cited_by` edges not in the list are kept.
+    pub fn add_labeled_citations(&mut self, citing_paper_id: &str, entries: Vec<(String, String)>) {
+        let mut unique_ids = Vec::new();
+        let mut edges: BTreeMap<String, EdgeInfo> = BTreeMap::new();
+        for (ref_id, label) in entries {
+            match edges.get_mut(&ref_id) {
+                Some(edge) => {
+                    edge.weight += 1;
+                    edge.labels.push(label);
+                }
+                None => {
+                    edges.insert(ref_id.clone(), EdgeInfo { weight: 1, labels: vec![label], kind: EdgeKind::Cites });
+                    unique_ids.push(ref_id);
+                }
+            }
+        }
         
-        // Update reverse citations
-        for ref_id in referenced_paper_ids {
-            self.reverse_citations
-                .entry(ref_id)
-                .or_insert_with(Vec::new)
-                .push(citing_paper_id.to_string());
+        for ref_id in self.citations.remove(citing_paper_id).unwrap_or_default() {
+            if edges.contains_key(&ref_id) {
+                continue;
+            }
+            if self.edge_kind(citing_paper_id, &ref_id) == EdgeKind::CitedBy {
+                let edge = self.edge_info[citing_paper_id][&ref_id].clone();
+                edges.insert(ref_id.clone(), edge);
+                unique_ids.push(ref_id);
+            } else {
+                self.remove_reverse_citation(&ref_id, citing_paper_id);
+            }
         }
+        for ref_id in &unique_ids {
+            self.add_reverse_citation(ref_id, citing_paper_id);
+        }
+        
+        self.citations.insert(citing_paper_id.to_string(), unique_ids);
+        self.edge_info.insert(citing_paper_id.to_string(), edges);
+    }
+    
+    /// Add papers found citing `cited_paper_id`, as `cited_by` edges pointing
+    /// from each of them to the cited paper. Edges already read from a
+    /// reference list are left as they are.
+    pub fn add_cited_by(&mut self, cited_paper_id: &str, citing_paper_ids: Vec<String>) {
+        for citing in citing_paper_ids {
+            let cited_ids = self.citations.entry(citing.clone()).or_default();
+            if cited_ids.iter().any(|id| id == cited_paper_id) {
+                continue;
+            }
+            cited_ids.push(cited_paper_id.to_string());
+            self.edge_info.entry(citing.clone()).or_default().insert(
+                cited_paper_id.to_string(),
+                EdgeInfo { weight: 1, labels: Vec::new(), kind: EdgeKind::CitedBy },
+            );
+            self.add_reverse_citation(cited_paper_id, &citing);
+        }
+    }
+    
+    fn add_reverse_citation(&mut self, cited_paper_id: &str, citing_paper_id: &str) {
+        let citing_ids = self.reverse_citations.entry(cited_paper_id.to_string()).or_default();
+        if !citing_ids.iter().any(|id| id == citing_paper_id) {
+            citing_ids.push(citing_paper_id.to_string());
+        }
+    }
+    
+    fn remove_reverse_citation(&mut self, cited_paper_id: &str, citing_paper_id: &str) {
+        if let Some(citing_ids) = self.reverse_citations.get_mut(cited_paper_id) {
+            citing_ids.retain(|id| id != citing_paper_id);
+            if citing_ids.is_empty() {
+                self.reverse_citations.remove(cited_paper_id);
+            }
+        }
+    }
+    
+    /// How an edge was found; `cites` for edges of older saved networks
+    pub fn edge_kind(&self, citing_paper_id: &str, cited_paper_id: &str) -> EdgeKind {
+        self.edge_info.get(citing_paper_id)
+            .and_then(|edges| edges.get(cited_paper_id))
+            .map_or(EdgeKind::Cites, |edge| edge.kind)
+    }
+    
+    /// Reference labels of the entries behind an edge; empty for older saved networks
+    pub fn edge_labels(&self, citing_paper_id: &str, cited_paper_id: &str) -> &[String] {
+        self.edge_info.get(citing_paper_id)
+            .and_then(|edges| edges.get(cited_paper_id))
+            .map_or(&[], |edge| edge.labels.as_slice())
+    }
+    
+    /// Weight of the edge from `citing_paper_id` to `cited_paper_id`, 0 without an edge.
+    ///
+    /// Edges of networks saved before weights were recorded count once.
+    pub fn edge_weight(&self, citing_paper_id: &str, cited_paper_id: &str) -> u32 {
+        if let Some(edge) = self.edge_info.get(citing_paper_id).and_then(|edges| edges.get(cited_paper_id)) {
+            return edge.weight;
+        }
+        let cites = self.citations.get(citing_paper_id)
+            .is_some_and(|refs| refs.iter().any(|id| id == cited_paper_id));
+        u32::from(cites)
     }
     
     /// Get the number of papers in the network
@@ -119,17 +539,354 @@ impl CitationNetwork {
         self.papers.values().collect()
     }
     
+    /// Number of papers in the network citing the given paper
+    pub fn citation_count(&self, paper_id: &str) -> usize {
+        self.reverse_citations.get(paper_id).map_or(0, |citing| citing.len())
+    }
+    
+    /// Aggregate per-author statistics, most cited authors first
+    pub fn author_stats(&self) -> Vec<AuthorStats> {
+        let mut counts_by_author: HashMap<&str, Vec<usize>> = HashMap::new();
+        for paper in self.papers.values() {
+            let citations = self.citation_count(&paper.id);
+            let unique_authors: HashSet<&str> = paper.authors.iter().map(|a| a.as_str()).collect();
+            for author in unique_authors {
+                counts_by_author.entry(author).or_default().push(citations);
+            }
+        }
+        
+        let mut stats: Vec<AuthorStats> = counts_by_author.into_iter()
+            .map(|(author, counts)| AuthorStats {
+                author: author.to_string(),
+                papers: counts.len(),
+                citations: counts.iter().sum(),
+                h_index: h_index(&counts),
+            })
+            .collect();
+        
+        stats.sort_by(|a, b| {
+            b.citations.cmp(&a.citations)
+                .then_with(|| b.papers.cmp(&a.papers))
+                .then_with(|| a.author.cmp(&b.author))
+        });
+        stats
+    }
+    
+    /// Per-author statistics as a report table
+    pub fn author_stats_table(&self) -> Table {
+        let mut table = Table::new(&["author", "papers", "citations_in_network", "h_index_in_network"]);
+        for stats in self.author_stats() {
+            table.push_row(vec![
+                stats.author,
+                stats.papers.to_string(),
+                stats.citations.to_string(),
+                stats.h_index.to_string(),
+            ]);
+        }
+        table
+    }
+    
+    /// Aggregate papers per institution, from the affiliations known for each
+    /// paper; institutions with most papers first
+    pub fn institution_stats(&self) -> Vec<InstitutionStats> {
+        let mut stats_by_institution: HashMap<&str, InstitutionStats> = HashMap::new();
+        for paper in self.papers.values() {
+            let citations = self.citation_count(&paper.id);
+            let unique_institutions: HashSet<&str> = paper.affiliations.iter().map(|a| a.as_str()).collect();
+            for institution in unique_institutions {
+                let stats = stats_by_institution.entry(institution).or_insert_with(|| InstitutionStats {
+                    institution: institution.to_string(),
+                    papers: 0,
+                    citations: 0,
+                });
+                stats.papers += 1;
+                stats.citations += citations;
+            }
+        }
+        
+        let mut stats: Vec<InstitutionStats> = stats_by_institution.into_values().collect();
+        stats.sort_by(|a, b| {
+            b.papers.cmp(&a.papers)
+                .then_with(|| b.citations.cmp(&a.citations))
+                .then_with(|| a.institution.cmp(&b.institution))
+        });
+        stats
+    }
+    
+    /// Per-institution statistics as a report table
+    pub fn institution_stats_table(&self) -> Table {
+        let mut table = Table::new(&["institution", "papers", "citations_in_network"]);
+        for stats in self.institution_stats() {
+            table.push_row(vec![
+                stats.institution,
+                stats.papers.to_string(),
+                stats.citations.to_string(),
+            ]);
+        }
+        table
+    }
+    
+    /// Papers per journal as a report table, most frequent first
+    pub fn journal_stats_table(&self) -> Table {
+        journals::journal_table(&journals::journal_stats(self.papers.values().map(|paper| paper.publication_info.as_ref())))
+    }
+    
+    /// Papers per funding agency as a report table, most frequent first
+    pub fn funding_stats_table(&self) -> Table {
+        funding::funding_table(&funding::funding_stats(self.papers.values()))
+    }
+    
+    /// Distinct institutions over the affiliations of all papers
+    pub fn institutions(&self) -> BTreeSet<&str> {
+        self.papers.values()
+            .flat_map(|paper| paper.affiliations.iter().map(|a| a.as_str()))
+            .collect()
+    }
+    
+    /// Countries of each paper with at least one affiliation in `countries`
+    /// (institution name -> country code)
+    fn paper_countries<'a>(&'a self, countries: &'a HashMap<String, String>) -> Vec<BTreeSet<&'a str>> {
+        self.papers.values()
+            .map(|paper| {
+                paper.affiliations.iter()
+                    .filter_map(|institution| countries.get(institution).map(|c| c.as_str()))
+                    .collect::<BTreeSet<&str>>()
+            })
+            .filter(|paper_countries| !paper_countries.is_empty())
+            .collect()
+    }
+    
+    /// Aggregate papers per country and the countries they share papers with,
+    /// given the country of each institution; countries with most papers first
+    pub fn country_stats(&self, countries: &HashMap<String, String>) -> Vec<CountryStats> {
+        let mut stats_by_country: HashMap<&str, (usize, usize, HashMap<&str, usize>)> = HashMap::new();
+        for paper_countries in self.paper_countries(countries) {
+            for &country in &paper_countries {
+                let (papers, international, partners) = stats_by_country.entry(country).or_default();
+                *papers += 1;
+                if paper_countries.len() > 1 {
+                    *international += 1;
+                }
+                for &partner in paper_countries.iter().filter(|&&other| other != country) {
+                    *partners.entry(partner).or_default() += 1;
+                }
+            }
+        }
+        
+        let mut stats: Vec<CountryStats> = stats_by_country.into_iter()
+            .map(|(country, (papers, international_papers, partners))| {
+                let mut partners: Vec<(String, usize)> = partners.into_iter()
+                    .map(|(partner, count)| (partner.to_string(), count))
+                    .collect();
+                partners.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                CountryStats { country: country.to_string(), papers, international_papers, partners }
+            })
+            .collect();
+        stats.sort_by(|a, b| b.papers.cmp(&a.papers).then_with(|| a.country.cmp(&b.country)));
+        stats
+    }
+    
+    /// Papers with a known country, and how many of them have authors in
+    /// more than one country
+    pub fn international_share(&self, countries: &HashMap<String, String>) -> (usize, usize) {
+        let paper_countries = self.paper_countries(countries);
+        let international = paper_countries.iter().filter(|c| c.len() > 1).count();
+        (paper_countries.len(), international)
+    }
+    
+    /// Per-country statistics as a report table, partners written as `DE (3); US (1)`.
+    /// With `sources`, a `country_source` column names the institution records
+    /// each country was read from, e.g. `inspire_institution (CERN; EPFL)`.
+    pub fn country_stats_table(&self, countries: &HashMap<String, String>, sources: bool) -> Table {
+        let mut headers = vec!["country", "papers", "international_papers", "partners"];
+        if sources {
+            headers.push("country_source");
+        }
+        let mut table = Table::new(&headers);
+        for stats in self.country_stats(countries) {
+            let partners = stats.partners.iter()
+                .map(|(partner, count)| format!("{} ({})", partner, count))
+                .collect::<Vec<_>>()
+                .join("; ");
+            let mut row = vec![
+                stats.country.clone(),
+                stats.papers.to_string(),
+                stats.international_papers.to_string(),
+                partners,
+            ];
+            if sources {
+                let mut institutions: Vec<&str> = countries.iter()
+                    .filter(|(_, country)| **country == stats.country)
+                    .map(|(institution, _)| institution.as_str())
+                    .collect();
+                institutions.sort();
+                row.push(format!("{} ({})", DataSource::InspireInstitution.as_str(), institutions.join("; ")));
+            }
+            table.push_row(row);
+        }
+        table
+    }
+    
+    /// Summary statistics (papers, citation links, most cited paper) as a report table
+    pub fn stats_table(&self) -> Table {
+        let links: usize = self.citations.values().map(|refs| refs.len()).sum();
+        let mut table = Table::new(&["metric", "value"]);
+        table.push_row(vec!["papers".to_string(), self.paper_count().to_string()]);
+        table.push_row(vec!["citation_links".to_string(), links.to_string()]);
+        table.push_row(vec!["expanded_papers".to_string(), self.citations.len().to_string()]);
+        
+        let most_cited = self.papers.values()
+            .max_by(|a, b| {
+                self.citation_count(&a.id).cmp(&self.citation_count(&b.id))
+                    .then_with(|| b.id.cmp(&a.id))
+            });
+        if let Some(paper) = most_cited {
+            table.push_row(vec![
+                "most_cited".to_string(),
+                format!("{} ({} citations in network)", paper.title, self.citation_count(&paper.id)),
+            ]);
+        }
+        table
+    }
+    
+    /// Find a paper by INSPIRE record ID or arXiv ID
+    pub fn find_paper(&self, id: &str) -> Option<&Paper> {
+        self.papers.get(id)
+            .or_else(|| self.papers.values().find(|p| p.arxiv_id.as_deref() == Some(id)))
+    }
+    
+    /// Shortest chain of citations leading from one paper to another, as record IDs.
+    ///
+    /// Each paper in the returned path cites the next one; `None` if no chain exists.
+    pub fn shortest_path(&self, from_id: &str, to_id: &str) -> Option<Vec<String>> {
+        let mut previous: HashMap<&str, &str> = HashMap::new();
+        let mut queue = std::collections::VecDeque::from([from_id]);
+        let mut visited = HashSet::from([from_id]);
+        
+        while let Some(current) = queue.pop_front() {
+            if current == to_id {
+                let mut path = vec![current.to_string()];
+                let mut node = current;
+                while let Some(&prev) = previous.get(node) {
+                    path.push(prev.to_string());
+                    node = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for next in self.citations.get(current).into_iter().flatten() {
+                if visited.insert(next.as_str()) {
+                    previous.insert(next.as_str(), current);
+                    queue.push_back(next.as_str());
+                }
+            }
+        }
+        None
+    }
+    
+    /// Check that citations and reverse citations mirror each other and only
+    /// refer to papers in the network
+    pub fn validate(&self) -> Vec<NetworkIssue> {
+        let mut issues = Vec::new();
+        
+        for (key, paper) in &self.papers {
+            if *key != paper.id {
+                issues.push(NetworkIssue::DuplicateId { id: paper.id.clone() });
+            }
+        }
+        
+        for (citing, cited_ids) in &self.citations {
+            let mut seen = HashSet::new();
+            for cited in cited_ids {
+                if !seen.insert(cited) {
+                    issues.push(NetworkIssue::DuplicateId { id: cited.clone() });
+                }
+                if !self.papers.contains_key(citing) || !self.papers.contains_key(cited) {
+                    issues.push(NetworkIssue::DanglingEdge { citing: citing.clone(), cited: cited.clone() });
+                }
+                let has_reverse = self.reverse_citations.get(cited)
+                    .is_some_and(|citing_ids| citing_ids.contains(citing));
+                if !has_reverse {
+                    issues.push(NetworkIssue::MissingReverseCitation { citing: citing.clone(), cited: cited.clone() });
+                }
+            }
+        }
+        
+        for (cited, citing_ids) in &self.reverse_citations {
+            for citing in citing_ids {
+                let has_citation = self.citations.get(citing)
+                    .is_some_and(|refs| refs.contains(cited));
+                if !has_citation {
+                    issues.push(NetworkIssue::OrphanedReverseCitation { cited: cited.clone(), citing: citing.clone() });
+                }
+            }
+        }
+        
+        issues
+    }
+    
     /// Convert network to JSON string
     pub fn to_json(&self) -> Result<String> {
-        Ok(serde_json::to_string_pretty(self)?)
+        let mut value = serde_json::to_value(self)?;
+        value["schema_version"] = EXPORT_SCHEMA_VERSION.into();
+        for seed in &self.seeds {
+            if let Some(paper) = value["papers"].get_mut(seed).and_then(Value::as_object_mut) {
+                paper.insert("is_seed".to_string(), true.into());
+            }
+        }
+        Ok(serde_json::to_string_pretty(&value)?)
+    }
+    
+    /// Load a network previously saved with `to_json` (see `input::parse_network`
+    /// for schema-version checking)
+    pub fn from_json(json: &str) -> Result<Self> {
+        Self::from_value(serde_json::from_str(json)?)
+    }
+    
+    /// Read a network from its `to_json` form. Networks saved before seeds
+    /// were marked take their depth-0 papers as seeds.
+    pub fn from_value(value: Value) -> Result<Self> {
+        let seeds: BTreeSet<String> = value["papers"].as_object().into_iter().flatten()
+            .filter(|(_, paper)| paper["is_seed"].as_bool() == Some(true))
+            .map(|(id, _)| id.clone())
+            .collect();
+        let mut network: CitationNetwork = serde_json::from_value(value)?;
+        network.seeds = if seeds.is_empty() {
+            network.depths.iter().filter(|(_, depth)| **depth == 0).map(|(id, _)| id.clone()).collect()
+        } else {
+            seeds
+        };
+        Ok(network)
     }
 }
 
+/// Record IDs of the references to expand at the next depth: all of them, or
+/// the `max_per_level` most cited, ties (and unknown counts) in list order
+fn expansion_candidates(references: &[Reference], max_per_level: Option<usize>) -> Vec<&str> {
+    let mut candidates: Vec<&Reference> = references.iter()
+        .filter(|r| r.inspire_id.is_some())
+        .collect();
+    if let Some(max) = max_per_level {
+        candidates.sort_by_key(|r| std::cmp::Reverse(r.citation_count));
+        candidates.truncate(max);
+    }
+    candidates.iter().filter_map(|r| r.inspire_id.as_deref()).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::models::Paper;
 
+    fn create_authored_paper(id: &str, authors: &[&str]) -> Paper {
+        Paper {
+            id: id.to_string(),
+            title: format!("Paper {}", id),
+            authors: authors.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
     fn create_test_paper(id: &str, title: &str, arxiv_id: Option<&str>) -> Paper {
         Paper {
             id: id.to_string(),
@@ -138,9 +895,103 @@ mod tests {
             arxiv_id: arxiv_id.map(|s| s.to_string()),
             categories: vec!["hep-th".to_string()],
             year: Some(2023),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_build_keeps_partial_network() {
+        let mut network = CitationNetwork::new();
+        network.add_paper(create_test_paper("1", "Already found", None));
+        
+        let options = NetworkBuildOptions::default();
+        options.cancel.cancel();
+        
+        let status = network.build_with_options(&InspireClient::new(), "2301.12345", &options).await.unwrap();
+        assert_eq!(status, BuildStatus::Cancelled);
+        assert_eq!(network.paper_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failing_records_are_retried_once_then_skipped() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Record 1 cites 2 and 3; record 3 always fails
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let failing_requests = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = std::sync::Arc::clone(&failing_requests);
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = [0; 1024];
+                let read = stream.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                let reference = |id: u32| serde_json::json!({"record": {"$ref": format!("https://inspirehep.net/api/literature/{}", id)}});
+                let (status, body) = if request.starts_with("GET /api/literature?") {
+                    ("200 OK", serde_json::json!({"hits": {"hits": [{"metadata": {"control_number": 1, "titles": [{"title": "Root"}]}}]}}))
+                } else if request.starts_with("GET /api/literature/1 ") {
+                    ("200 OK", serde_json::json!({"metadata": {"control_number": 1, "titles": [{"title": "Root"}], "references": [reference(2), reference(3)]}}))
+                } else if request.starts_with("GET /api/literature/2 ") {
+                    ("200 OK", serde_json::json!({"metadata": {"control_number": 2, "titles": [{"title": "Fine"}]}}))
+                } else {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    ("500 Internal Server Error", serde_json::json!({}))
+                };
+                let body = body.to_string();
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let client = InspireClient::new().with_base_urls(&[format!("http://{}/api", addr)]);
+        let options = NetworkBuildOptions { depth: 2, ..Default::default() };
+        let mut network = CitationNetwork::new();
+        let status = network.build_with_options(&client, "2301.12345", &options).await.unwrap();
+
+        assert_eq!(status, BuildStatus::Complete);
+        assert_eq!(network.paper_count(), 3);
+        assert_eq!(network.depths, BTreeMap::from([("1".to_string(), 0), ("2".to_string(), 1), ("3".to_string(), 1)]));
+        assert_eq!(network.failed_records.keys().collect::<Vec<_>>(), ["3"]);
+        assert!(network.failed_records["3"].contains("500"));
+        assert_eq!(failing_requests.load(Ordering::SeqCst), crate::api::RECORD_FAILURE_LIMIT as usize);
+        assert!(client.is_record_skipped("3"));
+        assert!(client.get_paper_by_id("3").await.unwrap_err().to_string().contains("skipped"));
+        assert_eq!(failing_requests.load(Ordering::SeqCst), crate::api::RECORD_FAILURE_LIMIT as usize);
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        finished: std::sync::Mutex<Vec<Progress>>,
+    }
+
+    impl crate::progress::ProgressObserver for RecordingObserver {
+        fn on_progress(&self, _progress: &Progress) {}
+
+        fn on_finish(&self, progress: &Progress) {
+            self.finished.lock().unwrap().push(progress.clone());
         }
     }
 
+    #[tokio::test]
+    async fn test_observer_sees_cancelled_build_finish() {
+        let observer = std::sync::Arc::new(RecordingObserver::default());
+        let options = NetworkBuildOptions {
+            observer: Some(observer.clone()),
+            ..Default::default()
+        };
+        options.cancel.cancel();
+        
+        let mut network = CitationNetwork::new();
+        network.build_with_options(&InspireClient::new(), "2301.12345", &options).await.unwrap();
+        
+        assert_eq!(*observer.finished.lock().unwrap(), vec![Progress::default()]);
+    }
+
     #[test]
     fn test_new_network() {
         let network = CitationNetwork::new();
@@ -196,4 +1047,270 @@ mod tests {
         let parsed: CitationNetwork = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.papers.len(), 1);
     }
+
+    #[test]
+    fn test_shortest_path() {
+        let mut network = CitationNetwork::new();
+        for id in ["1", "2", "3", "4"] {
+            network.add_paper(create_test_paper(id, &format!("Paper {}", id), None));
+        }
+        network.papers.get_mut("4").unwrap().arxiv_id = Some("2301.12345".to_string());
+        network.add_citations("1", vec!["2".to_string(), "3".to_string()]);
+        network.add_citations("2", vec!["4".to_string()]);
+        network.add_citations("3", vec!["2".to_string()]);
+        
+        assert_eq!(network.shortest_path("1", "4"), Some(vec!["1".to_string(), "2".to_string(), "4".to_string()]));
+        assert_eq!(network.shortest_path("1", "1"), Some(vec!["1".to_string()]));
+        assert_eq!(network.shortest_path("4", "1"), None);
+        assert_eq!(network.find_paper("2301.12345").map(|p| p.id.as_str()), Some("4"));
+        
+        let stats = network.stats_table().to_markdown();
+        assert!(stats.contains("| citation_links | 4 |"));
+        assert!(stats.contains("Paper 2 (2 citations in network)"));
+    }
+
+    #[test]
+    fn test_repeated_references_become_weighted_edges() {
+        let mut network = CitationNetwork::new();
+        network.add_citations("1", vec!["2".to_string(), "3".to_string(), "2".to_string()]);
+        
+        assert_eq!(network.citations["1"], ["2", "3"]);
+        assert_eq!(network.reverse_citations["2"], ["1"]);
+        assert_eq!(network.edge_weight("1", "2"), 2);
+        assert_eq!(network.edge_weight("1", "3"), 1);
+        assert_eq!(network.edge_weight("2", "1"), 0);
+        
+        assert_eq!(network.edge_labels("1", "2"), ["1", "3"]);
+        
+        network.add_labeled_citations("4", vec![("2".to_string(), "12".to_string())]);
+        assert_eq!(network.edge_labels("4", "2"), ["12"]);
+        
+        network.edge_info.clear();
+        assert_eq!(network.edge_weight("1", "2"), 1);
+        assert!(network.edge_labels("1", "2").is_empty());
+    }
+
+    #[test]
+    fn test_cited_by_edges_point_from_citing_paper() {
+        let mut network = CitationNetwork::new();
+        network.add_cited_by("1", vec!["2".to_string(), "3".to_string()]);
+        assert_eq!(network.citations["2"], ["1"]);
+        assert_eq!(network.reverse_citations["1"], ["2", "3"]);
+        assert_eq!(network.edge_kind("2", "1"), EdgeKind::CitedBy);
+
+        // Paper 2's reference list adds labels to the known edge and a new edge,
+        // without listing paper 2 twice among the papers citing paper 1
+        network.add_labeled_citations("2", vec![("1".to_string(), "7".to_string()), ("4".to_string(), "8".to_string())]);
+        assert_eq!(network.citations["2"], ["1", "4"]);
+        assert_eq!(network.edge_kind("2", "1"), EdgeKind::Cites);
+        assert_eq!(network.edge_labels("2", "1"), ["7"]);
+        assert_eq!(network.reverse_citations["1"], ["2", "3"]);
+
+        // A citation search cannot downgrade an edge read from a reference list,
+        // and a reference list keeps edges found only by citation search
+        network.add_cited_by("1", vec!["2".to_string()]);
+        assert_eq!(network.edge_kind("2", "1"), EdgeKind::Cites);
+        network.add_cited_by("5", vec!["2".to_string()]);
+        network.add_labeled_citations("2", vec![("1".to_string(), "7".to_string())]);
+        assert_eq!(network.citations["2"], ["1", "5"]);
+        assert!(!network.reverse_citations.contains_key("4"));
+        assert_eq!(network.edge_kind("2", "5"), EdgeKind::CitedBy);
+
+        for id in ["1", "2", "3", "5"] {
+            network.add_paper(create_test_paper(id, "Paper", None));
+        }
+        assert!(network.validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut network = CitationNetwork::new();
+        network.add_paper(create_test_paper("1", "Citing", None));
+        network.add_paper(create_test_paper("2", "Cited", None));
+        network.add_citations("1", vec!["2".to_string()]);
+        assert!(network.validate().is_empty());
+        
+        network.add_citations("2", vec!["3".to_string()]);
+        network.reverse_citations.entry("1".to_string()).or_default().push("2".to_string());
+        network.papers.insert("4".to_string(), create_test_paper("1", "Copy", None));
+        
+        network.citations.get_mut("1").unwrap().push("4".to_string());
+        
+        let issues = network.validate();
+        assert_eq!(issues.len(), 4);
+        assert!(issues.contains(&NetworkIssue::MissingReverseCitation { citing: "1".to_string(), cited: "4".to_string() }));
+        assert!(issues.contains(&NetworkIssue::DanglingEdge { citing: "2".to_string(), cited: "3".to_string() }));
+        assert!(issues.contains(&NetworkIssue::OrphanedReverseCitation { cited: "1".to_string(), citing: "2".to_string() }));
+        assert!(issues.contains(&NetworkIssue::DuplicateId { id: "1".to_string() }));
+    }
+
+    #[test]
+    fn test_expansion_candidates() {
+        let reference = |id: Option<&str>, citations: Option<u32>| Reference {
+            inspire_id: id.map(|s| s.to_string()),
+            citation_count: citations,
+            ..Default::default()
+        };
+        let references = vec![
+            reference(Some("1"), None),
+            reference(None, Some(900)),
+            reference(Some("2"), Some(10)),
+            reference(Some("3"), None),
+            reference(Some("4"), Some(50)),
+        ];
+        
+        assert_eq!(expansion_candidates(&references, None), ["1", "2", "3", "4"]);
+        assert_eq!(expansion_candidates(&references, Some(3)), ["4", "2", "1"]);
+        assert!(expansion_candidates(&references, Some(0)).is_empty());
+    }
+
+    #[test]
+    fn test_repeated_exports_are_identical() {
+        let build = |ids: &[&str]| {
+            let mut network = CitationNetwork::new();
+            for id in ids {
+                network.add_paper(create_test_paper(id, &format!("Paper {}", id), None));
+            }
+            network.add_citations("1", vec!["3".to_string(), "2".to_string(), "5".to_string()]);
+            network.add_citations("2", vec!["4".to_string()]);
+            network
+        };
+        let network = build(&["1", "2", "3", "4", "5"]);
+        let shuffled = build(&["5", "3", "1", "4", "2"]);
+        
+        let json = network.to_json().unwrap();
+        assert_eq!(json, shuffled.to_json().unwrap());
+        assert_eq!(json, CitationNetwork::from_json(&json).unwrap().to_json().unwrap());
+        #[cfg(feature = "export-graph")]
+        {
+            assert_eq!(crate::graph::to_graphml(&network), crate::graph::to_graphml(&shuffled));
+            assert_eq!(crate::graph::to_dot(&network), crate::graph::to_dot(&shuffled));
+        }
+    }
+
+    #[test]
+    fn test_author_stats() {
+        let mut network = CitationNetwork::new();
+        network.add_paper(create_authored_paper("root", &["Alice"]));
+        network.add_paper(create_authored_paper("a", &["Bob", "Carol"]));
+        network.add_paper(create_authored_paper("b", &["Bob"]));
+        network.add_citations("root", vec!["a".to_string(), "b".to_string()]);
+        network.add_citations("b", vec!["a".to_string()]);
+
+        let stats = network.author_stats();
+        assert_eq!(stats[0], AuthorStats { author: "Bob".to_string(), papers: 2, citations: 3, h_index: 1 });
+        assert_eq!(stats[1], AuthorStats { author: "Carol".to_string(), papers: 1, citations: 2, h_index: 1 });
+        assert_eq!(stats[2], AuthorStats { author: "Alice".to_string(), papers: 1, citations: 0, h_index: 0 });
+
+        let csv = network.author_stats_table().to_csv();
+        assert!(csv.starts_with("author,papers,citations_in_network,h_index_in_network\n"));
+        assert!(csv.contains("Bob,2,3,1\n"));
+    }
+
+    #[test]
+    fn test_institution_stats() {
+        let affiliated = |id: &str, affiliations: &[&str]| Paper {
+            affiliations: affiliations.iter().map(|a| a.to_string()).collect(),
+            ..create_authored_paper(id, &[])
+        };
+        let mut network = CitationNetwork::new();
+        network.add_paper(affiliated("root", &["DESY"]));
+        network.add_paper(affiliated("a", &["CERN", "DESY"]));
+        network.add_paper(affiliated("b", &["CERN"]));
+        network.add_paper(affiliated("c", &[]));
+        network.add_citations("root", vec!["a".to_string(), "b".to_string()]);
+        network.add_citations("b", vec!["a".to_string()]);
+
+        let stats = network.institution_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0], InstitutionStats { institution: "CERN".to_string(), papers: 2, citations: 3 });
+        assert_eq!(stats[1], InstitutionStats { institution: "DESY".to_string(), papers: 2, citations: 2 });
+
+        let csv = network.institution_stats_table().to_csv();
+        assert_eq!(csv, "institution,papers,citations_in_network\nCERN,2,3\nDESY,2,2\n");
+    }
+
+    #[test]
+    fn test_country_stats() {
+        let affiliated = |id: &str, affiliations: &[&str]| Paper {
+            affiliations: affiliations.iter().map(|a| a.to_string()).collect(),
+            ..create_authored_paper(id, &[])
+        };
+        let mut network = CitationNetwork::new();
+        network.add_paper(affiliated("a", &["CERN", "DESY"]));
+        network.add_paper(affiliated("b", &["CERN", "Geneva U."]));
+        network.add_paper(affiliated("c", &["DESY", "Fermilab", "Unknown U."]));
+        network.add_paper(affiliated("d", &["Unknown U."]));
+        let countries: HashMap<String, String> = [("CERN", "CH"), ("Geneva U.", "CH"), ("DESY", "DE"), ("Fermilab", "US")]
+            .into_iter()
+            .map(|(institution, country)| (institution.to_string(), country.to_string()))
+            .collect();
+
+        let stats = network.country_stats(&countries);
+        let rows: Vec<_> = stats.iter().map(|s| (s.country.as_str(), s.papers, s.international_papers)).collect();
+        assert_eq!(rows, [("CH", 2, 1), ("DE", 2, 2), ("US", 1, 1)]);
+        assert_eq!(stats[1].partners, vec![("CH".to_string(), 1), ("US".to_string(), 1)]);
+        assert_eq!(network.international_share(&countries), (3, 2));
+        assert_eq!(network.institutions().len(), 5);
+
+        let csv = network.country_stats_table(&countries, false).to_csv();
+        assert!(csv.starts_with("country,papers,international_papers,partners\nCH,2,1,DE (1)\n"));
+        let csv = network.country_stats_table(&countries, true).to_csv();
+        assert!(csv.starts_with("country,papers,international_papers,partners,country_source\nCH,2,1,DE (1),inspire_institution (CERN; Geneva U.)\n"));
+    }
+
+    #[test]
+    fn test_trimmed_to_depth() {
+        let mut network = CitationNetwork::new();
+        for id in ["1", "2", "3", "4"] {
+            network.add_paper(create_test_paper(id, "Paper", None));
+        }
+        network.add_citations("1", vec!["2".to_string(), "3".to_string()]);
+        network.add_citations("2", vec!["3".to_string(), "4".to_string()]);
+        // 3 is reached directly and through 2; the shorter route counts
+        for (id, depth) in [("1", 0), ("2", 1), ("3", 2), ("3", 1), ("4", 2)] {
+            network.record_depth(id, depth);
+        }
+        assert_eq!(network.depth("3"), Some(1));
+
+        let trimmed = network.trimmed_to_depth(1);
+        assert_eq!(trimmed.papers.keys().collect::<Vec<_>>(), ["1", "2", "3"]);
+        assert_eq!(trimmed.citations["2"], ["3"]);
+        assert_eq!(trimmed.edge_weight("2", "4"), 0);
+        assert!(!trimmed.reverse_citations.contains_key("4"));
+        assert!(trimmed.validate().is_empty());
+        assert_eq!(network.trimmed_to_depth(0).paper_count(), 1);
+
+        // Without recorded depths nothing is known to be close enough
+        assert_eq!(CitationNetwork { depths: BTreeMap::new(), ..network }.trimmed_to_depth(5).paper_count(), 0);
+    }
+
+    #[test]
+    fn test_from_json_round_trip() {
+        let mut network = CitationNetwork::new();
+        network.add_paper(create_authored_paper("a", &["Bob"]));
+        network.add_citations("a", vec!["b".to_string()]);
+
+        network.mark_seed("a");
+
+        let json = network.to_json().unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["papers"]["a"]["is_seed"], true);
+        let parsed = CitationNetwork::from_json(&json).unwrap();
+        assert_eq!(parsed.paper_count(), 1);
+        assert_eq!(parsed.citation_count("b"), 1);
+        assert!(parsed.is_seed("a"));
+    }
+
+    #[test]
+    fn test_seeds_of_older_exports_come_from_depths() {
+        let mut network = CitationNetwork::new();
+        network.add_paper(create_authored_paper("a", &["Bob"]));
+        network.add_paper(create_authored_paper("b", &["Ann"]));
+        network.record_depth("a", 0);
+        network.record_depth("b", 1);
+
+        let parsed = CitationNetwork::from_json(&network.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.seeds, BTreeSet::from(["a".to_string()]));
+    }
 }
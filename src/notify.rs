@@ -0,0 +1,164 @@
+use std::path::PathBuf;
+use anyhow::{Result, anyhow};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::atom::{self, AtomEntry};
+use crate::models::Paper;
+
+/// Where watch results are sent, configured under `[notifications]`
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct NotificationConfig {
+    /// URL receiving a JSON payload (`paper`, `new_citations`) via POST
+    pub webhook_url: Option<String>,
+
+    /// Slack or Mattermost incoming-webhook URL receiving a text message
+    pub slack_webhook_url: Option<String>,
+
+    /// Atom feed file new citations are added to, for feed readers to poll
+    pub atom_file: Option<PathBuf>,
+}
+
+impl NotificationConfig {
+    /// Whether any notifier is configured
+    pub fn is_enabled(&self) -> bool {
+        self.webhook_url.is_some() || self.slack_webhook_url.is_some() || self.atom_file.is_some()
+    }
+}
+
+/// Send new citations of a watched paper to every configured notifier.
+///
+/// Each notifier is attempted even if an earlier one fails; the first error is returned.
+pub async fn notify_new_citations(config: &NotificationConfig, paper: &Paper, new_citations: &[Paper]) -> Result<()> {
+    if new_citations.is_empty() {
+        return Ok(());
+    }
+
+    let client = reqwest::Client::new();
+    let mut first_error = None;
+
+    if let Some(url) = &config.webhook_url {
+        let payload = json!({
+            "paper": paper,
+            "new_citations": new_citations,
+        });
+        if let Err(e) = post_json(&client, url, &payload).await {
+            warn!("Webhook notification failed: {}", e);
+            first_error.get_or_insert(e);
+        }
+    }
+
+    if let Some(url) = &config.slack_webhook_url {
+        let payload = json!({ "text": chat_message(paper, new_citations) });
+        if let Err(e) = post_json(&client, url, &payload).await {
+            warn!("Chat notification failed: {}", e);
+            first_error.get_or_insert(e);
+        }
+    }
+
+    if let Some(path) = &config.atom_file {
+        if let Err(e) = append_to_feed(path, paper, new_citations) {
+            warn!("Atom feed update failed: {}", e);
+            first_error.get_or_insert(e);
+        }
+    }
+
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+async fn post_json(client: &reqwest::Client, url: &str, payload: &serde_json::Value) -> Result<()> {
+    let response = client.post(url).json(payload).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Notification to {} failed: {}", url, response.status()));
+    }
+    info!("Sent notification to {}", url);
+    Ok(())
+}
+
+/// Message text for Slack/Mattermost (both accept the same markdown-like links)
+pub fn chat_message(paper: &Paper, new_citations: &[Paper]) -> String {
+    let mut message = format!("{} new citation(s) of *{}*:\n", new_citations.len(), paper.title);
+    for citing in new_citations {
+        message.push_str(&format!(
+            "• <https://inspirehep.net/literature/{}|{}>\n",
+            citing.id, citing.title
+        ));
+    }
+    message
+}
+
+/// Add new citations to an Atom feed file, creating it if needed
+pub fn append_to_feed(path: &std::path::Path, paper: &Paper, new_citations: &[Paper]) -> Result<()> {
+    let updated = atom::now_rfc3339();
+    let entries: Vec<AtomEntry> = new_citations.iter()
+        .map(|citing| AtomEntry::from_paper(citing, &updated))
+        .collect();
+
+    let existing = if path.exists() { Some(std::fs::read_to_string(path)?) } else { None };
+    let content = match existing.as_deref().and_then(|xml| atom::prepend_entries(xml, &updated, &entries)) {
+        Some(content) => content,
+        None => atom::render_feed(
+            &format!("https://inspirehep.net/literature/{}#citations", paper.id),
+            &format!("New citations of {}", paper.title),
+            &updated,
+            &entries,
+        ),
+    };
+
+    std::fs::write(path, content)?;
+    info!("Added {} entries to {}", entries.len(), path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn create_paper(id: &str, title: &str) -> Paper {
+        Paper {
+            id: id.to_string(),
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_chat_message() {
+        let message = chat_message(
+            &create_paper("1", "My Paper"),
+            &[create_paper("2", "Citing Paper")],
+        );
+
+        assert!(message.starts_with("1 new citation(s) of *My Paper*:"));
+        assert!(message.contains("<https://inspirehep.net/literature/2|Citing Paper>"));
+    }
+
+    #[test]
+    fn test_append_to_feed() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("citations.atom");
+        let paper = create_paper("1", "My Paper");
+
+        append_to_feed(&path, &paper, &[create_paper("2", "First")]).unwrap();
+        append_to_feed(&path, &paper, &[create_paper("3", "Second")]).unwrap();
+
+        let feed = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(feed.matches("<feed").count(), 1);
+        assert_eq!(feed.matches("<entry>").count(), 2);
+        assert!(feed.contains("<title>New citations of My Paper</title>"));
+    }
+
+    #[tokio::test]
+    async fn test_notify_without_config_is_noop() {
+        let config = NotificationConfig::default();
+        assert!(!config.is_enabled());
+
+        let result = notify_new_citations(&config, &create_paper("1", "Paper"), &[create_paper("2", "Other")]).await;
+        assert!(result.is_ok());
+    }
+}
@@ -1,22 +1,104 @@
-use std::path::PathBuf;
-use clap::ValueEnum;
+use std::path::{Path, PathBuf};
 use tokio::fs::File;
-use tokio::io::{AsyncWriteExt, BufWriter};
-use anyhow::Result;
-use serde_json;
+use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
+use anyhow::{Context, Result, anyhow};
+use log::{info, warn};
 
-use crate::models::Reference;
+use crate::atom::{self, AtomEntry};
+use crate::aging::ReferenceAging;
+use crate::author::AuthorMetrics;
+use crate::bibliography::{self, BibMerge};
+use crate::bibtex;
+use crate::citation::{self, CitationStyle};
+use crate::hooks;
+use crate::journals::{self, JournalStats};
+use crate::names;
+#[cfg(feature = "pdf")]
+use crate::pdfcheck::PdfReport;
+use crate::sections::SectionGroup;
+use crate::models::{BibtexOptions, Conference, DatasetListing, Institution, Reference};
+#[cfg(feature = "network-analysis")]
+use crate::models::Paper;
+#[cfg(feature = "export-graph")]
+use crate::graph;
+#[cfg(feature = "network-analysis")]
 use crate::network::CitationNetwork;
+use crate::report::{ReportFormat, Table};
+#[cfg(feature = "network-analysis")]
+use crate::similarity::{BibliographyMap, SimilarPaper};
 
-#[derive(Debug, Clone, ValueEnum, serde::Serialize, serde::Deserialize, PartialEq)]
+/// Output format (`--format`, `default_format` in the config). Configs written
+/// before the names were lowercased (`"Json"`) are still accepted.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 pub enum OutputFormat {
+    #[serde(alias = "Json")]
     Json,
+    #[serde(alias = "Bibtex")]
     Bibtex,
+    #[serde(alias = "Atom")]
+    Atom,
+    /// GraphML (citation networks only)
+    #[serde(alias = "Graphml")]
+    Graphml,
+    /// Graphviz DOT (citation networks only)
+    #[serde(alias = "Dot")]
+    Dot,
+    /// Cytoscape.js JSON (citation networks only)
+    #[serde(alias = "Cytoscape")]
+    Cytoscape,
+    /// Formatted citations in the style chosen with `--style`
+    #[serde(alias = "Citations")]
+    Citations,
+    /// A LaTeX `thebibliography` environment of `\bibitem` entries
+    #[serde(alias = "Bibitem")]
+    Bibitem,
+    /// An HTML list of citations in the style chosen with `--style`
+    #[serde(alias = "Html")]
+    Html,
+}
+
+/// Order of entries in BibTeX output (`--bib-sort`)
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum BibSort {
+    /// By citation key
+    Key,
+    /// Oldest first
+    Year,
+    /// By first author's surname, then year
+    Author,
+    /// As returned by INSPIRE (the order of the paper's reference list)
+    #[default]
+    Appearance,
+}
+
+impl BibSort {
+    /// Sort references in place; ties keep their original order
+    pub fn sort(self, references: &mut [Reference]) {
+        match self {
+            BibSort::Key => references.sort_by_cached_key(|r| r.bibtex_key()),
+            BibSort::Year => references.sort_by_key(|r| r.year.unwrap_or(u32::MAX)),
+            BibSort::Author => references.sort_by_cached_key(|r| {
+                let author = r.authors.first()
+                    .map(|name| names::to_last_first(name).to_lowercase())
+                    .unwrap_or_default();
+                (author, r.year.unwrap_or(u32::MAX))
+            }),
+            BibSort::Appearance => {}
+        }
+    }
 }
 
 pub struct OutputWriter {
     format: OutputFormat,
     output_path: Option<PathBuf>,
+    citation_style: CitationStyle,
+    bib_sort: BibSort,
+    bibtex_options: BibtexOptions,
+    post_output_hook: Option<String>,
 }
 
 impl OutputWriter {
@@ -24,21 +106,116 @@ impl OutputWriter {
         Self {
             format,
             output_path,
+            citation_style: CitationStyle::default(),
+            bib_sort: BibSort::default(),
+            bibtex_options: BibtexOptions::default(),
+            post_output_hook: None,
         }
     }
     
+    /// Write in another format, keeping the destination and options
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+    
+    /// Style used by `OutputFormat::Citations` and `OutputFormat::Bibitem`
+    pub fn with_citation_style(mut self, style: CitationStyle) -> Self {
+        self.citation_style = style;
+        self
+    }
+    
+    /// Order of BibTeX entries
+    pub fn with_bib_sort(mut self, sort: BibSort) -> Self {
+        self.bib_sort = sort;
+        self
+    }
+    
+    /// Optional fields added to BibTeX entries
+    pub fn with_bibtex_options(mut self, options: BibtexOptions) -> Self {
+        self.bibtex_options = options;
+        self
+    }
+    
+    /// Shell command run after each write to a file (`[hooks] post_output`)
+    pub fn with_post_output_hook(mut self, hook: Option<String>) -> Self {
+        self.post_output_hook = hook;
+        self
+    }
+    
     /// Write references to output
     pub async fn write_references(&self, references: &[Reference]) -> Result<()> {
         let content = match self.format {
             OutputFormat::Json => self.format_json(references)?,
             OutputFormat::Bibtex => self.format_bibtex(references),
+            OutputFormat::Atom => self.format_atom(references),
+            OutputFormat::Citations | OutputFormat::Bibitem | OutputFormat::Html => self.format_citations(references),
+            OutputFormat::Graphml | OutputFormat::Dot | OutputFormat::Cytoscape => return Err(self.unsupported("reference lists")),
         };
         
         self.write_content(&content).await
     }
     
-    /// Write citation network to output
+    /// Add references as BibTeX entries to the end of the output file instead
+    /// of overwriting it. Works already in the file are skipped and keys taken
+    /// by a different entry are renamed; the returned merge lists both.
+    pub async fn append_references(&self, references: &[Reference]) -> Result<BibMerge> {
+        if self.format != OutputFormat::Bibtex {
+            return Err(self.unsupported("appending to a bibliography"));
+        }
+        let path = self.output_path.as_ref()
+            .ok_or_else(|| anyhow!("Appending needs an --output file"))?;
+        
+        let mut content = if path.exists() { tokio::fs::read_to_string(path).await? } else { String::new() };
+        let existing: Vec<Reference> = bibtex::parse(&content)
+            .with_context(|| format!("Could not parse {}", path.display()))?
+            .iter()
+            .map(|entry| entry.to_reference())
+            .collect();
+        
+        let merge = bibliography::merge_into(&existing, references.to_vec());
+        if !merge.added.is_empty() {
+            if !content.is_empty() && !content.ends_with("\n\n") {
+                content.push_str(if content.ends_with('\n') { "\n" } else { "\n\n" });
+            }
+            content.push_str(&self.format_bibtex(&merge.added));
+            self.write_content(&content).await?;
+        }
+        Ok(merge)
+    }
+    
+    /// Write references grouped by the section of the citing paper, each group
+    /// under a heading (a `%` comment in BibTeX)
+    pub async fn write_sections(&self, groups: &[SectionGroup]) -> Result<()> {
+        let content = match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(groups)?,
+            OutputFormat::Bibtex => groups.iter()
+                .map(|group| format!("% {}\n\n{}", group.title, self.format_bibtex(&group.references)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            OutputFormat::Citations => groups.iter()
+                .map(|group| format!("## {}\n\n{}", group.title, self.format_citations(&group.references)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            OutputFormat::Html => groups.iter()
+                .map(|group| format!("<h2>{}</h2>\n{}", atom::escape_xml(&group.title), self.format_citations(&group.references)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            OutputFormat::Atom | OutputFormat::Bibitem | OutputFormat::Graphml | OutputFormat::Dot | OutputFormat::Cytoscape => {
+                return Err(self.unsupported("references grouped by section"))
+            }
+        };
+        
+        self.write_content(&content).await
+    }
+    
+    /// Write citation network to output, warning about any integrity issues first
+    #[cfg(feature = "network-analysis")]
     pub async fn write_network(&self, network: &CitationNetwork) -> Result<()> {
+        for issue in network.validate() {
+            log::warn!("Network integrity: {}", issue);
+        }
+        
         let content = match self.format {
             OutputFormat::Json => network.to_json()?,
             OutputFormat::Bibtex => {
@@ -51,11 +228,163 @@ impl OutputWriter {
                     .collect::<Vec<_>>()
                     .join("\n")
             }
+            OutputFormat::Atom => {
+                let papers: Vec<&Paper> = network.get_all_papers();
+                self.format_paper_atom("Citation network", papers.into_iter())
+            }
+            #[cfg(feature = "export-graph")]
+            OutputFormat::Graphml => graph::to_graphml(network),
+            #[cfg(feature = "export-graph")]
+            OutputFormat::Dot => graph::to_dot(network),
+            #[cfg(feature = "export-graph")]
+            OutputFormat::Cytoscape => graph::to_cytoscape(network)?,
+            #[cfg(not(feature = "export-graph"))]
+            OutputFormat::Graphml | OutputFormat::Dot | OutputFormat::Cytoscape => {
+                return Err(anyhow!("{:?} output needs the `export-graph` feature", self.format));
+            }
+            OutputFormat::Citations | OutputFormat::Bibitem | OutputFormat::Html => {
+                let references: Vec<Reference> = network.get_all_papers().into_iter()
+                    .map(|paper| Reference::from(paper.clone()))
+                    .collect();
+                self.format_citations(&references)
+            }
+        };
+        
+        self.write_content(&content).await
+    }
+    
+    /// Write a ranked list of related papers to output
+    #[cfg(feature = "network-analysis")]
+    pub async fn write_similar(&self, similar: &[SimilarPaper]) -> Result<()> {
+        let content = match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(similar)?,
+            OutputFormat::Bibtex => {
+                let references: Vec<Reference> = similar.iter()
+                    .map(|s| Reference::from(s.paper.clone()))
+                    .collect();
+                self.format_bibtex(&references)
+            }
+            OutputFormat::Atom => self.format_paper_atom("Related papers", similar.iter().map(|s| &s.paper)),
+            OutputFormat::Citations | OutputFormat::Bibitem | OutputFormat::Html => {
+                let references: Vec<Reference> = similar.iter()
+                    .map(|s| Reference::from(s.paper.clone()))
+                    .collect();
+                self.format_citations(&references)
+            }
+            OutputFormat::Graphml | OutputFormat::Dot | OutputFormat::Cytoscape => return Err(self.unsupported("related papers")),
         };
         
         self.write_content(&content).await
     }
     
+    /// Write a bibliography map as JSON, or grouped Markdown or CSV in the given format
+    #[cfg(feature = "network-analysis")]
+    pub async fn write_bibliography_map(&self, map: &BibliographyMap, table: Option<ReportFormat>) -> Result<()> {
+        let content = match table {
+            Some(format) => map.render(format),
+            None => serde_json::to_string_pretty(map)?,
+        };
+        self.write_content(&content).await
+    }
+    
+    /// Write the datasets associated with papers to output
+    pub async fn write_datasets(&self, listings: &[DatasetListing]) -> Result<()> {
+        let content = match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(listings)?,
+            OutputFormat::Bibtex => self.format_dataset_bibtex(listings),
+            OutputFormat::Atom => {
+                let updated = atom::now_rfc3339();
+                let entries: Vec<AtomEntry> = listings.iter()
+                    .map(|listing| AtomEntry {
+                        id: format!("https://inspirehep.net/literature/{}", listing.inspire_id),
+                        title: format!("Data for: {}", listing.title),
+                        link: listing.datasets.iter()
+                            .find_map(|d| d.url.clone())
+                            .unwrap_or_else(|| format!("https://inspirehep.net/literature/{}", listing.inspire_id)),
+                        updated: updated.clone(),
+                        authors: Vec::new(),
+                        summary: Some(listing.datasets.iter()
+                            .filter_map(|d| d.doi.clone().or_else(|| d.url.clone()))
+                            .collect::<Vec<_>>()
+                            .join(", ")),
+                        rights: None,
+                    })
+                    .collect();
+                atom::render_feed("urn:reference_tool:datasets", "Datasets", &updated, &entries)
+            }
+            OutputFormat::Graphml
+            | OutputFormat::Dot
+            | OutputFormat::Cytoscape
+            | OutputFormat::Citations
+            | OutputFormat::Bibitem
+            | OutputFormat::Html => return Err(self.unsupported("datasets")),
+        };
+        
+        self.write_content(&content).await
+    }
+    
+    /// Write an author's citation metrics as JSON, or as tables in the given format
+    pub async fn write_author_metrics(&self, metrics: &AuthorMetrics, table: Option<ReportFormat>) -> Result<()> {
+        let content = match table {
+            Some(format) => metrics.render(format),
+            None => serde_json::to_string_pretty(metrics)?,
+        };
+        self.write_content(&content).await
+    }
+    
+    /// Write a reference aging report as JSON, or as tables in the given format
+    pub async fn write_reference_aging(&self, aging: &ReferenceAging, table: Option<ReportFormat>) -> Result<()> {
+        let content = match table {
+            Some(format) => aging.render(format),
+            None => serde_json::to_string_pretty(aging)?,
+        };
+        self.write_content(&content).await
+    }
+    
+    /// Write per-journal counts as JSON, or as a table in the given format
+    pub async fn write_journal_stats(&self, stats: &[JournalStats], table: Option<ReportFormat>) -> Result<()> {
+        let content = match table {
+            Some(format) => journals::journal_table(stats).render(format),
+            None => serde_json::to_string_pretty(stats)?,
+        };
+        self.write_content(&content).await
+    }
+    
+    /// Write an institution record as JSON
+    pub async fn write_institution(&self, institution: &Institution) -> Result<()> {
+        self.write_content(&serde_json::to_string_pretty(institution)?).await
+    }
+    
+    /// Write conference records as JSON
+    pub async fn write_conferences(&self, conferences: &[Conference]) -> Result<()> {
+        self.write_content(&serde_json::to_string_pretty(conferences)?).await
+    }
+    
+    /// Write the result of checking a PDF's bibliography as JSON
+    #[cfg(feature = "pdf")]
+    pub async fn write_pdf_report(&self, report: &PdfReport) -> Result<()> {
+        self.write_content(&serde_json::to_string_pretty(report)?).await
+    }
+    
+    /// Write a statistics table to output
+    pub async fn write_table(&self, table: &Table, format: ReportFormat) -> Result<()> {
+        self.write_content(&table.render(format)).await
+    }
+    
+    /// Error for a format that does not apply to what is being written
+    fn unsupported(&self, what: &str) -> anyhow::Error {
+        anyhow!("{:?} output is not available for {}", self.format, what)
+    }
+    
+    /// Format references as a citation list, a `thebibliography` environment or an HTML list
+    fn format_citations(&self, references: &[Reference]) -> String {
+        match self.format {
+            OutputFormat::Bibitem => citation::format_thebibliography(references, self.citation_style),
+            OutputFormat::Html => citation::format_html(references, self.citation_style),
+            _ => citation::format_citations(references, self.citation_style),
+        }
+    }
+    
     /// Format references as JSON
     fn format_json(&self, references: &[Reference]) -> Result<String> {
         Ok(serde_json::to_string_pretty(references)?)
@@ -63,8 +392,54 @@ impl OutputWriter {
     
     /// Format references as BibTeX
     fn format_bibtex(&self, references: &[Reference]) -> String {
+        let mut references = references.to_vec();
+        self.bib_sort.sort(&mut references);
         references.iter()
-            .map(|r| r.to_bibtex())
+            .map(|r| r.to_bibtex_with(&self.bibtex_options))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    
+    /// Format references as an Atom feed
+    fn format_atom(&self, references: &[Reference]) -> String {
+        let updated = atom::now_rfc3339();
+        let entries: Vec<AtomEntry> = references.iter()
+            .map(|r| AtomEntry::from_reference(r, &updated))
+            .collect();
+        atom::render_feed("urn:reference_tool:references", "References", &updated, &entries)
+    }
+    
+    /// Format papers as an Atom feed
+    #[cfg(feature = "network-analysis")]
+    fn format_paper_atom<'a>(&self, title: &str, papers: impl Iterator<Item = &'a Paper>) -> String {
+        let updated = atom::now_rfc3339();
+        let entries: Vec<AtomEntry> = papers
+            .map(|p| AtomEntry::from_paper(p, &updated))
+            .collect();
+        let feed_id = format!("urn:reference_tool:{}", title.to_lowercase().replace(' ', "-"));
+        atom::render_feed(&feed_id, title, &updated, &entries)
+    }
+    
+    /// Format datasets with a DOI as BibTeX `@misc` entries
+    fn format_dataset_bibtex(&self, listings: &[DatasetListing]) -> String {
+        listings.iter()
+            .flat_map(|listing| listing.datasets.iter().map(move |dataset| (listing, dataset)))
+            .filter_map(|(listing, dataset)| {
+                let doi = dataset.doi.as_ref()?;
+                let key: String = doi.chars()
+                    .filter(|c| c.is_alphanumeric() || matches!(c, '.' | '-' | ':' | '_'))
+                    .collect();
+                
+                let mut bibtex = format!("@misc{{{},\n", key);
+                bibtex.push_str(&format!("  title = {{Data for: {}}},\n", listing.title));
+                bibtex.push_str(&format!("  howpublished = {{{}}},\n", dataset.source));
+                bibtex.push_str(&format!("  doi = {{{}}},\n", doi));
+                if let Some(url) = &dataset.url {
+                    bibtex.push_str(&format!("  url = {{{}}},\n", url));
+                }
+                bibtex.push_str("}\n");
+                Some(bibtex)
+            })
             .collect::<Vec<_>>()
             .join("\n")
     }
@@ -77,7 +452,8 @@ impl OutputWriter {
                 let mut writer = BufWriter::new(file);
                 writer.write_all(content.as_bytes()).await?;
                 writer.flush().await?;
-                println!("Output written to: {}", path.display());
+                info!("Output written to: {}", path.display());
+                self.run_post_output_hook(path).await;
             }
             None => {
                 print!("{}", content);
@@ -85,6 +461,89 @@ impl OutputWriter {
         }
         Ok(())
     }
+    
+    /// Run the post-output hook, if any, on a written file; failures only warn
+    async fn run_post_output_hook(&self, path: &Path) {
+        if let Some(hook) = &self.post_output_hook {
+            let format = format!("{:?}", self.format).to_lowercase();
+            if let Err(e) = hooks::run_post_output(hook, path, &format).await {
+                warn!("Post-output hook failed: {}", e);
+            }
+        }
+    }
+    
+    /// Start writing references batch by batch as they arrive, for result sets
+    /// too large to hold in memory. Supports JSON, BibTeX, citation lists and
+    /// HTML; BibTeX entries keep the order they arrive in.
+    pub async fn stream_references(&self) -> Result<ReferenceStream<'_>> {
+        match self.format {
+            OutputFormat::Json | OutputFormat::Bibtex | OutputFormat::Citations | OutputFormat::Html => {}
+            _ => return Err(self.unsupported("streamed reference lists")),
+        }
+        if self.format == OutputFormat::Bibtex && self.bib_sort != BibSort::Appearance {
+            warn!("BibTeX entries are streamed in the order they arrive; --bib-sort is ignored");
+        }
+        
+        let out: Box<dyn AsyncWrite + Unpin + Send> = match &self.output_path {
+            Some(path) => Box::new(BufWriter::new(File::create(path).await?)),
+            None => Box::new(tokio::io::stdout()),
+        };
+        let mut stream = ReferenceStream { writer: self, out, written: 0 };
+        match self.format {
+            OutputFormat::Json => stream.out.write_all(b"[").await?,
+            OutputFormat::Html => stream.out.write_all(citation::HTML_LIST_START.as_bytes()).await?,
+            _ => {}
+        }
+        Ok(stream)
+    }
+}
+
+/// References being written batch by batch (see `OutputWriter::stream_references`)
+pub struct ReferenceStream<'a> {
+    writer: &'a OutputWriter,
+    out: Box<dyn AsyncWrite + Unpin + Send>,
+    written: usize,
+}
+
+impl ReferenceStream<'_> {
+    /// Write the next batch of references; the output matches what
+    /// `OutputWriter::write_references` writes for all of them at once
+    pub async fn write(&mut self, references: &[Reference]) -> Result<()> {
+        for reference in references {
+            let separator = if self.written == 0 { "" } else { "\n" };
+            let entry = match self.writer.format {
+                OutputFormat::Json => {
+                    let separator = if self.written == 0 { "\n" } else { ",\n" };
+                    let item = serde_json::to_string_pretty(std::slice::from_ref(reference))?;
+                    format!("{}{}", separator, &item[2..item.len() - 2])
+                }
+                OutputFormat::Bibtex => format!("{}{}", separator, reference.to_bibtex_with(&self.writer.bibtex_options)),
+                OutputFormat::Html => citation::format_html_item(reference, self.writer.citation_style),
+                _ => format!("{}{}", separator, citation::format_citation_entry(self.written + 1, reference, self.writer.citation_style)),
+            };
+            self.out.write_all(entry.as_bytes()).await?;
+            self.written += 1;
+        }
+        self.out.flush().await?;
+        Ok(())
+    }
+    
+    /// Close the output and run the post-output hook. Returns the number of
+    /// references written.
+    pub async fn finish(mut self) -> Result<usize> {
+        match self.writer.format {
+            OutputFormat::Json if self.written == 0 => self.out.write_all(b"]").await?,
+            OutputFormat::Json => self.out.write_all(b"\n]").await?,
+            OutputFormat::Html => self.out.write_all(citation::HTML_LIST_END.as_bytes()).await?,
+            _ => {}
+        }
+        self.out.flush().await?;
+        if let Some(path) = &self.writer.output_path {
+            info!("Output written to: {}", path.display());
+            self.writer.run_post_output_hook(path).await;
+        }
+        Ok(self.written)
+    }
 }
 
 #[cfg(test)]
@@ -102,6 +561,7 @@ mod tests {
                 inspire_id: Some("123456".to_string()),
                 categories: vec!["hep-th".to_string()],
                 year: Some(2023),
+                ..Default::default()
             },
             Reference {
                 title: "Second Test Paper".to_string(),
@@ -110,6 +570,7 @@ mod tests {
                 inspire_id: Some("789012".to_string()),
                 categories: vec!["hep-ph".to_string()],
                 year: Some(2023),
+                ..Default::default()
             },
         ]
     }
@@ -141,12 +602,51 @@ mod tests {
         assert!(bibtex.contains("@article{"));
         assert!(bibtex.contains("First Test Paper"));
         assert!(bibtex.contains("Second Test Paper"));
-        assert!(bibtex.contains("Alice Smith and Bob Jones"));
-        assert!(bibtex.contains("Charlie Brown"));
+        assert!(bibtex.contains("Smith, Alice and Jones, Bob"));
+        assert!(bibtex.contains("Brown, Charlie"));
         assert!(bibtex.contains("eprint = {2301.12345}"));
         assert!(bibtex.contains("eprint = {2302.67890}"));
     }
 
+    #[test]
+    fn test_bib_sort() {
+        let mut references = create_test_references();
+        references[0].year = Some(2024);
+        references.push(Reference {
+            title: "Third Test Paper".to_string(),
+            authors: vec!["Alice Smith".to_string()],
+            year: Some(2020),
+            ..Default::default()
+        });
+        let titles = |sort: BibSort| {
+            let mut sorted = references.clone();
+            sort.sort(&mut sorted);
+            sorted.into_iter().map(|r| r.title).collect::<Vec<_>>()
+        };
+        
+        assert_eq!(titles(BibSort::Appearance), ["First Test Paper", "Second Test Paper", "Third Test Paper"]);
+        assert_eq!(titles(BibSort::Year), ["Third Test Paper", "Second Test Paper", "First Test Paper"]);
+        assert_eq!(titles(BibSort::Author), ["Second Test Paper", "Third Test Paper", "First Test Paper"]);
+        assert_eq!(titles(BibSort::Key), ["Second Test Paper", "Third Test Paper", "First Test Paper"]);
+        
+        let writer = OutputWriter::new(OutputFormat::Bibtex, None).with_bib_sort(BibSort::Year);
+        let bibtex = writer.format_bibtex(&references);
+        assert!(bibtex.find("Third Test Paper") < bibtex.find("First Test Paper"));
+    }
+
+    #[test]
+    fn test_format_atom() {
+        let writer = OutputWriter::new(OutputFormat::Atom, None);
+        let references = create_test_references();
+        
+        let feed = writer.format_atom(&references);
+        
+        assert!(feed.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+        assert_eq!(feed.matches("<entry>").count(), 2);
+        assert!(feed.contains("<link href=\"https://arxiv.org/abs/2301.12345\"/>"));
+        assert!(feed.contains("<id>https://inspirehep.net/literature/789012</id>"));
+    }
+
     #[tokio::test]
     async fn test_write_references_to_file() {
         let temp_dir = tempdir().unwrap();
@@ -182,6 +682,88 @@ mod tests {
         assert!(content.contains("Second Test Paper"));
     }
 
+    #[tokio::test]
+    async fn test_append_references_bibtex() {
+        let temp_dir = tempdir().unwrap();
+        let output_path = temp_dir.path().join("refs.bib");
+        let references = create_test_references();
+        let key = references[1].bibtex_key();
+        let existing = format!("{}@misc{{{},\n  title = {{Someone else's paper}},\n}}\n", references[0].to_bibtex(), key);
+        fs::write(&output_path, &existing).await.unwrap();
+        
+        let writer = OutputWriter::new(OutputFormat::Bibtex, Some(output_path.clone()));
+        let merge = writer.append_references(&references).await.unwrap();
+        
+        assert_eq!(merge.duplicates, [references[0].bibtex_key()]);
+        assert_eq!(merge.renamed.len(), 1);
+        assert_eq!(merge.renamed[0].renamed, format!("{}_2", key));
+        let content = fs::read_to_string(&output_path).await.unwrap();
+        assert!(content.starts_with(&existing));
+        assert!(content.ends_with(&format!("\n\n{}", Reference { texkey: Some(format!("{}_2", key)), ..references[1].clone() }.to_bibtex())));
+        
+        let json = OutputWriter::new(OutputFormat::Json, Some(output_path));
+        assert!(json.append_references(&references).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stream_references_matches_write_references() {
+        let temp_dir = tempdir().unwrap();
+        let references = create_test_references();
+        
+        for format in [OutputFormat::Json, OutputFormat::Bibtex, OutputFormat::Citations, OutputFormat::Html] {
+            let whole_path = temp_dir.path().join("whole");
+            let streamed_path = temp_dir.path().join("streamed");
+            OutputWriter::new(format.clone(), Some(whole_path.clone()))
+                .with_citation_style(CitationStyle::Ieee)
+                .write_references(&references).await.unwrap();
+            
+            let writer = OutputWriter::new(format.clone(), Some(streamed_path.clone())).with_citation_style(CitationStyle::Ieee);
+            let mut stream = writer.stream_references().await.unwrap();
+            stream.write(&references[..1]).await.unwrap();
+            stream.write(&[]).await.unwrap();
+            stream.write(&references[1..]).await.unwrap();
+            assert_eq!(stream.finish().await.unwrap(), 2);
+            
+            assert_eq!(fs::read_to_string(&streamed_path).await.unwrap(), fs::read_to_string(&whole_path).await.unwrap(), "{:?}", format);
+        }
+        
+        let path = temp_dir.path().join("empty.json");
+        let writer = OutputWriter::new(OutputFormat::Json, Some(path.clone()));
+        writer.stream_references().await.unwrap().finish().await.unwrap();
+        assert_eq!(fs::read_to_string(&path).await.unwrap(), "[]");
+        assert!(OutputWriter::new(OutputFormat::Atom, None).stream_references().await.is_err());
+    }
+
+    #[test]
+    fn test_format_dataset_bibtex() {
+        use crate::models::Dataset;
+
+        let writer = OutputWriter::new(OutputFormat::Bibtex, None);
+        let listings = vec![DatasetListing {
+            inspire_id: "1234567".to_string(),
+            title: "Measurement of Things".to_string(),
+            datasets: vec![
+                Dataset {
+                    source: "HEPData".to_string(),
+                    doi: Some("10.17182/hepdata.12345.v1".to_string()),
+                    url: Some("https://doi.org/10.17182/hepdata.12345.v1".to_string()),
+                },
+                Dataset {
+                    source: "HEPData".to_string(),
+                    doi: None,
+                    url: Some("https://www.hepdata.net/record/ins1234567".to_string()),
+                },
+            ],
+        }];
+
+        let bibtex = writer.format_dataset_bibtex(&listings);
+        
+        assert!(bibtex.starts_with("@misc{10.17182hepdata.12345.v1,"));
+        assert!(bibtex.contains("title = {Data for: Measurement of Things}"));
+        assert!(bibtex.contains("doi = {10.17182/hepdata.12345.v1}"));
+        assert_eq!(bibtex.matches("@misc").count(), 1);
+    }
+
     #[test]
     fn test_output_writer_creation() {
         let writer1 = OutputWriter::new(OutputFormat::Json, None);
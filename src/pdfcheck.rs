@@ -0,0 +1,322 @@
+use std::path::Path;
+use std::sync::Arc;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use log::{debug, warn};
+use lopdf::Document;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::api::InspireClient;
+use crate::models::Reference;
+use crate::updates::normalize;
+
+lazy_static! {
+    /// Heading that starts the bibliography, optionally numbered (`7 References`, `VI. REFERENCES`)
+    static ref HEADING_RE: Regex = Regex::new(
+        r"(?i)^(?:\d+\.?\s*|[IVX]+\.\s*)?(?:references|bibliography|references and notes|literature cited)\s*:?$"
+    ).unwrap();
+
+    /// Start of a numbered entry: `[12]` or `12.`
+    static ref BRACKET_LABEL_RE: Regex = Regex::new(r"^\[(\d+)\]\s*").unwrap();
+    static ref NUMBER_LABEL_RE: Regex = Regex::new(r"^(\d+)\.\s+").unwrap();
+
+    static ref ARXIV_RE: Regex = Regex::new(
+        r"\b(\d{4}\.\d{4,5}|[a-z-]+(?:\.[A-Z]{2})?/\d{7})(?:v\d+)?\b"
+    ).unwrap();
+    static ref DOI_RE: Regex = Regex::new(r"\b10\.\d{4,9}/[^\s,;]+").unwrap();
+    static ref YEAR_RE: Regex = Regex::new(r"\b(?:19|20)\d{2}\b").unwrap();
+}
+
+/// Shortest normalized title matched by substring, to avoid matching on generic words
+const MIN_TITLE_LENGTH: usize = 12;
+
+/// One entry of the bibliography printed in a PDF
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PdfEntry {
+    /// Reference number (`12` for `[12]`)
+    pub label: String,
+    pub text: String,
+    pub arxiv_ids: Vec<String>,
+    pub dois: Vec<String>,
+}
+
+/// A .bib entry whose printed form disagrees with the .bib file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Mismatch {
+    pub label: String,
+    pub key: String,
+    pub reason: String,
+}
+
+/// An entry printed in the PDF without a matching .bib entry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MissingEntry {
+    pub entry: PdfEntry,
+    /// The record on INSPIRE, when the entry's arXiv ID was found there
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inspire: Option<Reference>,
+}
+
+/// Result of checking a PDF's bibliography against a .bib file
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PdfReport {
+    /// Entries found in the PDF's bibliography
+    pub entries: usize,
+    /// Entries matched to at least one .bib entry
+    pub matched: usize,
+    pub missing: Vec<MissingEntry>,
+    pub mismatched: Vec<Mismatch>,
+    /// Keys of .bib entries not found in the PDF
+    pub uncited: Vec<String>,
+}
+
+/// Extract the text of every page, spreading the pages over blocking worker threads
+pub async fn extract_pages(path: &Path) -> Result<Vec<String>> {
+    let owned = path.to_path_buf();
+    let document = tokio::task::spawn_blocking(move || Document::load(owned)).await?
+        .with_context(|| format!("Could not read {} as a PDF", path.display()))?;
+    let document = Arc::new(document);
+    let pages: Vec<u32> = document.get_pages().keys().copied().collect();
+
+    let workers = std::thread::available_parallelism().map_or(4, |n| n.get());
+    let chunk_size = pages.len().div_ceil(workers).max(1);
+    let tasks = pages.chunks(chunk_size).map(|chunk| {
+        let document = Arc::clone(&document);
+        let chunk = chunk.to_vec();
+        tokio::task::spawn_blocking(move || {
+            chunk.iter()
+                .map(|&page| document.extract_text(&[page]).unwrap_or_else(|e| {
+                    warn!("Could not extract the text of page {}: {}", page, e);
+                    String::new()
+                }))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let mut text = Vec::with_capacity(pages.len());
+    for pages in futures::future::join_all(tasks).await {
+        text.extend(pages?);
+    }
+    debug!("Extracted the text of {} pages", text.len());
+    Ok(text)
+}
+
+/// Text following the last bibliography heading, if there is one
+pub fn bibliography_section(text: &str) -> Option<&str> {
+    let mut start = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if HEADING_RE.is_match(line.trim()) {
+            start = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    start.map(|start| &text[start..])
+}
+
+/// Split a bibliography into numbered entries, joining wrapped lines.
+///
+/// `[12]` labels are used when present, `12.` labels otherwise; text before
+/// the first label is ignored.
+pub fn split_entries(section: &str) -> Vec<PdfEntry> {
+    let lines: Vec<&str> = section.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    let label_re: &Regex = if lines.iter().any(|line| BRACKET_LABEL_RE.is_match(line)) {
+        &BRACKET_LABEL_RE
+    } else {
+        &NUMBER_LABEL_RE
+    };
+
+    let mut entries: Vec<(String, String)> = Vec::new();
+    for line in lines {
+        if let Some(caps) = label_re.captures(line) {
+            entries.push((caps[1].to_string(), line[caps[0].len()..].to_string()));
+        } else if let Some((_, text)) = entries.last_mut() {
+            // A trailing hyphen is kept so that `hep-` + `th/9711200` stays an arXiv ID
+            if !text.ends_with('-') {
+                text.push(' ');
+            }
+            text.push_str(line);
+        }
+    }
+
+    entries.into_iter()
+        .map(|(label, text)| PdfEntry {
+            arxiv_ids: ARXIV_RE.captures_iter(&text).map(|caps| caps[1].to_string()).collect(),
+            dois: DOI_RE.find_iter(&text)
+                .map(|doi| doi.as_str().trim_end_matches(['.', ')', ']']).to_lowercase())
+                .collect(),
+            label,
+            text,
+        })
+        .collect()
+}
+
+/// Match the PDF's entries against the .bib entries by arXiv ID, DOI or title,
+/// and report entries missing on either side and matches that disagree.
+/// Missing entries are not looked up on INSPIRE here.
+pub fn compare(entries: &[PdfEntry], bibliography: &[Reference]) -> PdfReport {
+    let mut report = PdfReport { entries: entries.len(), ..Default::default() };
+    let mut cited = vec![false; bibliography.len()];
+
+    for entry in entries {
+        let text = normalize(&entry.text);
+        let mut matched = false;
+        for (index, reference) in bibliography.iter().enumerate() {
+            let arxiv_id = reference.arxiv_id.as_deref().map(strip_version);
+            let by_arxiv = arxiv_id.is_some_and(|id| entry.arxiv_ids.iter().any(|a| a == id));
+            let by_doi = reference.doi.as_deref().is_some_and(|doi| entry.dois.iter().any(|d| d.eq_ignore_ascii_case(doi)));
+            let title = normalize(&reference.title);
+            let by_title = title.len() >= MIN_TITLE_LENGTH && text.contains(&title);
+            if !(by_arxiv || by_doi || by_title) {
+                continue;
+            }
+
+            matched = true;
+            cited[index] = true;
+            let key = reference.texkey.clone().unwrap_or_else(|| reference.bibtex_key());
+            if let Some(reason) = disagreement(entry, reference, arxiv_id, by_arxiv) {
+                report.mismatched.push(Mismatch { label: entry.label.clone(), key, reason });
+            }
+        }
+
+        if matched {
+            report.matched += 1;
+        } else {
+            report.missing.push(MissingEntry { entry: entry.clone(), inspire: None });
+        }
+    }
+
+    report.uncited = bibliography.iter()
+        .zip(&cited)
+        .filter(|(_, &cited)| !cited)
+        .map(|(reference, _)| reference.texkey.clone().unwrap_or_else(|| reference.bibtex_key()))
+        .collect();
+    report
+}
+
+/// Why a matched entry disagrees with its .bib entry: a different arXiv ID or no sign of the year
+fn disagreement(entry: &PdfEntry, reference: &Reference, arxiv_id: Option<&str>, by_arxiv: bool) -> Option<String> {
+    if let (Some(arxiv_id), false, Some(printed)) = (arxiv_id, by_arxiv, entry.arxiv_ids.first()) {
+        return Some(format!("arXiv ID is {} in the .bib file but {} in the PDF", arxiv_id, printed));
+    }
+    let years: Vec<&str> = YEAR_RE.find_iter(&entry.text).map(|year| year.as_str()).collect();
+    match reference.year {
+        Some(year) if !years.is_empty() && !years.contains(&year.to_string().as_str()) => {
+            Some(format!("year is {} in the .bib file but {} in the PDF", year, years.join("/")))
+        }
+        _ => None,
+    }
+}
+
+fn strip_version(arxiv_id: &str) -> &str {
+    match arxiv_id.rsplit_once('v') {
+        Some((base, version)) if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) => base,
+        _ => arxiv_id,
+    }
+}
+
+/// Check the bibliography printed in a PDF against a .bib file, looking up the
+/// arXiv IDs of entries missing from the .bib file on INSPIRE unless `offline`
+pub async fn verify_pdf(client: &InspireClient, pdf: &Path, bibliography: &[Reference], offline: bool) -> Result<PdfReport> {
+    let text = extract_pages(pdf).await?.join("\n");
+    let section = bibliography_section(&text).unwrap_or_else(|| {
+        warn!("No References heading found in {}; reading the whole text", pdf.display());
+        &text
+    });
+
+    let mut report = compare(&split_entries(section), bibliography);
+    if !offline {
+        for missing in &mut report.missing {
+            if let Some(arxiv_id) = missing.entry.arxiv_ids.first() {
+                match client.get_paper_by_arxiv(arxiv_id).await {
+                    Ok(paper) => missing.inspire = Some(Reference::from(paper)),
+                    Err(e) => debug!("{} not found on INSPIRE: {}", arxiv_id, e),
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "6 Conclusions
+We have shown that [1, 2] agree.
+References
+[1] J. M. Maldacena, The Large N limit of superconformal field theories and supergrav-
+ity, Adv. Theor. Math. Phys. 2 (1998) 231, [hep-
+th/9711200].
+[2] E. Witten, Anti-de Sitter space and holography, 1999,
+arXiv:hep-th/9802150.
+[3] S. Gubser, I. Klebanov, A. Polyakov, Phys. Lett. B 428 (1998) 105,
+doi:10.1016/S0370-2693(98)00377-3.
+";
+
+    fn reference(key: &str, title: &str, arxiv_id: Option<&str>, year: u32) -> Reference {
+        Reference {
+            title: title.to_string(),
+            arxiv_id: arxiv_id.map(|s| s.to_string()),
+            year: Some(year),
+            texkey: Some(key.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_split_entries() {
+        let section = bibliography_section(PAGE).unwrap();
+        let entries = split_entries(section);
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].label, "1");
+        assert_eq!(entries[0].arxiv_ids, ["hep-th/9711200"]);
+        assert!(entries[0].text.contains("supergrav-ity"));
+        assert_eq!(entries[1].arxiv_ids, ["hep-th/9802150"]);
+        assert_eq!(entries[2].dois, ["10.1016/s0370-2693(98)00377-3"]);
+    }
+
+    #[test]
+    fn test_numbered_entries_and_headings() {
+        let text = "Contents\n1 Introduction\nVI. REFERENCES\n1. A. Author, Title one, 2020.\n2. B. Author,\nTitle two, 2021.\n";
+        let entries = split_entries(bibliography_section(text).unwrap());
+        let labels: Vec<&str> = entries.iter().map(|e| e.label.as_str()).collect();
+        assert_eq!(labels, ["1", "2"]);
+        assert_eq!(entries[1].text, "B. Author, Title two, 2021.");
+        assert!(bibliography_section("No bibliography here").is_none());
+    }
+
+    #[test]
+    fn test_compare() {
+        let entries = split_entries(bibliography_section(PAGE).unwrap());
+        let bibliography = vec![
+            reference("Maldacena:1997re", "The Large N limit of superconformal field theories and supergravity", Some("hep-th/9711200v3"), 1998),
+            reference("Witten:1998qj", "Anti de Sitter space and holography", Some("hep-th/9802150"), 1998),
+            reference("Unused:2020", "A paper the draft no longer cites", Some("2001.00001"), 2020),
+        ];
+
+        let report = compare(&entries, &bibliography);
+        assert_eq!(report.entries, 3);
+        assert_eq!(report.matched, 2);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].entry.label, "3");
+        assert_eq!(report.mismatched, vec![Mismatch {
+            label: "2".to_string(),
+            key: "Witten:1998qj".to_string(),
+            reason: "year is 1998 in the .bib file but 1999 in the PDF".to_string(),
+        }]);
+        assert_eq!(report.uncited, ["Unused:2020"]);
+    }
+
+    #[test]
+    fn test_compare_reports_different_arxiv_ids() {
+        let entries = split_entries("[1] J. Doe, A rather specific title, 2301.00002.\n");
+        let bibliography = vec![reference("Doe:2023", "A rather specific title", Some("2301.00001"), 2023)];
+
+        let report = compare(&entries, &bibliography);
+        assert_eq!(report.matched, 1);
+        assert_eq!(report.mismatched[0].reason, "arXiv ID is 2301.00001 in the .bib file but 2301.00002 in the PDF");
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use serde::Serialize;
+
+/// Snapshot of a long-running operation's progress
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Distinct papers found so far
+    pub papers_discovered: usize,
+    /// INSPIRE requests finished so far, successful or not
+    pub requests_completed: usize,
+    /// Depth being processed (network builds; 0 for flat batch operations)
+    pub current_depth: u32,
+    /// Operation reporting the progress (e.g. `network`, `similar`, `datasets`)
+    pub stage: &'static str,
+    /// Requests the operation will make in total, when known in advance
+    pub total: Option<usize>,
+    /// INSPIRE record ID of the item just processed
+    pub current_item: Option<String>,
+}
+
+impl Progress {
+    /// This snapshot as a machine-readable event; `event` is `progress` or `finish`
+    pub fn to_event(&self, event: &'static str) -> ProgressEvent<'_> {
+        ProgressEvent {
+            event,
+            stage: self.stage,
+            completed: self.requests_completed,
+            total: self.total,
+            current_item: self.current_item.as_deref(),
+            papers_discovered: self.papers_discovered,
+            depth: self.current_depth,
+        }
+    }
+}
+
+/// A progress snapshot as written by `--progress json`, one JSON object per line
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressEvent<'a> {
+    pub event: &'static str,
+    pub stage: &'a str,
+    /// Requests finished so far
+    pub completed: usize,
+    pub total: Option<usize>,
+    pub current_item: Option<&'a str>,
+    pub papers_discovered: usize,
+    pub depth: u32,
+}
+
+/// Receives progress from network builds and batch operations, so callers can
+/// render their own progress display.
+pub trait ProgressObserver: Send + Sync {
+    /// Called after each completed request
+    fn on_progress(&self, progress: &Progress);
+
+    /// Called once when the operation ends, whether complete or cancelled
+    fn on_finish(&self, _progress: &Progress) {}
+}
+
+/// Shared handle to an observer, as stored in operation options
+pub type SharedObserver = Arc<dyn ProgressObserver>;
+
+/// Report progress to an optional observer
+pub(crate) fn report(observer: Option<&dyn ProgressObserver>, progress: &Progress) {
+    if let Some(observer) = observer {
+        observer.on_progress(progress);
+    }
+}
+
+/// Report the end of an operation to an optional observer
+pub(crate) fn finish(observer: Option<&dyn ProgressObserver>, progress: &Progress) {
+    if let Some(observer) = observer {
+        observer.on_finish(progress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_event() {
+        let progress = Progress {
+            papers_discovered: 12,
+            requests_completed: 3,
+            stage: "similar",
+            total: Some(27),
+            current_item: Some("451647".to_string()),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&progress.to_event("progress")).unwrap();
+        assert_eq!(
+            json,
+            r#"{"event":"progress","stage":"similar","completed":3,"total":27,"current_item":"451647","papers_discovered":12,"depth":0}"#
+        );
+        assert_eq!(Progress::default().to_event("finish").total, None);
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{Result, anyhow};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::api::InspireClient;
+use crate::metrics::METRICS;
+use crate::models::{normalize_doi, Paper, Reference};
+use crate::server::{self, Route, MAX_NETWORK_DEPTH};
+use crate::texscan;
+
+/// One request line of the stdin protocol, e.g. `{"op":"cite","id":"2301.12345"}`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProtocolRequest {
+    /// Operation: `cite`, `paper`, `references` or `network`
+    pub op: String,
+    /// arXiv ID or TeX key (`cite`, `paper`, `network`), or INSPIRE record ID (`references`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Network depth for `network`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depth: Option<u32>,
+    /// Opaque value echoed back so clients can match responses to requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<Value>,
+}
+
+/// Answer newline-delimited JSON requests from `reader` until it is closed.
+///
+/// Every request line gets exactly one response line, `{"ok":true,"result":…}`
+/// or `{"ok":false,"error":"…"}`, in request order. Blank lines are ignored.
+pub async fn run<R, W>(client: &InspireClient, reader: R, mut writer: W) -> Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = reader.lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        debug!("Protocol request: {}", line);
+
+        let response = match serde_json::from_str::<ProtocolRequest>(&line) {
+            Ok(request) => {
+                let result = dispatch(client, &request).await;
+                let endpoint = match request.op.as_str() {
+                    op @ ("cite" | "paper" | "references" | "network") => op,
+                    _ => "unknown",
+                };
+                METRICS.record_request(endpoint, if result.is_ok() { "ok" } else { "error" });
+
+                let mut response = match result {
+                    Ok(result) => json!({ "ok": true, "result": result }),
+                    Err(e) => json!({ "ok": false, "error": e.to_string() }),
+                };
+                if let Some(seq) = request.seq {
+                    response["seq"] = seq;
+                }
+                response
+            }
+            Err(e) => json!({ "ok": false, "error": format!("Invalid request: {}", e) }),
+        };
+
+        let mut output = serde_json::to_string(&response)?;
+        output.push('\n');
+        writer.write_all(output.as_bytes()).await?;
+        writer.flush().await?;
+    }
+    Ok(())
+}
+
+/// Answer a single request
+pub async fn dispatch(client: &InspireClient, request: &ProtocolRequest) -> Result<Value> {
+    let id = || request.id.clone().ok_or_else(|| anyhow!("Missing id for {}", request.op));
+
+    match request.op.as_str() {
+        "cite" => {
+            let paper = resolve(client, &id()?).await?;
+            Ok(Value::String(Reference::from(paper).to_bibtex()))
+        }
+        "paper" => Ok(serde_json::to_value(resolve(client, &id()?).await?)?),
+        "references" => server::handle(client, &Route::References(id()?)).await,
+        "network" => {
+            let depth = request.depth.unwrap_or(1);
+            if depth > MAX_NETWORK_DEPTH {
+                return Err(anyhow!("Depth is limited to {}", MAX_NETWORK_DEPTH));
+            }
+            server::handle(client, &Route::Network { seed: id()?, depth }).await
+        }
+        op => Err(anyhow!("Unknown op: {}", op)),
+    }
+}
+
+/// Look up a paper by arXiv ID or DOI or, failing both patterns, by TeX key
+async fn resolve(client: &InspireClient, id: &str) -> Result<Paper> {
+    if texscan::looks_like_arxiv_id(id) {
+        client.get_paper_by_arxiv(id).await
+    } else if normalize_doi(id).is_some() {
+        client.get_paper_by_doi(id).await
+    } else {
+        client.get_paper_by_texkey(id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn run_lines(input: &str) -> Vec<Value> {
+        let mut output = Vec::new();
+        run(&InspireClient::new(), input.as_bytes(), &mut output).await.unwrap();
+        String::from_utf8(output).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_one_response_per_request() {
+        let responses = run_lines(
+            "{\"op\":\"frobnicate\",\"id\":\"1\",\"seq\":7}\n\nnot json\n{\"op\":\"cite\"}\n{\"op\":\"network\",\"id\":\"1\",\"depth\":9}\n"
+        ).await;
+
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0], json!({ "ok": false, "error": "Unknown op: frobnicate", "seq": 7 }));
+        assert!(responses[1]["error"].as_str().unwrap().starts_with("Invalid request:"));
+        assert_eq!(responses[2]["error"], "Missing id for cite");
+        assert_eq!(responses[3]["error"], "Depth is limited to 3");
+    }
+}
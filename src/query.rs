@@ -0,0 +1,158 @@
+use std::fmt;
+
+/// Order of literature search results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum SearchSort {
+    /// Newest first
+    #[default]
+    #[cfg_attr(feature = "cli", value(name = "mostrecent"))]
+    MostRecent,
+    /// Most cited first
+    #[cfg_attr(feature = "cli", value(name = "mostcited"))]
+    MostCited,
+}
+
+impl SearchSort {
+    /// Value of INSPIRE's `sort` parameter
+    pub fn as_param(self) -> &'static str {
+        match self {
+            SearchSort::MostRecent => "mostrecent",
+            SearchSort::MostCited => "mostcited",
+        }
+    }
+}
+
+/// An INSPIRE literature search, built from clauses joined with `and`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LiteratureQuery {
+    clauses: Vec<String>,
+}
+
+impl LiteratureQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Papers by an author (e.g. `Doe, J.`)
+    pub fn with_author(self, author: &str) -> Self {
+        self.with_clause(format!("a \"{}\"", author))
+    }
+
+    /// Papers associated with an INSPIRE experiment record (e.g. `CERN-LHC-ATLAS`)
+    pub fn with_experiment(self, experiment: &str) -> Self {
+        self.with_clause(format!("exp \"{}\"", experiment))
+    }
+
+    /// Papers signed by a collaboration (e.g. `ATLAS`)
+    pub fn with_collaboration(self, collaboration: &str) -> Self {
+        self.with_clause(format!("cn \"{}\"", collaboration))
+    }
+
+    /// Papers published in a journal (e.g. `JHEP`), optionally in one volume
+    pub fn with_journal(self, journal: &str, volume: Option<&str>) -> Self {
+        match volume {
+            Some(volume) => self.with_clause(format!("j \"{},{}\"", journal, volume)),
+            None => self.with_clause(format!("j \"{}\"", journal)),
+        }
+    }
+
+    /// Papers published in this journal issue
+    pub fn with_journal_issue(self, issue: &str) -> Self {
+        self.with_clause(format!("publication_info.journal_issue:\"{}\"", issue))
+    }
+
+    /// Contributions to a conference, by its INSPIRE conference number (e.g. `C17-07-05`)
+    pub fn with_conference(self, cnum: &str) -> Self {
+        self.with_clause(format!("publication_info.cnum:\"{}\"", cnum))
+    }
+
+    /// Papers citing an INSPIRE literature record
+    pub fn with_citing(self, record_id: &str) -> Self {
+        self.with_clause(format!("refersto:recid:{}", record_id))
+    }
+
+    /// Papers listed in an arXiv category (e.g. `hep-th`)
+    pub fn with_category(self, category: &str) -> Self {
+        self.with_clause(format!("arxiv_eprints.categories:\"{}\"", category))
+    }
+
+    /// Only papers dated on or after this day (`YYYY-MM-DD`)
+    pub fn with_min_date(self, date: &str) -> Self {
+        self.with_clause(format!("date >= {}", date))
+    }
+
+    /// Only papers from this year
+    pub fn with_year(self, year: u32) -> Self {
+        self.with_clause(format!("date {}", year))
+    }
+
+    /// Only papers from this year onwards
+    pub fn with_min_year(self, year: Option<u32>) -> Self {
+        match year {
+            Some(year) => self.with_clause(format!("date >= {}", year)),
+            None => self,
+        }
+    }
+
+    /// Add a clause in INSPIRE search syntax
+    pub fn with_clause(mut self, clause: impl Into<String>) -> Self {
+        self.clauses.push(clause.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+}
+
+impl fmt::Display for LiteratureQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.clauses.join(" and "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_experiment_query() {
+        let query = LiteratureQuery::new()
+            .with_experiment("CERN-LHC-ATLAS")
+            .with_min_year(Some(2020));
+        assert_eq!(query.to_string(), "exp \"CERN-LHC-ATLAS\" and date >= 2020");
+
+        let query = LiteratureQuery::new().with_collaboration("ATLAS").with_min_year(None);
+        assert_eq!(query.to_string(), "cn \"ATLAS\"");
+        assert!(LiteratureQuery::new().is_empty());
+    }
+
+    #[test]
+    fn test_category_queries() {
+        let query = LiteratureQuery::new().with_category("hep-th").with_min_date("2024-01-01");
+        assert_eq!(query.to_string(), "arxiv_eprints.categories:\"hep-th\" and date >= 2024-01-01");
+
+        let query = LiteratureQuery::new().with_category("hep-ex").with_year(2022);
+        assert_eq!(query.to_string(), "arxiv_eprints.categories:\"hep-ex\" and date 2022");
+    }
+
+    #[test]
+    fn test_journal_query() {
+        let query = LiteratureQuery::new().with_journal("JHEP", Some("2023")).with_journal_issue("5");
+        assert_eq!(query.to_string(), "j \"JHEP,2023\" and publication_info.journal_issue:\"5\"");
+        assert_eq!(LiteratureQuery::new().with_journal("Phys. Rev. D", None).to_string(), "j \"Phys. Rev. D\"");
+    }
+
+    #[test]
+    fn test_conference_query() {
+        let query = LiteratureQuery::new().with_conference("C17-07-05");
+        assert_eq!(query.to_string(), "publication_info.cnum:\"C17-07-05\"");
+    }
+
+    #[test]
+    fn test_citing_query() {
+        let query = LiteratureQuery::new().with_citing("451647").with_min_year(Some(2020));
+        assert_eq!(query.to_string(), "refersto:recid:451647 and date >= 2020");
+    }
+}
@@ -0,0 +1,292 @@
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::dates::PartialDate;
+use crate::models::{Address, Conference, Experiment, Institution, Job};
+
+/// An INSPIRE record collection, served under `/api/<path>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    Literature,
+    Institutions,
+    Conferences,
+    Experiments,
+    Journals,
+    Jobs,
+    Data,
+}
+
+impl Endpoint {
+    /// Path of the collection below the API root
+    pub fn path(self) -> &'static str {
+        match self {
+            Endpoint::Literature => "literature",
+            Endpoint::Institutions => "institutions",
+            Endpoint::Conferences => "conferences",
+            Endpoint::Experiments => "experiments",
+            Endpoint::Journals => "journals",
+            Endpoint::Jobs => "jobs",
+            Endpoint::Data => "data",
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.path())
+    }
+}
+
+/// A record type that can be read from the metadata of one INSPIRE collection.
+///
+/// Implementing this is all a new record type needs for
+/// `InspireClient::get_typed_record` and `InspireClient::search_typed_records`.
+pub trait InspireRecord: Sized {
+    /// Collection the records are served from
+    const ENDPOINT: Endpoint;
+
+    /// Field holding the name a record is usually looked up by (`legacy_ICN`
+    /// for institutions), searched when the identifier is not a record ID
+    const NAME_FIELD: &'static str;
+
+    /// Parse a record's `metadata`; `None` when it lacks a record ID
+    fn from_metadata(metadata: &Value) -> Option<Self>;
+}
+
+impl InspireRecord for Institution {
+    const ENDPOINT: Endpoint = Endpoint::Institutions;
+    const NAME_FIELD: &'static str = "legacy_ICN";
+
+    fn from_metadata(metadata: &Value) -> Option<Self> {
+        let external_id = |schema: &str| metadata["external_system_identifiers"].as_array()?
+            .iter()
+            .find(|identifier| identifier["schema"].as_str().is_some_and(|s| s.eq_ignore_ascii_case(schema)))
+            .and_then(|identifier| string(&identifier["value"]));
+
+        Some(Institution {
+            id: record_id(metadata)?,
+            name: string(&metadata["legacy_ICN"]).unwrap_or_default(),
+            full_name: string(&metadata["institution_hierarchy"][0]["name"]),
+            address: parse_address(&metadata["addresses"]),
+            ror: external_id("ROR"),
+            grid: external_id("GRID"),
+            paper_count: metadata["number_of_papers"].as_u64(),
+        })
+    }
+}
+
+impl InspireRecord for Conference {
+    const ENDPOINT: Endpoint = Endpoint::Conferences;
+    const NAME_FIELD: &'static str = "acronyms";
+
+    fn from_metadata(metadata: &Value) -> Option<Self> {
+        Some(Conference {
+            id: record_id(metadata)?,
+            cnum: string(&metadata["cnum"]),
+            title: string(&metadata["titles"][0]["title"]).unwrap_or_else(|| "Unknown Title".to_string()),
+            acronyms: strings(&metadata["acronyms"], None),
+            series: strings(&metadata["series"], Some("name")),
+            opening_date: date(&metadata["opening_date"]),
+            closing_date: date(&metadata["closing_date"]),
+            address: parse_address(&metadata["addresses"]),
+            contribution_count: metadata["number_of_contributions"].as_u64(),
+        })
+    }
+}
+
+impl InspireRecord for Experiment {
+    const ENDPOINT: Endpoint = Endpoint::Experiments;
+    const NAME_FIELD: &'static str = "legacy_name";
+
+    fn from_metadata(metadata: &Value) -> Option<Self> {
+        Some(Experiment {
+            id: record_id(metadata)?,
+            name: string(&metadata["legacy_name"]).unwrap_or_default(),
+            long_name: string(&metadata["long_name"]),
+            collaboration: string(&metadata["collaboration"]["value"]),
+            accelerator: string(&metadata["accelerator"]["value"]),
+            institutions: strings(&metadata["institutions"], Some("value")),
+            date_started: date(&metadata["date_started"]),
+            date_completed: date(&metadata["date_completed"]),
+            paper_count: metadata["number_of_papers"].as_u64(),
+        })
+    }
+}
+
+impl InspireRecord for Job {
+    const ENDPOINT: Endpoint = Endpoint::Jobs;
+    const NAME_FIELD: &'static str = "position";
+
+    fn from_metadata(metadata: &Value) -> Option<Self> {
+        Some(Job {
+            id: record_id(metadata)?,
+            position: string(&metadata["position"]).unwrap_or_default(),
+            institutions: strings(&metadata["institutions"], Some("value")),
+            regions: strings(&metadata["regions"], None),
+            ranks: strings(&metadata["ranks"], None),
+            categories: strings(&metadata["arxiv_categories"], None),
+            deadline: date(&metadata["deadline_date"]),
+            status: string(&metadata["status"]),
+            url: string(&metadata["urls"][0]["value"]),
+        })
+    }
+}
+
+/// Whether an identifier is an INSPIRE record ID rather than a name
+pub fn is_record_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Whether an identifier is an INSPIRE conference number (`C17-07-05`, `C17-07-05.1`)
+pub fn is_cnum(id: &str) -> bool {
+    id.strip_prefix('C').is_some_and(|rest| {
+        rest.len() >= 8 && rest.chars().all(|c| c.is_ascii_digit() || c == '-' || c == '.')
+    })
+}
+
+fn record_id(metadata: &Value) -> Option<String> {
+    metadata["control_number"].as_u64().map(|id| id.to_string())
+}
+
+fn string(value: &Value) -> Option<String> {
+    value.as_str().map(|s| s.to_string())
+}
+
+fn date(value: &Value) -> Option<PartialDate> {
+    value.as_str().and_then(PartialDate::parse)
+}
+
+/// The strings of an array, or of one field of each of its objects
+fn strings(value: &Value, field: Option<&str>) -> Vec<String> {
+    value.as_array().into_iter().flatten()
+        .filter_map(|item| field.map_or(item, |field| &item[field]).as_str())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// The first address of an institution or conference record that names a
+/// country, or else the first address
+fn parse_address(addresses: &Value) -> Option<Address> {
+    let addresses = addresses.as_array()?;
+    let address = addresses.iter().find(|a| a["country_code"].is_string()).or(addresses.first())?;
+    Some(Address {
+        lines: strings(&address["postal_address"], None),
+        city: string(&address["cities"][0]),
+        postal_code: string(&address["postal_code"]),
+        country_code: address["country_code"].as_str().map(|code| code.to_uppercase()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_institution() {
+        let metadata = json!({
+            "control_number": 902725,
+            "legacy_ICN": "CERN",
+            "institution_hierarchy": [{"name": "European Organization for Nuclear Research", "acronym": "CERN"}],
+            "addresses": [
+                {"cities": ["Geneva"]},
+                {"cities": ["Meyrin"], "postal_address": ["CERN", "CH-1211 Genève 23"], "postal_code": "1211", "country_code": "ch"}
+            ],
+            "external_system_identifiers": [
+                {"schema": "GRID", "value": "grid.9132.9"},
+                {"schema": "ROR", "value": "https://ror.org/01ggx4157"}
+            ],
+            "number_of_papers": 81234
+        });
+
+        let institution = Institution::from_metadata(&metadata).unwrap();
+        assert_eq!(institution.id, "902725");
+        assert_eq!(institution.name, "CERN");
+        assert_eq!(institution.full_name.as_deref(), Some("European Organization for Nuclear Research"));
+        assert_eq!(institution.country_code(), Some("CH"));
+        let address = institution.address.unwrap();
+        assert_eq!(address.city.as_deref(), Some("Meyrin"));
+        assert_eq!(address.lines, vec!["CERN", "CH-1211 Genève 23"]);
+        assert_eq!(institution.ror.as_deref(), Some("https://ror.org/01ggx4157"));
+        assert_eq!(institution.grid.as_deref(), Some("grid.9132.9"));
+        assert_eq!(institution.paper_count, Some(81234));
+
+        let bare = Institution::from_metadata(&json!({"control_number": 1, "legacy_ICN": "Nowhere U."})).unwrap();
+        assert_eq!(bare.country_code(), None);
+        assert!(Institution::from_metadata(&Value::Null).is_none());
+    }
+
+    #[test]
+    fn test_parse_conference() {
+        let metadata = json!({
+            "control_number": 1611564,
+            "cnum": "C17-07-05",
+            "titles": [{"title": "European Physical Society Conference on High Energy Physics"}],
+            "acronyms": ["EPS-HEP2017"],
+            "series": [{"name": "EPS-HEP", "number": 24}],
+            "opening_date": "2017-07-05",
+            "closing_date": "2017-07-12",
+            "addresses": [{"cities": ["Venice"], "country_code": "IT"}],
+            "number_of_contributions": 612
+        });
+
+        let conference = Conference::from_metadata(&metadata).unwrap();
+        assert_eq!(conference.id, "1611564");
+        assert_eq!(conference.cnum.as_deref(), Some("C17-07-05"));
+        assert_eq!(conference.acronyms, vec!["EPS-HEP2017"]);
+        assert_eq!(conference.series, vec!["EPS-HEP"]);
+        assert_eq!(conference.opening_date, PartialDate::parse("2017-07-05"));
+        assert_eq!(conference.address.unwrap().city.as_deref(), Some("Venice"));
+        assert_eq!(conference.contribution_count, Some(612));
+        assert!(Conference::from_metadata(&json!({"cnum": "C17-07-05"})).is_none());
+
+        assert!(is_cnum("C17-07-05"));
+        assert!(is_cnum("C17-07-05.1"));
+        assert!(!is_cnum("CERN"));
+        assert!(!is_cnum("EPS-HEP2017"));
+    }
+
+    #[test]
+    fn test_parse_experiment_and_job() {
+        let experiment = Experiment::from_metadata(&json!({
+            "control_number": 1108541,
+            "legacy_name": "CERN-LHC-ATLAS",
+            "long_name": "A Toroidal LHC ApparatuS",
+            "collaboration": {"value": "ATLAS"},
+            "accelerator": {"value": "LHC"},
+            "institutions": [{"value": "CERN"}],
+            "date_started": "2008-09-10"
+        })).unwrap();
+        assert_eq!(experiment.name, "CERN-LHC-ATLAS");
+        assert_eq!(experiment.collaboration.as_deref(), Some("ATLAS"));
+        assert_eq!(experiment.institutions, vec!["CERN"]);
+        assert_eq!(experiment.date_started, PartialDate::parse("2008-09-10"));
+        assert_eq!(experiment.date_completed, None);
+
+        let job = Job::from_metadata(&json!({
+            "control_number": 2650000,
+            "position": "Postdoctoral fellow in theoretical physics",
+            "institutions": [{"value": "DESY"}],
+            "regions": ["Europe"],
+            "ranks": ["POSTDOC"],
+            "arxiv_categories": ["hep-th", "hep-ph"],
+            "deadline_date": "2024-11-01",
+            "status": "open",
+            "urls": [{"value": "https://example.org/apply"}]
+        })).unwrap();
+        assert_eq!(job.institutions, vec!["DESY"]);
+        assert_eq!(job.categories, vec!["hep-th", "hep-ph"]);
+        assert_eq!(job.deadline.unwrap().to_string(), "2024-11-01");
+        assert_eq!(job.url.as_deref(), Some("https://example.org/apply"));
+    }
+
+    #[test]
+    fn test_endpoint_paths() {
+        assert_eq!(Endpoint::Literature.path(), "literature");
+        assert_eq!(Endpoint::Jobs.to_string(), "jobs");
+        assert!(is_record_id("902725"));
+        assert!(!is_record_id("CERN"));
+        assert!(!is_record_id(""));
+    }
+}
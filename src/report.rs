@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+/// Tabular formats for statistics reports
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ReportFormat {
+    Csv,
+    Markdown,
+}
+
+/// A simple table of string cells, rendered as CSV or Markdown
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push_row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// Render the table in the given format
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Csv => self.to_csv(),
+            ReportFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    /// Render as CSV, quoting cells that contain separators or quotes
+    pub fn to_csv(&self) -> String {
+        std::iter::once(&self.headers)
+            .chain(self.rows.iter())
+            .map(|row| {
+                row.iter()
+                    .map(|cell| csv_escape(cell))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    /// Render as a GitHub-flavoured Markdown table
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("| {} |\n", self.headers.join(" | "));
+        markdown.push_str(&format!("|{}\n", " --- |".repeat(self.headers.len())));
+        for row in &self.rows {
+            let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+            markdown.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+        markdown
+    }
+}
+
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_table() -> Table {
+        let mut table = Table::new(&["author", "papers"]);
+        table.push_row(vec!["Doe, John".to_string(), "3".to_string()]);
+        table.push_row(vec!["Smith|Jones".to_string(), "1".to_string()]);
+        table
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let csv = create_table().render(ReportFormat::Csv);
+        assert_eq!(csv, "author,papers\n\"Doe, John\",3\nSmith|Jones,1\n");
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let markdown = create_table().render(ReportFormat::Markdown);
+        assert_eq!(
+            markdown,
+            "| author | papers |\n| --- | --- |\n| Doe, John | 3 |\n| Smith\\|Jones | 1 |\n"
+        );
+    }
+}
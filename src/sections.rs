@@ -0,0 +1,173 @@
+use std::ops::RangeInclusive;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::models::Reference;
+
+/// Title of the group holding references not assigned to any section
+pub const OTHER_REFERENCES: &str = "Other references";
+
+/// References first cited in one section of the citing paper
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionGroup {
+    pub title: String,
+    pub references: Vec<Reference>,
+}
+
+/// A section of the citing paper and the reference numbers it cites
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelRanges {
+    pub title: String,
+    pub ranges: Vec<RangeInclusive<u32>>,
+}
+
+impl LabelRanges {
+    pub fn contains(&self, number: u32) -> bool {
+        self.ranges.iter().any(|range| range.contains(&number))
+    }
+}
+
+/// Parse section ranges, one section per line as `Title: 1-12, 15`, ignoring
+/// blank lines and `#` comments
+pub fn parse_label_ranges(content: &str) -> Result<Vec<LabelRanges>> {
+    let mut sections = Vec::new();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (title, ranges) = line.rsplit_once(':')
+            .ok_or_else(|| anyhow!("Expected `Title: 1-12, 15`, got: {}", line))?;
+        let ranges = ranges.split(',')
+            .map(|range| parse_range(range.trim()).ok_or_else(|| anyhow!("Invalid reference range: {}", range.trim())))
+            .collect::<Result<Vec<_>>>()?;
+        sections.push(LabelRanges { title: title.trim().to_string(), ranges });
+    }
+    Ok(sections)
+}
+
+fn parse_range(range: &str) -> Option<RangeInclusive<u32>> {
+    match range.split_once('-') {
+        Some((start, end)) => Some(start.trim().parse().ok()?..=end.trim().parse().ok()?),
+        None => {
+            let number = range.parse().ok()?;
+            Some(number..=number)
+        }
+    }
+}
+
+/// Group references by their number in the citing paper's reference list: the
+/// numeric label (`[42]`) when there is one, otherwise the position in `references`
+pub fn group_by_labels(references: &[Reference], sections: &[LabelRanges]) -> Vec<SectionGroup> {
+    let numbers = references.iter()
+        .enumerate()
+        .map(|(index, reference)| {
+            reference.label.as_deref()
+                .and_then(|label| label.parse().ok())
+                .unwrap_or(index as u32 + 1)
+        });
+    let assigned: Vec<Option<usize>> = numbers
+        .map(|number| sections.iter().position(|section| section.contains(number)))
+        .collect();
+    collect_groups(references, sections.iter().map(|s| s.title.as_str()), &assigned)
+}
+
+/// Group references by the sections citing them. Each section lists the INSPIRE
+/// IDs or arXiv IDs it cites; a reference goes to the first section citing it.
+pub fn group_by_identifiers(references: &[Reference], sections: &[(String, Vec<String>)]) -> Vec<SectionGroup> {
+    let assigned: Vec<Option<usize>> = references.iter()
+        .map(|reference| {
+            sections.iter().position(|(_, ids)| {
+                ids.iter().any(|id| reference.inspire_id.as_ref() == Some(id) || reference.arxiv_id.as_ref() == Some(id))
+            })
+        })
+        .collect();
+    collect_groups(references, sections.iter().map(|(title, _)| title.as_str()), &assigned)
+}
+
+/// Build the groups in section order, leaving out empty sections and ending
+/// with the unassigned references
+fn collect_groups<'a>(references: &[Reference], titles: impl Iterator<Item = &'a str>, assigned: &[Option<usize>]) -> Vec<SectionGroup> {
+    let mut groups: Vec<SectionGroup> = titles
+        .map(|title| SectionGroup { title: title.to_string(), references: Vec::new() })
+        .collect();
+    let mut other = Vec::new();
+    for (reference, section) in references.iter().zip(assigned) {
+        match section {
+            Some(index) => groups[*index].references.push(reference.clone()),
+            None => other.push(reference.clone()),
+        }
+    }
+    if !other.is_empty() {
+        groups.push(SectionGroup { title: OTHER_REFERENCES.to_string(), references: other });
+    }
+    groups.retain(|group| !group.references.is_empty());
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_reference(title: &str, label: Option<&str>) -> Reference {
+        Reference {
+            title: title.to_string(),
+            label: label.map(|s| s.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_label_ranges() {
+        let content = "# Sections of the paper\nIntroduction: 1-3, 7\n\nModel: 4-6\n";
+        let sections = parse_label_ranges(content).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].title, "Introduction");
+        assert!(sections[0].contains(7));
+        assert!(!sections[0].contains(4));
+        assert_eq!(sections[1].ranges, vec![4..=6]);
+
+        assert!(parse_label_ranges("Introduction 1-3").is_err());
+        assert!(parse_label_ranges("Introduction: one-three").is_err());
+    }
+
+    #[test]
+    fn test_group_by_labels() {
+        let references = vec![
+            create_reference("A", Some("1")),
+            create_reference("B", Some("5")),
+            create_reference("C", None),
+            create_reference("D", Some("Wit98")),
+            create_reference("E", Some("9")),
+        ];
+        let sections = parse_label_ranges("Introduction: 1-3\nModel: 4-6\nOutlook: 10-12").unwrap();
+
+        let groups = group_by_labels(&references, &sections);
+        let summary: Vec<(&str, Vec<&str>)> = groups.iter()
+            .map(|g| (g.title.as_str(), g.references.iter().map(|r| r.title.as_str()).collect()))
+            .collect();
+        // C has no label and is third in the list; D's label is not a number, so it counts as fourth
+        assert_eq!(summary, vec![
+            ("Introduction", vec!["A", "C"]),
+            ("Model", vec!["B", "D"]),
+            (OTHER_REFERENCES, vec!["E"]),
+        ]);
+    }
+
+    #[test]
+    fn test_group_by_identifiers() {
+        let mut cited = create_reference("Cited twice", None);
+        cited.inspire_id = Some("451647".to_string());
+        let mut arxiv = create_reference("By arXiv ID", None);
+        arxiv.arxiv_id = Some("hep-th/9802150".to_string());
+        let references = vec![cited, arxiv, create_reference("Uncited", None)];
+        let sections = vec![
+            ("Introduction".to_string(), vec!["451647".to_string()]),
+            ("Setup".to_string(), vec!["451647".to_string(), "hep-th/9802150".to_string()]),
+        ];
+
+        let groups = group_by_identifiers(&references, &sections);
+        let titles: Vec<(&str, usize)> = groups.iter().map(|g| (g.title.as_str(), g.references.len())).collect();
+        assert_eq!(titles, vec![("Introduction", 1), ("Setup", 1), (OTHER_REFERENCES, 1)]);
+    }
+}
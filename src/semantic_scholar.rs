@@ -0,0 +1,197 @@
+use anyhow::{anyhow, Result};
+use log::{debug, info};
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::models::{DataSource, Reference};
+use crate::updates::normalize;
+
+/// Most references Semantic Scholar returns per request
+const PAGE_SIZE: usize = 1000;
+
+/// Client for the Semantic Scholar Graph API, used where INSPIRE has no
+/// equivalent data (e.g. the sentences citing each reference)
+pub struct SemanticScholarClient {
+    client: Client,
+    base_url: String,
+}
+
+impl Default for SemanticScholarClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A paper's reference as listed by Semantic Scholar, with the sentences citing it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CitationContext {
+    pub title: String,
+    pub arxiv_id: Option<String>,
+    pub doi: Option<String>,
+    pub contexts: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ReferencePage {
+    #[serde(default)]
+    data: Vec<ReferenceEntry>,
+    next: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReferenceEntry {
+    #[serde(default)]
+    contexts: Option<Vec<String>>,
+    cited_paper: Option<CitedPaper>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CitedPaper {
+    title: Option<String>,
+    external_ids: Option<ExternalIds>,
+}
+
+#[derive(Deserialize)]
+struct ExternalIds {
+    #[serde(rename = "ArXiv")]
+    arxiv: Option<String>,
+    #[serde(rename = "DOI")]
+    doi: Option<String>,
+}
+
+impl SemanticScholarClient {
+    pub fn new() -> Self {
+        Self {
+            client: Client::new(),
+            base_url: "https://api.semanticscholar.org/graph/v1".to_string(),
+        }
+    }
+
+    /// The references of an arXiv paper with the sentences citing each one.
+    ///
+    /// Semantic Scholar withholds contexts for some publishers, so entries may
+    /// have none.
+    pub async fn get_citation_contexts(&self, arxiv_id: &str) -> Result<Vec<CitationContext>> {
+        let url = format!("{}/paper/arXiv:{}/references", self.base_url, arxiv_id);
+        let limit = PAGE_SIZE.to_string();
+        let mut contexts = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            debug!("Fetching citation contexts for {} from offset {}", arxiv_id, offset);
+            let offset_param = offset.to_string();
+            let response = self.client.get(&url)
+                .query(&[
+                    ("fields", "contexts,title,externalIds"),
+                    ("limit", limit.as_str()),
+                    ("offset", offset_param.as_str()),
+                ])
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(anyhow!("Failed to fetch citation contexts: {}", response.status()));
+            }
+
+            let page: ReferencePage = response.json().await?;
+            contexts.extend(page.data.into_iter().filter_map(|entry| {
+                let cited = entry.cited_paper?;
+                let ids = cited.external_ids;
+                Some(CitationContext {
+                    title: cited.title.unwrap_or_default(),
+                    arxiv_id: ids.as_ref().and_then(|ids| ids.arxiv.clone()),
+                    doi: ids.and_then(|ids| ids.doi),
+                    contexts: entry.contexts.unwrap_or_default(),
+                })
+            }));
+
+            match page.next {
+                Some(next) if next > offset => offset = next,
+                _ => break,
+            }
+        }
+
+        info!("Found {} references on Semantic Scholar", contexts.len());
+        Ok(contexts)
+    }
+}
+
+/// Attach citation contexts to the matching references, matched by arXiv ID,
+/// then DOI, then title. Returns the number of references that got contexts.
+pub fn attach_contexts(references: &mut [Reference], contexts: &[CitationContext]) -> usize {
+    let mut attached = 0;
+    for reference in references.iter_mut() {
+        let title = normalize(&reference.title);
+        let found = contexts.iter().find(|context| {
+            let same_arxiv = reference.arxiv_id.is_some() && context.arxiv_id == reference.arxiv_id;
+            let same_doi = match (&reference.doi, &context.doi) {
+                (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+                _ => false,
+            };
+            same_arxiv || same_doi
+        }).or_else(|| {
+            contexts.iter().find(|context| !title.is_empty() && normalize(&context.title) == title)
+        });
+
+        if let Some(context) = found.filter(|context| !context.contexts.is_empty()) {
+            reference.contexts = context.contexts.clone();
+            reference.sources.contexts_source = Some(DataSource::SemanticScholar);
+            attached += 1;
+        }
+    }
+    attached
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(title: &str, arxiv_id: Option<&str>, doi: Option<&str>, sentence: &str) -> CitationContext {
+        CitationContext {
+            title: title.to_string(),
+            arxiv_id: arxiv_id.map(|s| s.to_string()),
+            doi: doi.map(|s| s.to_string()),
+            contexts: vec![sentence.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_attach_contexts() {
+        let mut references = vec![
+            Reference { title: "Large N limit".to_string(), arxiv_id: Some("hep-th/9711200".to_string()), ..Default::default() },
+            Reference { title: "Anti de Sitter space".to_string(), doi: Some("10.4310/ATMP.1998.v2.n2.a2".to_string()), ..Default::default() },
+            Reference { title: "Gauge Theory Correlators".to_string(), ..Default::default() },
+            Reference { title: "Uncited here".to_string(), ..Default::default() },
+        ];
+        let contexts = vec![
+            context("The Large N Limit of Superconformal Field Theories", Some("hep-th/9711200"), None, "as conjectured in [1]"),
+            context("Anti-de Sitter space and holography", None, Some("10.4310/atmp.1998.v2.n2.a2"), "see [2]"),
+            context("Gauge theory correlators from non-critical string theory", None, None, "unmatched title"),
+            context("Gauge Theory  Correlators", None, None, "following [3]"),
+        ];
+
+        assert_eq!(attach_contexts(&mut references, &contexts), 3);
+        assert_eq!(references[0].contexts, ["as conjectured in [1]"]);
+        assert_eq!(references[1].contexts, ["see [2]"]);
+        assert_eq!(references[2].contexts, ["following [3]"]);
+        assert_eq!(references[2].sources.contexts_source, Some(DataSource::SemanticScholar));
+        assert!(references[3].contexts.is_empty());
+        assert_eq!(references[3].sources.contexts_source, None);
+    }
+
+    #[test]
+    fn test_parse_reference_page() {
+        let body = r#"{"offset": 0, "data": [
+            {"contexts": ["first mention"], "citedPaper": {"paperId": "abc", "title": "Paper", "externalIds": {"ArXiv": "2301.00001", "DOI": "10.1/x"}}},
+            {"contexts": null, "citedPaper": {"paperId": null, "title": "Unknown", "externalIds": null}}
+        ]}"#;
+        let page: ReferencePage = serde_json::from_str(body).unwrap();
+        assert_eq!(page.data.len(), 2);
+        assert!(page.next.is_none());
+        let ids = page.data[0].cited_paper.as_ref().unwrap().external_ids.as_ref().unwrap();
+        assert_eq!(ids.arxiv.as_deref(), Some("2301.00001"));
+        assert!(page.data[1].contexts.is_none());
+    }
+}
@@ -0,0 +1,309 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use anyhow::{Result, anyhow};
+use log::{debug, info, warn};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::api::InspireClient;
+use crate::metrics::METRICS;
+use crate::network::CitationNetwork;
+
+/// Deepest network a client may request; deeper builds take too long to serve
+pub const MAX_NETWORK_DEPTH: u32 = 3;
+
+/// Largest request head accepted, in bytes
+const MAX_REQUEST_SIZE: usize = 16 * 1024;
+
+/// A request understood by the REST API
+#[derive(Debug, Clone, PartialEq)]
+pub enum Route {
+    /// `GET /paper/:arxiv`
+    Paper(String),
+    /// `GET /references/:id` (INSPIRE record ID)
+    References(String),
+    /// `GET /network?seed=…&depth=…`
+    Network { seed: String, depth: u32 },
+    /// `GET /metrics` (Prometheus text format)
+    Metrics,
+}
+
+impl Route {
+    /// Endpoint name used as a metrics label
+    pub fn name(&self) -> &'static str {
+        match self {
+            Route::Paper(_) => "paper",
+            Route::References(_) => "references",
+            Route::Network { .. } => "network",
+            Route::Metrics => "metrics",
+        }
+    }
+}
+
+/// Error response: HTTP status and message
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl RouteError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+}
+
+/// Map a request method and target (path plus query string) to a route
+pub fn parse_route(method: &str, target: &str) -> Result<Route, RouteError> {
+    if method != "GET" {
+        return Err(RouteError::new(405, format!("Method {} not allowed", method)));
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    if let Some(arxiv_id) = path.strip_prefix("/paper/").filter(|rest| !rest.is_empty()) {
+        return Ok(Route::Paper(percent_decode(arxiv_id)));
+    }
+    if let Some(id) = path.strip_prefix("/references/").filter(|rest| !rest.is_empty()) {
+        return Ok(Route::References(percent_decode(id)));
+    }
+    if path == "/metrics" {
+        return Ok(Route::Metrics);
+    }
+    if path == "/network" {
+        let mut seed = None;
+        let mut depth = 1;
+        for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+            match key {
+                "seed" => seed = Some(percent_decode(value)),
+                "depth" => depth = value.parse()
+                    .map_err(|_| RouteError::new(400, format!("Invalid depth: {}", value)))?,
+                _ => {}
+            }
+        }
+        let seed = seed.filter(|s| !s.is_empty())
+            .ok_or_else(|| RouteError::new(400, "Missing seed parameter"))?;
+        if depth > MAX_NETWORK_DEPTH {
+            return Err(RouteError::new(400, format!("Depth is limited to {}", MAX_NETWORK_DEPTH)));
+        }
+        return Ok(Route::Network { seed, depth });
+    }
+
+    Err(RouteError::new(404, format!("No route for {}", path)))
+}
+
+/// Answer a route with the JSON the endpoint returns
+pub async fn handle(client: &InspireClient, route: &Route) -> Result<Value> {
+    match route {
+        Route::Paper(arxiv_id) => {
+            let paper = client.get_paper_by_arxiv(arxiv_id).await?;
+            Ok(serde_json::to_value(paper)?)
+        }
+        Route::References(id) => {
+            let references = client.get_paper_references(id).await?;
+            Ok(serde_json::to_value(references)?)
+        }
+        Route::Network { seed, depth } => {
+            let mut network = CitationNetwork::new();
+            network.build(client, seed, *depth).await?;
+            Ok(serde_json::to_value(network)?)
+        }
+        Route::Metrics => Ok(Value::String(METRICS.render())),
+    }
+}
+
+/// Serve the REST API on `addr` until the process is stopped
+pub async fn serve(client: Arc<InspireClient>, addr: SocketAddr) -> Result<()> {
+    serve_routes(client, addr, false).await
+}
+
+/// Serve only `/metrics` on `addr`, for processes whose API is not HTTP
+pub async fn serve_metrics(addr: SocketAddr) -> Result<()> {
+    serve_routes(Arc::new(InspireClient::new()), addr, true).await
+}
+
+async fn serve_routes(client: Arc<InspireClient>, addr: SocketAddr, metrics_only: bool) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("Listening on http://{}", listener.local_addr()?);
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let client = Arc::clone(&client);
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(&client, stream, metrics_only).await {
+                warn!("Connection from {} failed: {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(client: &InspireClient, mut stream: TcpStream, metrics_only: bool) -> Result<()> {
+    let head = read_request_head(&mut stream).await?;
+    let request_line = head.lines().next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method, target),
+        _ => return write_json(&mut stream, 400, &json!({ "error": "Malformed request" })).await,
+    };
+    debug!("{} {}", method, target);
+
+    let route = parse_route(method, target)
+        .and_then(|route| match route {
+            Route::Metrics => Ok(route),
+            _ if metrics_only => Err(RouteError::new(404, "Only /metrics is served here")),
+            _ => Ok(route),
+        });
+    let (endpoint, status, body) = match route {
+        Ok(Route::Metrics) => {
+            METRICS.record_request("metrics", "200");
+            return write_response(&mut stream, 200, "text/plain; version=0.0.4", &METRICS.render()).await;
+        }
+        Ok(route) => match handle(client, &route).await {
+            Ok(body) => (route.name(), 200, body),
+            Err(e) => (route.name(), 502, json!({ "error": e.to_string() })),
+        },
+        Err(e) => ("unknown", e.status, json!({ "error": e.message })),
+    };
+
+    METRICS.record_request(endpoint, &status.to_string());
+    write_json(&mut stream, status, &body).await
+}
+
+async fn read_request_head(stream: &mut TcpStream) -> Result<String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+        if buffer.windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+        if buffer.len() > MAX_REQUEST_SIZE {
+            return Err(anyhow!("Request head too large"));
+        }
+    }
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+async fn write_json(stream: &mut TcpStream, status: u16, body: &Value) -> Result<()> {
+    write_response(stream, status, "application/json", &serde_json::to_string(body)?).await
+}
+
+async fn write_response(stream: &mut TcpStream, status: u16, content_type: &str, body: &str) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason_phrase(status),
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        502 => "Bad Gateway",
+        _ => "Error",
+    }
+}
+
+/// Decode `%XX` escapes and `+` in a path segment or query value
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_route() {
+        assert_eq!(parse_route("GET", "/paper/2301.12345"), Ok(Route::Paper("2301.12345".to_string())));
+        assert_eq!(parse_route("GET", "/paper/hep-th/9711200"), Ok(Route::Paper("hep-th/9711200".to_string())));
+        assert_eq!(parse_route("GET", "/paper/hep-th%2F9711200"), Ok(Route::Paper("hep-th/9711200".to_string())));
+        assert_eq!(parse_route("GET", "/references/123456"), Ok(Route::References("123456".to_string())));
+        assert_eq!(parse_route("GET", "/metrics"), Ok(Route::Metrics));
+        assert_eq!(
+            parse_route("GET", "/network?seed=2301.12345&depth=2"),
+            Ok(Route::Network { seed: "2301.12345".to_string(), depth: 2 })
+        );
+        assert_eq!(
+            parse_route("GET", "/network?seed=2301.12345"),
+            Ok(Route::Network { seed: "2301.12345".to_string(), depth: 1 })
+        );
+    }
+
+    #[test]
+    fn test_parse_route_errors() {
+        assert_eq!(parse_route("POST", "/paper/1").unwrap_err().status, 405);
+        assert_eq!(parse_route("GET", "/paper/").unwrap_err().status, 404);
+        assert_eq!(parse_route("GET", "/unknown").unwrap_err().status, 404);
+        assert_eq!(parse_route("GET", "/network?depth=1").unwrap_err().status, 400);
+        assert_eq!(parse_route("GET", "/network?seed=1&depth=x").unwrap_err().status, 400);
+        assert_eq!(parse_route("GET", "/network?seed=1&depth=10").unwrap_err().status, 400);
+    }
+
+    #[tokio::test]
+    async fn test_serve_answers_errors_and_metrics() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = InspireClient::new();
+        tokio::spawn(async move {
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().await.unwrap();
+                handle_connection(&client, stream, false).await.unwrap();
+            }
+        });
+
+        let response = get(addr, "/unknown").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(response.contains("Content-Type: application/json"));
+        assert!(response.ends_with("{\"error\":\"No route for /unknown\"}"));
+
+        let response = get(addr, "/metrics").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4"));
+        assert!(response.contains("reference_tool_requests_total{endpoint=\"unknown\",status=\"404\"}"));
+    }
+
+    async fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+}
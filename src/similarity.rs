@@ -0,0 +1,541 @@
+use std::collections::{HashMap, HashSet};
+use anyhow::Result;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::api::InspireClient;
+use crate::models::{Paper, Reference};
+use crate::progress::{self, Progress, SharedObserver};
+use crate::report::{ReportFormat, Table};
+
+/// A paper related to the seed through shared references
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarPaper {
+    pub paper: Paper,
+    /// Number of the seed's references this paper also cites
+    pub shared_references: usize,
+    /// Bibliographic coupling strength: shared references over the references
+    /// whose citing papers were fetched
+    pub score: f64,
+}
+
+/// Limits for the similarity search, keeping the number of API requests bounded
+#[derive(Clone)]
+pub struct SimilarityOptions {
+    /// Number of the seed's references to examine
+    pub max_references: usize,
+    /// Number of citing papers fetched per reference
+    pub citing_per_reference: usize,
+    /// Number of results to return
+    pub limit: usize,
+    /// Stops fetching citing papers; results are ranked from what was fetched so far
+    pub cancel: CancellationToken,
+    /// Receives progress after each request
+    pub observer: Option<SharedObserver>,
+}
+
+impl Default for SimilarityOptions {
+    fn default() -> Self {
+        Self {
+            max_references: 25,
+            citing_per_reference: 50,
+            limit: 20,
+            cancel: CancellationToken::new(),
+            observer: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for SimilarityOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SimilarityOptions")
+            .field("max_references", &self.max_references)
+            .field("citing_per_reference", &self.citing_per_reference)
+            .field("limit", &self.limit)
+            .field("cancel", &self.cancel)
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
+}
+
+/// Find papers related to the seed by bibliographic coupling.
+///
+/// For each of the seed's references, the papers citing that reference are
+/// "siblings" of the seed; the more references a sibling shares with the seed,
+/// the more related it is. Papers the seed already cites are excluded.
+pub async fn find_similar(
+    client: &InspireClient,
+    arxiv_id: &str,
+    options: &SimilarityOptions,
+) -> Result<(Paper, Vec<SimilarPaper>)> {
+    let seed = client.get_paper_by_arxiv(arxiv_id).await?;
+    let references = client.get_paper_references(&seed.id).await?;
+
+    let seed_references: Vec<String> = references.iter()
+        .filter_map(|r| r.inspire_id.clone())
+        .collect();
+    let reference_ids = &seed_references[..seed_references.len().min(options.max_references)];
+
+    info!("Looking for papers sharing {} references with {}", reference_ids.len(), seed.title);
+
+    let mut progress = Progress {
+        requests_completed: 2,
+        stage: "similar",
+        total: Some(reference_ids.len() + 2),
+        ..Default::default()
+    };
+    let mut discovered = HashSet::new();
+    let mut citing = Vec::new();
+    for reference_id in reference_ids {
+        let request = client.get_citing_papers(reference_id, options.citing_per_reference);
+        match options.cancel.run_until_cancelled(request).await {
+            Some(Ok(papers)) => {
+                discovered.extend(papers.iter().map(|p| p.id.clone()));
+                citing.push(papers);
+            }
+            Some(Err(e)) => debug!("Failed to get citing papers for {}: {}", reference_id, e),
+            None => {
+                info!("Similarity search cancelled after {} of {} references", citing.len(), reference_ids.len());
+                break;
+            }
+        }
+        progress.requests_completed += 1;
+        progress.papers_discovered = discovered.len();
+        progress.current_item = Some(reference_id.clone());
+        progress::report(options.observer.as_deref(), &progress);
+    }
+    progress::finish(options.observer.as_deref(), &progress);
+
+    let mut similar = rank_by_coupling(&seed.id, &seed_references, &citing);
+    similar.truncate(options.limit);
+    Ok((seed, similar))
+}
+
+/// Rank candidate papers by how many of the seed's references they cite.
+///
+/// `seed_references` holds every reference of the seed, all of which are
+/// excluded from the results. `citing` holds, for each examined seed reference
+/// whose citing papers could be fetched, the papers citing it; the score is
+/// relative to these lists only.
+pub fn rank_by_coupling(seed_id: &str, seed_references: &[String], citing: &[Vec<Paper>]) -> Vec<SimilarPaper> {
+    let excluded: HashSet<&str> = seed_references.iter()
+        .map(|id| id.as_str())
+        .chain(std::iter::once(seed_id))
+        .collect();
+
+    let mut candidates: HashMap<&str, (&Paper, usize)> = HashMap::new();
+    for papers in citing {
+        let mut seen_in_list = HashSet::new();
+        for paper in papers {
+            if excluded.contains(paper.id.as_str()) || !seen_in_list.insert(paper.id.as_str()) {
+                continue;
+            }
+            candidates.entry(paper.id.as_str())
+                .or_insert((paper, 0))
+                .1 += 1;
+        }
+    }
+
+    let examined = citing.len().max(1) as f64;
+    let mut similar: Vec<SimilarPaper> = candidates.into_values()
+        .map(|(paper, shared)| SimilarPaper {
+            paper: paper.clone(),
+            shared_references: shared,
+            score: shared as f64 / examined,
+        })
+        .collect();
+
+    similar.sort_by(|a, b| {
+        b.shared_references.cmp(&a.shared_references)
+            .then_with(|| a.paper.id.cmp(&b.paper.id))
+    });
+    similar
+}
+
+/// A group of the seed's references that cite much of the same literature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceCluster {
+    pub references: Vec<Reference>,
+    /// Papers cited by the most members of the cluster, most cited first
+    pub common_references: Vec<CommonReference>,
+    /// Average coupling strength between pairs of members
+    pub cohesion: f64,
+}
+
+/// A paper cited by several members of a cluster
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommonReference {
+    pub inspire_id: String,
+    pub title: String,
+    /// Number of cluster members citing it
+    pub cited_by: usize,
+}
+
+/// The seed's references grouped by bibliographic coupling
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BibliographyMap {
+    pub seed: Paper,
+    /// Clusters of two or more references, largest first
+    pub clusters: Vec<ReferenceCluster>,
+    /// References not coupled strongly enough to any other, or whose own
+    /// references could not be fetched
+    pub unclustered: Vec<Reference>,
+}
+
+/// Limits for building a bibliography map
+#[derive(Clone)]
+pub struct MapOptions {
+    /// Number of the seed's references to examine
+    pub max_references: usize,
+    /// Lowest average coupling strength at which two clusters are merged
+    pub threshold: f64,
+    /// Number of common references listed per cluster
+    pub common_references: usize,
+    /// Stops fetching reference lists; clusters are built from what was fetched so far
+    pub cancel: CancellationToken,
+    /// Receives progress after each request
+    pub observer: Option<SharedObserver>,
+}
+
+impl Default for MapOptions {
+    fn default() -> Self {
+        Self {
+            max_references: 50,
+            threshold: 0.1,
+            common_references: 3,
+            cancel: CancellationToken::new(),
+            observer: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for MapOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MapOptions")
+            .field("max_references", &self.max_references)
+            .field("threshold", &self.threshold)
+            .field("common_references", &self.common_references)
+            .field("cancel", &self.cancel)
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
+}
+
+impl BibliographyMap {
+    /// Markdown with one section per cluster, or CSV with one row per reference
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Csv => self.table().to_csv(),
+        }
+    }
+
+    /// Every examined reference with the number of its cluster (empty when unclustered)
+    pub fn table(&self) -> Table {
+        let mut table = Table::new(&["cluster", "title", "year", "arxiv_id", "inspire_id"]);
+        let clustered = self.clusters.iter()
+            .enumerate()
+            .flat_map(|(index, cluster)| cluster.references.iter().map(move |r| ((index + 1).to_string(), r)));
+        let unclustered = self.unclustered.iter().map(|r| (String::new(), r));
+        for (cluster, reference) in clustered.chain(unclustered) {
+            table.push_row(vec![
+                cluster,
+                reference.title.clone(),
+                reference.year.map(|year| year.to_string()).unwrap_or_default(),
+                reference.arxiv_id.clone().unwrap_or_default(),
+                reference.inspire_id.clone().unwrap_or_default(),
+            ]);
+        }
+        table
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# Map of the bibliography of {}\n", self.seed.title);
+        for (index, cluster) in self.clusters.iter().enumerate() {
+            markdown.push_str(&format!(
+                "\n## Cluster {} ({} references, cohesion {:.2})\n\n",
+                index + 1, cluster.references.len(), cluster.cohesion
+            ));
+            if !cluster.common_references.is_empty() {
+                let common: Vec<String> = cluster.common_references.iter()
+                    .map(|c| format!("{} ({})", c.title, c.cited_by))
+                    .collect();
+                markdown.push_str(&format!("Common references: {}\n\n", common.join("; ")));
+            }
+            push_reference_items(&mut markdown, &cluster.references);
+        }
+        if !self.unclustered.is_empty() {
+            markdown.push_str(&format!("\n## Unclustered ({} references)\n\n", self.unclustered.len()));
+            push_reference_items(&mut markdown, &self.unclustered);
+        }
+        markdown
+    }
+}
+
+fn push_reference_items(markdown: &mut String, references: &[Reference]) {
+    for reference in references {
+        markdown.push_str(&format!("- {}", reference.title));
+        if let Some(year) = reference.year {
+            markdown.push_str(&format!(" ({})", year));
+        }
+        if let Some(arxiv_id) = &reference.arxiv_id {
+            markdown.push_str(&format!(" arXiv:{}", arxiv_id));
+        }
+        markdown.push('\n');
+    }
+}
+
+/// Group a paper's references by bibliographic coupling: two references are
+/// close when their own reference lists overlap.
+///
+/// The reference lists are fetched in batches, then clustered by average
+/// linkage until no two clusters are coupled at `threshold` or above.
+pub async fn map_bibliography(
+    client: &InspireClient,
+    arxiv_id: &str,
+    options: &MapOptions,
+) -> Result<BibliographyMap> {
+    let seed = client.get_paper_by_arxiv(arxiv_id).await?;
+    let references = client.get_paper_references(&seed.id).await?;
+
+    let mut unclustered: Vec<Reference> = Vec::new();
+    let mut examined: Vec<Reference> = Vec::new();
+    for reference in references {
+        if reference.inspire_id.is_some() && examined.len() < options.max_references {
+            examined.push(reference);
+        } else {
+            unclustered.push(reference);
+        }
+    }
+    let ids: Vec<String> = examined.iter().filter_map(|r| r.inspire_id.clone()).collect();
+
+    info!("Mapping {} references of {}", ids.len(), seed.title);
+
+    let batches = ids.len().div_ceil(crate::api::RECORD_BATCH_SIZE);
+    let mut progress = Progress {
+        requests_completed: 2,
+        stage: "map",
+        total: Some(batches + 2),
+        ..Default::default()
+    };
+    // Individual fetches below still cover any record a failed batch missed
+    match options.cancel.run_until_cancelled(client.prefetch_records(&ids)).await {
+        Some(Ok(fetched)) => debug!("Prefetched {} reference records", fetched),
+        Some(Err(e)) => warn!("Batch fetch failed, fetching references one by one: {}", e),
+        None => {}
+    }
+    progress.requests_completed += batches;
+    progress::report(options.observer.as_deref(), &progress);
+
+    let mut titles: HashMap<String, String> = HashMap::new();
+    let mut lists: Vec<HashSet<String>> = Vec::new();
+    let mut fetched: Vec<Reference> = Vec::new();
+    for (reference, id) in examined.into_iter().zip(&ids) {
+        if options.cancel.is_cancelled() {
+            unclustered.push(reference);
+            continue;
+        }
+        match client.get_paper_references(id).await {
+            Ok(cited) if !cited.is_empty() => {
+                let mut list = HashSet::new();
+                for cited in cited {
+                    if let Some(cited_id) = cited.inspire_id {
+                        titles.entry(cited_id.clone()).or_insert(cited.title);
+                        list.insert(cited_id);
+                    }
+                }
+                progress.papers_discovered += list.len();
+                lists.push(list);
+                fetched.push(reference);
+            }
+            Ok(_) => unclustered.push(reference),
+            Err(e) => {
+                debug!("Failed to get references of {}: {}", id, e);
+                unclustered.push(reference);
+            }
+        }
+    }
+    progress::finish(options.observer.as_deref(), &progress);
+
+    let mut clusters = Vec::new();
+    for members in cluster_by_coupling(&lists, options.threshold) {
+        if members.len() < 2 {
+            unclustered.extend(members.iter().map(|&i| fetched[i].clone()));
+            continue;
+        }
+        let cohesion = average_coupling(&lists, &members, &members);
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for &member in &members {
+            for id in &lists[member] {
+                *counts.entry(id.as_str()).or_insert(0) += 1;
+            }
+        }
+        let mut common: Vec<CommonReference> = counts.into_iter()
+            .filter(|&(_, cited_by)| cited_by > 1)
+            .map(|(id, cited_by)| CommonReference {
+                inspire_id: id.to_string(),
+                title: titles.get(id).cloned().unwrap_or_default(),
+                cited_by,
+            })
+            .collect();
+        common.sort_by(|a, b| b.cited_by.cmp(&a.cited_by).then_with(|| a.inspire_id.cmp(&b.inspire_id)));
+        common.truncate(options.common_references);
+        clusters.push(ReferenceCluster {
+            references: members.iter().map(|&i| fetched[i].clone()).collect(),
+            common_references: common,
+            cohesion,
+        });
+    }
+
+    Ok(BibliographyMap { seed, clusters, unclustered })
+}
+
+/// Bibliographic coupling strength of two reference lists: shared references
+/// over the geometric mean of the list lengths (0 for an empty list)
+pub fn coupling_strength(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(b).count();
+    shared as f64 / ((a.len() * b.len()) as f64).sqrt()
+}
+
+/// Average coupling strength between members of two groups, skipping a member paired with itself
+fn average_coupling(lists: &[HashSet<String>], first: &[usize], second: &[usize]) -> f64 {
+    let mut total = 0.0;
+    let mut pairs = 0;
+    for &i in first {
+        for &j in second {
+            if i != j {
+                total += coupling_strength(&lists[i], &lists[j]);
+                pairs += 1;
+            }
+        }
+    }
+    if pairs == 0 { 0.0 } else { total / pairs as f64 }
+}
+
+/// Cluster reference lists by average linkage, merging the most strongly coupled
+/// pair of clusters while its coupling is at least `threshold`.
+///
+/// Returns the indices of each cluster's lists, largest cluster first.
+pub fn cluster_by_coupling(lists: &[HashSet<String>], threshold: f64) -> Vec<Vec<usize>> {
+    let mut clusters: Vec<Vec<usize>> = (0..lists.len()).map(|i| vec![i]).collect();
+    loop {
+        let mut best: Option<(usize, usize, f64)> = None;
+        for i in 0..clusters.len() {
+            for j in i + 1..clusters.len() {
+                let coupling = average_coupling(lists, &clusters[i], &clusters[j]);
+                if coupling >= threshold && best.is_none_or(|(_, _, strongest)| coupling > strongest) {
+                    best = Some((i, j, coupling));
+                }
+            }
+        }
+        let Some((i, j, _)) = best else { break };
+        let merged = clusters.remove(j);
+        clusters[i].extend(merged);
+    }
+
+    for cluster in &mut clusters {
+        cluster.sort_unstable();
+    }
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_paper(id: &str) -> Paper {
+        Paper {
+            id: id.to_string(),
+            title: format!("Paper {}", id),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rank_by_coupling() {
+        // r4 was not examined and the citing papers of r3 could not be fetched
+        let seed_references = vec!["r1".to_string(), "r2".to_string(), "r3".to_string(), "r4".to_string()];
+        let citing = vec![
+            vec![create_paper("seed"), create_paper("a"), create_paper("b")],
+            vec![create_paper("a"), create_paper("c"), create_paper("r4")],
+        ];
+
+        let similar = rank_by_coupling("seed", &seed_references, &citing);
+        let ids: Vec<&str> = similar.iter().map(|s| s.paper.id.as_str()).collect();
+
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(similar[0].shared_references, 2);
+        assert!((similar[0].score - 1.0).abs() < f64::EPSILON);
+        assert!((similar[1].score - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_rank_by_coupling_counts_duplicates_once() {
+        let reference_ids = vec!["r1".to_string()];
+        let citing = vec![vec![create_paper("a"), create_paper("a")]];
+
+        let similar = rank_by_coupling("seed", &reference_ids, &citing);
+        assert_eq!(similar.len(), 1);
+        assert_eq!(similar[0].shared_references, 1);
+    }
+
+    #[test]
+    fn test_rank_by_coupling_empty() {
+        assert!(rank_by_coupling("seed", &[], &[]).is_empty());
+    }
+
+    fn reference_list(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_coupling_strength() {
+        let a = reference_list(&["1", "2", "3", "4"]);
+        let b = reference_list(&["3", "4", "5", "6"]);
+        assert!((coupling_strength(&a, &b) - 0.5).abs() < f64::EPSILON);
+        assert_eq!(coupling_strength(&a, &HashSet::new()), 0.0);
+    }
+
+    #[test]
+    fn test_cluster_by_coupling() {
+        let lists = vec![
+            reference_list(&["1", "2", "3"]),
+            reference_list(&["7", "8", "9"]),
+            reference_list(&["1", "2", "4"]),
+            reference_list(&["7", "8", "10"]),
+            reference_list(&["2", "3", "4"]),
+            reference_list(&["42"]),
+        ];
+
+        let clusters = cluster_by_coupling(&lists, 0.3);
+        assert_eq!(clusters, vec![vec![0, 2, 4], vec![1, 3], vec![5]]);
+
+        // Nothing is coupled strongly enough at a threshold above 1
+        assert_eq!(cluster_by_coupling(&lists, 1.1).len(), lists.len());
+    }
+
+    #[test]
+    fn test_bibliography_map_rendering() {
+        let reference = |title: &str, year: u32| Reference { title: title.to_string(), year: Some(year), ..Default::default() };
+        let map = BibliographyMap {
+            seed: create_paper("seed"),
+            clusters: vec![ReferenceCluster {
+                references: vec![reference("A", 2020), reference("B", 2021)],
+                common_references: vec![CommonReference { inspire_id: "9".to_string(), title: "Classic".to_string(), cited_by: 2 }],
+                cohesion: 0.5,
+            }],
+            unclustered: vec![reference("C", 2019)],
+        };
+
+        let markdown = map.render(ReportFormat::Markdown);
+        assert!(markdown.starts_with("# Map of the bibliography of Paper seed\n\n## Cluster 1 (2 references, cohesion 0.50)\n\n"));
+        assert!(markdown.contains("Common references: Classic (2)\n\n- A (2020)\n- B (2021)\n"));
+        assert!(markdown.ends_with("## Unclustered (1 references)\n\n- C (2019)\n"));
+        assert!(map.render(ReportFormat::Csv).ends_with("1,B,2021,,\n,C,2019,,\n"));
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use lazy_static::lazy_static;
+use log::{debug, info};
+use regex::Regex;
+
+use crate::api::InspireClient;
+use crate::bibtex;
+use crate::report::Table;
+use crate::texscan;
+
+lazy_static! {
+    static ref DOCUMENTCLASS_RE: Regex = Regex::new(r"(?m)^[^%]*\\documentclass").unwrap();
+    static ref BIBLIOGRAPHY_RE: Regex = Regex::new(r"(?m)^[^%]*\\(?:bibliography|addbibresource)\{([^}]*)\}").unwrap();
+}
+
+/// A LaTeX project found in a workspace
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatexProject {
+    pub dir: PathBuf,
+    /// Main document (the `.tex` file containing `\documentclass`)
+    pub main_tex: PathBuf,
+    /// Bibliography file new entries are appended to (the first one listed)
+    pub bib_path: PathBuf,
+    /// Further bibliography files the document lists; only read for existing keys
+    pub other_bibs: Vec<PathBuf>,
+}
+
+/// Outcome of syncing one project's bibliography
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncSummary {
+    pub project: PathBuf,
+    /// Citation keys used by the document
+    pub cited: usize,
+    /// Keys already present in the `.bib` file
+    pub existing: usize,
+    /// Keys resolved on INSPIRE and appended to the `.bib` file
+    pub added: Vec<String>,
+    /// Keys that could not be resolved
+    pub unresolved: Vec<String>,
+}
+
+/// Find LaTeX projects in the immediate child directories of a workspace
+pub fn discover_projects(workspace: &Path) -> Result<Vec<LatexProject>> {
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(workspace)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+
+    let mut projects = Vec::new();
+    for dir in dirs {
+        match find_project(&dir)? {
+            Some(project) => projects.push(project),
+            None => debug!("No LaTeX document found in {}", dir.display()),
+        }
+    }
+
+    Ok(projects)
+}
+
+/// Detect the main document and bibliography of a project directory
+pub fn find_project(dir: &Path) -> Result<Option<LatexProject>> {
+    let mut tex_files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "tex"))
+        .collect();
+    tex_files.sort();
+
+    for main_tex in tex_files {
+        let content = std::fs::read_to_string(&main_tex)?;
+        if !DOCUMENTCLASS_RE.is_match(&content) {
+            continue;
+        }
+
+        let mut bib_paths: Vec<PathBuf> = Vec::new();
+        let names = BIBLIOGRAPHY_RE.captures_iter(&content)
+            .flat_map(|caps| caps[1].split(',').map(|name| name.trim().to_string()).collect::<Vec<_>>())
+            .filter(|name| !name.is_empty());
+        for name in names {
+            let path = dir.join(&name);
+            let path = if path.extension().is_some_and(|ext| ext == "bib") { path } else { dir.join(format!("{}.bib", name)) };
+            if !bib_paths.contains(&path) {
+                bib_paths.push(path);
+            }
+        }
+        if bib_paths.is_empty() {
+            bib_paths.push(main_tex.with_extension("bib"));
+        }
+        let bib_path = bib_paths.remove(0);
+
+        return Ok(Some(LatexProject { dir: dir.to_path_buf(), main_tex, bib_path, other_bibs: bib_paths }));
+    }
+
+    Ok(None)
+}
+
+/// Add entries for newly cited keys to a project's bibliography.
+///
+/// Existing entries are left untouched; only keys missing from every listed
+/// `.bib` file are resolved and appended to the first one. With `dry_run`
+/// nothing is written.
+pub async fn sync_project(client: &InspireClient, project: &LatexProject, dry_run: bool) -> Result<SyncSummary> {
+    let keys = texscan::citation_keys(&project.main_tex)?;
+
+    let existing_bib = read_bib(&project.bib_path)?;
+    let mut existing_keys: HashSet<String> = bibtex::parse(&existing_bib)?
+        .into_iter()
+        .map(|entry| entry.key)
+        .collect();
+    for path in &project.other_bibs {
+        existing_keys.extend(bibtex::parse(&read_bib(path)?)?.into_iter().map(|entry| entry.key));
+    }
+
+    let missing: Vec<String> = keys.iter()
+        .filter(|key| !existing_keys.contains(*key))
+        .cloned()
+        .collect();
+
+    info!("{}: {} cited, {} missing from {}", project.dir.display(), keys.len(), missing.len(), project.bib_path.display());
+
+    let (references, unresolved) = texscan::resolve_keys(client, &missing).await;
+
+    if !dry_run && !references.is_empty() {
+        let mut content = existing_bib;
+        if !content.is_empty() && !content.ends_with("\n\n") {
+            content.push_str(if content.ends_with('\n') { "\n" } else { "\n\n" });
+        }
+        let entries: Vec<String> = references.iter().map(|r| r.to_bibtex()).collect();
+        content.push_str(&entries.join("\n"));
+        std::fs::write(&project.bib_path, content)?;
+    }
+
+    Ok(SyncSummary {
+        project: project.dir.clone(),
+        cited: keys.len(),
+        existing: keys.len() - missing.len(),
+        added: references.into_iter().map(|r| r.bibtex_key()).collect(),
+        unresolved,
+    })
+}
+
+/// Contents of a bibliography file, or nothing if it does not exist yet
+fn read_bib(path: &Path) -> Result<String> {
+    if path.exists() {
+        Ok(std::fs::read_to_string(path)?)
+    } else {
+        Ok(String::new())
+    }
+}
+
+/// Cross-project summary table
+pub fn summary_table(summaries: &[SyncSummary]) -> Table {
+    let mut table = Table::new(&["project", "cited", "existing", "added", "unresolved"]);
+    for summary in summaries {
+        table.push_row(vec![
+            summary.project.display().to_string(),
+            summary.cited.to_string(),
+            summary.existing.to_string(),
+            summary.added.len().to_string(),
+            summary.unresolved.join(" "),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_discover_projects() {
+        let workspace = tempdir().unwrap();
+
+        let paper = workspace.path().join("paper-a");
+        std::fs::create_dir(&paper).unwrap();
+        std::fs::write(paper.join("intro.tex"), "\\section{Intro}").unwrap();
+        std::fs::write(paper.join("main.tex"), "\\documentclass{article}\n\\bibliography{refs,extra}\n").unwrap();
+
+        let biblatex = workspace.path().join("paper-b");
+        std::fs::create_dir(&biblatex).unwrap();
+        std::fs::write(biblatex.join("draft.tex"), "\\documentclass{revtex4}\n\\addbibresource{library.bib}\n").unwrap();
+
+        let no_bib = workspace.path().join("paper-c");
+        std::fs::create_dir(&no_bib).unwrap();
+        std::fs::write(no_bib.join("notes.tex"), "% \\bibliography{old}\n\\documentclass{article}\n").unwrap();
+
+        std::fs::create_dir(workspace.path().join("figures")).unwrap();
+
+        let projects = discover_projects(workspace.path()).unwrap();
+        assert_eq!(projects.len(), 3);
+        assert_eq!(projects[0].main_tex, paper.join("main.tex"));
+        assert_eq!(projects[0].bib_path, paper.join("refs.bib"));
+        assert_eq!(projects[0].other_bibs, vec![paper.join("extra.bib")]);
+        assert_eq!(projects[1].bib_path, biblatex.join("library.bib"));
+        assert_eq!(projects[2].bib_path, no_bib.join("notes.bib"));
+        assert!(projects[2].other_bibs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sync_project_with_complete_bib_makes_no_requests() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("main.tex"), "\\documentclass{article}\n\\cite{Known:2020,Other:2021}\n\\bibliography{refs,extra}\n").unwrap();
+        let bib = "@article{Known:2020,\n  title = {Known},\n}\n";
+        std::fs::write(dir.path().join("refs.bib"), bib).unwrap();
+        std::fs::write(dir.path().join("extra.bib"), "@article{Other:2021,\n  title = {Other},\n}\n").unwrap();
+
+        let project = find_project(dir.path()).unwrap().unwrap();
+        let summary = sync_project(&InspireClient::new(), &project, false).await.unwrap();
+
+        assert_eq!(summary.cited, 2);
+        assert_eq!(summary.existing, 2);
+        assert!(summary.added.is_empty());
+        assert_eq!(std::fs::read_to_string(dir.path().join("refs.bib")).unwrap(), bib);
+    }
+
+    #[test]
+    fn test_summary_table() {
+        let summaries = vec![SyncSummary {
+            project: PathBuf::from("papers/a"),
+            cited: 3,
+            existing: 1,
+            added: vec!["Doe:2020ab".to_string()],
+            unresolved: vec!["mystery".to_string()],
+        }];
+
+        let csv = summary_table(&summaries).to_csv();
+        assert!(csv.contains("papers/a,3,1,1,mystery\n"));
+    }
+}
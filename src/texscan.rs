@@ -0,0 +1,264 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, anyhow};
+use lazy_static::lazy_static;
+use log::warn;
+use regex::Regex;
+
+use crate::api::InspireClient;
+use crate::models::Reference;
+
+lazy_static! {
+    /// `\cite`, `\citep`, `\citet*`, `\nocite`, `\autocite`, ... with optional arguments
+    static ref CITE_RE: Regex = Regex::new(
+        r"\\(?:no)?[a-zA-Z]*cite[a-zA-Z]*\*?\s*(?:\[[^\]]*\]\s*){0,2}\{([^}]*)\}"
+    ).unwrap();
+
+    /// `\chapter{...}` and `\section{...}` headings, starred or with a short title
+    static ref SECTION_RE: Regex = Regex::new(
+        r"\\(?:chapter|section)\*?\s*(?:\[[^\]]*\]\s*)?\{([^}]*)\}"
+    ).unwrap();
+
+    /// `\citation{...}` lines written by LaTeX into `.aux` files
+    static ref AUX_CITATION_RE: Regex = Regex::new(r"\\citation\{([^}]*)\}").unwrap();
+
+    /// New-style (`2301.12345`) and old-style (`hep-th/9711200`) arXiv identifiers
+    static ref ARXIV_ID_RE: Regex = Regex::new(
+        r"^(?:\d{4}\.\d{4,5}|[a-z-]+(?:\.[A-Z]{2})?/\d{7})(?:v\d+)?$"
+    ).unwrap();
+}
+
+/// Extract citation keys from LaTeX source, in order of first appearance
+pub fn scan_tex(content: &str) -> Vec<String> {
+    let content: String = content.lines()
+        .map(strip_comment)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    collect_keys(CITE_RE.captures_iter(&content).map(|caps| caps[1].to_string()))
+}
+
+/// Title given to citations that appear before the first section heading
+pub const FRONT_MATTER: &str = "Front matter";
+
+/// A top-level section of a LaTeX document with the keys first cited in it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TexSection {
+    pub title: String,
+    pub keys: Vec<String>,
+}
+
+/// Split LaTeX source into its chapters and sections, assigning each citation
+/// key to the section where it is first cited. Subsections belong to their
+/// section; sections without new citations are left out.
+pub fn scan_sections(content: &str) -> Vec<TexSection> {
+    let content: String = content.lines()
+        .map(strip_comment)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let headings = SECTION_RE.captures_iter(&content)
+        .map(|caps| (caps.get(0).unwrap().start(), Some(caps[1].trim().to_string())));
+    let citations = CITE_RE.captures_iter(&content)
+        .map(|caps| (caps.get(0).unwrap().start(), None));
+    let mut events: Vec<(usize, Option<String>)> = headings.chain(citations).collect();
+    events.sort_by_key(|(position, _)| *position);
+
+    let mut sections = vec![TexSection { title: FRONT_MATTER.to_string(), keys: Vec::new() }];
+    let mut seen = HashSet::new();
+    for (position, heading) in events {
+        if let Some(title) = heading {
+            sections.push(TexSection { title, keys: Vec::new() });
+            continue;
+        }
+        let group = CITE_RE.captures(&content[position..]).map(|caps| caps[1].to_string()).unwrap_or_default();
+        let current = sections.last_mut().expect("sections starts non-empty");
+        for key in collect_keys(std::iter::once(group)) {
+            if seen.insert(key.clone()) {
+                current.keys.push(key);
+            }
+        }
+    }
+
+    sections.retain(|section| !section.keys.is_empty());
+    sections
+}
+
+/// Extract the citation keys LaTeX actually used from an `.aux` file.
+///
+/// Unlike `scan_tex` this also sees keys produced by macros, since the `.aux`
+/// file is written after macro expansion.
+pub fn parse_aux(content: &str) -> Vec<String> {
+    collect_keys(AUX_CITATION_RE.captures_iter(content).map(|caps| caps[1].to_string()))
+}
+
+/// Collect citation keys for a `.tex` or `.aux` file.
+///
+/// For a `.tex` file the sibling `.aux` file is preferred when it exists, as
+/// it reflects what LaTeX resolved on the last compilation.
+pub fn citation_keys(path: &Path) -> Result<Vec<String>> {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+
+    match extension {
+        "aux" => Ok(parse_aux(&std::fs::read_to_string(path)?)),
+        "tex" => {
+            let aux_path = aux_path_for(path);
+            if aux_path.exists() {
+                Ok(parse_aux(&std::fs::read_to_string(aux_path)?))
+            } else {
+                Ok(scan_tex(&std::fs::read_to_string(path)?))
+            }
+        }
+        _ => Err(anyhow!("Unsupported file type: {} (expected .tex or .aux)", path.display())),
+    }
+}
+
+/// Whether a citation key is a bare arXiv identifier rather than a TeX key
+pub fn looks_like_arxiv_id(key: &str) -> bool {
+    ARXIV_ID_RE.is_match(key)
+}
+
+/// Look up citation keys on INSPIRE, as arXiv IDs or TeX keys.
+///
+/// Returns the resolved references, keyed by the original citation key, and
+/// the keys that could not be resolved.
+pub async fn resolve_keys(client: &InspireClient, keys: &[String]) -> (Vec<Reference>, Vec<String>) {
+    let mut references = Vec::new();
+    let mut unresolved = Vec::new();
+
+    for key in keys {
+        let result = if looks_like_arxiv_id(key) {
+            client.get_paper_by_arxiv(key).await
+        } else {
+            client.get_paper_by_texkey(key).await
+        };
+
+        match result {
+            Ok(paper) => {
+                let mut reference = Reference::from(paper);
+                reference.texkey = Some(key.clone());
+                references.push(reference);
+            }
+            Err(e) => {
+                warn!("Could not resolve citation key {}: {}", key, e);
+                unresolved.push(key.clone());
+            }
+        }
+    }
+
+    (references, unresolved)
+}
+
+/// Path of the `.aux` file LaTeX would write for a `.tex` file
+pub fn aux_path_for(tex_path: &Path) -> PathBuf {
+    tex_path.with_extension("aux")
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '\\' => escaped = !escaped,
+            '%' if !escaped => return &line[..index],
+            _ => escaped = false,
+        }
+    }
+    line
+}
+
+fn collect_keys(groups: impl Iterator<Item = String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut keys = Vec::new();
+
+    for group in groups {
+        for key in group.split(',').map(str::trim) {
+            if key.is_empty() || key == "*" {
+                continue;
+            }
+            if seen.insert(key.to_string()) {
+                keys.push(key.to_string());
+            }
+        }
+    }
+
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_scan_tex() {
+        let tex = r"
+As shown in \cite{Maldacena:1997re} and \citep[see][p. 3]{Witten:1998qj, Gubser:1998bc},
+% \cite{Commented:2000}
+the result \citet*{Maldacena:1997re} holds. 50\% \nocite{Extra:2001}
+";
+
+        let keys = scan_tex(tex);
+        assert_eq!(keys, vec!["Maldacena:1997re", "Witten:1998qj", "Gubser:1998bc", "Extra:2001"]);
+    }
+
+    #[test]
+    fn test_scan_sections() {
+        let tex = r"
+\begin{abstract} Building on \cite{Maldacena:1997re}. \end{abstract}
+\section{Introduction}
+We follow \cite{Maldacena:1997re, Witten:1998qj}.
+\subsection{Outline}
+See also \citep[][]{Gubser:1998bc}.
+% \section{Commented out}
+\section*{Related work}
+\section[Setup]{The holographic setup}
+As in \cite{Witten:1998qj} and \cite{Aharony:1999ti}.
+";
+
+        let sections = scan_sections(tex);
+        let titles: Vec<&str> = sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, [FRONT_MATTER, "Introduction", "The holographic setup"]);
+        assert_eq!(sections[0].keys, ["Maldacena:1997re"]);
+        assert_eq!(sections[1].keys, ["Witten:1998qj", "Gubser:1998bc"]);
+        assert_eq!(sections[2].keys, ["Aharony:1999ti"]);
+    }
+
+    #[test]
+    fn test_parse_aux() {
+        let aux = r"\relax
+\citation{Maldacena:1997re}
+\citation{Witten:1998qj,Gubser:1998bc}
+\citation{Maldacena:1997re}
+\citation{*}
+\bibdata{refs}
+";
+
+        let keys = parse_aux(aux);
+        assert_eq!(keys, vec!["Maldacena:1997re", "Witten:1998qj", "Gubser:1998bc"]);
+    }
+
+    #[test]
+    fn test_citation_keys_prefers_aux() {
+        let dir = tempdir().unwrap();
+        let tex_path = dir.path().join("paper.tex");
+        std::fs::write(&tex_path, r"\cite{FromTex}").unwrap();
+
+        assert_eq!(citation_keys(&tex_path).unwrap(), vec!["FromTex"]);
+
+        std::fs::write(aux_path_for(&tex_path), r"\citation{FromAux}").unwrap();
+        assert_eq!(citation_keys(&tex_path).unwrap(), vec!["FromAux"]);
+    }
+
+    #[test]
+    fn test_looks_like_arxiv_id() {
+        assert!(looks_like_arxiv_id("2301.12345"));
+        assert!(looks_like_arxiv_id("2301.12345v2"));
+        assert!(looks_like_arxiv_id("hep-th/9711200"));
+        assert!(!looks_like_arxiv_id("Maldacena:1997re"));
+    }
+
+    #[test]
+    fn test_citation_keys_unsupported_extension() {
+        assert!(citation_keys(Path::new("refs.bib")).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use log::debug;
+use reqwest::{header, Response, StatusCode};
+
+/// Shortest delay the throttle speeds up to
+pub const MIN_REQUEST_DELAY: Duration = Duration::from_millis(10);
+/// Longest delay the throttle backs off to
+pub const MAX_REQUEST_DELAY: Duration = Duration::from_secs(30);
+/// Responses slower than this count as a sign of an overloaded server
+pub const SLOW_RESPONSE: Duration = Duration::from_secs(2);
+/// Smallest delay after a back-off, however fast the throttle was going
+const MIN_BACKOFF_DELAY: Duration = Duration::from_millis(250);
+
+/// How a request went, as far as throttling is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// A fast 2xx (or other non-error) response
+    Fast,
+    /// HTTP 429, a 5xx, a slow response or a failed connection
+    Overloaded,
+    /// The server asked to wait this long (`Retry-After`)
+    RetryAfter(Duration),
+}
+
+impl Outcome {
+    /// Classify a response (or a failed request, `None`) and its latency
+    pub fn of(response: Option<&Response>, latency: Duration) -> Self {
+        let Some(response) = response else { return Outcome::Overloaded };
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return retry_after(response).map_or(Outcome::Overloaded, Outcome::RetryAfter);
+        }
+        if status.is_server_error() || latency > SLOW_RESPONSE {
+            Outcome::Overloaded
+        } else {
+            Outcome::Fast
+        }
+    }
+}
+
+/// How long a response asks to wait before trying again (`Retry-After` in seconds)
+pub fn retry_after(response: &Response) -> Option<Duration> {
+    response.headers().get(header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Spacing between requests that adapts to how the server responds.
+///
+/// Fast successful responses shrink the delay by a tenth; rate limiting,
+/// server errors and slow responses double it (or follow `Retry-After`).
+#[derive(Debug)]
+pub struct Throttle {
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    delay: Duration,
+    /// Earliest time the next request may start
+    next_slot: Instant,
+}
+
+impl Throttle {
+    /// Start with the given delay between requests
+    pub fn new(initial_delay: Duration) -> Self {
+        Self {
+            state: Mutex::new(State {
+                delay: initial_delay.clamp(MIN_REQUEST_DELAY, MAX_REQUEST_DELAY),
+                next_slot: Instant::now(),
+            }),
+        }
+    }
+
+    /// Current delay between requests
+    pub fn delay(&self) -> Duration {
+        self.lock().delay
+    }
+
+    /// Wait for this request's slot; concurrent callers are spaced one delay apart
+    pub async fn wait(&self) {
+        let slot = {
+            let mut state = self.lock();
+            let slot = state.next_slot.max(Instant::now());
+            state.next_slot = slot + state.delay;
+            slot
+        };
+        tokio::time::sleep_until(slot.into()).await;
+    }
+
+    /// Adjust the delay after a request
+    pub fn record(&self, outcome: Outcome) {
+        let mut state = self.lock();
+        let delay = next_delay(state.delay, outcome);
+        if delay != state.delay {
+            if outcome != Outcome::Fast {
+                // Hold back requests already queued behind the shorter delay
+                state.next_slot = state.next_slot.max(Instant::now() + delay);
+            }
+            state.delay = delay;
+            debug!(
+                "Request delay now {} ms ({:.1} requests/s) after {:?}",
+                delay.as_millis(), 1.0 / delay.as_secs_f64(), outcome
+            );
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Delay to use after a request with the given outcome
+fn next_delay(current: Duration, outcome: Outcome) -> Duration {
+    match outcome {
+        Outcome::Fast => (current * 9 / 10).max(MIN_REQUEST_DELAY),
+        Outcome::Overloaded => (current * 2).max(MIN_BACKOFF_DELAY).min(MAX_REQUEST_DELAY),
+        Outcome::RetryAfter(wait) => wait.clamp(MIN_BACKOFF_DELAY, MAX_REQUEST_DELAY),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_delay() {
+        let ms = Duration::from_millis;
+        assert_eq!(next_delay(ms(100), Outcome::Fast), ms(90));
+        assert_eq!(next_delay(ms(10), Outcome::Fast), MIN_REQUEST_DELAY);
+        assert_eq!(next_delay(ms(20), Outcome::Overloaded), MIN_BACKOFF_DELAY);
+        assert_eq!(next_delay(ms(400), Outcome::Overloaded), ms(800));
+        assert_eq!(next_delay(ms(20_000), Outcome::Overloaded), MAX_REQUEST_DELAY);
+        assert_eq!(next_delay(ms(20), Outcome::RetryAfter(Duration::from_secs(5))), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_throttle_spaces_and_adapts() {
+        let throttle = Throttle::new(Duration::from_millis(50));
+        let started = Instant::now();
+        throttle.wait().await;
+        throttle.wait().await;
+        assert!(started.elapsed() >= Duration::from_millis(50));
+
+        throttle.record(Outcome::Overloaded);
+        assert_eq!(throttle.delay(), MIN_BACKOFF_DELAY);
+        throttle.record(Outcome::Fast);
+        assert_eq!(throttle.delay(), MIN_BACKOFF_DELAY * 9 / 10);
+    }
+}
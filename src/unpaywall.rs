@@ -0,0 +1,84 @@
+use anyhow::{anyhow, Result};
+use log::debug;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+
+use crate::models::{OpenAccess, OpenAccessSource};
+
+/// Client for the Unpaywall REST API, used to find free copies of works that
+/// are not on arXiv. Unpaywall asks every caller for a contact email address.
+pub struct UnpaywallClient {
+    client: Client,
+    base_url: String,
+    email: String,
+}
+
+#[derive(Deserialize)]
+struct Work {
+    is_oa: bool,
+    oa_status: Option<String>,
+    best_oa_location: Option<Location>,
+}
+
+#[derive(Deserialize)]
+struct Location {
+    url: Option<String>,
+    url_for_pdf: Option<String>,
+}
+
+impl UnpaywallClient {
+    pub fn new(email: &str) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: "https://api.unpaywall.org/v2".to_string(),
+            email: email.to_string(),
+        }
+    }
+
+    /// Open-access status of a DOI; `None` when Unpaywall does not know the DOI
+    pub async fn lookup(&self, doi: &str) -> Result<Option<OpenAccess>> {
+        debug!("Looking up open-access status of {}", doi);
+        let response = self.client.get(format!("{}/{}", self.base_url, doi))
+            .query(&[("email", self.email.as_str())])
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to query Unpaywall: {}", response.status()));
+        }
+
+        let work: Work = response.json().await?;
+        Ok(Some(open_access(work)))
+    }
+}
+
+fn open_access(work: Work) -> OpenAccess {
+    OpenAccess {
+        is_open: work.is_oa,
+        source: OpenAccessSource::Unpaywall,
+        oa_status: work.oa_status,
+        url: work.best_oa_location.and_then(|location| location.url_for_pdf.or(location.url)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_work() {
+        let body = r#"{"doi": "10.1016/0370-2693(86)90001-0", "is_oa": true, "oa_status": "bronze",
+            "best_oa_location": {"url": "https://example.org/article", "url_for_pdf": null, "host_type": "publisher"}}"#;
+        let status = open_access(serde_json::from_str(body).unwrap());
+        assert!(status.is_open);
+        assert_eq!(status.oa_status.as_deref(), Some("bronze"));
+        assert_eq!(status.url.as_deref(), Some("https://example.org/article"));
+
+        let closed = open_access(serde_json::from_str(r#"{"is_oa": false, "oa_status": "closed", "best_oa_location": null}"#).unwrap());
+        assert!(!closed.is_open);
+        assert_eq!(closed.url, None);
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::fmt;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::api::InspireClient;
+use crate::arxiv::ArxivClient;
+use crate::models::{PublicationInfo, Reference};
+use crate::names::PersonName;
+
+/// One way an entry's INSPIRE metadata differs from the saved copy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MetadataChange {
+    /// The work has been retracted or withdrawn since it was saved
+    Retracted,
+    /// A saved preprint has since been published
+    Published { journal: String },
+    /// The publication info (journal, volume, pages) was corrected
+    Publication { old: String, new: String },
+    Title { old: String, new: String },
+    /// Authors (by surname) added to or removed from the author list
+    Authors { added: Vec<String>, removed: Vec<String> },
+    Doi { old: Option<String>, new: String },
+    /// An arXiv ID was added to the record
+    Arxiv { new: String },
+    /// The saved entry cites an older version of the preprint than arXiv's latest
+    NewVersion { arxiv_id: String, pinned: u32, latest: u32 },
+}
+
+impl fmt::Display for MetadataChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataChange::Retracted => write!(f, "RETRACTED or withdrawn"),
+            MetadataChange::Published { journal } => write!(f, "published in {}", journal),
+            MetadataChange::Publication { old, new } => write!(f, "publication info changed from {} to {}", old, new),
+            MetadataChange::Title { old, new } => write!(f, "title changed from \"{}\" to \"{}\"", old, new),
+            MetadataChange::Authors { added, removed } => {
+                write!(f, "author list changed")?;
+                if !added.is_empty() {
+                    write!(f, ", added {}", added.join(", "))?;
+                }
+                if !removed.is_empty() {
+                    write!(f, ", removed {}", removed.join(", "))?;
+                }
+                Ok(())
+            }
+            MetadataChange::Doi { old: Some(old), new } => write!(f, "DOI changed from {} to {}", old, new),
+            MetadataChange::Doi { old: None, new } => write!(f, "DOI {} added", new),
+            MetadataChange::Arxiv { new } => write!(f, "arXiv ID {} added", new),
+            MetadataChange::NewVersion { arxiv_id, pinned, latest } => {
+                write!(f, "cites {}v{} but v{} is the latest version", arxiv_id, pinned, latest)
+            }
+        }
+    }
+}
+
+/// A saved entry whose INSPIRE record has changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryUpdate {
+    /// BibTeX key of the saved entry
+    pub key: String,
+    pub changes: Vec<MetadataChange>,
+    /// The entry as INSPIRE has it now
+    pub current: Reference,
+}
+
+/// Outcome of checking a saved bibliography against INSPIRE
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpdateReport {
+    pub updates: Vec<EntryUpdate>,
+    /// Number of entries whose metadata is unchanged
+    pub unchanged: usize,
+    /// Keys of entries that could not be found on INSPIRE
+    pub unresolved: Vec<String>,
+}
+
+/// Compare a saved entry with the current INSPIRE metadata of the same work.
+///
+/// Only fields present in the current metadata are compared, so a saved entry
+/// that simply lacks fields INSPIRE does not have is not reported.
+pub fn compare(saved: &Reference, current: &Reference) -> Vec<MetadataChange> {
+    let mut changes = Vec::new();
+
+    if current.retracted && !saved.retracted {
+        changes.push(MetadataChange::Retracted);
+    }
+
+    if let Some(new_info) = current.publication_info.as_ref().filter(|info| info.journal_title.is_some()) {
+        match saved.publication_info.as_ref().filter(|info| info.journal_title.is_some()) {
+            None => changes.push(MetadataChange::Published { journal: journal_reference(new_info) }),
+            Some(old_info) if !same_publication(old_info, new_info) => changes.push(MetadataChange::Publication {
+                old: journal_reference(old_info),
+                new: journal_reference(new_info),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    if !current.title.is_empty() && normalize(&saved.title) != normalize(&current.title) {
+        changes.push(MetadataChange::Title { old: saved.title.clone(), new: current.title.clone() });
+    }
+
+    // A saved entry may cut the author list short with `and others`
+    let truncated = saved.authors.iter().any(|author| author.eq_ignore_ascii_case("others"));
+    if !current.authors.is_empty() && !saved.authors.is_empty() {
+        let old = surnames(&saved.authors);
+        let new = surnames(&current.authors);
+        let added: Vec<String> = new.iter().filter(|name| !old.contains(name)).cloned().collect();
+        let removed: Vec<String> = old.iter().filter(|name| !new.contains(name)).cloned().collect();
+        if !removed.is_empty() || (!added.is_empty() && !truncated) {
+            changes.push(MetadataChange::Authors { added, removed });
+        }
+    }
+
+    if let Some(new) = &current.doi {
+        if !saved.doi.as_ref().is_some_and(|old| old.eq_ignore_ascii_case(new)) {
+            changes.push(MetadataChange::Doi { old: saved.doi.clone(), new: new.clone() });
+        }
+    }
+
+    if let (None, Some(new)) = (&saved.arxiv_id, &current.arxiv_id) {
+        changes.push(MetadataChange::Arxiv { new: new.clone() });
+    }
+
+    changes
+}
+
+/// Report a newer arXiv version than the one the saved entry is pinned to
+pub fn compare_version(saved: &Reference, latest: Option<u32>) -> Option<MetadataChange> {
+    match (&saved.arxiv_id, saved.arxiv_version, latest) {
+        (Some(arxiv_id), Some(pinned), Some(latest)) if latest > pinned => {
+            Some(MetadataChange::NewVersion { arxiv_id: arxiv_id.clone(), pinned, latest })
+        }
+        _ => None,
+    }
+}
+
+/// Look up every saved entry on INSPIRE (by record ID, arXiv ID, then TeX key)
+/// and report the ones whose metadata changed. Entries pinned to an arXiv
+/// version are also checked against arXiv for newer versions. Nothing is written.
+pub async fn check_updates(client: &InspireClient, arxiv: &ArxivClient, saved: &[Reference]) -> UpdateReport {
+    let mut report = UpdateReport::default();
+
+    let pinned: Vec<String> = saved.iter()
+        .filter(|reference| reference.arxiv_version.is_some())
+        .filter_map(|reference| reference.arxiv_id.clone())
+        .collect();
+    let latest_versions = if pinned.is_empty() {
+        HashMap::new()
+    } else {
+        arxiv.latest_versions(&pinned).await.unwrap_or_else(|e| {
+            warn!("Could not look up the latest arXiv versions: {}", e);
+            HashMap::new()
+        })
+    };
+
+    for reference in saved {
+        let key = reference.bibtex_key();
+        let result = if let Some(id) = &reference.inspire_id {
+            client.get_paper_by_id(id).await
+        } else if let Some(arxiv_id) = &reference.arxiv_id {
+            client.get_paper_by_arxiv(arxiv_id).await
+        } else if let Some(texkey) = &reference.texkey {
+            client.get_paper_by_texkey(texkey).await
+        } else {
+            Err(anyhow::anyhow!("no INSPIRE ID, arXiv ID or TeX key"))
+        };
+
+        match result {
+            Ok(paper) => {
+                let mut current = Reference::from(paper);
+                let mut changes = compare(reference, &current);
+                let latest = reference.arxiv_id.as_ref().and_then(|id| latest_versions.get(id)).copied();
+                current.arxiv_version = latest.or(reference.arxiv_version);
+                changes.extend(compare_version(reference, latest));
+                if changes.is_empty() {
+                    report.unchanged += 1;
+                } else {
+                    report.updates.push(EntryUpdate { key, changes, current });
+                }
+            }
+            Err(e) => {
+                warn!("Could not look up {} on INSPIRE: {}", key, e);
+                report.unresolved.push(key);
+            }
+        }
+    }
+
+    report
+}
+
+/// `Phys. Rev. D 98 (2018) 030001`
+fn journal_reference(info: &PublicationInfo) -> String {
+    let mut reference = info.journal_title.clone().unwrap_or_default();
+    if let Some(volume) = &info.journal_volume {
+        reference.push_str(&format!(" {}", volume));
+    }
+    if let Some(year) = info.year {
+        reference.push_str(&format!(" ({})", year));
+    }
+    if let Some(page) = first_page(info) {
+        reference.push_str(&format!(" {}", page));
+    }
+    reference
+}
+
+/// Same journal and volume, and same page where both know it (BibTeX files
+/// read back often lack pages)
+fn same_publication(old: &PublicationInfo, new: &PublicationInfo) -> bool {
+    let same = |a: Option<&String>, b: Option<&String>| match (a, b) {
+        (Some(a), Some(b)) => normalize(a) == normalize(b),
+        _ => true,
+    };
+    same(old.journal_title.as_ref(), new.journal_title.as_ref())
+        && same(old.journal_volume.as_ref(), new.journal_volume.as_ref())
+        && same(first_page(old), first_page(new))
+}
+
+fn first_page(info: &PublicationInfo) -> Option<&String> {
+    info.artid.as_ref().or(info.page_start.as_ref())
+}
+
+fn surnames(authors: &[String]) -> Vec<String> {
+    authors.iter()
+        .filter(|author| !author.eq_ignore_ascii_case("others"))
+        .map(|author| PersonName::parse(author).full_surname())
+        .collect()
+}
+
+/// Ignore case, punctuation, braces and spacing differences
+pub(crate) fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_reference(title: &str, authors: &[&str]) -> Reference {
+        Reference {
+            title: title.to_string(),
+            authors: authors.iter().map(|author| author.to_string()).collect(),
+            arxiv_id: Some("2301.12345".to_string()),
+            year: Some(2023),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_compare_unchanged() {
+        let saved = create_reference("{Holography} and black holes", &["Smith, Alice", "Jones, Bob"]);
+        let current = create_reference("Holography and Black Holes", &["Smith, A.", "Jones, B."]);
+        assert!(compare(&saved, &current).is_empty());
+    }
+
+    #[test]
+    fn test_compare_reports_changes() {
+        let saved = create_reference("Holography and black hole", &["Smith, Alice", "Jones, Bob"]);
+        let mut current = create_reference("Holography and black holes", &["Smith, Alice", "Brown, Carol"]);
+        current.doi = Some("10.1103/PhysRevD.98.030001".to_string());
+        current.publication_info = Some(PublicationInfo {
+            journal_title: Some("Phys. Rev. D".to_string()),
+            journal_volume: Some("98".to_string()),
+            artid: Some("030001".to_string()),
+            year: Some(2018),
+            ..Default::default()
+        });
+
+        let changes = compare(&saved, &current);
+        assert_eq!(changes, vec![
+            MetadataChange::Published { journal: "Phys. Rev. D 98 (2018) 030001".to_string() },
+            MetadataChange::Title { old: saved.title.clone(), new: current.title.clone() },
+            MetadataChange::Authors { added: vec!["Brown".to_string()], removed: vec!["Jones".to_string()] },
+            MetadataChange::Doi { old: None, new: "10.1103/PhysRevD.98.030001".to_string() },
+        ]);
+        assert_eq!(changes[0].to_string(), "published in Phys. Rev. D 98 (2018) 030001");
+    }
+
+    #[test]
+    fn test_compare_publication_from_bibtex() {
+        let mut saved = create_reference("Title", &["Smith, Alice"]);
+        saved.publication_info = Some(PublicationInfo {
+            journal_title: Some("Phys.Rev.D".to_string()),
+            journal_volume: Some("98".to_string()),
+            ..Default::default()
+        });
+        let mut current = saved.clone();
+        current.publication_info = Some(PublicationInfo {
+            journal_title: Some("Phys. Rev. D".to_string()),
+            journal_volume: Some("98".to_string()),
+            artid: Some("030001".to_string()),
+            year: Some(2018),
+            ..Default::default()
+        });
+        assert!(compare(&saved, &current).is_empty());
+
+        current.publication_info.as_mut().unwrap().journal_volume = Some("99".to_string());
+        assert_eq!(compare(&saved, &current), vec![MetadataChange::Publication {
+            old: "Phys.Rev.D 98".to_string(),
+            new: "Phys. Rev. D 99 (2018) 030001".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn test_compare_truncated_author_list() {
+        let saved = create_reference("Title", &["Smith, Alice", "others"]);
+        let current = create_reference("Title", &["Smith, Alice", "Jones, Bob", "Brown, Carol"]);
+        assert!(compare(&saved, &current).is_empty());
+    }
+
+    #[test]
+    fn test_compare_retracted() {
+        let saved = create_reference("Title", &["Smith, Alice"]);
+        let current = Reference { retracted: true, ..saved.clone() };
+        assert_eq!(compare(&saved, &current), vec![MetadataChange::Retracted]);
+        assert!(compare(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_compare_version() {
+        let unpinned = create_reference("Title", &["Smith, Alice"]);
+        assert_eq!(compare_version(&unpinned, Some(3)), None);
+
+        let pinned = Reference { arxiv_version: Some(2), ..unpinned };
+        assert_eq!(compare_version(&pinned, Some(2)), None);
+        assert_eq!(compare_version(&pinned, None), None);
+        let change = compare_version(&pinned, Some(3)).unwrap();
+        assert_eq!(change, MetadataChange::NewVersion { arxiv_id: "2301.12345".to_string(), pinned: 2, latest: 3 });
+        assert_eq!(change.to_string(), "cites 2301.12345v2 but v3 is the latest version");
+    }
+}
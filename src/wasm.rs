@@ -0,0 +1,27 @@
+use wasm_bindgen::prelude::*;
+
+use crate::api::InspireClient;
+use crate::models::Reference;
+
+/// Fetch the references of a paper, returned as a JSON array
+#[wasm_bindgen(js_name = fetchReferences)]
+pub async fn fetch_references(arxiv_id: String) -> Result<String, JsValue> {
+    let client = InspireClient::new();
+    let paper = client.get_paper_by_arxiv(&arxiv_id).await.map_err(to_js_error)?;
+    let references = client.get_paper_references(&paper.id).await.map_err(to_js_error)?;
+    serde_json::to_string(&references).map_err(to_js_error)
+}
+
+/// Convert a JSON array of references (as returned by `fetchReferences`) to BibTeX
+#[wasm_bindgen(js_name = referencesToBibtex)]
+pub fn references_to_bibtex(json: &str) -> Result<String, JsValue> {
+    let references: Vec<Reference> = serde_json::from_str(json).map_err(to_js_error)?;
+    Ok(references.iter()
+        .map(|r| r.to_bibtex())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn to_js_error(error: impl std::fmt::Display) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, anyhow};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::api::InspireClient;
+use crate::atom;
+use crate::models::Paper;
+
+/// Persisted state of a watched paper: which citing papers were already reported
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WatchState {
+    /// INSPIRE record ID of the watched paper
+    pub paper_id: String,
+    /// INSPIRE record IDs of citing papers already seen
+    pub seen: BTreeSet<String>,
+    /// When the paper was last checked (RFC 3339)
+    pub last_checked: Option<String>,
+    /// When the paper's INSPIRE record was last updated, as of the last check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paper_updated: Option<String>,
+}
+
+/// Result of checking a watched paper
+#[derive(Debug, Clone)]
+pub struct WatchResult {
+    pub paper: Paper,
+    /// Citing papers not seen in earlier checks
+    pub new_citations: Vec<Paper>,
+    /// Whether this was the first check, which only records a baseline
+    pub first_run: bool,
+    /// Whether the paper's own record was updated since the last check
+    pub paper_changed: bool,
+}
+
+impl WatchState {
+    /// Load state from a file, returning `None` if it does not exist yet
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Save state to a file, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Citing papers not yet seen, recording them as seen
+    pub fn record_citations(&mut self, citing: Vec<Paper>) -> Vec<Paper> {
+        citing.into_iter()
+            .filter(|paper| self.seen.insert(paper.id.clone()))
+            .collect()
+    }
+    
+    /// Record the paper's update timestamp, returning whether it changed since it was last recorded
+    pub fn record_update(&mut self, updated: Option<&str>) -> bool {
+        let Some(updated) = updated else { return false };
+        let changed = self.paper_updated.as_deref().is_some_and(|previous| previous != updated);
+        self.paper_updated = Some(updated.to_string());
+        changed
+    }
+}
+
+/// Default state file for a watched paper, under the user's data directory
+pub fn default_state_path(arxiv_id: &str) -> Result<PathBuf> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| anyhow!("Could not find data directory"))?;
+    let file_name: String = arxiv_id.chars()
+        .map(|c| if c.is_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+
+    Ok(data_dir.join("reference_tool").join("watch").join(format!("{}.json", file_name)))
+}
+
+/// Check a paper for citing papers that appeared since the last check.
+///
+/// The first check for a paper records all current citations as a baseline
+/// and reports none as new.
+pub async fn check(client: &InspireClient, arxiv_id: &str, state_path: &Path, limit: usize) -> Result<WatchResult> {
+    let paper = client.get_paper_by_arxiv(arxiv_id).await?;
+    let citing = client.get_recent_citing_papers(&paper.id, limit).await?;
+
+    let existing = WatchState::load(state_path)?
+        .filter(|state| state.paper_id == paper.id);
+    let first_run = existing.is_none();
+    let mut state = existing.unwrap_or_else(|| WatchState {
+        paper_id: paper.id.clone(),
+        ..Default::default()
+    });
+
+    let mut new_citations = state.record_citations(citing);
+    let paper_changed = state.record_update(paper.last_updated.as_deref());
+    if first_run {
+        info!("Recorded baseline of {} citations for {}", new_citations.len(), paper.title);
+        new_citations.clear();
+    }
+
+    state.last_checked = Some(atom::now_rfc3339());
+    state.save(state_path)?;
+
+    Ok(WatchResult { paper, new_citations, first_run, paper_changed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn create_paper(id: &str) -> Paper {
+        Paper {
+            id: id.to_string(),
+            title: format!("Paper {}", id),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_record_citations() {
+        let mut state = WatchState {
+            paper_id: "1".to_string(),
+            ..Default::default()
+        };
+
+        let first = state.record_citations(vec![create_paper("2"), create_paper("3")]);
+        assert_eq!(first.len(), 2);
+
+        let second = state.record_citations(vec![create_paper("3"), create_paper("4")]);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].id, "4");
+        assert_eq!(state.seen.len(), 3);
+    }
+
+    #[test]
+    fn test_record_update() {
+        let mut state = WatchState::default();
+        assert!(!state.record_update(Some("2024-01-01T00:00:00+00:00")));
+        assert!(!state.record_update(Some("2024-01-01T00:00:00+00:00")));
+        assert!(!state.record_update(None));
+        assert!(state.record_update(Some("2024-02-01T00:00:00+00:00")));
+        assert_eq!(state.paper_updated.as_deref(), Some("2024-02-01T00:00:00+00:00"));
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("state.json");
+
+        assert!(WatchState::load(&path).unwrap().is_none());
+
+        let mut state = WatchState {
+            paper_id: "1".to_string(),
+            last_checked: Some("2024-01-01T00:00:00Z".to_string()),
+            ..Default::default()
+        };
+        state.seen.insert("2".to_string());
+        state.save(&path).unwrap();
+
+        assert_eq!(WatchState::load(&path).unwrap(), Some(state));
+    }
+
+    #[test]
+    fn test_default_state_path_sanitizes_id() {
+        if let Ok(path) = default_state_path("hep-th/9711200") {
+            assert!(path.ends_with("watch/hep-th_9711200.json"));
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use anyhow::{anyhow, Result};
+
+use crate::api::{DEFAULT_REQUEST_DELAY, MAX_RETRIES};
+use crate::config::Config;
+use crate::output::OutputFormat;
+use crate::throttle::{MAX_REQUEST_DELAY, MIN_REQUEST_DELAY};
+
+/// Most retries the wizard accepts; each one waits twice as long as the last
+const MAX_RETRIES_LIMIT: u32 = 10;
+
+/// Ask for the most common settings on `output`, reading answers from `input`,
+/// and return `config` with them applied. An empty answer keeps the value
+/// shown in brackets; invalid answers are explained and asked again.
+pub fn run<R: BufRead, W: Write>(mut config: Config, mut input: R, mut output: W) -> Result<Config> {
+    writeln!(output, "Press Enter to keep the value in brackets.")?;
+
+    let current = config.default_format.as_ref().map(format_name).unwrap_or_else(|| "json".to_string());
+    config.default_format = Some(ask(&mut input, &mut output, "Default output format", &current, parse_format)?);
+
+    let current = config.default_output_dir.as_ref().map(|dir| dir.display().to_string()).unwrap_or_default();
+    config.default_output_dir = ask(&mut input, &mut output, "Output directory (- for none)", &current, |answer| {
+        Ok((answer != "-").then(|| PathBuf::from(answer)))
+    })?;
+
+    let current = config.default_categories.as_ref().map(|categories| categories.join(",")).unwrap_or_default();
+    config.default_categories = ask(&mut input, &mut output, "Categories to keep, comma-separated (- for all)", &current, parse_categories)?;
+
+    let current = config.api.request_delay_ms.unwrap_or(DEFAULT_REQUEST_DELAY.as_millis() as u64).to_string();
+    config.api.request_delay_ms = Some(ask(&mut input, &mut output, "Delay between requests in ms", &current, parse_delay)?);
+
+    let current = config.api.max_retries.unwrap_or(MAX_RETRIES).to_string();
+    config.api.max_retries = Some(ask(&mut input, &mut output, "Retries of rate-limited or unavailable requests", &current, parse_retries)?);
+
+    Ok(config)
+}
+
+/// Prompt until `parse` accepts the answer (or the current value, on Enter)
+fn ask<T, R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    question: &str,
+    current: &str,
+    parse: impl Fn(&str) -> Result<T>,
+) -> Result<T> {
+    loop {
+        match current {
+            "" => write!(output, "{}: ", question)?,
+            _ => write!(output, "{} [{}]: ", question, current)?,
+        }
+        output.flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Err(anyhow!("Setup cancelled; the configuration was not changed"));
+        }
+        let answer = match line.trim() {
+            "" if current.is_empty() => "-",
+            "" => current,
+            answer => answer,
+        };
+        match parse(answer) {
+            Ok(value) => return Ok(value),
+            Err(e) => writeln!(output, "  {}", e)?,
+        }
+    }
+}
+
+fn format_name(format: &OutputFormat) -> String {
+    serde_json::to_value(format).ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn parse_format(answer: &str) -> Result<OutputFormat> {
+    serde_json::from_value(serde_json::Value::String(answer.to_lowercase()))
+        .map_err(|_| anyhow!("Unknown format '{}' (json, bibtex, atom, graphml, dot, cytoscape, citations, bibitem or html)", answer))
+}
+
+/// Comma-separated arXiv categories (`hep-th`, `astro-ph.CO`); `-` for none
+fn parse_categories(answer: &str) -> Result<Option<Vec<String>>> {
+    if answer == "-" {
+        return Ok(None);
+    }
+    let categories: Vec<String> = answer.split(',')
+        .map(|category| category.trim())
+        .filter(|category| !category.is_empty())
+        .map(str::to_string)
+        .collect();
+    let valid = |category: &str| {
+        let (archive, subject) = category.split_once('.').unwrap_or((category, "x"));
+        !archive.is_empty()
+            && archive.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+            && !subject.is_empty()
+            && subject.chars().all(|c| c.is_ascii_alphabetic() || c == '-')
+    };
+    match categories.iter().find(|category| !valid(category)) {
+        Some(invalid) => Err(anyhow!("'{}' is not an arXiv category (e.g. hep-th, astro-ph.CO)", invalid)),
+        None if categories.is_empty() => Ok(None),
+        None => Ok(Some(categories)),
+    }
+}
+
+fn parse_delay(answer: &str) -> Result<u64> {
+    let (min, max) = (MIN_REQUEST_DELAY.as_millis() as u64, MAX_REQUEST_DELAY.as_millis() as u64);
+    match answer.parse::<u64>() {
+        Ok(delay) if (min..=max).contains(&delay) => Ok(delay),
+        _ => Err(anyhow!("Enter a number of milliseconds from {} to {}", min, max)),
+    }
+}
+
+fn parse_retries(answer: &str) -> Result<u32> {
+    match answer.parse::<u32>() {
+        Ok(retries) if retries <= MAX_RETRIES_LIMIT => Ok(retries),
+        _ => Err(anyhow!("Enter a number from 0 to {}", MAX_RETRIES_LIMIT)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(lines: &str) -> (Result<Config>, String) {
+        let mut output = Vec::new();
+        let config = run(Config::default(), lines.as_bytes(), &mut output);
+        (config, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_wizard() {
+        let (config, output) = answer("BibTeX\n./bib\nhep-th, astro-ph.CO\n250\n5\n");
+        let config = config.unwrap();
+        assert_eq!(config.default_format, Some(OutputFormat::Bibtex));
+        assert_eq!(config.default_output_dir, Some(PathBuf::from("./bib")));
+        assert_eq!(config.default_categories, Some(vec!["hep-th".to_string(), "astro-ph.CO".to_string()]));
+        assert_eq!((config.api.request_delay_ms, config.api.max_retries), (Some(250), Some(5)));
+        assert!(output.contains("Default output format [json]: "));
+        assert!(output.contains("Delay between requests in ms [100]: "));
+
+        // Enter keeps the current values
+        let (config, _) = answer("\n\n\n\n\n");
+        let config = config.unwrap();
+        assert_eq!(config.default_format, Some(OutputFormat::Json));
+        assert_eq!(config.default_output_dir, None);
+        assert_eq!(config.default_categories, None);
+        assert_eq!(config.api.max_retries, Some(MAX_RETRIES));
+    }
+
+    #[test]
+    fn test_invalid_answers_are_asked_again() {
+        let (config, output) = answer("pdf\njson\n-\nhep th\nhep-th\n5\n100\n99\n2\n");
+        let config = config.unwrap();
+        assert_eq!(config.default_categories, Some(vec!["hep-th".to_string()]));
+        assert_eq!((config.api.request_delay_ms, config.api.max_retries), (Some(100), Some(2)));
+        assert!(output.contains("  Unknown format 'pdf'"));
+        assert!(output.contains("  'hep th' is not an arXiv category"));
+        assert!(output.contains("  Enter a number of milliseconds from 10 to 30000"));
+        assert!(output.contains("  Enter a number from 0 to 10"));
+
+        let (config, _) = answer("json\n");
+        assert!(config.unwrap_err().to_string().contains("cancelled"));
+    }
+}
@@ -0,0 +1,230 @@
+//! Golden-file tests for every output format.
+//!
+//! Each test renders fixed fixtures and compares the result with a file
+//! under `tests/snapshots/`, so that any change to an output format shows up
+//! as a diff in review. After a deliberate change, regenerate the files with
+//! `UPDATE_SNAPSHOTS=1 cargo test --test output_formats` and commit them.
+#![cfg(all(feature = "native", feature = "network-analysis"))]
+
+use std::fs;
+use std::path::PathBuf;
+
+use regex::Regex;
+use tempfile::TempDir;
+
+use reference_tool::citation::CitationStyle;
+use reference_tool::dates::PartialDate;
+use reference_tool::commands;
+use reference_tool::models::{BibtexOptions, Paper, PublicationInfo, Reference, ThesisInfo};
+use reference_tool::network::CitationNetwork;
+use reference_tool::output::{OutputFormat, OutputWriter};
+
+/// A journal article, a conference paper, a preprint and a thesis
+fn references() -> Vec<Reference> {
+    vec![
+        Reference {
+            title: "The Large N limit of superconformal field theories and supergravity".to_string(),
+            authors: vec!["Maldacena, Juan Martin".to_string()],
+            arxiv_id: Some("hep-th/9711200".to_string()),
+            inspire_id: Some("451647".to_string()),
+            categories: vec!["hep-th".to_string()],
+            year: Some(1997),
+            texkey: Some("Maldacena:1997re".to_string()),
+            document_type: vec!["article".to_string()],
+            doi: Some("10.1023/A:1026654312961".to_string()),
+            publication_info: Some(PublicationInfo {
+                journal_title: Some("Int.J.Theor.Phys.".to_string()),
+                journal_volume: Some("38".to_string()),
+                page_start: Some("1113".to_string()),
+                page_end: Some("1133".to_string()),
+                year: Some(1999),
+                issn: Some("0020-7748".to_string()),
+                ..Default::default()
+            }),
+            citation_count: Some(20000),
+            label: Some("1".to_string()),
+            ..Default::default()
+        },
+        Reference {
+            title: "Search for new physics with $b \\to s \\ell^+ \\ell^-$ decays & more".to_string(),
+            authors: vec!["van der Berg, Jan".to_string(), "Müller, Anna".to_string(), "O'Neil, P.".to_string()],
+            inspire_id: Some("1600001".to_string()),
+            year: Some(2017),
+            texkey: Some("vanderBerg:2017abc".to_string()),
+            document_type: vec!["conference paper".to_string()],
+            publication_info: Some(PublicationInfo {
+                journal_title: Some("PoS".to_string()),
+                journal_volume: Some("EPS-HEP2017".to_string()),
+                artid: Some("123".to_string()),
+                year: Some(2017),
+                cnum: Some("C17-07-05".to_string()),
+                conf_acronym: Some("EPS-HEP2017".to_string()),
+                isbn: Some("9780521670531".to_string()),
+                ..Default::default()
+            }),
+            label: Some("2".to_string()),
+            ..Default::default()
+        },
+        Reference {
+            title: "A preprint on {holography}".to_string(),
+            authors: vec!["Doe, Jane".to_string(), "Roe, Richard".to_string()],
+            arxiv_id: Some("2301.12345".to_string()),
+            arxiv_version: Some(2),
+            categories: vec!["hep-th".to_string(), "gr-qc".to_string()],
+            year: Some(2023),
+            date: PartialDate::parse("2023-01-15"),
+            document_type: vec!["article".to_string()],
+            retracted: true,
+            ..Default::default()
+        },
+        Reference {
+            title: "Aspects of black hole thermodynamics".to_string(),
+            authors: vec!["Smith, Alex".to_string()],
+            inspire_id: Some("2000002".to_string()),
+            year: Some(2020),
+            document_type: vec!["thesis".to_string()],
+            thesis_info: Some(ThesisInfo {
+                institution: Some("Cambridge U.".to_string()),
+                degree_type: Some("phd".to_string()),
+                year: Some(2020),
+            }),
+            ..Default::default()
+        },
+    ]
+}
+
+/// Four papers: two citing the first, and one (the seed) citing those two
+fn network() -> CitationNetwork {
+    let mut network = CitationNetwork::new();
+    for (id, title, year) in [
+        ("1", "Foundations", 1990),
+        ("2", "A follow-up", 2000),
+        ("3", "Another follow-up", 2005),
+        ("4", "A review of \"everything\" & more", 2020),
+    ] {
+        network.add_paper(Paper {
+            id: id.to_string(),
+            title: title.to_string(),
+            authors: vec![format!("Author{}, A.", id), "Shared, S.".to_string()],
+            year: Some(year),
+            ..Default::default()
+        });
+    }
+    network.add_citations("2", vec!["1".to_string()]);
+    network.add_citations("3", vec!["1".to_string()]);
+    network.add_labeled_citations("4", vec![("2".to_string(), "1".to_string()), ("3".to_string(), "2".to_string())]);
+    for (id, depth) in [("4", 0), ("2", 1), ("3", 1), ("1", 2)] {
+        network.record_depth(id, depth);
+    }
+    network.mark_seed("4");
+    network
+}
+
+/// Compare `actual` with the golden file `tests/snapshots/<name>`, or
+/// (re)write the file when `UPDATE_SNAPSHOTS` is set
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots").join(name);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("Missing snapshot {}; create it with UPDATE_SNAPSHOTS=1", path.display()));
+    assert!(
+        expected == actual,
+        "{} output changed; rerun with UPDATE_SNAPSHOTS=1 if this is intended\n--- expected\n{}\n+++ actual\n{}",
+        name, expected, actual
+    );
+}
+
+/// A writer for `format` into a file in `dir`, and the path of that file
+fn file_writer(format: OutputFormat, dir: &TempDir) -> (OutputWriter, PathBuf) {
+    let path = dir.path().join("output");
+    (OutputWriter::new(format, Some(path.clone())), path)
+}
+
+/// Replace the generation time in an Atom feed, which changes on every run
+fn strip_timestamps(feed: &str) -> String {
+    let updated = Regex::new(r"<updated>[^<]*</updated>").unwrap();
+    updated.replace_all(feed, "<updated>[timestamp]</updated>").into_owned()
+}
+
+#[tokio::test]
+async fn test_reference_formats() {
+    let dir = TempDir::new().unwrap();
+    for (format, name) in [
+        (OutputFormat::Json, "references.json"),
+        (OutputFormat::Bibtex, "references.bib"),
+        (OutputFormat::Bibitem, "references.bibitem.tex"),
+    ] {
+        let (writer, path) = file_writer(format, &dir);
+        writer.write_references(&references()).await.unwrap();
+        assert_snapshot(name, &fs::read_to_string(&path).unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_bibtex_options() {
+    let dir = TempDir::new().unwrap();
+    let options = BibtexOptions { keywords: true, url: true, pin_versions: true, month: true, ..Default::default() };
+    let (writer, path) = file_writer(OutputFormat::Bibtex, &dir);
+    writer.with_bibtex_options(options.clone()).write_references(&references()).await.unwrap();
+    assert_snapshot("references.options.bib", &fs::read_to_string(&path).unwrap());
+
+    let options = BibtexOptions { biblatex: true, ..options };
+    let (writer, path) = file_writer(OutputFormat::Bibtex, &dir);
+    writer.with_bibtex_options(options).write_references(&references()).await.unwrap();
+    assert_snapshot("references.biblatex.bib", &fs::read_to_string(&path).unwrap());
+}
+
+#[tokio::test]
+async fn test_citation_styles() {
+    let dir = TempDir::new().unwrap();
+    for (style, name) in [
+        (CitationStyle::Apa, "apa"),
+        (CitationStyle::Chicago, "chicago"),
+        (CitationStyle::Ieee, "ieee"),
+        (CitationStyle::Jhep, "jhep"),
+        (CitationStyle::Prd, "prd"),
+        (CitationStyle::Revtex, "revtex"),
+    ] {
+        let (writer, path) = file_writer(OutputFormat::Citations, &dir);
+        writer.with_citation_style(style).write_references(&references()).await.unwrap();
+        assert_snapshot(&format!("citations.{}.txt", name), &fs::read_to_string(&path).unwrap());
+
+        let (writer, path) = file_writer(OutputFormat::Html, &dir);
+        writer.with_citation_style(style).write_references(&references()).await.unwrap();
+        assert_snapshot(&format!("citations.{}.html", name), &fs::read_to_string(&path).unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_atom_feed() {
+    let dir = TempDir::new().unwrap();
+    let (writer, path) = file_writer(OutputFormat::Atom, &dir);
+    writer.write_references(&references()).await.unwrap();
+    assert_snapshot("references.atom.xml", &strip_timestamps(&fs::read_to_string(&path).unwrap()));
+}
+
+#[cfg(feature = "export-graph")]
+#[tokio::test]
+async fn test_network_formats() {
+    let dir = TempDir::new().unwrap();
+    for (format, name) in [
+        (OutputFormat::Json, "network.json"),
+        (OutputFormat::Graphml, "network.graphml"),
+        (OutputFormat::Dot, "network.dot"),
+        (OutputFormat::Cytoscape, "network.cytoscape.json"),
+    ] {
+        let (writer, path) = file_writer(format, &dir);
+        writer.write_network(&network()).await.unwrap();
+        assert_snapshot(name, &fs::read_to_string(&path).unwrap());
+    }
+}
+
+#[test]
+fn test_markdown_tables() {
+    assert_snapshot("references.md", &commands::reference_table(&references()).to_markdown());
+    assert_snapshot("network_stats.md", &network().stats_table().to_markdown());
+}